@@ -0,0 +1,137 @@
+//! Feeds arbitrary bytes to every parser and validator that accepts external
+//! input. Anything a parser accepts must also be safe to use.
+//!
+//! Run with `cargo fuzz run parsers` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use spaceship_duel::archive::{HallOfFame, OpponentSampling};
+use spaceship_duel::config::Config;
+use spaceship_duel::evolution::Population;
+use spaceship_duel::genome::{
+    Genome, Initialization, LayerMutation, NetworkConfig, NetworkState, WeightInit, SENSOR_COUNT,
+};
+use spaceship_duel::ladder::{Difficulty, PlayerRecord};
+use spaceship_duel::replay::{Replay, ReplayNotes, ReplayPlayer};
+use spaceship_duel::simulation::FitnessWeights;
+
+fuzz_target!(|data: &[u8]| {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    if let Ok(text) = std::str::from_utf8(data) {
+        if let Ok((genome, _)) = Genome::from_file_str(text) {
+            let outputs = genome.evaluate(&[0.5; SENSOR_COUNT], &mut NetworkState::default());
+            assert!(outputs.iter().all(|o| o.is_finite()));
+        }
+
+        if let Ok(pop) = Population::from_checkpoint_str(text) {
+            let mut pop = Population {
+                genomes: pop.genomes.into_iter().take(8).collect(),
+                ..pop
+            };
+            pop.evolve(&mut rng);
+        }
+
+        if let Ok(replay) = Replay::from_file_str(text) {
+            let mut player = ReplayPlayer::new(replay);
+            for _ in 0..100 {
+                player.step();
+            }
+            player.seek(0.0);
+        }
+
+        if let Ok(notes) = ReplayNotes::from_file_str(text) {
+            assert_eq!(
+                ReplayNotes::from_file_str(&notes.to_file_string()).unwrap(),
+                notes
+            );
+            for a in &notes.annotations {
+                let _ = notes.next_bookmark(a.time);
+                let _ = notes.previous_bookmark(a.time);
+                let _ = notes.showing_at(a.time).count();
+            }
+        }
+
+        if let Ok(mut record) = PlayerRecord::from_file_str(text) {
+            assert_eq!(
+                PlayerRecord::from_file_str(&record.to_file_string()).unwrap(),
+                record
+            );
+            for difficulty in Difficulty::ALL {
+                for winner in [Some(0), Some(1), None] {
+                    record.record(difficulty, winner);
+                }
+            }
+        }
+
+        if let Ok(config) = Config::parse(text) {
+            let init = Initialization {
+                network: config.network,
+                ..Initialization::default()
+            };
+            let mut genome = Genome::random_with(&init, &mut rng);
+            genome.mutate(&config.mutation, &mut rng);
+            // Combinations of arena settings are checked where a run starts
+            if config.layout.validate().is_ok() {
+                let mut state = config.layout.new_match(&mut rng);
+                let mut networks = [NetworkState::default(), NetworkState::default()];
+                for _ in 0..10 {
+                    let actions = [0, 1]
+                        .map(|i| genome.evaluate(&genome.sensors(&state, i), &mut networks[i]));
+                    state.update(1.0 / 60.0, &actions);
+                }
+            }
+        }
+
+        if let Ok(sampling) = OpponentSampling::parse(text) {
+            let mut archive = HallOfFame::default();
+            for generation in 0..3 {
+                let mut genome = Genome::random(&mut rng);
+                genome.fitness = generation as f32 * 100.0;
+                archive.add(&genome, generation);
+            }
+            assert!(archive.sample(&sampling, &mut rng).is_some());
+        }
+
+        if let Ok(weights) = WeightInit::parse(text) {
+            let init = Initialization {
+                weights,
+                ..Initialization::default()
+            };
+            Genome::from_weights(Genome::random_with(&init, &mut rng).weights).unwrap();
+        }
+
+        if let Ok(network) = NetworkConfig::parse(text) {
+            let init = Initialization {
+                network,
+                ..Initialization::default()
+            };
+            let genome = Genome::random_with(&init, &mut rng);
+            let outputs = genome.evaluate(&[0.5; SENSOR_COUNT], &mut NetworkState::default());
+            assert!(outputs.iter().all(|o| o.is_finite()));
+        }
+
+        if let Some((name, value)) = text.split_once('=') {
+            if let Ok(value) = value.parse::<f32>() {
+                let _ = FitnessWeights::default().set(name, value);
+                let mut mutation = LayerMutation::default();
+                if mutation.set(name, value).is_ok() {
+                    Genome::random(&mut rng).mutate(&mutation, &mut rng);
+                }
+            }
+        }
+    }
+
+    let weights: Vec<f32> = data
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+    if let Ok(genome) = Genome::from_weights(weights) {
+        let outputs = genome.evaluate(&[0.5; SENSOR_COUNT], &mut NetworkState::default());
+        assert!(outputs.iter().all(|o| o.is_finite()));
+    }
+});
@@ -0,0 +1,78 @@
+/*
+ * C interface to the spaceship duel match engine.
+ *
+ * Build with `cargo build --release --features ffi` and link against the
+ * resulting libspaceship_duel shared library. All handles are owned by the
+ * caller and must be released with the matching *_free function. Functions
+ * returning int use 0 for success and -1 for invalid arguments.
+ */
+#ifndef DUEL_H
+#define DUEL_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct Genome DuelGenome;
+typedef struct DuelMatch DuelMatch;
+
+typedef struct DuelShip {
+    float x;
+    float y;
+    float vx;
+    float vy;
+    float rotation;
+    uint8_t alive;
+    uint8_t cloaked;
+    uint32_t shots_fired;
+    uint32_t hits_scored;
+    float shield;
+    float hull;
+    float energy;
+    uint32_t ammo;
+    uint8_t deflecting;
+} DuelShip;
+
+/* Sizes of the default network's flat weight vector and of the per-ship
+ * action vector. Actions are [thrust, turn_left, turn_right, fire, special],
+ * so duel_action_size() is 5. a[4] is the special: it raises the cloak, or
+ * the deflector in matches played under deflector rules. */
+size_t duel_genome_size(void);
+size_t duel_action_size(void);
+
+/* Genomes. duel_genome_from_weights builds the default network from
+ * duel_genome_size() weights; duel_genome_load reads a .genome file saved by
+ * the trainer, of any network shape, and returns NULL if it cannot be read or
+ * was saved under other game constants. duel_genome_weights copies
+ * duel_genome_weight_count(genome) weights. */
+DuelGenome *duel_genome_from_weights(const float *weights, size_t len);
+DuelGenome *duel_genome_load(const char *path);
+DuelGenome *duel_genome_random(uint64_t seed);
+size_t duel_genome_weight_count(const DuelGenome *genome);
+int duel_genome_weights(const DuelGenome *genome, float *out, size_t len);
+void duel_genome_free(DuelGenome *genome);
+
+/* Full match at training speed; writes two fitness values */
+int duel_run_match(const DuelGenome *g1, const DuelGenome *g2, uint64_t seed, float *out_fitness);
+
+/* Step-wise matches. Every match is a duel of two ships, indexed 0 and 1;
+ * ship_idx outside that range returns -1. The match keeps each ship's
+ * network memory for recurrent genomes, so call duel_genome_act once per
+ * step, with the same genome for a ship throughout the match. */
+DuelMatch *duel_match_new(uint64_t seed);
+int duel_match_step(DuelMatch *m, float dt, const float *actions0, const float *actions1);
+int duel_genome_act(const DuelGenome *genome, DuelMatch *m, uint32_t ship_idx, float *out_actions);
+int duel_match_ship(const DuelMatch *m, uint32_t ship_idx, DuelShip *out);
+float duel_match_time(const DuelMatch *m);
+int duel_match_is_over(const DuelMatch *m);
+int duel_match_winner(const DuelMatch *m);
+void duel_match_free(DuelMatch *m);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DUEL_H */
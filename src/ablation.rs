@@ -0,0 +1,48 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::game::ArenaLayout;
+use crate::gauntlet::Gauntlet;
+use crate::genome::*;
+
+/// Gauntlet score of a genome with one sensor input silenced
+#[derive(Clone, Debug)]
+pub struct InputImportance {
+    pub input: usize,
+    pub name: &'static str,
+    pub score: f32,
+    /// Score lost against the intact genome; near zero for an input evolution ignores
+    pub drop: f32,
+}
+
+/// Score `genome` against the gauntlet intact and then with each sensor input
+/// zeroed in turn, returning (intact score, inputs by descending drop). Every
+/// run plays the same seeded matches, so differences come from the input alone.
+pub fn ablate(
+    genome: &Genome,
+    gauntlet: &Gauntlet,
+    layout: &ArenaLayout,
+    matches_each: usize,
+    rng: &mut impl Rng,
+) -> (f32, Vec<InputImportance>) {
+    let seed: u64 = rng.gen();
+    let score =
+        |g: &Genome| gauntlet.score(g, layout, matches_each, &mut StdRng::seed_from_u64(seed));
+    let baseline = score(genome);
+    let mut importance: Vec<InputImportance> = genome
+        .network
+        .sensors()
+        .enumerate()
+        .map(|(input, sensor)| {
+            let score = score(&genome.without_input(input));
+            InputImportance {
+                input,
+                name: INPUT_NAMES[sensor],
+                score,
+                drop: baseline - score,
+            }
+        })
+        .collect();
+    importance.sort_by(|a, b| b.drop.total_cmp(&a.drop));
+    (baseline, importance)
+}
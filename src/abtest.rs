@@ -0,0 +1,75 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::thread;
+
+use crate::error::DuelError;
+use crate::evolution::*;
+use crate::game::ArenaLayout;
+use crate::gauntlet::Gauntlet;
+use crate::simulation::FitnessWeights;
+
+/// Two populations evolving side by side under different fitness weights,
+/// periodically scored against the same frozen gauntlet
+pub struct AbTest {
+    pub populations: [Population; 2],
+    pub gauntlet: Gauntlet,
+}
+
+impl AbTest {
+    pub fn new(
+        layout: &ArenaLayout,
+        weights: [FitnessWeights; 2],
+        gauntlet: Gauntlet,
+        rng: &mut impl Rng,
+    ) -> Result<Self, DuelError> {
+        let [weights_a, weights_b] = weights;
+        let mut populations = [
+            Population::new(layout.clone(), rng),
+            Population::new(layout.clone(), rng),
+        ];
+        populations[0].fitness_weights = weights_a;
+        populations[1].fitness_weights = weights_b;
+        for pop in &mut populations {
+            pop.evaluate(rng)?;
+        }
+        Ok(AbTest {
+            populations,
+            gauntlet,
+        })
+    }
+
+    /// Advance both populations by one generation, each on its own thread
+    pub fn step(&mut self, rng: &mut impl Rng) -> Result<(), DuelError> {
+        let seeds: [u64; 2] = [rng.gen(), rng.gen()];
+        thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .populations
+                .iter_mut()
+                .zip(seeds)
+                .map(|(pop, seed)| {
+                    scope.spawn(move || {
+                        let mut rng = StdRng::seed_from_u64(seed);
+                        pop.evolve(&mut rng);
+                        pop.evaluate(&mut rng)
+                    })
+                })
+                .collect();
+            handles.into_iter().try_for_each(|h| {
+                h.join()
+                    .map_err(|_| DuelError::WorkerPanicked { task: "A/B test" })?
+            })
+        })
+    }
+
+    /// Gauntlet score of each population's current champion
+    pub fn compare(&self, matches_each: usize, rng: &mut impl Rng) -> [f32; 2] {
+        let mut scores = [0.0; 2];
+        for (score, pop) in scores.iter_mut().zip(&self.populations) {
+            let (champion, _) = pop.get_top_two();
+            *score = self
+                .gauntlet
+                .score(&champion, &pop.layout, matches_each, rng);
+        }
+        scores
+    }
+}
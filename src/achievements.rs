@@ -0,0 +1,173 @@
+use crate::error::DuelError;
+use crate::events::MatchEvent;
+
+/// A kill this soon after either ship crossed an arena edge was made around it
+pub const WRAP_KILL_WINDOW: f32 = 1.0;
+/// Wins faster than this earn a blitz
+pub const BLITZ_TIME: f32 = 5.0;
+/// Fewest shots a flawless match needs before it counts as marksmanship
+pub const MARKSMAN_MIN_SHOTS: usize = 3;
+
+/// Notable feat a champion pulled off in one of its matches
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Badge {
+    /// Destroyed the opponent within WRAP_KILL_WINDOW of a wrap-around
+    WrapKill,
+    /// Won in under BLITZ_TIME seconds
+    Blitz,
+    /// Won without missing a shot, firing at least MARKSMAN_MIN_SHOTS
+    Marksman,
+}
+
+impl Badge {
+    pub const ALL: [Badge; 3] = [Badge::WrapKill, Badge::Blitz, Badge::Marksman];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Badge::WrapKill => "wrap-kill",
+            Badge::Blitz => "blitz",
+            Badge::Marksman => "marksman",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, DuelError> {
+        Badge::ALL
+            .into_iter()
+            .find(|b| b.name() == name)
+            .ok_or_else(|| DuelError::UnknownName {
+                kind: "badge",
+                name: name.to_string(),
+            })
+    }
+}
+
+/// Badges `ship` earned in the match `events` were recorded from
+pub fn earned(events: &[MatchEvent], ship: usize) -> Vec<Badge> {
+    let mut badges = Vec::new();
+    let won_at = events.iter().find_map(|e| match *e {
+        MatchEvent::MatchOver {
+            winner: Some(w),
+            time,
+        } if w == ship => Some(time),
+        _ => None,
+    });
+
+    let wrap_kill = events.iter().any(|e| match *e {
+        MatchEvent::Destroyed { shooter, time, .. } if shooter == ship => {
+            events.iter().any(|w| match *w {
+                MatchEvent::Wrapped { time: wrapped, .. } => {
+                    wrapped <= time && time - wrapped <= WRAP_KILL_WINDOW
+                }
+                _ => false,
+            })
+        }
+        _ => false,
+    });
+    if wrap_kill {
+        badges.push(Badge::WrapKill);
+    }
+
+    if let Some(time) = won_at {
+        if time < BLITZ_TIME {
+            badges.push(Badge::Blitz);
+        }
+        let shots = events
+            .iter()
+            .filter(|e| matches!(e, MatchEvent::ShotFired { ship: s, .. } if *s == ship))
+            .count();
+        let missed = events
+            .iter()
+            .any(|e| matches!(e, MatchEvent::ShotMissed { ship: s, .. } if *s == ship));
+        if shots >= MARKSMAN_MIN_SHOTS && !missed {
+            badges.push(Badge::Marksman);
+        }
+    }
+    badges
+}
+
+/// Add `new` badges to `badges`, keeping each once and in `Badge::ALL` order
+pub fn award(badges: &mut Vec<Badge>, new: &[Badge]) {
+    badges.extend_from_slice(new);
+    badges.sort();
+    badges.dedup();
+}
+
+/// Space-free checkpoint token for a badge list, "-" when empty
+pub fn spec(badges: &[Badge]) -> String {
+    if badges.is_empty() {
+        return "-".to_string();
+    }
+    let names: Vec<&str> = badges.iter().map(|b| b.name()).collect();
+    names.join(",")
+}
+
+/// Parse a badge list written by `spec`
+pub fn parse_list(spec: &str) -> Result<Vec<Badge>, DuelError> {
+    if spec == "-" {
+        return Ok(Vec::new());
+    }
+    let mut badges = spec
+        .split(',')
+        .map(Badge::parse)
+        .collect::<Result<_, _>>()?;
+    award(&mut badges, &[]);
+    Ok(badges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badges_come_from_the_winners_events() {
+        let mut events = vec![
+            MatchEvent::ShotFired { ship: 0, time: 1.0 },
+            MatchEvent::ShotFired { ship: 0, time: 1.5 },
+            MatchEvent::ShotFired { ship: 0, time: 2.0 },
+            MatchEvent::ShotFired { ship: 1, time: 2.0 },
+            MatchEvent::ShotMissed { ship: 1, time: 2.5 },
+            MatchEvent::Wrapped {
+                ship: 0,
+                opponent: 1,
+                opponent_distance: 200.0,
+                time: 2.6,
+            },
+            MatchEvent::Destroyed {
+                shooter: 0,
+                target: 1,
+                shots: 3,
+                time: 3.0,
+            },
+            MatchEvent::MatchOver {
+                winner: Some(0),
+                time: 3.0,
+            },
+        ];
+        assert_eq!(
+            earned(&events, 0),
+            vec![Badge::WrapKill, Badge::Blitz, Badge::Marksman]
+        );
+        assert!(earned(&events, 1).is_empty());
+
+        // One miss and a long wait between wrap and kill earn only the blitz
+        events.insert(3, MatchEvent::ShotMissed { ship: 0, time: 2.2 });
+        events.retain(|e| !matches!(e, MatchEvent::Wrapped { .. }));
+        events.insert(
+            0,
+            MatchEvent::Wrapped {
+                ship: 1,
+                opponent: 0,
+                opponent_distance: 100.0,
+                time: 0.5,
+            },
+        );
+        assert_eq!(earned(&events, 0), vec![Badge::Blitz]);
+
+        let mut badges = vec![Badge::Marksman];
+        award(&mut badges, &[Badge::WrapKill, Badge::Marksman]);
+        assert_eq!(badges, vec![Badge::WrapKill, Badge::Marksman]);
+        assert_eq!(parse_list(&spec(&badges)).unwrap(), badges);
+        assert_eq!(parse_list(&spec(&[])).unwrap(), vec![]);
+        assert!(Badge::parse("sniper").is_err());
+    }
+}
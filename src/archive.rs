@@ -0,0 +1,238 @@
+use rand::Rng;
+
+use crate::achievements::Badge;
+use crate::error::{check_range, DuelError};
+use crate::genome::Genome;
+use crate::rating::{scheduling_weight, INITIAL_RATING};
+
+/// A past champion kept as a fixed evaluation opponent
+#[derive(Clone, Debug)]
+pub struct ArchiveEntry {
+    pub genome: Genome,
+    pub generation: usize,
+    /// Fitness the genome had when archived (relative to its own generation)
+    pub fitness: f32,
+    /// Elo rating from matches against other archived champions
+    pub rating: f32,
+    /// Feats seen in its rating matches, in `Badge::ALL` order
+    pub badges: Vec<Badge>,
+}
+
+/// How evaluation picks opponents from the hall of fame
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum OpponentSampling {
+    /// Every archived champion equally likely
+    #[default]
+    Uniform,
+    /// Weight halves every `half_life` generations of age
+    Recency { half_life: f32 },
+    /// Softmax over archived fitness; low temperature favours the strongest
+    Strength { temperature: f32 },
+    /// Favour champions rated near the newest one, the population's current level
+    Rating { spread: f32 },
+}
+
+impl OpponentSampling {
+    /// The spec `parse` turns back into this sampling
+    pub fn spec(&self) -> String {
+        match self {
+            OpponentSampling::Uniform => "uniform".to_string(),
+            OpponentSampling::Recency { half_life } => format!("recency:{}", half_life),
+            OpponentSampling::Strength { temperature } => format!("strength:{}", temperature),
+            OpponentSampling::Rating { spread } => format!("rating:{}", spread),
+        }
+    }
+
+    /// Parse "uniform", "recency:HALF_LIFE", "strength:TEMPERATURE" or "rating:SPREAD"
+    pub fn parse(spec: &str) -> Result<Self, DuelError> {
+        let (kind, param) = match spec.split_once(':') {
+            Some((kind, param)) => {
+                let value = param.parse::<f32>().map_err(|_| DuelError::InvalidValue {
+                    what: "opponent sampling parameter",
+                    value: param.to_string(),
+                })?;
+                check_range("opponent sampling parameter", value, 0.01, 1e6)?;
+                (kind, Some(value))
+            }
+            None => (spec, None),
+        };
+        match kind {
+            "uniform" => Ok(OpponentSampling::Uniform),
+            "recency" => Ok(OpponentSampling::Recency {
+                half_life: param.unwrap_or(10.0),
+            }),
+            "strength" => Ok(OpponentSampling::Strength {
+                temperature: param.unwrap_or(0.5),
+            }),
+            "rating" => Ok(OpponentSampling::Rating {
+                spread: param.unwrap_or(200.0),
+            }),
+            _ => Err(DuelError::UnknownName {
+                kind: "opponent sampling",
+                name: kind.to_string(),
+            }),
+        }
+    }
+}
+
+/// Champions of past generations, one per generation until `evict_to` thins them out
+#[derive(Clone, Debug, Default)]
+pub struct HallOfFame {
+    pub entries: Vec<ArchiveEntry>,
+}
+
+impl HallOfFame {
+    pub fn add(&mut self, genome: &Genome, generation: usize) {
+        self.entries.push(ArchiveEntry {
+            genome: genome.clone(),
+            generation,
+            fitness: genome.fitness,
+            rating: INITIAL_RATING,
+            badges: Vec::new(),
+        });
+    }
+
+    /// Generation of the earliest archived champion to earn `badge`
+    pub fn first_earned(&self, badge: Badge) -> Option<usize> {
+        self.entries
+            .iter()
+            .filter(|e| e.badges.contains(&badge))
+            .map(|e| e.generation)
+            .min()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Evict champions until at most `cap` remain, so week-long runs keep a
+    /// bounded archive. The newest champion and the `protected` earliest
+    /// stay; each eviction takes the lower-rated of the two consecutive
+    /// champions whose genomes are closest, keeping the strongest and most
+    /// varied opponents. Returns how many were evicted.
+    pub fn evict_to(&mut self, cap: usize, protected: usize) -> usize {
+        let mut evicted = 0;
+        while self.entries.len() > cap.max(protected + 1) {
+            let last = self.entries.len() - 1;
+            let closest = (protected..last).min_by(|&a, &b| {
+                let gap = |i: usize| self.entries[i].genome.distance(&self.entries[i + 1].genome);
+                gap(a).total_cmp(&gap(b))
+            });
+            let Some(i) = closest else {
+                break;
+            };
+            let victim = if i + 1 == last || self.entries[i].rating <= self.entries[i + 1].rating {
+                i
+            } else {
+                i + 1
+            };
+            self.entries.remove(victim);
+            evicted += 1;
+        }
+        evicted
+    }
+
+    /// Approximate memory held by the archived genomes, in bytes
+    pub fn memory_bytes(&self) -> usize {
+        let genomes: usize = self
+            .entries
+            .iter()
+            .map(|e| {
+                e.genome.weights.capacity() * size_of::<f32>()
+                    + e.badges.capacity() * size_of::<Badge>()
+            })
+            .sum();
+        self.entries.capacity() * size_of::<ArchiveEntry>() + genomes
+    }
+
+    /// Draw one archived champion according to `sampling`
+    /// The `n` earliest archived champions, which are typically the weakest
+    pub fn earliest(&self, n: usize) -> &[ArchiveEntry] {
+        &self.entries[..n.min(self.entries.len())]
+    }
+
+    pub fn sample(&self, sampling: &OpponentSampling, rng: &mut impl Rng) -> Option<&ArchiveEntry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let weights = self.weights(sampling);
+        let total: f32 = weights.iter().sum();
+        let mut pick = rng.gen_range(0.0..total);
+        for (entry, w) in self.entries.iter().zip(&weights) {
+            if pick < *w {
+                return Some(entry);
+            }
+            pick -= w;
+        }
+        self.entries.last()
+    }
+
+    /// Unnormalized sampling weight of each entry
+    fn weights(&self, sampling: &OpponentSampling) -> Vec<f32> {
+        match *sampling {
+            OpponentSampling::Uniform => vec![1.0; self.entries.len()],
+            OpponentSampling::Recency { half_life } => {
+                let newest = self.entries.iter().map(|e| e.generation).max().unwrap_or(0);
+                self.entries
+                    .iter()
+                    .map(|e| 0.5f32.powf((newest - e.generation) as f32 / half_life))
+                    .collect()
+            }
+            OpponentSampling::Strength { temperature } => {
+                let max = self
+                    .entries
+                    .iter()
+                    .map(|e| e.fitness)
+                    .fold(f32::MIN, f32::max);
+                let min = self
+                    .entries
+                    .iter()
+                    .map(|e| e.fitness)
+                    .fold(f32::MAX, f32::min);
+                let spread = (max - min).max(1e-3);
+                self.entries
+                    .iter()
+                    .map(|e| ((e.fitness - max) / spread / temperature).exp())
+                    .collect()
+            }
+            OpponentSampling::Rating { spread } => {
+                let newest = self.entries.last().map_or(INITIAL_RATING, |e| e.rating);
+                self.entries
+                    .iter()
+                    .map(|e| scheduling_weight(newest, e.rating, spread))
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn eviction_drops_the_weaker_of_near_duplicates() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut hall_of_fame = HallOfFame::default();
+        for generation in 0..6 {
+            hall_of_fame.add(&Genome::random(&mut rng), generation);
+        }
+        // Generation 3 is a slightly weaker copy of generation 2
+        hall_of_fame.entries[3].genome = hall_of_fame.entries[2].genome.clone();
+        hall_of_fame.entries[3].rating -= 50.0;
+        assert_eq!(hall_of_fame.evict_to(5, 1), 1);
+        let kept: Vec<usize> = hall_of_fame.entries.iter().map(|e| e.generation).collect();
+        assert_eq!(kept, [0, 1, 2, 4, 5]);
+
+        assert_eq!(hall_of_fame.evict_to(2, 1), 3);
+        let kept: Vec<usize> = hall_of_fame.entries.iter().map(|e| e.generation).collect();
+        assert_eq!(kept, [0, 5]);
+        assert_eq!(hall_of_fame.evict_to(1, 1), 0);
+    }
+}
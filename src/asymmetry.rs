@@ -0,0 +1,184 @@
+use rand::Rng;
+
+use crate::bots::GenomeController;
+use crate::error::{check_range, DuelError};
+use crate::game::*;
+use crate::gauntlet::Gauntlet;
+use crate::genome::Genome;
+use crate::simulation::{FitnessWeights, MatchSession};
+
+/// Weapon constant an asymmetry experiment varies on one ship
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsymmetricConstant {
+    /// FIRE_COOLDOWN, as a multiple of the stock value
+    FireCooldown,
+    /// MAX_PROJECTILES_PER_SHIP, as a count
+    ProjectileCap,
+}
+
+impl AsymmetricConstant {
+    pub fn name(self) -> &'static str {
+        match self {
+            AsymmetricConstant::FireCooldown => "cooldown",
+            AsymmetricConstant::ProjectileCap => "projectiles",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, DuelError> {
+        [
+            AsymmetricConstant::FireCooldown,
+            AsymmetricConstant::ProjectileCap,
+        ]
+        .into_iter()
+        .find(|c| c.name() == name)
+        .ok_or_else(|| DuelError::UnknownName {
+            kind: "asymmetric constant",
+            name: name.to_string(),
+        })
+    }
+
+    /// The value ships train under
+    pub fn stock(self) -> f32 {
+        match self {
+            AsymmetricConstant::FireCooldown => 1.0,
+            AsymmetricConstant::ProjectileCap => MAX_PROJECTILES_PER_SHIP as f32,
+        }
+    }
+
+    /// Handicap setting this constant to `value`; projectile caps are
+    /// rounded to a whole count
+    pub fn handicap(self, value: f32) -> Result<Handicap, DuelError> {
+        match self {
+            AsymmetricConstant::FireCooldown => {
+                check_range("fire cooldown multiplier", value, 0.05, 20.0)?;
+                Ok(Handicap {
+                    fire_cooldown: value,
+                    ..Handicap::default()
+                })
+            }
+            AsymmetricConstant::ProjectileCap => {
+                check_range("projectile cap", value, 1.0, 100.0)?;
+                Ok(Handicap {
+                    max_projectiles: value.round() as usize,
+                    ..Handicap::default()
+                })
+            }
+        }
+    }
+}
+
+/// How a genome fares against the gauntlet with one constant of its own
+/// ship changed, the opponents flying stock ships
+#[derive(Clone, Debug)]
+pub struct AsymmetryPoint {
+    pub value: f32,
+    pub win_rate: f32,
+    pub draw_rate: f32,
+}
+
+/// Win rate of `genome` against every gauntlet opponent, `matches_each`
+/// matches apiece alternating sides, with its ship's `constant` set to each
+/// of `values` in turn. A win rate that holds up as the constant moves away
+/// from stock means real maneuvering skill; one that collapses means the
+/// genome is exploiting the exact constant it evolved under.
+pub fn measure(
+    genome: &Genome,
+    gauntlet: &Gauntlet,
+    layout: &ArenaLayout,
+    constant: AsymmetricConstant,
+    values: &[f32],
+    matches_each: usize,
+    rng: &mut impl Rng,
+) -> Result<Vec<AsymmetryPoint>, DuelError> {
+    let weights = FitnessWeights::default();
+    values
+        .iter()
+        .map(|&value| {
+            let handicap = constant.handicap(value)?;
+            let (mut wins, mut draws, mut played) = (0, 0, 0);
+            for opponent in &gauntlet.opponents {
+                for m in 0..matches_each {
+                    let side = m % 2;
+                    let mut ours = GenomeController::new(genome);
+                    let mut theirs = GenomeController::new(opponent);
+                    let controllers = if side == 0 {
+                        [&mut ours as _, &mut theirs as _]
+                    } else {
+                        [&mut theirs as _, &mut ours as _]
+                    };
+                    let mut session = MatchSession::new(controllers, layout, &weights, rng);
+                    let mut handicaps = [Handicap::default(); 2];
+                    handicaps[side] = handicap;
+                    session.set_handicaps(handicaps);
+                    while session.step() {}
+                    match session.result().winner {
+                        Some(w) if w == side => wins += 1,
+                        Some(_) => {}
+                        None => draws += 1,
+                    }
+                    played += 1;
+                }
+            }
+            let played = played.max(1) as f32;
+            Ok(AsymmetryPoint {
+                value,
+                win_rate: wins as f32 / played,
+                draw_rate: draws as f32 / played,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn handicaps_change_only_the_handicapped_ship() {
+        let mut state = GameState::new();
+        // Flying parallel, out of each other's line of fire
+        state.ships[0] = Ship::new(400.0, 300.0, 0.0);
+        state.ships[1] = Ship::new(400.0, 900.0, 0.0);
+        state.ships[0].handicap = AsymmetricConstant::ProjectileCap.handicap(1.0).unwrap();
+        state.ships[1].handicap = AsymmetricConstant::FireCooldown.handicap(2.0).unwrap();
+        let fire = [[0.0, 0.0, 0.0, 1.0, 0.0]; 2];
+        for _ in 0..40 {
+            state.update(1.0 / 60.0, &fire);
+        }
+        let in_flight = |owner| {
+            state
+                .projectiles
+                .iter()
+                .filter(|p| p.owner == owner)
+                .count()
+        };
+        assert_eq!(in_flight(0), 1);
+        // Half the fire rate: two shots in two thirds of a second instead of three
+        assert_eq!(state.ships[1].shots_fired, 2);
+
+        assert_eq!(AsymmetricConstant::parse("cooldown").unwrap().stock(), 1.0);
+        assert!(AsymmetricConstant::parse("recoil").is_err());
+        assert!(AsymmetricConstant::FireCooldown.handicap(0.0).is_err());
+
+        let mut rng = StdRng::seed_from_u64(4);
+        let gauntlet = Gauntlet {
+            opponents: vec![Genome::random(&mut rng)],
+        };
+        let genome = Genome::random(&mut rng);
+        let layout = ArenaLayout::default();
+        let curve = measure(
+            &genome,
+            &gauntlet,
+            &layout,
+            AsymmetricConstant::ProjectileCap,
+            &[1.0, 5.0],
+            2,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(curve.len(), 2);
+        assert!(curve.iter().all(|p| p.win_rate + p.draw_rate <= 1.0));
+    }
+}
@@ -0,0 +1,170 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::game::{ArenaLayout, ShipClass};
+use crate::genome::Genome;
+use crate::simulation::{run_match, FitnessWeights};
+
+/// How a fixed set of pilots fares against each other under one set of match
+/// rules: every pair plays a series, alternating sides
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BalanceStats {
+    pub matches: usize,
+    pub draws: usize,
+    /// Simulated seconds across all matches
+    pub seconds: f32,
+    /// Points (1 per win, 0.5 per draw) and matches played by each pilot
+    pub pilots: Vec<(f32, usize)>,
+    /// Points and matches by ship class, in `ShipClass::ALL` order; mirror
+    /// matches are left out, since a class cannot beat itself
+    pub classes: [(f32, usize); ShipClass::ALL.len()],
+}
+
+impl BalanceStats {
+    pub fn draw_rate(&self) -> f32 {
+        self.draws as f32 / self.matches.max(1) as f32
+    }
+
+    pub fn mean_duration(&self) -> f32 {
+        self.seconds / self.matches.max(1) as f32
+    }
+
+    /// Share of the available points pilot `i` took
+    pub fn win_rate(&self, i: usize) -> f32 {
+        let (points, played) = self.pilots[i];
+        points / played.max(1) as f32
+    }
+
+    /// Share of the points `class` took against other classes, None if it never met one
+    pub fn class_win_rate(&self, class: ShipClass) -> Option<f32> {
+        let (points, played) = self.classes[class as usize];
+        (played > 0).then(|| points / played as f32)
+    }
+
+    /// Standard deviation of the pilots' win rates; near zero when the rules
+    /// leave little room for skill to show
+    pub fn win_rate_spread(&self) -> f32 {
+        let n = self.pilots.len().max(1) as f32;
+        let rates: Vec<f32> = (0..self.pilots.len()).map(|i| self.win_rate(i)).collect();
+        let mean = rates.iter().sum::<f32>() / n;
+        (rates.iter().map(|r| (r - mean) * (r - mean)).sum::<f32>() / n).sqrt()
+    }
+}
+
+/// Play every pair of `pilots` `matches_each` times under `layout`. Match
+/// `k` of the round robin is seeded from `seed` and `k` alone, so two
+/// layouts measured with the same seed see the same spawns and draws
+/// wherever their rules allow.
+pub fn measure(
+    pilots: &[Genome],
+    layout: &ArenaLayout,
+    matches_each: usize,
+    seed: u64,
+) -> BalanceStats {
+    let weights = FitnessWeights::default();
+    let mut stats = BalanceStats {
+        pilots: vec![(0.0, 0); pilots.len()],
+        ..Default::default()
+    };
+    let mut k = 0u64;
+    for a in 0..pilots.len() {
+        for b in a + 1..pilots.len() {
+            for m in 0..matches_each {
+                // Alternate sides so spawn order cannot decide a series
+                let sides = if m % 2 == 0 { [a, b] } else { [b, a] };
+                let mut rng = StdRng::seed_from_u64(seed ^ k.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+                k += 1;
+                let result = run_match(
+                    &pilots[sides[0]],
+                    &pilots[sides[1]],
+                    layout,
+                    &weights,
+                    &mut rng,
+                );
+                stats.matches += 1;
+                stats.seconds += result.duration;
+                stats.draws += result.winner.is_none() as usize;
+                let mirror = result.classes[0] == result.classes[1];
+                for (ship, (&pilot, class)) in sides.iter().zip(result.classes).enumerate() {
+                    let points = match result.winner {
+                        Some(w) if w == ship => 1.0,
+                        Some(_) => 0.0,
+                        None => 0.5,
+                    };
+                    let pilot = &mut stats.pilots[pilot];
+                    pilot.0 += points;
+                    pilot.1 += 1;
+                    if !mirror {
+                        let class = &mut stats.classes[class as usize];
+                        class.0 += points;
+                        class.1 += 1;
+                    }
+                }
+            }
+        }
+    }
+    stats
+}
+
+/// The same pilots measured under a baseline and a candidate layout
+#[derive(Clone, Debug)]
+pub struct BalanceReport {
+    pub baseline: BalanceStats,
+    pub candidate: BalanceStats,
+}
+
+impl BalanceReport {
+    /// Measure `pilots` under both layouts with shared match seeds
+    pub fn compare(
+        pilots: &[Genome],
+        baseline: &ArenaLayout,
+        candidate: &ArenaLayout,
+        matches_each: usize,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let seed = rng.gen();
+        BalanceReport {
+            baseline: measure(pilots, baseline, matches_each, seed),
+            candidate: measure(pilots, candidate, matches_each, seed),
+        }
+    }
+
+    /// Largest change in any pilot's win rate, the headline regression figure
+    pub fn max_win_rate_shift(&self) -> f32 {
+        (0..self.baseline.pilots.len().min(self.candidate.pilots.len()))
+            .map(|i| (self.candidate.win_rate(i) - self.baseline.win_rate(i)).abs())
+            .fold(0.0, f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_rules_measure_identically_and_tougher_hulls_draw_more() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let pilots: Vec<Genome> = (0..4).map(|_| Genome::random(&mut rng)).collect();
+        let baseline = ArenaLayout::default();
+        let same = BalanceReport::compare(&pilots, &baseline, &baseline, 2, &mut rng);
+        assert_eq!(same.baseline, same.candidate);
+        assert_eq!(same.max_win_rate_shift(), 0.0);
+        assert_eq!(same.baseline.matches, 6 * 2);
+        // Every match hands out exactly one point
+        let points: f32 = same.baseline.pilots.iter().map(|p| p.0).sum();
+        assert_eq!(points, same.baseline.matches as f32);
+
+        let mut armoured = baseline.clone();
+        armoured.set("hull", "20").unwrap();
+        let report = BalanceReport::compare(&pilots, &baseline, &armoured, 2, &mut rng);
+        assert!(report.candidate.draw_rate() >= report.baseline.draw_rate());
+        assert!(report.candidate.mean_duration() >= report.baseline.mean_duration());
+        assert!(report
+            .baseline
+            .class_win_rate(ShipClass::Standard)
+            .is_none());
+        assert!(armoured.set("hull", "0").is_err());
+        assert_eq!(armoured.physics.hull, 20.0);
+        assert!(armoured.set("gravity", "1").is_err());
+    }
+}
@@ -0,0 +1,1691 @@
+//! Headless command-line tools for experiments that don't need the game window.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use spaceship_duel::ablation;
+use spaceship_duel::abtest::AbTest;
+use spaceship_duel::archive::OpponentSampling;
+use spaceship_duel::asymmetry::{self, AsymmetricConstant};
+use spaceship_duel::balance::{self, BalanceReport};
+use spaceship_duel::bots::{self, Bot};
+use spaceship_duel::checkpoint::in_flight_path;
+use spaceship_duel::columns;
+use spaceship_duel::config::Config;
+use spaceship_duel::error::DuelError;
+use spaceship_duel::evolution::{PartialEvaluation, Population, Selection, POPULATION_SIZE};
+use spaceship_duel::game::{ArenaLayout, ArenaSize, ClassMatchups, ShipClass};
+use spaceship_duel::gauntlet::Gauntlet;
+use spaceship_duel::genome::{ConfigCheck, Genome, Initialization, LayerMutation, NetworkConfig};
+use spaceship_duel::landscape;
+use spaceship_duel::league::{League, LeagueConfig};
+use spaceship_duel::merge;
+use spaceship_duel::novelty::NoveltySearch;
+use spaceship_duel::profiling;
+use spaceship_duel::remote::RemoteStore;
+use spaceship_duel::replay::Replay;
+use spaceship_duel::scaling;
+use spaceship_duel::simulation::{run_match, run_match_with_timeline, FitnessWeights};
+use spaceship_duel::speciation::Speciation;
+use spaceship_duel::stats::{self, StatsLog};
+use spaceship_duel::surrogate::Surrogate;
+use spaceship_duel::sweep;
+use spaceship_duel::watermark;
+
+const USAGE: &str = "usage: duel-cli <command> [options]
+
+commands:
+  ab-test   Evolve two populations under different fitness weights and compare
+            their champions against a shared frozen gauntlet
+      --generations N           generations to run (default 50)
+      --every N                 compare every N generations (default 5)
+      --gauntlet-generations N  warm-up generations for the gauntlet (default 5)
+      --gauntlet-size N         opponents frozen into the gauntlet (default 8)
+      --matches N               matches per gauntlet opponent (default 4)
+      --a NAME=VALUE            fitness weight override for population A
+      --b NAME=VALUE            fitness weight override for population B
+
+  balance   Measure how match rules A and B shift win rates, draw rate and
+            match length, playing the same pilots with the same match seeds
+      --a NAME=VALUE            match rule for A, e.g. recoil=30, hull=3 or
+                                class_matchups=mixed (default: standard rules)
+      --b NAME=VALUE            match rule for B
+      --genomes FILES           comma-separated frozen pilots, played under both;
+                                without them pilots are trained under each rule set
+      --generations N           training generations per rule set (default 5)
+      --pilots N                pilots trained per rule set (default 8)
+      --matches N               matches per pair of pilots (default 4)
+      --seed N                  seed every random choice, for reproducible runs
+      --allow-config-mismatch   accept genomes saved under other game constants
+
+  league    Train a main population alongside main exploiters (trained against
+            the main champion) and league exploiters (trained against the archive)
+      --generations N           generations to run (default 50)
+      --exploiters N            size of each exploiter population (default 30)
+      --main-vs-exploiters N    matches per main genome vs exploiter champions (default 2)
+      --exploiter-matches N     matches per exploiter genome (default 6)
+      --reset-every N           restart main exploiters every N generations, 0 = never (default 25)
+
+  train     Run evolution without a window, printing per-generation stats and
+            saving the champion genome
+      --generations N           generations to run (default 100)
+      --config FILE.conf        population size, selection, mutation, fitness,
+                                [arena], [network] and [sync] settings; later
+                                options override it
+      --population N            population size (default 100)
+      --asteroids N             asteroids per match (default 0)
+      --shield N                regenerating shield points per ship (default 0)
+      --shield-regen N          shield points regenerated per second (default 0)
+      --hull N                  hull points per ship; hits beyond the shield
+                                cost one each (default 1)
+      --ammo N                  shots per ship, 0 = unlimited (default 0)
+      --ship-classes MATCHUPS   standard, mirror (both ships fly one random class)
+                                or mixed (each ship a random class) (default standard)
+      --pickup-interval S       seconds between ammo pickup spawns, 0 = none (default 0)
+      --energy                  thrust and shots draw on a recharging energy pool
+      --deflector               the cloak output raises an energy-draining deflector
+                                that absorbs hits instead
+      --projectile-collisions   opposing projectiles that touch destroy each other
+      --randomize-flow-field    random flow field every match
+      --opponent-sampling SPEC  uniform, recency:HALF_LIFE, strength:TEMPERATURE or
+                                rating:SPREAD (champions rated near the newest)
+      --self-adaptation TAU     let each genome's mutation rate and strength evolve,
+                                scaled by exp(TAU x N(0,1)) per offspring (default 0)
+      --time-budget S           evaluate each generation for S wall-clock seconds,
+                                as many matches as fit, instead of a fixed schedule
+      --surrogate               pre-screen offspring with a fitness predictor
+      --speciation SPEC         share fitness within species of similar genomes:
+                                THRESHOLD (RMS weight distance) and optionally
+                                :STAGNATION generations before a species is culled
+      --novelty SPEC            add WEIGHT x novelty (mean behavior distance to the
+                                K nearest, default 15) to fitness: WEIGHT[:K]
+      --pareto                  breed by Pareto front over win rate, accuracy,
+                                survival and aggression instead of fitness
+      --rollback SHARE          breed a generation again, under a new seed, if its
+                                best genome takes less than SHARE of the points
+                                against the reigning champion (up to 3 times)
+      --fitness NAME=VALUE      fitness weight override, e.g. shot_cost=1 accuracy=0
+                                to price ammunition instead of rewarding hit ratio
+      --hidden WIDTHS           comma-separated hidden layer widths (default 20)
+      --recurrent               evolve networks that keep hidden state between ticks
+      --position-inputs         also feed networks their own arena position
+      --defense-inputs          also feed networks both ships' shield and hull
+      --ammo-inputs             also feed networks their ammo and the nearest pickup
+      --energy-inputs           also feed networks their energy and the opponent's deflector
+      --signed-turn             steer with one signed turn output instead of
+                                competing left and right outputs
+      --rays N                  read N raycast sensors (distance to the opponent,
+                                fire and asteroids along fixed angles) instead of
+                                the relative sensors; N divides 16 (default 0)
+      --out FILE                champion genome output (default champion.genome)
+      --save-every N            also save every N generations, 0 = only at the end (default 10)
+      --checkpoint FILE         save the population to FILE every generation; pressing
+                                Enter stops once the matches in play are done and
+                                saves the unfinished generation to FILE.in-flight
+      --resume FILE             continue a run saved by --checkpoint, finishing its
+                                unfinished generation; its population, arena and
+                                evolution settings replace those options
+      --stats FILE              append per-generation fitness statistics to FILE,
+                                as JSON lines if it ends in .jsonl, else CSV
+      --sync URL                mirror the checkpoint and champion into the HTTP or
+                                WebDAV collection http://[USER:PASSWORD@]HOST[:PORT]/PATH
+                                after each save; --resume fetches a checkpoint missing
+                                here from it first
+      --seed N                  seed every random choice, for reproducible runs
+
+  sweep     Repeat a training run under several seeds, each in its own process,
+            and print the mean and standard deviation of the fitness curves:
+            duel-cli sweep [options] [train options]
+      --runs N                  independent runs (default 5)
+      --seed N                  seed of the first run; run k uses N+k (default 1)
+      --parallel N              runs in flight at once (default 1)
+      --dir DIR                 directory for each run's statistics, champion and
+                                log (default sweep)
+            Any other option is passed on to every train run, except --seed,
+            --stats and --out, which the sweep sets per run
+
+  merge     Pool the fittest genomes of two population checkpoints into a new
+            run, evaluated together and saved as a checkpoint to resume from:
+            duel-cli merge A.checkpoint B.checkpoint [options]. The new run
+            takes A's settings; both must evolve the same network shape
+      --top PERCENT             share of each population to keep (default 25)
+      --population N            size of the new run, topped up with mutated
+                                copies of the kept genomes (default A's size)
+      --out FILE                checkpoint output (default merged.checkpoint)
+      --seed N                  seed every random choice, for reproducible runs
+
+  probe     Score random perturbations of a genome at several magnitudes
+            against a gauntlet, showing how brittle the optimum is
+      --genome FILE             genome to probe (default champion.genome)
+      --magnitudes LIST         comma-separated perturbation lengths (default 0,0.5,1,2,4,8)
+      --directions N            random directions per magnitude (default 8)
+      --matches N               matches per gauntlet opponent (default 2)
+      --gauntlet-generations N  warm-up generations for the gauntlet (default 5)
+      --gauntlet-size N         opponents frozen into the gauntlet (default 8)
+      --seed N                  seed every random choice, for reproducible runs
+      --allow-config-mismatch   accept a genome saved under other game constants
+
+  ablate    Zero each sensor input of a genome in turn and report how much
+            its gauntlet score drops, most relied-on inputs first
+      --genome FILE             genome to analyse (default champion.genome)
+      --matches N               matches per gauntlet opponent (default 4)
+      --gauntlet-generations N  warm-up generations for the gauntlet (default 5)
+      --gauntlet-size N         opponents frozen into the gauntlet (default 8)
+      --seed N                  seed every random choice, for reproducible runs
+      --allow-config-mismatch   accept a genome saved under other game constants
+
+  asymmetry Change one weapon constant on a genome's own ship only and report
+            its win rate against a gauntlet of stock ships at each value
+      --genome FILE             genome to test (default champion.genome)
+      --constant NAME           cooldown (fire cooldown multiplier) or projectiles
+                                (projectiles in flight at once) (default cooldown)
+      --values LIST             comma-separated values (default 0.5,0.75,1,1.25,1.5,2
+                                for cooldown, 1,2,3,4,5,6,8 for projectiles)
+      --matches N               matches per gauntlet opponent (default 4)
+      --gauntlet-generations N  warm-up generations for the gauntlet (default 5)
+      --gauntlet-size N         opponents frozen into the gauntlet (default 8)
+      --seed N                  seed every random choice, for reproducible runs
+      --allow-config-mismatch   accept a genome saved under other game constants
+
+  scaling   Score a genome against a gauntlet in the stock arena and in resized
+            ones, reporting how much of its score each size costs it
+      --genome FILE             genome to test (default champion.genome)
+      --scales LIST             comma-separated size factors applied to both sides
+                                of the stock arena (default 0.5,0.75,1.25,1.5,2)
+      --arenas LIST             comma-separated WIDTHxHEIGHT sizes, instead of --scales
+      --config FILE.conf        [arena] settings the genome trained under; the sizes
+                                are compared against its arena
+      --asteroids N, --shield N, ...
+                                match rules as for train, applied at every size
+      --matches N               matches per gauntlet opponent (default 4)
+      --gauntlet-generations N  warm-up generations for the gauntlet (default 5)
+      --gauntlet-size N         opponents frozen into the gauntlet (default 8)
+      --seed N                  seed every random choice, for reproducible runs
+      --allow-config-mismatch   accept a genome saved under other game constants
+
+  rank      Score every .genome file in a directory against the scripted bots
+            and a frozen gauntlet, all under the same match seeds, and write
+            them best first as CSV: duel-cli rank DIR [options]. Files that
+            fail to load are skipped with a warning
+      --out FILE                CSV output (default ranking.csv)
+      --matches N               matches per gauntlet opponent and bot (default 4)
+      --gauntlet-generations N  warm-up generations for the gauntlet (default 5)
+      --gauntlet-size N         opponents frozen into the gauntlet (default 8)
+      --seed N                  seed every random choice, for reproducible runs
+      --allow-config-mismatch   accept genomes saved under other game constants
+
+  duel      Play two saved genomes against each other, alternating sides:
+            duel-cli duel A.genome B.genome [options]. Both genomes must have
+            been saved under this build's game constants
+      --matches N               matches to play (default 20)
+      --seed N                  seed every random choice, for reproducible runs
+      --allow-config-mismatch   play even if a genome was saved under other game constants
+      --pacing                  print each match's engagement timeline and stalled share
+
+  watermark Mark a genome as submitted by an owner, in the low bits of its
+            weights, or check whose mark it carries: duel-cli watermark
+            GENOME --owner NAME [options]. Marking moves no weight by more
+            than 0.01% of itself
+      --owner NAME              user or run the mark identifies (required)
+      --out FILE                marked genome output (default: overwrite GENOME)
+      --check                   only report how much of NAME's mark the genome
+                                carries, exiting with status 1 if it is unmarked
+      --allow-config-mismatch   accept a genome saved under other game constants
+
+  columns   Re-simulate a replay into one NumPy .npy file per column, with a row
+            per step (the state before it and both ships' actions), for
+            pandas or polars: duel-cli columns REPLAY [options]. The
+            directory's schema.txt lists every column and its dtype
+      --out DIR                 output directory (default columns)
+
+  fetch     Download files a run mirrored with --sync, such as its checkpoint
+            or champion, to inspect or resume them here: duel-cli fetch URL
+            NAME... [options]
+      --dir DIR                 directory to save them in (default .)
+
+  profile   Time one generation of evaluation and write folded stacks for
+            flamegraph.pl or inferno-flamegraph (build with --features profiling)
+      --out FILE                folded-stack output (default profile.folded)
+      --trace FILE              also write a Chrome trace of the spans, for
+                                chrome://tracing, Perfetto or speedscope";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("ab-test") => ab_test(args),
+        Some("balance") => balance(args),
+        Some("league") => league(args),
+        Some("train") => train(args),
+        Some("sweep") => sweep(args),
+        Some("merge") => merge(args),
+        Some("probe") => probe(args),
+        Some("ablate") => ablate(args),
+        Some("asymmetry") => asymmetry(args),
+        Some("scaling") => scaling(args),
+        Some("rank") => rank(args),
+        Some("duel") => duel(args),
+        Some("watermark") => watermark(args),
+        Some("columns") => columns(args),
+        Some("fetch") => fetch(args),
+        Some("profile") => profile(args),
+        Some("-h" | "--help") => println!("{}", USAGE),
+        Some(other) => usage_error(&format!("unknown command '{}'", other)),
+        None => usage_error("missing command"),
+    }
+}
+
+fn ab_test(mut args: impl Iterator<Item = String>) {
+    let mut generations = 50;
+    let mut every = 5;
+    let mut gauntlet_generations = 5;
+    let mut gauntlet_size = 8;
+    let mut matches = 4;
+    let mut weights = [FitnessWeights::default(), FitnessWeights::default()];
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--generations" => generations = parse_value(&arg, args.next()),
+            "--every" => every = parse_value::<usize>(&arg, args.next()).max(1),
+            "--gauntlet-generations" => gauntlet_generations = parse_value(&arg, args.next()),
+            "--gauntlet-size" => gauntlet_size = parse_value(&arg, args.next()),
+            "--matches" => matches = parse_value(&arg, args.next()),
+            "--a" | "--b" => {
+                let side = if arg == "--a" { 0 } else { 1 };
+                let spec = args.next().unwrap_or_default();
+                let (name, value) = spec.split_once('=').unwrap_or_else(|| {
+                    usage_error(&format!("expected NAME=VALUE, got '{}'", spec))
+                });
+                let value = parse_value(name, Some(value.to_string()));
+                weights[side]
+                    .set(name, value)
+                    .unwrap_or_else(|e| usage_error(&e.to_string()));
+            }
+            _ => usage_error(&format!("unknown option '{}'", arg)),
+        }
+    }
+
+    let mut rng = rand::thread_rng();
+    let layout = ArenaLayout::default();
+
+    println!(
+        "Warming up gauntlet ({} generations)...",
+        gauntlet_generations
+    );
+    let gauntlet = or_exit(Gauntlet::warm_up(
+        &layout,
+        gauntlet_generations,
+        gauntlet_size,
+        &mut rng,
+    ));
+
+    println!("A: {:?}", weights[0]);
+    println!("B: {:?}", weights[1]);
+    let mut test = or_exit(AbTest::new(&layout, weights, gauntlet, &mut rng));
+
+    println!("generation,score_a,score_b");
+    let mut totals = [0.0; 2];
+    let mut checkpoints = 0;
+    for generation in 1..=generations {
+        or_exit(test.step(&mut rng));
+        if generation % every == 0 || generation == generations {
+            let scores = test.compare(matches, &mut rng);
+            println!("{},{:.3},{:.3}", generation, scores[0], scores[1]);
+            totals[0] += scores[0];
+            totals[1] += scores[1];
+            checkpoints += 1;
+        }
+    }
+
+    if checkpoints > 0 {
+        let mean = [
+            totals[0] / checkpoints as f32,
+            totals[1] / checkpoints as f32,
+        ];
+        let verdict = if (mean[0] - mean[1]).abs() < 0.02 {
+            "no clear difference"
+        } else if mean[0] > mean[1] {
+            "A plays stronger"
+        } else {
+            "B plays stronger"
+        };
+        println!(
+            "Mean gauntlet score: A {:.3}, B {:.3} ({})",
+            mean[0], mean[1], verdict
+        );
+    }
+}
+
+fn balance(mut args: impl Iterator<Item = String>) {
+    let mut layouts = [ArenaLayout::default(), ArenaLayout::default()];
+    let mut genome_paths = Vec::new();
+    let mut generations = 5;
+    let mut pilot_count = 8;
+    let mut matches = 4;
+    let mut seed = None;
+    let mut allow_mismatch = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--a" | "--b" => {
+                let side = if arg == "--a" { 0 } else { 1 };
+                let spec = args.next().unwrap_or_default();
+                let (name, value) = spec.split_once('=').unwrap_or_else(|| {
+                    usage_error(&format!("expected NAME=VALUE, got '{}'", spec))
+                });
+                layouts[side]
+                    .set(name, value)
+                    .unwrap_or_else(|e| usage_error(&e.to_string()));
+            }
+            "--genomes" => {
+                let list = args.next().unwrap_or_default();
+                genome_paths = list.split(',').map(|p| p.trim().to_string()).collect();
+            }
+            "--generations" => generations = parse_value(&arg, args.next()),
+            "--pilots" => pilot_count = parse_value(&arg, args.next()),
+            "--matches" => matches = parse_value(&arg, args.next()),
+            "--seed" => seed = Some(parse_value(&arg, args.next())),
+            "--allow-config-mismatch" => allow_mismatch = true,
+            _ => usage_error(&format!("unknown option '{}'", arg)),
+        }
+    }
+
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let frozen = !genome_paths.is_empty();
+    let report = if frozen {
+        let pilots: Vec<Genome> = genome_paths
+            .iter()
+            .map(|path| load_genome(path, allow_mismatch).0)
+            .collect();
+        if pilots.len() < 2 {
+            usage_error("balance needs at least two genomes");
+        }
+        BalanceReport::compare(&pilots, &layouts[0], &layouts[1], matches, &mut rng)
+    } else {
+        // Pilots trained under each rule set show how evolved play adapts to it
+        let [baseline, candidate] = [0, 1].map(|side| {
+            println!(
+                "Training {} pilots under {} ({} generations)...",
+                pilot_count,
+                ["A", "B"][side],
+                generations
+            );
+            let gauntlet = or_exit(Gauntlet::warm_up(
+                &layouts[side],
+                generations,
+                pilot_count.max(2),
+                &mut rng,
+            ));
+            gauntlet.opponents
+        });
+        let seed = rng.gen();
+        BalanceReport {
+            baseline: balance::measure(&baseline, &layouts[0], matches, seed),
+            candidate: balance::measure(&candidate, &layouts[1], matches, seed),
+        }
+    };
+
+    let (a, b) = (&report.baseline, &report.candidate);
+    println!("metric,a,b,shift");
+    let row = |metric: &str, a: f32, b: f32| {
+        println!("{},{:.3},{:.3},{:+.3}", metric, a, b, b - a);
+    };
+    row("matches", a.matches as f32, b.matches as f32);
+    row("draw_rate", a.draw_rate(), b.draw_rate());
+    row("mean_duration", a.mean_duration(), b.mean_duration());
+    row("win_rate_spread", a.win_rate_spread(), b.win_rate_spread());
+    // Classes only show up under class matchups, possibly under one rule set alone
+    for class in ShipClass::ALL {
+        match (a.class_win_rate(class), b.class_win_rate(class)) {
+            (Some(ra), Some(rb)) => row(&format!("win_rate[{}]", class.name()), ra, rb),
+            (None, None) => {}
+            (ra, rb) => {
+                let rate = |r: Option<f32>| r.map_or("-".to_string(), |r| format!("{:.3}", r));
+                println!("win_rate[{}],{},{},-", class.name(), rate(ra), rate(rb));
+            }
+        }
+    }
+    if frozen {
+        for (i, path) in genome_paths.iter().enumerate() {
+            row(&format!("win_rate[{}]", path), a.win_rate(i), b.win_rate(i));
+        }
+        println!("Largest win rate shift: {:.3}", report.max_win_rate_shift());
+    }
+}
+
+fn league(mut args: impl Iterator<Item = String>) {
+    let mut generations = 50;
+    let mut config = LeagueConfig::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--generations" => generations = parse_value(&arg, args.next()),
+            "--exploiters" => config.exploiter_population = parse_value(&arg, args.next()),
+            "--main-vs-exploiters" => config.main_vs_exploiters = parse_value(&arg, args.next()),
+            "--exploiter-matches" => {
+                let matches = parse_value(&arg, args.next());
+                config.main_exploiter_matches = matches;
+                config.league_exploiter_matches = matches;
+            }
+            "--reset-every" => config.exploiter_reset_every = parse_value(&arg, args.next()),
+            _ => usage_error(&format!("unknown option '{}'", arg)),
+        }
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut league = or_exit(League::new(&ArenaLayout::default(), config, &mut rng));
+
+    println!("generation,main_best,main_exploiter_best,league_exploiter_best");
+    for _ in 0..generations {
+        or_exit(league.step(&mut rng));
+        println!(
+            "{},{:.1},{:.1},{:.1}",
+            league.main.generation,
+            league.main.best_fitness,
+            league.main_exploiters.best_fitness,
+            league.league_exploiters.best_fitness
+        );
+    }
+}
+
+fn train(mut args: impl Iterator<Item = String>) {
+    let mut generations = 100;
+    let mut population_size = POPULATION_SIZE;
+    let mut layout = ArenaLayout::default();
+    let mut opponent_sampling = OpponentSampling::default();
+    let mut surrogate = false;
+    let mut pareto = false;
+    let mut rollback_threshold = None;
+    let mut speciation = None;
+    let mut novelty = None;
+    let mut time_budget = None;
+    let mut selection = Selection::default();
+    let mut mutation = LayerMutation::default();
+    let mut fitness_weights = FitnessWeights::default();
+    let mut initialization = Initialization::default();
+    let mut out = String::from("champion.genome");
+    let mut save_every = 10;
+    let mut checkpoint = None;
+    let mut resume = None;
+    let mut stats = None;
+    let mut sync = None;
+    let mut seed = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--generations" => generations = parse_value(&arg, args.next()),
+            "--config" => {
+                let path = args.next().unwrap_or_default();
+                let config = Config::load(&path)
+                    .unwrap_or_else(|e| usage_error(&format!("cannot load {}: {}", path, e)));
+                population_size = config.population_size;
+                selection = config.selection;
+                mutation = config.mutation;
+                fitness_weights = config.fitness_weights;
+                initialization.network = config.network;
+                layout = config.layout;
+                sync = config.sync;
+            }
+            "--population" => population_size = parse_value(&arg, args.next()),
+            _ if layout_option(&arg, &mut args, &mut layout) => {}
+            "--opponent-sampling" => {
+                let spec = args.next().unwrap_or_default();
+                opponent_sampling =
+                    OpponentSampling::parse(&spec).unwrap_or_else(|e| usage_error(&e.to_string()));
+            }
+            "--surrogate" => surrogate = true,
+            "--pareto" => pareto = true,
+            "--rollback" => {
+                let share: f32 = parse_value(&arg, args.next());
+                if !(0.0..=1.0).contains(&share) {
+                    usage_error("--rollback must be a share between 0 and 1");
+                }
+                rollback_threshold = Some(share);
+            }
+            "--speciation" => {
+                let spec = args.next().unwrap_or_default();
+                speciation =
+                    Some(Speciation::parse(&spec).unwrap_or_else(|e| usage_error(&e.to_string())));
+            }
+            "--novelty" => {
+                let spec = args.next().unwrap_or_default();
+                novelty = Some(
+                    NoveltySearch::parse(&spec).unwrap_or_else(|e| usage_error(&e.to_string())),
+                );
+            }
+            "--self-adaptation" => mutation
+                .set("self_adaptation", parse_value(&arg, args.next()))
+                .unwrap_or_else(|e| usage_error(&e.to_string())),
+            "--time-budget" => {
+                let seconds: f64 = parse_value(&arg, args.next());
+                if seconds <= 0.0 {
+                    usage_error("--time-budget must be positive");
+                }
+                time_budget = Some(seconds);
+            }
+            "--fitness" => {
+                let spec = args.next().unwrap_or_default();
+                let (name, value) = spec.split_once('=').unwrap_or_else(|| {
+                    usage_error(&format!("expected NAME=VALUE, got '{}'", spec))
+                });
+                let value = parse_value(name, Some(value.to_string()));
+                fitness_weights
+                    .set(name, value)
+                    .unwrap_or_else(|e| usage_error(&e.to_string()));
+            }
+            "--hidden" => {
+                let spec = args.next().unwrap_or_default();
+                initialization.network.hidden = NetworkConfig::parse_widths(&spec)
+                    .unwrap_or_else(|e| usage_error(&e.to_string()));
+            }
+            "--recurrent" => initialization.network.recurrent = true,
+            "--position-inputs" => initialization.network.position_inputs = true,
+            "--defense-inputs" => initialization.network.defense_inputs = true,
+            "--ammo-inputs" => initialization.network.ammo_inputs = true,
+            "--energy-inputs" => initialization.network.energy_inputs = true,
+            "--signed-turn" => initialization.network.signed_turn = true,
+            "--rays" => {
+                let count = args.next().unwrap_or_default();
+                initialization.network.rays = count
+                    .parse()
+                    .unwrap_or_else(|_| usage_error(&format!("invalid ray count: {}", count)));
+            }
+            "--out" => {
+                out = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("missing value for --out"))
+            }
+            "--save-every" => save_every = parse_value(&arg, args.next()),
+            "--checkpoint" => {
+                checkpoint = Some(
+                    args.next()
+                        .unwrap_or_else(|| usage_error("missing value for --checkpoint")),
+                )
+            }
+            "--resume" => {
+                resume = Some(
+                    args.next()
+                        .unwrap_or_else(|| usage_error("missing value for --resume")),
+                )
+            }
+            "--stats" => {
+                stats = Some(
+                    args.next()
+                        .unwrap_or_else(|| usage_error("missing value for --stats")),
+                )
+            }
+            "--sync" => {
+                let url = args.next().unwrap_or_default();
+                sync =
+                    Some(RemoteStore::parse(&url).unwrap_or_else(|e| usage_error(&e.to_string())));
+            }
+            "--seed" => seed = Some(parse_value(&arg, args.next())),
+            _ => usage_error(&format!("unknown option '{}'", arg)),
+        }
+    }
+    if let Err(e) = layout.validate() {
+        usage_error(&e.to_string());
+    }
+    if let Err(e) = initialization.network.validate() {
+        usage_error(&e.to_string());
+    }
+
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let started = Instant::now();
+    let mut pop = match &resume {
+        Some(path) => {
+            if let Some(store) = &sync {
+                fetch_checkpoint(store, path);
+            }
+            resume_population(path)
+        }
+        None => {
+            let mut pop = Population::with_init(layout, population_size, &initialization, &mut rng);
+            pop.opponent_sampling = opponent_sampling;
+            pop.selection = selection;
+            pop.mutation = mutation;
+            pop.fitness_weights = fitness_weights;
+            if surrogate {
+                pop.surrogate = Some(Surrogate::new(&initialization.network));
+            }
+            pop.speciation = speciation;
+            pop.novelty = novelty;
+            pop.pareto = pareto;
+            pop.rollback_threshold = rollback_threshold;
+            or_exit(pop.evaluate(&mut rng));
+            pop
+        }
+    };
+    // The budget suits this machine, so it applies to resumed runs too
+    pop.time_budget = time_budget;
+    if let Some(path) = &checkpoint {
+        or_exit(pop.save(path));
+        eprintln!(
+            "Press Enter to stop after the matches in play, saving the generation to {}",
+            in_flight_path(path)
+        );
+        let progress = Arc::clone(&pop.progress);
+        thread::spawn(move || {
+            // Only a line counts, so runs without a terminal are not stopped by end of input
+            if let Some(Ok(_)) = std::io::stdin().lines().next() {
+                progress.interrupt();
+            }
+        });
+    }
+
+    println!("generation,best_fitness,champion_generation,champion_elo,bot_score,matches,speedup,elapsed_s");
+    let mut stats_log = stats.map(|path| or_exit(StatsLog::open(path)));
+    let mut report = |pop: &Population| {
+        if let Some(log) = &mut stats_log {
+            or_exit(log.record(pop));
+        }
+        println!(
+            "{},{:.1},{},{:.0},{:.2},{},{:.0},{:.1}",
+            pop.generation,
+            pop.best_fitness,
+            pop.champion.as_ref().map_or(0, |c| c.generation),
+            pop.champion.as_ref().map_or(0.0, |c| c.rating),
+            pop.bot_scores
+                .map_or(0.0, |s| s.iter().sum::<f32>() / s.len() as f32),
+            pop.matches_played,
+            pop.speedup(),
+            started.elapsed().as_secs_f64()
+        );
+    };
+    // A resumed generation was already reported by the run that saved it
+    if resume.is_none() {
+        report(&pop);
+    }
+    for _ in 0..generations {
+        match pop.advance(&mut rng) {
+            Err(DuelError::Interrupted { .. }) => {
+                if let (Some(path), Some(partial)) = (&checkpoint, &pop.partial) {
+                    save_in_flight(partial, path);
+                    mirror(&sync, &in_flight_path(path));
+                }
+                break;
+            }
+            result => or_exit(result),
+        }
+        if pop.rollbacks > 0 {
+            eprintln!(
+                "generation {}: fitness cliff, bred again {} times",
+                pop.generation, pop.rollbacks
+            );
+        }
+        if pop.restarted {
+            eprintln!(
+                "generation {}: no new best in {} generations, reseeded half the population",
+                pop.generation, pop.selection.restart_after
+            );
+        }
+        if pop.mutation.self_adaptation > 0.0 {
+            let scale = pop.mutation_scale();
+            eprintln!(
+                "generation {}: mutation rate x{:.2}, strength x{:.2}",
+                pop.generation, scale.rate, scale.strength
+            );
+        }
+        // The schedule the next generation will be bred under
+        if let Some((strength, tournament)) = pop.annealed_selection() {
+            eprintln!(
+                "generation {}: annealed mutation strength x{:.2}, tournament size {}",
+                pop.generation, strength, tournament
+            );
+        }
+        if let Some(newest) = pop.hall_of_fame.entries.last() {
+            for &badge in &newest.badges {
+                if pop.hall_of_fame.first_earned(badge) == Some(newest.generation) {
+                    eprintln!(
+                        "generation {}: first {} badge of the run",
+                        newest.generation,
+                        badge.name()
+                    );
+                }
+            }
+        }
+        report(&pop);
+        if save_every > 0 && pop.generation.is_multiple_of(save_every) {
+            save_champion(&pop, &out);
+            mirror(&sync, &out);
+        }
+        if let Some(path) = &checkpoint {
+            or_exit(pop.save(path));
+            // A generation in flight at the last stop is done now
+            let _ = std::fs::remove_file(in_flight_path(path));
+            mirror(&sync, path);
+        }
+        // Asked to stop after the last match of the generation
+        if pop.progress.is_interrupted() {
+            break;
+        }
+    }
+    save_champion(&pop, &out);
+    mirror(&sync, &out);
+    println!("Saved champion to {}", out);
+}
+
+/// Upload a file just saved to the run's remote store, if it has one. A
+/// store that cannot be reached only warns, so it never stops a long run.
+fn mirror(store: &Option<RemoteStore>, path: &str) {
+    if let Some(store) = store {
+        if let Err(e) = store.upload(path) {
+            eprintln!(
+                "warning: could not mirror {} to {}: {}",
+                path,
+                store.url(),
+                e
+            );
+        }
+    }
+}
+
+/// Download the checkpoint `path` and the generation it had in flight from
+/// the run's remote store, unless the checkpoint is already here
+fn fetch_checkpoint(store: &RemoteStore, path: &str) {
+    if Path::new(path).exists() {
+        return;
+    }
+    for file in [path.to_string(), in_flight_path(path)] {
+        let name = Path::new(&file)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_else(|| usage_error(&format!("invalid checkpoint path {}", file)));
+        if or_exit(store.download(name, &file)) {
+            eprintln!("Fetched {} from {}", file, store.url());
+        }
+    }
+}
+
+/// Population saved by `train --checkpoint`, with the generation a stop cut
+/// short if it was saved next to it
+fn resume_population(path: &str) -> Population {
+    let mut pop = Population::load(path)
+        .unwrap_or_else(|e| usage_error(&format!("cannot resume from {}: {}", path, e)));
+    eprintln!("Resumed {} at generation {}", path, pop.generation);
+    let in_flight = in_flight_path(path);
+    if Path::new(&in_flight).exists() {
+        match PartialEvaluation::load(&in_flight, &pop) {
+            Ok(partial) => {
+                eprintln!(
+                    "Continuing generation {} with {} of {} genomes already evaluated",
+                    partial.generation,
+                    partial.evaluated(),
+                    partial.genomes.len()
+                );
+                pop.partial = Some(partial);
+            }
+            Err(e) => eprintln!("Ignoring {}: {}", in_flight, e),
+        }
+    }
+    pop
+}
+
+/// Write the generation a stop cut short next to checkpoint `path`
+fn save_in_flight(partial: &PartialEvaluation, path: &str) {
+    let in_flight = in_flight_path(path);
+    or_exit(partial.save(&in_flight));
+    eprintln!(
+        "Saved generation {} with {} of {} genomes evaluated to {}",
+        partial.generation,
+        partial.evaluated(),
+        partial.genomes.len(),
+        in_flight
+    );
+}
+
+fn sweep(mut args: impl Iterator<Item = String>) {
+    let mut runs = 5;
+    let mut first_seed: u64 = 1;
+    let mut parallel = 1;
+    let mut dir = String::from("sweep");
+    let mut train_args = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" => runs = parse_value(&arg, args.next()),
+            "--seed" => first_seed = parse_value(&arg, args.next()),
+            "--parallel" => parallel = parse_value::<usize>(&arg, args.next()).max(1),
+            "--dir" => {
+                dir = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("missing value for --dir"))
+            }
+            "--stats" | "--out" => usage_error(&format!("{} is set per run by the sweep", arg)),
+            _ => train_args.push(arg),
+        }
+    }
+    if runs == 0 {
+        usage_error("--runs must be at least 1");
+    }
+    let dir = Path::new(&dir);
+    or_exit(std::fs::create_dir_all(dir).map_err(DuelError::from));
+    let exe = or_exit(std::env::current_exe().map_err(DuelError::from));
+
+    let seeds: Vec<u64> = (0..runs as u64).map(|k| first_seed + k).collect();
+    let stats_path = |seed: u64| dir.join(format!("seed-{}.csv", seed));
+    let started = Instant::now();
+    for batch in seeds.chunks(parallel) {
+        let mut children = Vec::new();
+        for &seed in batch {
+            // The stats log appends, so a rerun into the same directory starts afresh
+            let _ = std::fs::remove_file(stats_path(seed));
+            let log = or_exit(
+                File::create(dir.join(format!("seed-{}.log", seed))).map_err(DuelError::from),
+            );
+            let child = Command::new(&exe)
+                .arg("train")
+                .args(&train_args)
+                .arg("--seed")
+                .arg(seed.to_string())
+                .arg("--stats")
+                .arg(stats_path(seed))
+                .arg("--out")
+                .arg(dir.join(format!("seed-{}.genome", seed)))
+                .stdout(Stdio::from(log))
+                .spawn();
+            children.push((seed, or_exit(child.map_err(DuelError::from))));
+        }
+        for (seed, mut child) in children {
+            let status = or_exit(child.wait().map_err(DuelError::from));
+            if !status.success() {
+                eprintln!("error: run with seed {} failed ({})", seed, status);
+                process::exit(1);
+            }
+            eprintln!(
+                "Run with seed {} finished ({:.1}s)",
+                seed,
+                started.elapsed().as_secs_f64()
+            );
+        }
+    }
+
+    let curves: Vec<_> = seeds
+        .iter()
+        .map(|&seed| or_exit(stats::read_csv(stats_path(seed))))
+        .collect();
+    println!("{}", sweep::CSV_HEADER);
+    let points = sweep::aggregate(&curves);
+    for point in &points {
+        println!("{}", point.csv_row());
+    }
+    if let Some(last) = points.last() {
+        println!(
+            "Best fitness after generation {}: {:.1} ± {:.1} over {} runs",
+            last.generation, last.best_mean, last.best_std, last.runs
+        );
+    }
+}
+
+fn save_champion(pop: &Population, path: &str) {
+    if let Err(e) = pop.save_champion(path) {
+        eprintln!("error: failed to write {}: {}", path, e);
+        process::exit(1);
+    }
+}
+
+/// Unwrap a library result, reporting the error and exiting on failure
+fn or_exit<T>(result: Result<T, DuelError>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    })
+}
+
+fn merge(mut args: impl Iterator<Item = String>) {
+    let mut paths = Vec::new();
+    let mut top = 25.0;
+    let mut population_size = None;
+    let mut out = String::from("merged.checkpoint");
+    let mut seed = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--top" => top = parse_value(&arg, args.next()),
+            "--population" => population_size = Some(parse_value(&arg, args.next())),
+            "--out" => {
+                out = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("missing value for --out"))
+            }
+            "--seed" => seed = Some(parse_value(&arg, args.next())),
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option '{}'", arg)),
+            _ => paths.push(arg),
+        }
+    }
+    let [path_a, path_b] = <[String; 2]>::try_from(paths)
+        .unwrap_or_else(|_| usage_error("merge needs exactly two checkpoint files"));
+    if !(0.0..=100.0).contains(&top) {
+        usage_error("--top must be a percentage between 0 and 100");
+    }
+
+    let a = or_exit(Population::load(&path_a));
+    let b = or_exit(Population::load(&path_b));
+    let size = population_size.unwrap_or(a.genomes.len());
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let pop = or_exit(merge::merge(a, &b, top / 100.0, size, &mut rng));
+    println!(
+        "Merged {} genomes, best fitness {:.1}, {} matches",
+        pop.genomes.len(),
+        pop.best_fitness,
+        pop.matches_played
+    );
+    or_exit(pop.save(&out));
+    println!("Saved checkpoint to {}", out);
+}
+
+fn probe(mut args: impl Iterator<Item = String>) {
+    let mut genome_path = String::from("champion.genome");
+    let mut magnitudes = vec![0.0, 0.5, 1.0, 2.0, 4.0, 8.0];
+    let mut directions = 8;
+    let mut matches = 2;
+    let mut gauntlet_generations = 5;
+    let mut gauntlet_size = 8;
+    let mut seed = None;
+    let mut allow_mismatch = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--genome" => {
+                genome_path = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("missing value for --genome"))
+            }
+            "--magnitudes" => {
+                let list = args.next().unwrap_or_default();
+                magnitudes = list
+                    .split(',')
+                    .map(|m| parse_value::<f32>(&arg, Some(m.trim().to_string())))
+                    .collect();
+                if let Some(&bad) = magnitudes.iter().find(|m| !m.is_finite() || **m < 0.0) {
+                    usage_error(&format!("invalid magnitude {}", bad));
+                }
+            }
+            "--directions" => directions = parse_value(&arg, args.next()),
+            "--matches" => matches = parse_value(&arg, args.next()),
+            "--gauntlet-generations" => gauntlet_generations = parse_value(&arg, args.next()),
+            "--gauntlet-size" => gauntlet_size = parse_value(&arg, args.next()),
+            "--seed" => seed = Some(parse_value(&arg, args.next())),
+            "--allow-config-mismatch" => allow_mismatch = true,
+            _ => usage_error(&format!("unknown option '{}'", arg)),
+        }
+    }
+
+    let (genome, generation) = load_genome(&genome_path, allow_mismatch);
+    println!("Probing {} (generation {})", genome_path, generation);
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let layout = ArenaLayout::default();
+
+    println!(
+        "Warming up gauntlet ({} generations)...",
+        gauntlet_generations
+    );
+    let gauntlet = or_exit(Gauntlet::warm_up(
+        &layout,
+        gauntlet_generations,
+        gauntlet_size,
+        &mut rng,
+    ));
+
+    println!("magnitude,per_weight,mean_score,min_score,max_score");
+    let curve = landscape::probe(
+        &genome,
+        &gauntlet,
+        &layout,
+        &magnitudes,
+        directions,
+        matches,
+        &mut rng,
+    );
+    for point in &curve {
+        println!(
+            "{},{:.4},{:.3},{:.3},{:.3}",
+            point.magnitude,
+            point.per_weight(),
+            point.mean,
+            point.min,
+            point.max
+        );
+    }
+}
+
+fn asymmetry(mut args: impl Iterator<Item = String>) {
+    let mut genome_path = String::from("champion.genome");
+    let mut constant = AsymmetricConstant::FireCooldown;
+    let mut values = None;
+    let mut matches = 4;
+    let mut gauntlet_generations = 5;
+    let mut gauntlet_size = 8;
+    let mut seed = None;
+    let mut allow_mismatch = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--genome" => {
+                genome_path = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("missing value for --genome"))
+            }
+            "--constant" => {
+                constant = or_exit(AsymmetricConstant::parse(&args.next().unwrap_or_default()))
+            }
+            "--values" => {
+                let list = args.next().unwrap_or_default();
+                values = Some(
+                    list.split(',')
+                        .map(|v| parse_value::<f32>(&arg, Some(v.trim().to_string())))
+                        .collect::<Vec<_>>(),
+                );
+            }
+            "--matches" => matches = parse_value(&arg, args.next()),
+            "--gauntlet-generations" => gauntlet_generations = parse_value(&arg, args.next()),
+            "--gauntlet-size" => gauntlet_size = parse_value(&arg, args.next()),
+            "--seed" => seed = Some(parse_value(&arg, args.next())),
+            "--allow-config-mismatch" => allow_mismatch = true,
+            _ => usage_error(&format!("unknown option '{}'", arg)),
+        }
+    }
+    let values = values.unwrap_or_else(|| match constant {
+        AsymmetricConstant::FireCooldown => vec![0.5, 0.75, 1.0, 1.25, 1.5, 2.0],
+        AsymmetricConstant::ProjectileCap => vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0],
+    });
+    // Reject bad values before the gauntlet warms up
+    for &value in &values {
+        or_exit(constant.handicap(value));
+    }
+
+    let (genome, generation) = load_genome(&genome_path, allow_mismatch);
+    println!(
+        "Varying {} on {} (generation {}), stock value {}",
+        constant.name(),
+        genome_path,
+        generation,
+        constant.stock()
+    );
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let layout = ArenaLayout::default();
+
+    println!(
+        "Warming up gauntlet ({} generations)...",
+        gauntlet_generations
+    );
+    let gauntlet = or_exit(Gauntlet::warm_up(
+        &layout,
+        gauntlet_generations,
+        gauntlet_size,
+        &mut rng,
+    ));
+
+    println!("value,win_rate,draw_rate");
+    let curve = or_exit(asymmetry::measure(
+        &genome, &gauntlet, &layout, constant, &values, matches, &mut rng,
+    ));
+    for point in &curve {
+        println!(
+            "{},{:.3},{:.3}",
+            point.value, point.win_rate, point.draw_rate
+        );
+    }
+}
+
+fn scaling(mut args: impl Iterator<Item = String>) {
+    let mut genome_path = String::from("champion.genome");
+    let mut arenas: Vec<ArenaSize> = [0.5, 0.75, 1.25, 1.5, 2.0]
+        .into_iter()
+        .map(ArenaSize::scaled)
+        .collect();
+    let mut matches = 4;
+    let mut gauntlet_generations = 5;
+    let mut gauntlet_size = 8;
+    let mut layout = ArenaLayout::default();
+    let mut seed = None;
+    let mut allow_mismatch = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--genome" => {
+                genome_path = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("missing value for --genome"))
+            }
+            "--config" => {
+                let path = args.next().unwrap_or_default();
+                let config = Config::load(&path)
+                    .unwrap_or_else(|e| usage_error(&format!("cannot load {}: {}", path, e)));
+                layout = config.layout;
+            }
+            _ if layout_option(&arg, &mut args, &mut layout) => {}
+            "--scales" => {
+                let list = args.next().unwrap_or_default();
+                arenas = list
+                    .split(',')
+                    .map(|f| ArenaSize::scaled(parse_value(&arg, Some(f.trim().to_string()))))
+                    .collect();
+            }
+            "--arenas" => {
+                let list = args.next().unwrap_or_default();
+                arenas = list
+                    .split(',')
+                    .map(|spec| or_exit(ArenaSize::parse(spec)))
+                    .collect();
+            }
+            "--matches" => matches = parse_value(&arg, args.next()),
+            "--gauntlet-generations" => gauntlet_generations = parse_value(&arg, args.next()),
+            "--gauntlet-size" => gauntlet_size = parse_value(&arg, args.next()),
+            "--seed" => seed = Some(parse_value(&arg, args.next())),
+            "--allow-config-mismatch" => allow_mismatch = true,
+            _ => usage_error(&format!("unknown option '{}'", arg)),
+        }
+    }
+    // Reject bad sizes before the gauntlet warms up
+    for arena in &arenas {
+        or_exit(arena.validate());
+    }
+
+    let (genome, generation) = load_genome(&genome_path, allow_mismatch);
+    println!("Scaling {} (generation {})", genome_path, generation);
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+
+    println!(
+        "Warming up gauntlet ({} generations)...",
+        gauntlet_generations
+    );
+    let gauntlet = or_exit(Gauntlet::warm_up(
+        &layout,
+        gauntlet_generations,
+        gauntlet_size,
+        &mut rng,
+    ));
+
+    let report = or_exit(scaling::measure(
+        &genome, &gauntlet, &layout, &arenas, matches, &mut rng,
+    ));
+    println!("width,height,scale,score,degradation");
+    let stock = layout.arena;
+    println!(
+        "{},{},{:.3},{:.3},{:.3}",
+        stock.width,
+        stock.height,
+        stock.scale(),
+        report.stock,
+        0.0
+    );
+    for point in &report.points {
+        println!(
+            "{},{},{:.3},{:.3},{:.3}",
+            point.arena.width,
+            point.arena.height,
+            point.arena.scale(),
+            point.score,
+            point.degradation
+        );
+    }
+    println!("Worst degradation: {:.3}", report.worst_degradation());
+}
+
+fn ablate(mut args: impl Iterator<Item = String>) {
+    let mut genome_path = String::from("champion.genome");
+    let mut matches = 4;
+    let mut gauntlet_generations = 5;
+    let mut gauntlet_size = 8;
+    let mut seed = None;
+    let mut allow_mismatch = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--genome" => {
+                genome_path = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("missing value for --genome"))
+            }
+            "--matches" => matches = parse_value(&arg, args.next()),
+            "--gauntlet-generations" => gauntlet_generations = parse_value(&arg, args.next()),
+            "--gauntlet-size" => gauntlet_size = parse_value(&arg, args.next()),
+            "--seed" => seed = Some(parse_value(&arg, args.next())),
+            "--allow-config-mismatch" => allow_mismatch = true,
+            _ => usage_error(&format!("unknown option '{}'", arg)),
+        }
+    }
+
+    let (genome, generation) = load_genome(&genome_path, allow_mismatch);
+    println!("Ablating {} (generation {})", genome_path, generation);
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let layout = ArenaLayout::default();
+
+    println!(
+        "Warming up gauntlet ({} generations)...",
+        gauntlet_generations
+    );
+    let gauntlet = or_exit(Gauntlet::warm_up(
+        &layout,
+        gauntlet_generations,
+        gauntlet_size,
+        &mut rng,
+    ));
+
+    let (baseline, importance) = ablation::ablate(&genome, &gauntlet, &layout, matches, &mut rng);
+    println!("Intact gauntlet score {:.3}", baseline);
+    println!("input,name,score,drop");
+    for entry in &importance {
+        println!(
+            "{},{},{:.3},{:.3}",
+            entry.input, entry.name, entry.score, entry.drop
+        );
+    }
+}
+
+fn rank(mut args: impl Iterator<Item = String>) {
+    let mut dir = None;
+    let mut out = String::from("ranking.csv");
+    let mut matches = 4;
+    let mut gauntlet_generations = 5;
+    let mut gauntlet_size = 8;
+    let mut seed = None;
+    let mut allow_mismatch = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out = parse_value(&arg, args.next()),
+            "--matches" => matches = parse_value(&arg, args.next()),
+            "--gauntlet-generations" => gauntlet_generations = parse_value(&arg, args.next()),
+            "--gauntlet-size" => gauntlet_size = parse_value(&arg, args.next()),
+            "--seed" => seed = Some(parse_value(&arg, args.next())),
+            "--allow-config-mismatch" => allow_mismatch = true,
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option '{}'", arg)),
+            _ if dir.is_none() => dir = Some(arg),
+            _ => usage_error("rank takes one genome directory"),
+        }
+    }
+    let dir = dir.unwrap_or_else(|| usage_error("rank needs a genome directory"));
+
+    let mut paths: Vec<_> = or_exit(std::fs::read_dir(&dir).map_err(DuelError::from))
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "genome"))
+        .collect();
+    paths.sort();
+    let check = if allow_mismatch {
+        ConfigCheck::Ignore
+    } else {
+        ConfigCheck::Require
+    };
+    let genomes: Vec<(String, Genome, usize)> = paths
+        .iter()
+        .filter_map(|path| {
+            let name = path.display().to_string();
+            match Genome::load_with(path, check) {
+                Ok((genome, generation)) => Some((name, genome, generation)),
+                Err(e) => {
+                    eprintln!("warning: skipping {}: {}", name, e);
+                    None
+                }
+            }
+        })
+        .collect();
+    if genomes.is_empty() {
+        eprintln!("error: no loadable .genome files in {}", dir);
+        process::exit(1);
+    }
+
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let layout = ArenaLayout::default();
+    eprintln!(
+        "Warming up gauntlet ({} generations)...",
+        gauntlet_generations
+    );
+    let gauntlet = or_exit(Gauntlet::warm_up(
+        &layout,
+        gauntlet_generations,
+        gauntlet_size,
+        &mut rng,
+    ));
+
+    // Every genome plays the same match seeds, so luck doesn't reorder them
+    let match_seed: u64 = rng.gen();
+    let total = genomes.len();
+    let mut ranked: Vec<_> = genomes
+        .into_iter()
+        .enumerate()
+        .map(|(k, (name, genome, generation))| {
+            eprintln!("[{}/{}] {}", k + 1, total, name);
+            let mut rng = StdRng::seed_from_u64(match_seed);
+            let score = gauntlet.score(&genome, &layout, matches, &mut rng);
+            let bots = bots::benchmark(&genome, &layout, matches, &mut rng);
+            (name, genome, generation, score, bots)
+        })
+        .collect();
+    let mean = |bots: &[f32]| bots.iter().sum::<f32>() / bots.len() as f32;
+    ranked.sort_by(|a, b| b.3.total_cmp(&a.3).then(mean(&b.4).total_cmp(&mean(&a.4))));
+
+    let written = File::create(&out).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        write!(writer, "rank,genome,generation,network,gauntlet,bots")?;
+        for bot in Bot::ALL {
+            write!(writer, ",{}", bot.name())?;
+        }
+        writeln!(writer)?;
+        for (k, (name, genome, generation, score, bots)) in ranked.iter().enumerate() {
+            write!(
+                writer,
+                // Network specs list hidden widths with commas
+                "{},\"{}\",{},\"{}\",{:.3},{:.3}",
+                k + 1,
+                name,
+                generation,
+                genome.network.spec(),
+                score,
+                mean(bots)
+            )?;
+            for share in bots {
+                write!(writer, ",{:.3}", share)?;
+            }
+            writeln!(writer)?;
+        }
+        writer.flush()
+    });
+    match written {
+        Ok(()) => println!("Ranked {} genomes into {}", ranked.len(), out),
+        Err(e) => {
+            eprintln!("error: failed to write {}: {}", out, e);
+            process::exit(1);
+        }
+    }
+    for (k, (name, _, _, score, _)) in ranked.iter().take(3).enumerate() {
+        println!("  {}. {} ({:.3})", k + 1, name, score);
+    }
+}
+
+fn duel(mut args: impl Iterator<Item = String>) {
+    let mut paths = Vec::new();
+    let mut matches = 20;
+    let mut seed = None;
+    let mut allow_mismatch = false;
+    let mut pacing = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--matches" => matches = parse_value(&arg, args.next()),
+            "--seed" => seed = Some(parse_value(&arg, args.next())),
+            "--allow-config-mismatch" => allow_mismatch = true,
+            "--pacing" => pacing = true,
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option '{}'", arg)),
+            _ => paths.push(arg),
+        }
+    }
+    let [path_a, path_b] = <[String; 2]>::try_from(paths)
+        .unwrap_or_else(|_| usage_error("duel needs exactly two genome files"));
+
+    // Handshake: both sides must agree with this build on the game constants
+    let (a, _) = load_genome(&path_a, allow_mismatch);
+    let (b, _) = load_genome(&path_b, allow_mismatch);
+    let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+    let layout = ArenaLayout::default();
+    let weights = FitnessWeights::default();
+
+    let mut wins = [0; 2];
+    let mut draws = 0;
+    let mut stalled = 0.0;
+    for m in 0..matches {
+        // Alternate sides so neither genome always starts as ship 0
+        let swapped = m % 2 == 1;
+        let (first, second) = if swapped { (&b, &a) } else { (&a, &b) };
+        let result = if pacing {
+            let (result, timeline) =
+                run_match_with_timeline(first, second, &layout, &weights, &mut rng);
+            let share = timeline.stall_fraction();
+            println!(
+                "match {:>3}: {:>3.0}% stalled {}",
+                m + 1,
+                share * 100.0,
+                timeline.sparkline()
+            );
+            stalled += share;
+            result
+        } else {
+            run_match(first, second, &layout, &weights, &mut rng)
+        };
+        match result.winner {
+            Some(ship) => wins[ship ^ swapped as usize] += 1,
+            None => draws += 1,
+        }
+    }
+    println!("{} wins: {}", path_a, wins[0]);
+    println!("{} wins: {}", path_b, wins[1]);
+    println!("draws: {}", draws);
+    if pacing && matches > 0 {
+        println!("stalled: {:.0}%", stalled / matches as f32 * 100.0);
+    }
+}
+
+fn watermark(mut args: impl Iterator<Item = String>) {
+    let mut path = None;
+    let mut owner = None;
+    let mut out = None;
+    let mut check = false;
+    let mut allow_mismatch = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--owner" => owner = Some(parse_value::<String>(&arg, args.next())),
+            "--out" => out = Some(parse_value::<String>(&arg, args.next())),
+            "--check" => check = true,
+            "--allow-config-mismatch" => allow_mismatch = true,
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option '{}'", arg)),
+            _ if path.is_none() => path = Some(arg),
+            _ => usage_error("watermark takes one genome file"),
+        }
+    }
+    let path = path.unwrap_or_else(|| usage_error("watermark needs a genome file"));
+    let owner = owner.unwrap_or_else(|| usage_error("watermark needs --owner"));
+
+    let (mut genome, generation) = load_genome(&path, allow_mismatch);
+    if check {
+        let rate = watermark::match_rate(&genome, &owner);
+        println!("{}: {:.1}% of {}'s mark", path, rate * 100.0, owner);
+        if !watermark::is_marked_by(&genome, &owner) {
+            process::exit(1);
+        }
+        return;
+    }
+    watermark::embed(&mut genome, &owner);
+    let out = out.unwrap_or(path);
+    or_exit(genome.save(&out, generation));
+    println!("Marked {} for {}", out, owner);
+}
+
+/// Load a genome, refusing one saved under other game constants unless `allow_mismatch`
+fn load_genome(path: &str, allow_mismatch: bool) -> (Genome, usize) {
+    let check = if allow_mismatch {
+        ConfigCheck::Ignore
+    } else {
+        ConfigCheck::Require
+    };
+    Genome::load_with(path, check).unwrap_or_else(|e| {
+        match e {
+            DuelError::ConfigMismatch { .. } => eprintln!(
+                "error: {}: {}; pass --allow-config-mismatch to use it anyway",
+                path, e
+            ),
+            _ => eprintln!("error: {}: {}", path, e),
+        }
+        process::exit(1);
+    })
+}
+
+fn columns(mut args: impl Iterator<Item = String>) {
+    let mut path = None;
+    let mut out = String::from("columns");
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out = parse_value(&arg, args.next()),
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option '{}'", arg)),
+            _ if path.is_none() => path = Some(arg),
+            _ => usage_error("columns takes one replay file"),
+        }
+    }
+    let path = path.unwrap_or_else(|| usage_error("columns needs a replay file"));
+
+    let replay = or_exit(Replay::load(&path));
+    let rows = or_exit(columns::export_replay(&replay, &out));
+    println!("Wrote {} steps of {} to {}", rows, path, out);
+}
+
+fn fetch(mut args: impl Iterator<Item = String>) {
+    let mut store = None;
+    let mut names = Vec::new();
+    let mut dir = String::from(".");
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dir" => dir = parse_value(&arg, args.next()),
+            _ if arg.starts_with("--") => usage_error(&format!("unknown option '{}'", arg)),
+            _ if store.is_none() => store = Some(or_exit(RemoteStore::parse(&arg))),
+            _ => names.push(arg),
+        }
+    }
+    let store = store.unwrap_or_else(|| usage_error("fetch needs a store URL"));
+    if names.is_empty() {
+        usage_error("fetch needs the names of the files to download");
+    }
+
+    for name in &names {
+        let local = Path::new(&dir).join(name);
+        if or_exit(store.download(name, &local)) {
+            println!("Fetched {} to {}", name, local.display());
+        } else {
+            eprintln!("error: {} has no file {}", store.url(), name);
+            process::exit(1);
+        }
+    }
+}
+
+fn profile(mut args: impl Iterator<Item = String>) {
+    let mut out = String::from("profile.folded");
+    let mut trace = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => {
+                out = args
+                    .next()
+                    .unwrap_or_else(|| usage_error("missing value for --out"))
+            }
+            "--trace" => {
+                trace = Some(
+                    args.next()
+                        .unwrap_or_else(|| usage_error("missing value for --trace")),
+                )
+            }
+            _ => usage_error(&format!("unknown option '{}'", arg)),
+        }
+    }
+    if !profiling::ENABLED {
+        eprintln!("error: duel-cli was built without profiling; rebuild with --features profiling");
+        process::exit(1);
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut pop = Population::new(ArenaLayout::default(), &mut rng);
+    or_exit(pop.evaluate(&mut rng));
+
+    let stacks = profiling::take_folded();
+    let total: u64 = stacks.iter().map(|(_, nanos)| nanos).sum();
+    let mut by_frame: Vec<(&str, u64)> = Vec::new();
+    for (stack, nanos) in &stacks {
+        let frame = stack.rsplit(';').next().unwrap_or(stack);
+        match by_frame.iter_mut().find(|(name, _)| *name == frame) {
+            Some((_, sum)) => *sum += nanos,
+            None => by_frame.push((frame, *nanos)),
+        }
+    }
+    by_frame.sort_by_key(|&(_, nanos)| std::cmp::Reverse(nanos));
+    println!(
+        "Self time over one generation ({:.2}s):",
+        total as f64 / 1e9
+    );
+    for (frame, nanos) in &by_frame {
+        println!(
+            "  {:<24} {:>8.1} ms  {:>5.1}%",
+            frame,
+            *nanos as f64 / 1e6,
+            100.0 * *nanos as f64 / total.max(1) as f64
+        );
+    }
+
+    let written = File::create(&out).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        profiling::write_folded(&stacks, &mut writer)?;
+        writer.flush()
+    });
+    match written {
+        Ok(()) => println!("Wrote folded stacks to {}", out),
+        Err(e) => {
+            eprintln!("error: failed to write {}: {}", out, e);
+            process::exit(1);
+        }
+    }
+
+    let Some(trace) = trace else {
+        return;
+    };
+    let events = profiling::take_trace();
+    let written = File::create(&trace).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        profiling::write_chrome_trace(&events, &mut writer)?;
+        writer.flush()
+    });
+    match written {
+        Ok(()) => println!("Wrote {} spans to {}", events.len(), trace),
+        Err(e) => {
+            eprintln!("error: failed to write {}: {}", trace, e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Apply one of the match-rule options shared by the commands that build an
+/// arena, consuming its value; false if `flag` is not one of them
+fn layout_option(
+    flag: &str,
+    args: &mut impl Iterator<Item = String>,
+    layout: &mut ArenaLayout,
+) -> bool {
+    match flag {
+        "--asteroids" => layout.asteroid_count = parse_value(flag, args.next()),
+        "--shield" => layout.physics.shield = parse_value(flag, args.next()),
+        "--shield-regen" => layout.physics.shield_regen = parse_value(flag, args.next()),
+        "--hull" => layout.physics.hull = parse_value(flag, args.next()),
+        "--ammo" => layout.physics.ammo = parse_value(flag, args.next()),
+        "--ship-classes" => {
+            let name = args.next().unwrap_or_default();
+            layout.class_matchups =
+                ClassMatchups::parse(&name).unwrap_or_else(|e| usage_error(&e.to_string()));
+        }
+        "--pickup-interval" => layout.physics.pickup_interval = parse_value(flag, args.next()),
+        "--energy" => layout.physics.energy = true,
+        "--deflector" => layout.physics.deflector = true,
+        "--projectile-collisions" => layout.physics.projectile_collisions = true,
+        "--randomize-flow-field" => layout.randomize_flow_field = true,
+        _ => return false,
+    }
+    true
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    let value = value.unwrap_or_default();
+    value
+        .parse()
+        .unwrap_or_else(|_| usage_error(&format!("invalid value '{}' for {}", value, flag)))
+}
+
+fn usage_error(msg: &str) -> ! {
+    eprintln!("error: {}", msg);
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,197 @@
+use rand::Rng;
+use std::borrow::Borrow;
+
+use crate::game::*;
+use crate::genome::{Genome, NetworkState};
+use crate::simulation::{run_controllers, FitnessWeights};
+
+/// Anything that can fly a ship: evolved genomes, the scripted bots and a
+/// human at the keyboard. Matches and the showcase only talk to ships
+/// through this, so any pilot can face any other.
+pub trait Controller {
+    /// Controls for ship `ship_idx` this tick, laid out like `GameState::update` expects
+    fn act(&mut self, state: &GameState, ship_idx: usize) -> [f32; ACTION_SIZE];
+
+    /// Handling of the ship this controller flies
+    fn loadout(&self) -> Loadout {
+        Loadout::default()
+    }
+
+    /// The evolved network flying, with its recurrent state, for controllers
+    /// that have one
+    fn brain(&self) -> Option<(&Genome, &NetworkState)> {
+        None
+    }
+}
+
+/// A genome, borrowed or owned, flying with its own network state
+pub struct GenomeController<G> {
+    pub genome: G,
+    pub network: NetworkState,
+}
+
+impl<G: Borrow<Genome>> GenomeController<G> {
+    pub fn new(genome: G) -> Self {
+        GenomeController {
+            genome,
+            network: NetworkState::default(),
+        }
+    }
+}
+
+impl<G: Borrow<Genome>> Controller for GenomeController<G> {
+    fn act(&mut self, state: &GameState, ship_idx: usize) -> [f32; ACTION_SIZE] {
+        let genome = self.genome.borrow();
+        genome.evaluate(&genome.sensors(state, ship_idx), &mut self.network)
+    }
+
+    fn loadout(&self) -> Loadout {
+        self.genome.borrow().loadout
+    }
+
+    fn brain(&self) -> Option<(&Genome, &NetworkState)> {
+        Some((self.genome.borrow(), &self.network))
+    }
+}
+
+/// Hand-written opponents of fixed strength, so champions of different runs
+/// and generations can be scored on the same absolute scale
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bot {
+    /// Drifts and never fires
+    Idle,
+    /// Turns in place, firing constantly
+    Spinner,
+    /// Flies straight at the opponent, firing once it is lined up
+    Chaser,
+    /// Circles the opponent at a distance, turning in to take leading shots
+    Orbiter,
+}
+
+/// Distance (px) the orbiter tries to keep from its target
+const ORBIT_RADIUS: f32 = 250.0;
+/// Seconds of every orbiter cycle spent circling; the rest is spent shooting
+const ORBIT_PHASE: (f32, f32) = (1.2, 2.0);
+/// Heading error (radians) within which the bots stop turning and fire
+const AIM_TOLERANCE: f32 = 0.08;
+
+impl Bot {
+    pub const ALL: [Bot; 4] = [Bot::Idle, Bot::Spinner, Bot::Chaser, Bot::Orbiter];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Bot::Idle => "idle",
+            Bot::Spinner => "spinner",
+            Bot::Chaser => "chaser",
+            Bot::Orbiter => "orbiter",
+        }
+    }
+}
+
+impl Controller for Bot {
+    fn act(&mut self, state: &GameState, ship: usize) -> [f32; ACTION_SIZE] {
+        let me = &state.ships[ship];
+        let opponent = state.nearest_opponent(ship);
+        let target = &state.ships[opponent];
+        let dx = toroidal_diff(target.x, me.x, state.arena.width);
+        let dy = toroidal_diff(target.y, me.y, state.arena.height);
+        let bearing = dy.atan2(dx);
+        let (heading, thrust, fire) = match self {
+            Bot::Idle => return [0.0; ACTION_SIZE],
+            Bot::Spinner => return [0.0, 0.0, 1.0, 1.0, 0.0],
+            Bot::Chaser => (bearing, 1.0, true),
+            Bot::Orbiter => {
+                let aim = lead_intercept(state, ship, opponent).map_or(bearing, |i| i.aim_angle);
+                let circling = state.time % ORBIT_PHASE.1 < ORBIT_PHASE.0;
+                let distance = dx.hypot(dy);
+                if circling {
+                    // Fly across the line to the target, closing or opening the range
+                    let correction = ((distance - ORBIT_RADIUS) / ORBIT_RADIUS).clamp(-0.5, 0.5);
+                    (
+                        bearing + std::f32::consts::FRAC_PI_2 - correction,
+                        1.0,
+                        false,
+                    )
+                } else {
+                    (aim, 0.0, true)
+                }
+            }
+        };
+        let error = (heading - me.rotation)
+            .sin()
+            .atan2((heading - me.rotation).cos());
+        let lined_up = error.abs() < AIM_TOLERANCE;
+        [
+            thrust,
+            (error < -AIM_TOLERANCE) as u8 as f32,
+            (error > AIM_TOLERANCE) as u8 as f32,
+            (fire && lined_up && !target.cloaked) as u8 as f32,
+            0.0,
+        ]
+    }
+}
+
+/// Share of the points (win 1, draw 0.5) `genome` takes from `matches`
+/// matches against each bot in `Bot::ALL` order, alternating sides
+pub fn benchmark(
+    genome: &Genome,
+    layout: &ArenaLayout,
+    matches: usize,
+    rng: &mut impl Rng,
+) -> [f32; Bot::ALL.len()] {
+    let weights = FitnessWeights::default();
+    Bot::ALL.map(|bot| {
+        let mut points = 0.0;
+        for m in 0..matches {
+            let side = m % 2;
+            let mut pilot = GenomeController::new(genome);
+            let mut bot = bot;
+            let controllers: [&mut dyn Controller; 2] = if side == 0 {
+                [&mut pilot, &mut bot]
+            } else {
+                [&mut bot, &mut pilot]
+            };
+            let result = run_controllers(controllers, layout, &weights, rng);
+            points += match result.winner {
+                Some(w) if w == side => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+        }
+        points / matches.max(1) as f32
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn bots_behave_to_script_and_the_chaser_beats_the_idle_bot() {
+        let state = GameState::new();
+        assert_eq!(Bot::Idle.act(&state, 0), [0.0; ACTION_SIZE]);
+        assert_eq!(Bot::Spinner.act(&state, 1)[3], 1.0);
+
+        // The chaser turns towards its target and only fires once lined up
+        let mut state = GameState::new();
+        state.ships[0] = Ship::new(400.0, 600.0, 0.0);
+        state.ships[1] = Ship::new(400.0, 300.0, 0.0);
+        let actions = Bot::Chaser.act(&state, 0);
+        assert_eq!((actions[0], actions[1], actions[3]), (1.0, 1.0, 0.0));
+        state.ships[0].rotation = -std::f32::consts::FRAC_PI_2;
+        assert_eq!(Bot::Chaser.act(&state, 0)[3], 1.0);
+
+        let mut rng = StdRng::seed_from_u64(6);
+        let layout = ArenaLayout::default();
+        let weights = FitnessWeights::default();
+        let (mut chaser, mut idle) = (Bot::Chaser, Bot::Idle);
+        let result = run_controllers([&mut chaser, &mut idle], &layout, &weights, &mut rng);
+        assert_eq!(result.winner, Some(0));
+
+        let genome = Genome::random(&mut rng);
+        let scores = benchmark(&genome, &layout, 2, &mut rng);
+        assert!(scores.iter().all(|s| (0.0..=1.0).contains(s)));
+    }
+}
@@ -0,0 +1,112 @@
+use macroquad::prelude::*;
+
+use spaceship_duel::genome::*;
+
+/// Names of the network outputs, in action order
+const OUTPUT_NAMES: [&str; OUTPUT_SIZE] = ["thrust", "left", "right", "fire", "cloak"];
+/// Names of a signed-turn network's outputs
+const SIGNED_TURN_NAMES: [&str; SIGNED_TURN_OUTPUTS] = ["thrust", "turn", "fire", "cloak"];
+/// Room left of the input column for sensor names
+const LABEL_WIDTH: f32 = 120.0;
+/// Room right of the output column for action names
+const OUTPUT_LABEL_WIDTH: f32 = 50.0;
+const PADDING: f32 = 10.0;
+
+/// Draw a genome's feedforward network inside `area`: one column of neurons
+/// per layer, filled by this tick's activation (warm positive, cool
+/// negative), joined by lines as strong as their weights (light positive,
+/// red negative). Recurrent weights are left out. `layers` comes from
+/// `Genome::activations`; `color` outlines the neurons in the ship's color.
+pub fn render_brain(genome: &Genome, layers: &[Vec<f32>], area: Rect, color: Color) {
+    draw_rectangle(
+        area.x,
+        area.y,
+        area.w,
+        area.h,
+        Color::new(0.0, 0.0, 0.0, 0.75),
+    );
+    draw_rectangle_lines(
+        area.x,
+        area.y,
+        area.w,
+        area.h,
+        1.0,
+        Color { a: 0.5, ..color },
+    );
+    if layers.len() < 2 {
+        return;
+    }
+    let left = area.x + PADDING + LABEL_WIDTH;
+    let right = area.x + area.w - PADDING - OUTPUT_LABEL_WIDTH;
+    let column_gap = (right - left) / (layers.len() - 1) as f32;
+    let (top, height) = (area.y + PADDING, area.h - 2.0 * PADDING);
+    let node = |layer: usize, i: usize| {
+        let spacing = height / layers[layer].len() as f32;
+        vec2(
+            left + layer as f32 * column_gap,
+            top + spacing * (i as f32 + 0.5),
+        )
+    };
+
+    // Weights are stored layer by layer, each neuron a row of its incoming
+    // weights followed by its bias
+    let mut idx = 0;
+    for (layer, (fan_in, width)) in genome.network.layers().enumerate() {
+        for j in 0..width {
+            let to = node(layer + 1, j);
+            for (k, &w) in genome.weights[idx..idx + fan_in].iter().enumerate() {
+                let from = node(layer, k);
+                let strength = (w.abs() / WEIGHT_LIMIT).min(1.0) * 0.6;
+                let line_color = if w >= 0.0 {
+                    Color::new(0.9, 0.9, 0.8, strength)
+                } else {
+                    Color::new(1.0, 0.25, 0.2, strength)
+                };
+                draw_line(from.x, from.y, to.x, to.y, 1.0, line_color);
+            }
+            idx += fan_in + 1;
+        }
+    }
+
+    let outputs = layers.len() - 1;
+    for (layer, activations) in layers.iter().enumerate() {
+        let spacing = height / activations.len() as f32;
+        let radius = (spacing * 0.4).min(6.0);
+        for (i, &a) in activations.iter().enumerate() {
+            let at = node(layer, i);
+            // Outputs are squashed to [0, 1] except a signed turn, everything
+            // else to about [-1, 1]
+            let signed = genome.network.signed_turn && i == 1;
+            let a = if layer == outputs && !signed {
+                2.0 * a - 1.0
+            } else {
+                a
+            };
+            let fill = if a >= 0.0 {
+                Color::new(1.0, 0.75, 0.2, a.min(1.0))
+            } else {
+                Color::new(0.3, 0.5, 1.0, (-a).min(1.0))
+            };
+            draw_circle(at.x, at.y, radius, fill);
+            draw_circle_lines(at.x, at.y, radius, 1.0, color);
+        }
+    }
+
+    let label_color = Color::new(0.6, 0.6, 0.6, 1.0);
+    let font_size = (height / layers[0].len() as f32).clamp(8.0, 13.0);
+    for (i, sensor) in genome.network.sensors().enumerate() {
+        let at = node(0, i);
+        let name = INPUT_NAMES[sensor];
+        let width = measure_text(name, None, font_size as u16, 1.0).width;
+        draw_text(name, at.x - width - 9.0, at.y + 4.0, font_size, label_color);
+    }
+    let names: &[&str] = if genome.network.signed_turn {
+        &SIGNED_TURN_NAMES
+    } else {
+        &OUTPUT_NAMES
+    };
+    for (i, name) in names.iter().enumerate() {
+        let at = node(outputs, i);
+        draw_text(name, at.x + 9.0, at.y + 4.0, 13.0, label_color);
+    }
+}
@@ -0,0 +1,1049 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::achievements;
+use crate::archive::{ArchiveEntry, HallOfFame, OpponentSampling};
+use crate::error::{check_range, DuelError};
+use crate::evolution::*;
+use crate::game::*;
+use crate::genome::*;
+use crate::novelty::{NoveltySearch, BEHAVIOR_SIZE};
+use crate::pareto::OBJECTIVE_COUNT;
+use crate::rating::INITIAL_RATING;
+use crate::simulation::FitnessWeights;
+use crate::speciation::Speciation;
+use crate::spectate::Spectator;
+use crate::surrogate::Surrogate;
+
+/// Version written in the header of population checkpoints
+pub const CHECKPOINT_VERSION: u32 = 16;
+const CHECKPOINT_MAGIC: &str = "spaceship-duel checkpoint";
+const IN_FLIGHT_MAGIC: &str = "spaceship-duel in-flight generation";
+
+/// File next to checkpoint `path` that holds a generation cut short
+/// mid-evaluation, bred from the population in the checkpoint
+pub fn in_flight_path(path: &str) -> String {
+    format!("{}.in-flight", path)
+}
+
+/// Copy of checkpoint `path` kept for `generation` under a `Retention`
+pub fn generation_path(path: &str, generation: usize) -> String {
+    format!("{}.gen{}", path, generation)
+}
+
+/// Which per-generation copies of a checkpoint a long run keeps beside it:
+/// the newest `keep_last`, plus every generation divisible by `every`, so
+/// the copies stop growing with the length of the run. Copies are written
+/// uncompressed: zstd or gzip would be the crate's first dependency beyond
+/// macroquad and rand. Pipe old copies through an external compressor if
+/// disk space runs short.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Retention {
+    pub keep_last: usize,
+    pub every: Option<usize>,
+}
+
+impl Retention {
+    /// The spec `parse` turns back into this policy
+    pub fn spec(&self) -> String {
+        match self.every {
+            Some(every) => format!("{}:{}", self.keep_last, every),
+            None => self.keep_last.to_string(),
+        }
+    }
+
+    /// Parse "KEEP_LAST" or "KEEP_LAST:EVERY"
+    pub fn parse(spec: &str) -> Result<Self, DuelError> {
+        let invalid = || DuelError::InvalidValue {
+            what: "checkpoint retention",
+            value: spec.to_string(),
+        };
+        let (keep_last, every) = match spec.split_once(':') {
+            Some((keep_last, every)) => (keep_last, Some(every)),
+            None => (spec, None),
+        };
+        let keep_last = keep_last.parse().map_err(|_| invalid())?;
+        let every = match every {
+            Some(every) => match every.parse() {
+                Ok(0) | Err(_) => return Err(invalid()),
+                Ok(every) => Some(every),
+            },
+            None => None,
+        };
+        Ok(Retention { keep_last, every })
+    }
+
+    /// Whether the copy of `generation` stays once `latest` has been saved
+    pub fn keeps(&self, generation: usize, latest: usize) -> bool {
+        latest.saturating_sub(generation) < self.keep_last
+            || self
+                .every
+                .is_some_and(|every| generation.is_multiple_of(every))
+    }
+}
+
+impl Population {
+    /// Text checkpoint of the whole run: counters, seed, hyperparameters, every
+    /// genome, the hall of fame and the reigning champion. The surrogate model,
+    /// the species and the novelty archive are not saved; they are rebuilt from
+    /// the next generations.
+    /// Neither is the time budget, which belongs to the machine, not the run.
+    pub fn to_checkpoint_string(&self) -> String {
+        let mut out = String::new();
+        let physics = &self.layout.physics;
+        // Writing to a String cannot fail
+        let _ = writeln!(out, "{} {}", CHECKPOINT_MAGIC, CHECKPOINT_VERSION);
+        let _ = writeln!(out, "generation {}", self.generation);
+        let _ = writeln!(out, "seed {}", self.seed);
+        let _ = writeln!(out, "best_fitness {}", self.best_fitness);
+        let _ = writeln!(out, "matches_played {}", self.matches_played);
+        let _ = writeln!(out, "simulated_seconds {}", self.simulated_seconds);
+        let _ = writeln!(out, "eval_seconds {}", self.eval_seconds);
+        let _ = writeln!(
+            out,
+            "projectile_inheritance {}",
+            physics.projectile_inheritance
+        );
+        let _ = writeln!(out, "recoil {}", physics.recoil);
+        let _ = writeln!(
+            out,
+            "defenses {} {} {}",
+            physics.shield, physics.shield_regen, physics.hull
+        );
+        let _ = writeln!(out, "ammo {} {}", physics.ammo, physics.pickup_interval);
+        let _ = writeln!(out, "energy {} {}", physics.energy, physics.deflector);
+        let _ = writeln!(
+            out,
+            "projectile_collisions {}",
+            physics.projectile_collisions
+        );
+        let _ = writeln!(out, "asteroid_count {}", self.layout.asteroid_count);
+        let _ = writeln!(out, "class_matchups {}", self.layout.class_matchups.name());
+        let _ = writeln!(
+            out,
+            "randomize_flow_field {}",
+            self.layout.randomize_flow_field
+        );
+        let _ = writeln!(
+            out,
+            "flow_field {}",
+            flow_field_spec(&self.layout.flow_field)
+        );
+        let _ = writeln!(
+            out,
+            "fitness_weights {}",
+            settings(&self.fitness_weights.values())
+        );
+        let _ = writeln!(out, "mutation {}", settings(&self.mutation.values()));
+        let _ = writeln!(out, "selection {}", settings(&self.selection.values()));
+        let _ = writeln!(out, "opponent_sampling {}", self.opponent_sampling.spec());
+        let _ = writeln!(out, "surrogate {}", self.surrogate.is_some());
+        let _ = writeln!(out, "network {}", self.network().spec());
+        let _ = writeln!(
+            out,
+            "speciation {}",
+            self.speciation
+                .as_ref()
+                .map_or("none".to_string(), |s| s.spec())
+        );
+        let _ = writeln!(
+            out,
+            "novelty {}",
+            self.novelty
+                .as_ref()
+                .map_or("none".to_string(), |n| n.spec())
+        );
+        let _ = writeln!(out, "pareto {}", self.pareto);
+        let _ = writeln!(
+            out,
+            "rollback {}",
+            self.rollback_threshold
+                .map_or("none".to_string(), |t| t.to_string())
+        );
+
+        let _ = writeln!(out, "genomes {}", self.genomes.len());
+        for genome in &self.genomes {
+            let _ = writeln!(out, "{} {}{}", genome.fitness, genome.age, genes(genome));
+        }
+        let _ = writeln!(out, "hall_of_fame {}", self.hall_of_fame.len());
+        for entry in &self.hall_of_fame.entries {
+            let _ = writeln!(
+                out,
+                "{} {} {} {}{}",
+                entry.generation,
+                entry.fitness,
+                entry.rating,
+                achievements::spec(&entry.badges),
+                genes(&entry.genome)
+            );
+        }
+        match &self.champion {
+            Some(entry) => {
+                let _ = writeln!(
+                    out,
+                    "champion {} {} {} {}{}",
+                    entry.generation,
+                    entry.fitness,
+                    entry.rating,
+                    achievements::spec(&entry.badges),
+                    genes(&entry.genome)
+                );
+            }
+            None => out.push_str("champion none\n"),
+        }
+        out
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DuelError> {
+        std::fs::write(path, self.to_checkpoint_string())?;
+        Ok(())
+    }
+
+    /// Save this generation's copy of checkpoint `path` and delete the
+    /// earlier copies `retention` no longer keeps. Returns how many went.
+    pub fn save_retained(&self, path: &str, retention: &Retention) -> Result<usize, DuelError> {
+        self.save(generation_path(path, self.generation))?;
+        let path = Path::new(path);
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(0);
+        };
+        let prefix = format!("{}.gen", name);
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut removed = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let generation = file_name
+                .to_str()
+                .and_then(|f| f.strip_prefix(&prefix))
+                .and_then(|g| g.parse::<usize>().ok());
+            if let Some(generation) = generation {
+                if !retention.keeps(generation, self.generation) {
+                    std::fs::remove_file(entry.path())?;
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Rebuild a population from `to_checkpoint_string` output, validating
+    /// every field
+    pub fn from_checkpoint_str(text: &str) -> Result<Population, DuelError> {
+        let mut reader = Reader {
+            lines: text.lines(),
+        };
+
+        let header = reader.lines.next().unwrap_or_default();
+        let version: u32 = header
+            .strip_prefix(CHECKPOINT_MAGIC)
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| DuelError::InvalidValue {
+                what: "checkpoint header",
+                value: excerpt(header),
+            })?;
+        if version == 0 || version > CHECKPOINT_VERSION {
+            return Err(DuelError::UnsupportedVersion {
+                found: version,
+                supported: CHECKPOINT_VERSION,
+            });
+        }
+
+        let generation: usize = reader.value("generation")?;
+        let seed: u64 = reader.value("seed")?;
+        let best_fitness: f32 = reader.value("best_fitness")?;
+        check_range("best_fitness", best_fitness, f32::MIN, f32::MAX)?;
+        let matches_played: usize = reader.value("matches_played")?;
+        let simulated_seconds: f64 = reader.value("simulated_seconds")?;
+        let eval_seconds: f64 = reader.value("eval_seconds")?;
+        for (what, seconds) in [
+            ("simulated_seconds", simulated_seconds),
+            ("eval_seconds", eval_seconds),
+        ] {
+            check_range(what, seconds as f32, 0.0, f32::MAX)?;
+        }
+
+        let mut layout = ArenaLayout::default();
+        layout.physics.projectile_inheritance = reader.value("projectile_inheritance")?;
+        layout.physics.recoil = reader.value("recoil")?;
+        // Shields and hull points arrived with version 5; older runs were one hit, one kill
+        if version >= 5 {
+            let mut fields = reader.field("defenses")?.split_whitespace();
+            layout.physics.shield = parse_token("shield", fields.next())?;
+            layout.physics.shield_regen = parse_token("shield regeneration", fields.next())?;
+            layout.physics.hull = parse_token("hull", fields.next())?;
+        }
+        // Limited ammo arrived with version 6
+        if version >= 6 {
+            let mut fields = reader.field("ammo")?.split_whitespace();
+            layout.physics.ammo = parse_token("ammo", fields.next())?;
+            layout.physics.pickup_interval = parse_token("pickup interval", fields.next())?;
+        }
+        // Energy and the deflector arrived with version 8
+        if version >= 8 {
+            let mut fields = reader.field("energy")?.split_whitespace();
+            layout.physics.energy = parse_token("energy", fields.next())?;
+            layout.physics.deflector = parse_token("deflector", fields.next())?;
+        }
+        // Projectile collisions arrived with version 14
+        if version >= 14 {
+            layout.physics.projectile_collisions = reader.value("projectile_collisions")?;
+        }
+        layout.asteroid_count = reader.value("asteroid_count")?;
+        // Ship classes arrived with version 7; older runs flew the standard ship only
+        if version >= 7 {
+            layout.class_matchups = ClassMatchups::parse(reader.field("class_matchups")?.trim())?;
+        }
+        layout.randomize_flow_field = reader.value("randomize_flow_field")?;
+        layout.flow_field = parse_flow_field(reader.field("flow_field")?)?;
+        layout.validate()?;
+
+        let mut fitness_weights = FitnessWeights::default();
+        for (name, value) in parse_settings(reader.field("fitness_weights")?)? {
+            fitness_weights.set(name, value)?;
+        }
+        let mut mutation = LayerMutation::default();
+        for (name, value) in parse_settings(reader.field("mutation")?)? {
+            mutation.set(name, value)?;
+        }
+        // Selection settings arrived with version 10; older runs used the defaults
+        let mut selection = Selection::default();
+        if version >= 10 {
+            for (name, value) in parse_settings(reader.field("selection")?)? {
+                selection.set(name, value)?;
+            }
+        }
+        let opponent_sampling = OpponentSampling::parse(reader.field("opponent_sampling")?)?;
+        let surrogate: bool = reader.value("surrogate")?;
+        // Version 1 checkpoints predate configurable networks and always hold the default shape
+        let network = if version >= 2 {
+            NetworkConfig::parse(reader.field("network")?.trim())?
+        } else {
+            NetworkConfig::default()
+        };
+        // Speciation arrived with version 3; older runs never used it
+        let speciation = if version >= 3 {
+            match reader.field("speciation")?.trim() {
+                "none" => None,
+                spec => Some(Speciation::parse(spec)?),
+            }
+        } else {
+            None
+        };
+        // Novelty search arrived with version 11
+        let novelty = if version >= 11 {
+            match reader.field("novelty")?.trim() {
+                "none" => None,
+                spec => Some(NoveltySearch::parse(spec)?),
+            }
+        } else {
+            None
+        };
+        // Pareto selection arrived with version 12
+        let pareto = version >= 12 && reader.value("pareto")?;
+        // The fitness cliff guard arrived with version 13
+        let rollback_threshold = if version >= 13 {
+            match reader.field("rollback")?.trim() {
+                "none" => None,
+                value => {
+                    let threshold = parse_token("rollback threshold", Some(value))?;
+                    check_range("rollback threshold", threshold, 0.0, 1.0)?;
+                    Some(threshold)
+                }
+            }
+        } else {
+            None
+        };
+
+        let genome_count: usize = reader.value("genomes")?;
+        if genome_count < MIN_POPULATION_SIZE {
+            return Err(DuelError::OutOfRange {
+                what: "population size".to_string(),
+                value: genome_count as f64,
+                min: MIN_POPULATION_SIZE as f64,
+                max: f64::INFINITY,
+            });
+        }
+        // Records are read one at a time, so a huge declared count cannot force a huge allocation
+        let mut genomes = Vec::new();
+        for _ in 0..genome_count {
+            let mut fields = reader.record("genome")?.split_whitespace();
+            let fitness: f32 = parse_token("genome fitness", fields.next())?;
+            check_range("genome fitness", fitness, f32::MIN, f32::MAX)?;
+            let age: usize = parse_token("genome age", fields.next())?;
+            let mut genome = parse_genes(version, &network, fields)?;
+            genome.fitness = fitness;
+            genome.age = age;
+            genomes.push(genome);
+        }
+
+        let archive_count: usize = reader.value("hall_of_fame")?;
+        let mut hall_of_fame = HallOfFame::default();
+        for _ in 0..archive_count {
+            let record = reader.record("hall of fame entry")?;
+            hall_of_fame
+                .entries
+                .push(parse_entry(version, &network, record)?);
+        }
+
+        let champion = match reader.field("champion")? {
+            "none" => None,
+            record => Some(parse_entry(version, &network, record)?),
+        };
+
+        Ok(Population {
+            genomes,
+            generation,
+            best_fitness,
+            // The wait for a new best starts over on resume
+            peak_fitness: best_fitness,
+            peak_generation: generation,
+            restarted: false,
+            layout,
+            fitness_weights,
+            mutation,
+            selection,
+            hall_of_fame,
+            champion,
+            champion_changed: false,
+            challenger_score: None,
+            rollback_threshold,
+            rollbacks: 0,
+            archive_win_rate: None,
+            opponent_sampling,
+            surrogate: surrogate.then(|| Surrogate::new(&network)),
+            speciation,
+            novelty,
+            pareto,
+            objectives: Vec::new(),
+            progress: Arc::new(EvalProgress::default()),
+            spectator: Arc::new(Spectator::default()),
+            seed,
+            matches_played,
+            simulated_seconds,
+            eval_seconds,
+            time_budget: None,
+            matches_per_genome: Vec::new(),
+            bot_scores: None,
+            partial: None,
+        })
+    }
+
+    /// Read a checkpoint written by `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<Population, DuelError> {
+        Self::from_checkpoint_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+impl PartialEvaluation {
+    /// Text form of the cut-short generation: its counters, genomes, match
+    /// seeds, which genomes were evaluated and every result they earned.
+    /// Fitness, behaviors and objectives are written at full precision, so
+    /// the resumed generation totals exactly what an unbroken one would.
+    pub fn to_file_string(&self) -> String {
+        let mut out = String::new();
+        let eval = &self.eval;
+        let _ = writeln!(out, "{} {}", IN_FLIGHT_MAGIC, CHECKPOINT_VERSION);
+        let _ = writeln!(out, "generation {}", self.generation);
+        let _ = writeln!(out, "seed {}", self.seed);
+        let _ = writeln!(out, "rollbacks {}", self.rollbacks);
+        let _ = writeln!(out, "restarted {}", self.restarted);
+        let _ = writeln!(out, "genomes {}", self.genomes.len());
+        for genome in &self.genomes {
+            let _ = writeln!(out, "{}{}", genome.age, genes(genome));
+        }
+        let _ = writeln!(
+            out,
+            "schedule {} {}",
+            eval.archive_matches, eval.finish_seed
+        );
+        let seeds: Vec<String> = eval.seeds.iter().map(|s| s.to_string()).collect();
+        let _ = writeln!(out, "seeds {}", seeds.join(" "));
+        let evaluated: String = eval
+            .evaluated
+            .iter()
+            .map(|&e| if e { '1' } else { '0' })
+            .collect();
+        let _ = writeln!(out, "evaluated {}", evaluated);
+        let tally = &eval.tally;
+        let _ = writeln!(
+            out,
+            "tally {} {} {} {}",
+            tally.matches, tally.simulated_seconds, tally.archive_played, tally.archive_wins
+        );
+        let _ = writeln!(out, "scores {}", tally.scores.len());
+        let matches = tally.behaviors.iter().zip(&tally.objectives);
+        for (&(i, fitness), (behavior, objectives)) in tally.scores.iter().zip(matches) {
+            let _ = write!(out, "{} {}", i, fitness);
+            for value in behavior.iter().chain(objectives) {
+                let _ = write!(out, " {}", value);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DuelError> {
+        std::fs::write(path, self.to_file_string())?;
+        Ok(())
+    }
+
+    /// Rebuild a cut-short generation from `to_file_string` output,
+    /// validating every field against `parent`, the checkpointed population
+    /// it was bred from
+    pub fn from_file_str(text: &str, parent: &Population) -> Result<PartialEvaluation, DuelError> {
+        let mut reader = Reader {
+            lines: text.lines(),
+        };
+
+        let header = reader.lines.next().unwrap_or_default();
+        let version: u32 = header
+            .strip_prefix(IN_FLIGHT_MAGIC)
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| DuelError::InvalidValue {
+                what: "in-flight header",
+                value: excerpt(header),
+            })?;
+        if version == 0 || version > CHECKPOINT_VERSION {
+            return Err(DuelError::UnsupportedVersion {
+                found: version,
+                supported: CHECKPOINT_VERSION,
+            });
+        }
+
+        let generation: usize = reader.value("generation")?;
+        if generation != parent.generation + 1 {
+            return Err(DuelError::InvalidValue {
+                what: "in-flight generation, not bred from the checkpoint's",
+                value: generation.to_string(),
+            });
+        }
+        let seed: u64 = reader.value("seed")?;
+        let rollbacks: usize = reader.value("rollbacks")?;
+        let restarted: bool = reader.value("restarted")?;
+
+        let size = parent.genomes.len();
+        let genome_count: usize = reader.value("genomes")?;
+        if genome_count != size {
+            return Err(DuelError::WrongSize {
+                what: "in-flight genomes",
+                expected: size,
+                found: genome_count,
+            });
+        }
+        let network = parent.network();
+        let mut genomes = Vec::with_capacity(size);
+        for _ in 0..size {
+            let mut fields = reader.record("genome")?.split_whitespace();
+            let age: usize = parse_token("genome age", fields.next())?;
+            let mut genome = parse_genes(version, &network, fields)?;
+            genome.age = age;
+            genomes.push(genome);
+        }
+
+        let mut fields = reader.field("schedule")?.split_whitespace();
+        let archive_matches: usize = parse_token("archive matches", fields.next())?;
+        if archive_matches > ARCHIVE_MATCHES_PER_EVAL {
+            return Err(DuelError::OutOfRange {
+                what: "archive matches".to_string(),
+                value: archive_matches as f64,
+                min: 0.0,
+                max: ARCHIVE_MATCHES_PER_EVAL as f64,
+            });
+        }
+        let finish_seed: u64 = parse_token("finish seed", fields.next())?;
+        let seeds = reader
+            .field("seeds")?
+            .split_whitespace()
+            .take(size + 1)
+            .map(|token| parse_token("match seed", Some(token)))
+            .collect::<Result<Vec<u64>, _>>()?;
+        let evaluated = reader
+            .field("evaluated")?
+            .trim()
+            .chars()
+            .take(size + 1)
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(DuelError::InvalidValue {
+                    what: "evaluated flag",
+                    value: c.to_string(),
+                }),
+            })
+            .collect::<Result<Vec<bool>, _>>()?;
+        for (what, found) in [
+            ("match seeds", seeds.len()),
+            ("evaluated flags", evaluated.len()),
+        ] {
+            if found != size {
+                return Err(DuelError::WrongSize {
+                    what,
+                    expected: size,
+                    found,
+                });
+            }
+        }
+
+        let mut fields = reader.field("tally")?.split_whitespace();
+        let mut tally = EvalTally {
+            matches: parse_token("tally matches", fields.next())?,
+            simulated_seconds: parse_token("tally seconds", fields.next())?,
+            archive_played: parse_token("archive matches played", fields.next())?,
+            archive_wins: parse_token("archive matches won", fields.next())?,
+            ..Default::default()
+        };
+        check_range(
+            "tally seconds",
+            tally.simulated_seconds as f32,
+            0.0,
+            f32::MAX,
+        )?;
+        let score_count: usize = reader.value("scores")?;
+        // Records are read one at a time, so a huge declared count cannot force a huge allocation
+        for _ in 0..score_count {
+            let mut fields = reader.record("score")?.split_whitespace();
+            let i: usize = parse_token("scored genome", fields.next())?;
+            if i >= size {
+                return Err(DuelError::OutOfRange {
+                    what: "scored genome".to_string(),
+                    value: i as f64,
+                    min: 0.0,
+                    max: (size - 1) as f64,
+                });
+            }
+            let fitness: f32 = parse_token("score", fields.next())?;
+            check_range("score", fitness, f32::MIN, f32::MAX)?;
+            let mut behavior = [0.0; BEHAVIOR_SIZE];
+            let mut objectives = [0.0; OBJECTIVE_COUNT];
+            for value in behavior.iter_mut().chain(&mut objectives) {
+                *value = parse_token("match measure", fields.next())?;
+                check_range("match measure", *value, f32::MIN, f32::MAX)?;
+            }
+            tally.scores.push((i, fitness));
+            tally.behaviors.push(behavior);
+            tally.objectives.push(objectives);
+        }
+
+        Ok(PartialEvaluation {
+            generation,
+            seed,
+            rollbacks,
+            restarted,
+            genomes,
+            eval: EvalState {
+                seeds,
+                archive_matches,
+                finish_seed,
+                evaluated,
+                tally,
+            },
+        })
+    }
+
+    /// Read a cut-short generation written by `save`, bred from `parent`
+    pub fn load(
+        path: impl AsRef<Path>,
+        parent: &Population,
+    ) -> Result<PartialEvaluation, DuelError> {
+        Self::from_file_str(&std::fs::read_to_string(path)?, parent)
+    }
+}
+
+/// Line-by-line reader over the "key value" layout of a checkpoint
+struct Reader<'a> {
+    lines: std::str::Lines<'a>,
+}
+
+impl<'a> Reader<'a> {
+    /// Rest of the next line, which must start with `key`
+    fn field(&mut self, key: &'static str) -> Result<&'a str, DuelError> {
+        let line = self.lines.next().unwrap_or_default();
+        line.strip_prefix(key)
+            .and_then(|rest| rest.strip_prefix(' '))
+            .ok_or_else(|| DuelError::InvalidValue {
+                what: key,
+                value: excerpt(line),
+            })
+    }
+
+    fn value<T: FromStr>(&mut self, key: &'static str) -> Result<T, DuelError> {
+        let value = self.field(key)?;
+        parse_token(key, Some(value.trim()))
+    }
+
+    /// Next line as an unlabelled record
+    fn record(&mut self, what: &'static str) -> Result<&'a str, DuelError> {
+        self.lines.next().ok_or(DuelError::InvalidValue {
+            what,
+            value: "end of file".to_string(),
+        })
+    }
+}
+
+fn parse_token<T: FromStr>(what: &'static str, token: Option<&str>) -> Result<T, DuelError> {
+    let token = token.unwrap_or_default();
+    token.parse().map_err(|_| DuelError::InvalidValue {
+        what,
+        value: excerpt(token),
+    })
+}
+
+/// Genome from the remaining whitespace-separated "TURN_RATE SMOOTHING
+/// RATE_SCALE STRENGTH_SCALE WEIGHTS..." of a record; before version 4
+/// records hold only the weights, and before version 16 no mutation scale
+fn parse_genes<'a>(
+    version: u32,
+    network: &NetworkConfig,
+    mut tokens: impl Iterator<Item = &'a str>,
+) -> Result<Genome, DuelError> {
+    let loadout = if version >= 4 {
+        let loadout = Loadout {
+            turn_rate: parse_token("turn rate", tokens.next())?,
+            smoothing: parse_token("control smoothing", tokens.next())?,
+        };
+        loadout.validate()?;
+        loadout
+    } else {
+        Loadout::default()
+    };
+    let mutation_scale = if version >= 16 {
+        let scale = MutationScale {
+            rate: parse_token("mutation rate scale", tokens.next())?,
+            strength: parse_token("mutation strength scale", tokens.next())?,
+        };
+        scale.validate()?;
+        scale
+    } else {
+        MutationScale::default()
+    };
+    // One past the expected count, so trailing extra weights are reported
+    let weights = tokens
+        .take(network.genome_size() + 1)
+        .map(|token| parse_token("weight", Some(token)))
+        .collect::<Result<Vec<f32>, _>>()?;
+    let mut genome = Genome::with_network(network.clone(), weights)?;
+    genome.loadout = loadout;
+    genome.mutation_scale = mutation_scale;
+    Ok(genome)
+}
+
+/// "GENERATION FITNESS RATING BADGES GENES..." record of an archived genome;
+/// ratings arrived with version 9 and badges with version 15, and older
+/// entries start from the initial rating with no badges
+fn parse_entry(
+    version: u32,
+    network: &NetworkConfig,
+    record: &str,
+) -> Result<ArchiveEntry, DuelError> {
+    let mut fields = record.split_whitespace();
+    let generation: usize = parse_token("entry generation", fields.next())?;
+    let fitness: f32 = parse_token("entry fitness", fields.next())?;
+    check_range("entry fitness", fitness, f32::MIN, f32::MAX)?;
+    let rating = if version >= 9 {
+        let rating: f32 = parse_token("entry rating", fields.next())?;
+        check_range("entry rating", rating, f32::MIN, f32::MAX)?;
+        rating
+    } else {
+        INITIAL_RATING
+    };
+    let badges = if version >= 15 {
+        achievements::parse_list(fields.next().unwrap_or_default())?
+    } else {
+        Vec::new()
+    };
+    let mut genome = parse_genes(version, network, fields)?;
+    genome.fitness = fitness;
+    Ok(ArchiveEntry {
+        genome,
+        generation,
+        fitness,
+        rating,
+        badges,
+    })
+}
+
+/// " TURN_RATE SMOOTHING RATE_SCALE STRENGTH_SCALE WEIGHTS..." of a genome record
+fn genes(genome: &Genome) -> String {
+    let mut out = String::with_capacity(genome.weights.len() * 12);
+    let _ = write!(
+        out,
+        " {} {} {} {}",
+        genome.loadout.turn_rate,
+        genome.loadout.smoothing,
+        genome.mutation_scale.rate,
+        genome.mutation_scale.strength
+    );
+    for w in &genome.weights {
+        let _ = write!(out, " {}", w);
+    }
+    out
+}
+
+fn settings(values: &[(&str, f32)]) -> String {
+    values
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_settings(text: &str) -> Result<Vec<(&str, f32)>, DuelError> {
+    text.split_whitespace()
+        .map(|setting| {
+            let (name, value) = setting
+                .split_once('=')
+                .ok_or_else(|| DuelError::InvalidValue {
+                    what: "setting",
+                    value: excerpt(setting),
+                })?;
+            Ok((name, parse_token("setting value", Some(value))?))
+        })
+        .collect()
+}
+
+pub(crate) fn flow_field_spec(flow_field: &FlowField) -> String {
+    match *flow_field {
+        FlowField::None => "none".to_string(),
+        FlowField::Wind { ax, ay } => format!("wind {} {}", ax, ay),
+        FlowField::Vortex {
+            x,
+            y,
+            strength,
+            radius,
+        } => format!("vortex {} {} {} {}", x, y, strength, radius),
+        FlowField::Noise {
+            seed,
+            cells,
+            strength,
+        } => format!("noise {} {} {}", seed, cells, strength),
+    }
+}
+
+pub(crate) fn parse_flow_field(spec: &str) -> Result<FlowField, DuelError> {
+    let mut fields = spec.split_whitespace();
+    let kind = fields.next().unwrap_or_default();
+    let mut number = |what| parse_token::<f32>(what, fields.next());
+    let flow_field = match kind {
+        "none" => FlowField::None,
+        "wind" => FlowField::Wind {
+            ax: number("wind x")?,
+            ay: number("wind y")?,
+        },
+        "vortex" => FlowField::Vortex {
+            x: number("vortex x")?,
+            y: number("vortex y")?,
+            strength: number("vortex strength")?,
+            radius: number("vortex radius")?,
+        },
+        "noise" => FlowField::Noise {
+            seed: parse_token("noise seed", fields.next())?,
+            cells: parse_token("noise cells", fields.next())?,
+            strength: parse_token("noise strength", fields.next())?,
+        },
+        _ => {
+            return Err(DuelError::UnknownName {
+                kind: "flow field",
+                name: excerpt(kind),
+            })
+        }
+    };
+    Ok(flow_field)
+}
+
+/// Start of a rejected value, short enough to quote in an error message
+fn excerpt(text: &str) -> String {
+    text.chars().take(40).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::achievements::Badge;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn retention_keeps_the_newest_and_every_nth_generation() {
+        let retention = Retention::parse("3:10").unwrap();
+        assert_eq!(retention.spec(), "3:10");
+        let kept: Vec<usize> = (0..=25).filter(|&g| retention.keeps(g, 25)).collect();
+        assert_eq!(kept, vec![0, 10, 20, 23, 24, 25]);
+
+        let newest = Retention::parse("2").unwrap();
+        assert_eq!(newest.every, None);
+        assert!(newest.keeps(9, 10) && !newest.keeps(8, 10));
+        for spec in ["", "x", "2:0", "2:", "-1:5"] {
+            assert!(Retention::parse(spec).is_err(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn checkpoint_round_trip_restores_the_run() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let layout = ArenaLayout {
+            flow_field: FlowField::Noise {
+                seed: 9,
+                cells: 4,
+                strength: 50.0,
+            },
+            asteroid_count: 3,
+            physics: PhysicsOptions {
+                projectile_collisions: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut pop = Population::with_size(layout, MIN_POPULATION_SIZE, &mut rng);
+        pop.generation = 17;
+        pop.genomes[2].fitness = -3.25;
+        pop.genomes[2].age = 4;
+        pop.genomes[2].mutation_scale = MutationScale {
+            rate: 0.5,
+            strength: 2.25,
+        };
+        pop.genomes[2].loadout = Loadout {
+            turn_rate: 1.25,
+            smoothing: 0.2,
+        };
+        pop.hall_of_fame.add(&pop.genomes[2], 16);
+        pop.hall_of_fame.entries[0].rating = 1337.5;
+        pop.hall_of_fame.entries[0].badges = vec![Badge::WrapKill, Badge::Marksman];
+        pop.fitness_weights.win = 250.0;
+        pop.mutation.bias.rate = 0.05;
+        pop.selection.tournament_size = 3;
+        pop.surrogate = Some(Surrogate::new(&pop.network()));
+        pop.speciation = Some(Speciation::new(0.25, 8));
+        pop.novelty = Some(NoveltySearch::new(40.0, 5));
+        pop.pareto = true;
+        pop.rollback_threshold = Some(0.25);
+
+        let loaded = Population::from_checkpoint_str(&pop.to_checkpoint_string()).unwrap();
+        assert_eq!(loaded.generation, 17);
+        assert_eq!(loaded.seed, pop.seed);
+        assert_eq!(loaded.layout.flow_field, pop.layout.flow_field);
+        assert_eq!(loaded.layout.asteroid_count, 3);
+        assert!(loaded.layout.physics.projectile_collisions);
+        assert_eq!(loaded.genomes.len(), pop.genomes.len());
+        assert_eq!(loaded.genomes[2].weights, pop.genomes[2].weights);
+        assert_eq!(loaded.genomes[2].fitness, -3.25);
+        assert_eq!(loaded.genomes[2].age, 4);
+        assert_eq!(loaded.genomes[2].loadout, pop.genomes[2].loadout);
+        assert_eq!(
+            loaded.genomes[2].mutation_scale,
+            pop.genomes[2].mutation_scale
+        );
+        assert_eq!(
+            loaded.hall_of_fame.entries[0].genome.loadout,
+            pop.genomes[2].loadout
+        );
+        assert_eq!(loaded.hall_of_fame.len(), 1);
+        assert_eq!(loaded.hall_of_fame.entries[0].rating, 1337.5);
+        assert_eq!(
+            loaded.hall_of_fame.entries[0].badges,
+            pop.hall_of_fame.entries[0].badges
+        );
+        assert_eq!(loaded.hall_of_fame.first_earned(Badge::Marksman), Some(16));
+        assert_eq!(loaded.hall_of_fame.first_earned(Badge::Blitz), None);
+        assert_eq!(loaded.fitness_weights.win, 250.0);
+        assert_eq!(loaded.mutation.bias.rate, 0.05);
+        assert_eq!(loaded.selection, pop.selection);
+        assert!(loaded.surrogate.is_some());
+        assert_eq!(
+            loaded.speciation.as_ref().map(|s| s.spec()),
+            Some("0.25:8".to_string())
+        );
+        assert_eq!(
+            loaded.novelty.as_ref().map(|n| n.spec()),
+            Some("40:5".to_string())
+        );
+        assert!(loaded.pareto);
+        assert_eq!(loaded.rollback_threshold, Some(0.25));
+        assert!(loaded.champion.is_none());
+        assert_eq!(
+            loaded.generation_rng().gen::<u64>(),
+            pop.generation_rng().gen::<u64>()
+        );
+
+        let truncated: String = pop
+            .to_checkpoint_string()
+            .lines()
+            .take(20)
+            .map(|l| format!("{}\n", l))
+            .collect();
+        assert!(Population::from_checkpoint_str(&truncated).is_err());
+    }
+
+    #[test]
+    fn malformed_checkpoints_are_rejected_with_the_reason() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let pop = Population::with_size(ArenaLayout::default(), MIN_POPULATION_SIZE, &mut rng);
+        let text = pop.to_checkpoint_string();
+        let load = |text: &str| Population::from_checkpoint_str(text).err().unwrap();
+
+        let header = format!("{} {}", CHECKPOINT_MAGIC, CHECKPOINT_VERSION);
+        let newer = text.replacen(&header, &format!("{} 99", CHECKPOINT_MAGIC), 1);
+        assert!(matches!(
+            load(&newer),
+            DuelError::UnsupportedVersion {
+                found: 99,
+                supported: CHECKPOINT_VERSION
+            }
+        ));
+        let unversioned = text.replacen(&header, &format!("{} 0", CHECKPOINT_MAGIC), 1);
+        assert!(matches!(
+            load(&unversioned),
+            DuelError::UnsupportedVersion { found: 0, .. }
+        ));
+        assert!(matches!(
+            load(&text.replacen(&header, "spaceship-duel replay 1", 1)),
+            DuelError::InvalidValue { .. }
+        ));
+
+        let count = format!("genomes {}\n", MIN_POPULATION_SIZE);
+        assert!(matches!(
+            load(&text.replacen(&count, "genomes 2\n", 1)),
+            DuelError::OutOfRange { value, .. } if value == 2.0
+        ));
+
+        // The first genome record: fitness, age, loadout, mutation scale, weights
+        let record = text
+            .lines()
+            .skip_while(|l| !l.starts_with("genomes "))
+            .nth(1)
+            .unwrap();
+        let tokens: Vec<&str> = record.split_whitespace().collect();
+        let with_record = |tokens: &[&str]| text.replacen(record, &tokens.join(" "), 1);
+        assert!(Population::from_checkpoint_str(&with_record(&tokens)).is_ok());
+        let mut nan_fitness = tokens.clone();
+        nan_fitness[0] = "NaN";
+        assert!(matches!(
+            load(&with_record(&nan_fitness)),
+            DuelError::NonFinite { .. }
+        ));
+        assert!(matches!(
+            load(&with_record(&tokens[..tokens.len() - 1])),
+            DuelError::WrongSize { expected: GENOME_SIZE, found, .. } if found == GENOME_SIZE - 1
+        ));
+        let mut wild_weight = tokens.clone();
+        *wild_weight.last_mut().unwrap() = "99";
+        assert!(matches!(
+            load(&with_record(&wild_weight)),
+            DuelError::OutOfRange { value, .. } if value == 99.0
+        ));
+        let mut garbled = tokens.clone();
+        garbled[1] = "old";
+        assert!(matches!(
+            load(&with_record(&garbled)),
+            DuelError::InvalidValue {
+                what: "genome age",
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,108 @@
+use crate::game::*;
+use crate::genome::*;
+
+const CHAMPION_TEMPLATE: &str = include_str!("templates/champion.rs.in");
+
+/// Render a self-contained Rust source file embedding the genome's network,
+/// its sensor computation and the topology it was trained with. The sensors
+/// are scaled to `arena`, the field the genome was trained in.
+///
+/// The sensor code in the template mirrors `Genome::get_inputs` and must be
+/// kept in step with it whenever inputs change.
+pub fn champion_source(genome: &Genome, generation: usize, arena: ArenaSize) -> String {
+    let weights = genome
+        .weights
+        .chunks(8)
+        .map(|row| {
+            let values: Vec<String> = row.iter().map(|w| format!("{:?}", w)).collect();
+            format!("    {},", values.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let network = &genome.network;
+    let join = |widths: &[usize], separator| {
+        let widths: Vec<String> = widths.iter().map(|w| w.to_string()).collect();
+        widths.join(separator)
+    };
+    let layers: Vec<usize> = std::iter::once(network.inputs())
+        .chain(network.hidden.iter().copied())
+        .chain(std::iter::once(network.outputs()))
+        .collect();
+    let max_width = layers.iter().copied().max().unwrap_or(SENSOR_COUNT);
+
+    let substitutions = [
+        ("GENERATION", generation.to_string()),
+        ("FITNESS", format!("{:.1}", genome.fitness)),
+        ("INPUT_SIZE", network.inputs().to_string()),
+        ("SENSOR_COUNT", SENSOR_COUNT.to_string()),
+        ("MAX_RAYS", MAX_RAYS.to_string()),
+        ("RAY_CHANNELS", RAY_CHANNELS.to_string()),
+        (
+            "SENSORS",
+            join(&network.sensors().collect::<Vec<_>>(), ", "),
+        ),
+        ("HIDDEN_SIZES", join(&network.hidden, " -> ")),
+        ("OUTPUT_SIZE", network.outputs().to_string()),
+        ("ACTION_SIZE", ACTION_SIZE.to_string()),
+        ("SIGNED_TURN", network.signed_turn.to_string()),
+        ("LAYER_COUNT", layers.len().to_string()),
+        ("LAYERS", join(&layers, ", ")),
+        ("HIDDEN_UNITS", network.hidden_units().to_string()),
+        ("RECURRENT", network.recurrent.to_string()),
+        ("RECURRENT_OFFSET", network.feedforward_len().to_string()),
+        ("MAX_WIDTH", max_width.to_string()),
+        ("GENOME_SIZE", genome.weights.len().to_string()),
+        ("ARENA_WIDTH", format!("{:?}", arena.width)),
+        ("ARENA_HEIGHT", format!("{:?}", arena.height)),
+        ("FIRE_COOLDOWN", format!("{:?}", FIRE_COOLDOWN)),
+        ("PROJECTILE_LIFETIME", format!("{:?}", PROJECTILE_LIFETIME)),
+        (
+            "MAX_PROJECTILES_PER_SHIP",
+            MAX_PROJECTILES_PER_SHIP.to_string(),
+        ),
+        (
+            "CROSSHAIR_HALF_ANGLE",
+            format!("{:?}", CROSSHAIR_HALF_ANGLE),
+        ),
+        ("THREAT_RADIUS", format!("{:?}", THREAT_RADIUS)),
+        ("SENSOR_RANGE", format!("{:?}", arena.engagement_range())),
+        ("MAX_SHIP_SPEED", format!("{:?}", MAX_SHIP_SPEED)),
+        ("SHIP_RADIUS", format!("{:?}", SHIP_RADIUS)),
+        ("PROJECTILE_RADIUS", format!("{:?}", PROJECTILE_RADIUS)),
+        ("TURN_RATE", format!("{:?}", genome.loadout.turn_rate)),
+        (
+            "CONTROL_SMOOTHING",
+            format!("{:?}", genome.loadout.smoothing),
+        ),
+        ("WEIGHTS", weights),
+    ];
+
+    substitutions
+        .iter()
+        .fold(CHAMPION_TEMPLATE.to_string(), |source, (key, value)| {
+            source.replace(&format!("{{{{{}}}}}", key), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn exports_the_arena_the_genome_trained_in() {
+        let genome = Genome::random(&mut StdRng::seed_from_u64(2));
+        let arena = ArenaSize::parse("2400x1800").unwrap();
+        let source = champion_source(&genome, 7, arena);
+        assert!(source.contains("pub const ARENA_WIDTH: f32 = 2400.0;"));
+        assert!(source.contains("pub const ARENA_HEIGHT: f32 = 1800.0;"));
+        let range = format!(
+            "pub const SENSOR_RANGE: f32 = {:?};",
+            arena.engagement_range()
+        );
+        assert!(source.contains(&range));
+        assert!(!source.contains("{{"));
+    }
+}
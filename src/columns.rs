@@ -0,0 +1,318 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::error::DuelError;
+use crate::game::{GameState, Ship, ACTION_SIZE};
+use crate::replay::{Replay, ReplayPlayer};
+
+/// Name of the file listing the columns of an export
+pub const SCHEMA_FILE: &str = "schema.txt";
+/// Every `.npy` header, magic included, is padded to a multiple of this
+const NPY_ALIGNMENT: usize = 64;
+const NPY_MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+
+/// Element type of a column, named by its NumPy dtype
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Little-endian f32, `<f4`
+    F32,
+    /// Little-endian u32, `<u4`
+    U32,
+    /// One byte, 0 or 1, `|b1`
+    Bool,
+}
+
+impl ColumnType {
+    pub fn descr(self) -> &'static str {
+        match self {
+            ColumnType::F32 => "<f4",
+            ColumnType::U32 => "<u4",
+            ColumnType::Bool => "|b1",
+        }
+    }
+}
+
+/// Per-ship state columns, exported once for each ship as `ship0_NAME` and
+/// `ship1_NAME`
+const SHIP_COLUMNS: [(&str, ColumnType, &str); 10] = [
+    ("x", ColumnType::F32, "position, arena units"),
+    ("y", ColumnType::F32, "position, arena units"),
+    ("vx", ColumnType::F32, "velocity, arena units per second"),
+    ("vy", ColumnType::F32, "velocity, arena units per second"),
+    ("rotation", ColumnType::F32, "heading, radians"),
+    ("alive", ColumnType::Bool, "still in the match"),
+    ("shield", ColumnType::F32, "shield points left"),
+    ("hull", ColumnType::F32, "hull points left"),
+    ("energy", ColumnType::F32, "energy pool, when energy is on"),
+    ("ammo", ColumnType::U32, "shots left, when ammo is limited"),
+];
+
+/// Names of the action outputs, in `ACTION_SIZE` order
+const ACTION_NAMES: [&str; ACTION_SIZE] = ["thrust", "turn_left", "turn_right", "fire", "special"];
+
+fn ship_value(ship: &Ship, column: usize) -> f32 {
+    match column {
+        0 => ship.x,
+        1 => ship.y,
+        2 => ship.vx,
+        3 => ship.vy,
+        4 => ship.rotation,
+        5 => ship.alive as u8 as f32,
+        6 => ship.shield,
+        7 => ship.hull,
+        8 => ship.energy,
+        _ => ship.ammo as f32,
+    }
+}
+
+/// One column of an export, its values stored as they are written out
+#[derive(Clone, Debug)]
+pub struct Column {
+    pub name: String,
+    pub kind: ColumnType,
+    pub description: String,
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+impl Column {
+    fn new(name: String, kind: ColumnType, description: &str) -> Self {
+        Column {
+            name,
+            kind,
+            description: description.to_string(),
+            len: 0,
+            bytes: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, value: f32) {
+        match self.kind {
+            ColumnType::F32 => self.bytes.extend_from_slice(&value.to_le_bytes()),
+            ColumnType::U32 => self.bytes.extend_from_slice(&(value as u32).to_le_bytes()),
+            ColumnType::Bool => self.bytes.push((value != 0.0) as u8),
+        }
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The column as a NumPy `.npy` file (format 1.0): a one-dimensional
+    /// array that `numpy.load` reads without copying or parsing
+    pub fn to_npy(&self) -> Vec<u8> {
+        let mut header = format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': ({},), }}",
+            self.kind.descr(),
+            self.len
+        );
+        // Magic, version and the two length bytes come first; the header ends in a newline
+        let unpadded = NPY_MAGIC.len() + 2 + header.len() + 1;
+        let padding = (NPY_ALIGNMENT - unpadded % NPY_ALIGNMENT) % NPY_ALIGNMENT;
+        header.extend(std::iter::repeat_n(' ', padding));
+        header.push('\n');
+
+        let mut out = Vec::with_capacity(NPY_MAGIC.len() + 2 + header.len() + self.bytes.len());
+        out.extend_from_slice(NPY_MAGIC);
+        out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        out.extend_from_slice(header.as_bytes());
+        out.extend_from_slice(&self.bytes);
+        out
+    }
+}
+
+/// Re-simulate `replay` into columns with one row per recorded step: the
+/// step number, the match time and the state before the step, then the step
+/// length and the actions both ships applied over it. Schema:
+///
+/// ```text
+/// step                 <u4  index of the step
+/// time                 <f4  match seconds before the step
+/// dt                   <f4  seconds the step advances
+/// shipS_x, shipS_y     <f4  position, arena units
+/// shipS_vx, shipS_vy   <f4  velocity, arena units per second
+/// shipS_rotation       <f4  heading, radians
+/// shipS_alive          |b1  still in the match
+/// shipS_shield         <f4  shield points left
+/// shipS_hull           <f4  hull points left
+/// shipS_energy         <f4  energy pool, when energy is on
+/// shipS_ammo           <u4  shots left, when ammo is limited
+/// shipS_A              <f4  action output A: thrust, turn_left, turn_right,
+///                           fire, special
+/// ```
+///
+/// with S 0 or 1. The same list goes into the `schema.txt` of an export.
+pub fn replay_columns(replay: &Replay) -> Vec<Column> {
+    let mut columns = vec![
+        Column::new("step".into(), ColumnType::U32, "index of the step"),
+        Column::new(
+            "time".into(),
+            ColumnType::F32,
+            "match seconds before the step",
+        ),
+        Column::new("dt".into(), ColumnType::F32, "seconds the step advances"),
+    ];
+    for ship in 0..2 {
+        for (name, kind, description) in SHIP_COLUMNS {
+            columns.push(Column::new(
+                format!("ship{}_{}", ship, name),
+                kind,
+                description,
+            ));
+        }
+    }
+    for ship in 0..2 {
+        for name in ACTION_NAMES {
+            columns.push(Column::new(
+                format!("ship{}_{}", ship, name),
+                ColumnType::F32,
+                "action output applied over the step",
+            ));
+        }
+    }
+
+    let mut player = ReplayPlayer::new(replay.clone());
+    for (index, step) in replay.steps.iter().enumerate() {
+        push_row(&mut columns, index, player.state(), step.dt, &step.actions);
+        player.step();
+    }
+    columns
+}
+
+fn push_row(
+    columns: &mut [Column],
+    index: usize,
+    state: &GameState,
+    dt: f32,
+    actions: &[[f32; ACTION_SIZE]; 2],
+) {
+    let ship_values = state
+        .ships
+        .iter()
+        .flat_map(|ship| (0..SHIP_COLUMNS.len()).map(move |c| ship_value(ship, c)));
+    // The step index is written as is, past where an f32 stops counting exactly
+    let (step, rest) = columns.split_first_mut().unwrap();
+    step.bytes.extend_from_slice(&(index as u32).to_le_bytes());
+    step.len += 1;
+    let values = [state.time, dt]
+        .into_iter()
+        .chain(ship_values)
+        .chain(actions.iter().flatten().copied());
+    for (column, value) in rest.iter_mut().zip(values) {
+        column.push(value);
+    }
+}
+
+/// The schema as written to `schema.txt`: one "NAME DTYPE DESCRIPTION" line
+/// per column, in file order
+pub fn schema_text(columns: &[Column]) -> String {
+    let mut out = String::new();
+    for column in columns {
+        // Writing to a String cannot fail
+        let _ = writeln!(
+            out,
+            "{} {} {}",
+            column.name,
+            column.kind.descr(),
+            column.description
+        );
+    }
+    out
+}
+
+/// Export `replay` into directory `dir`, created if missing, as one
+/// `NAME.npy` file per column plus `schema.txt`. Each file loads with
+/// `numpy.load`; a pandas frame is
+/// `pd.DataFrame({p.stem: np.load(p) for p in Path(dir).glob("*.npy")})`
+/// and polars takes the same dict. Returns the number of rows.
+pub fn export_replay(replay: &Replay, dir: impl AsRef<Path>) -> Result<usize, DuelError> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let columns = replay_columns(replay);
+    for column in &columns {
+        fs::write(dir.join(format!("{}.npy", column.name)), column.to_npy())?;
+    }
+    fs::write(dir.join(SCHEMA_FILE), schema_text(&columns))?;
+    Ok(replay.steps.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ArenaLayout;
+
+    fn recorded(steps: usize) -> Replay {
+        let mut replay = Replay::new(ArenaLayout::default(), 7);
+        let mut state = replay.initial_state();
+        for i in 0..steps {
+            let turn = (i % 3) as f32 / 2.0;
+            let actions = [[1.0, turn, 0.0, 1.0, 0.0], [0.5, 0.0, turn, 0.0, 1.0]];
+            replay.record(&state, 1.0 / 60.0, &actions);
+            state.update(1.0 / 60.0, &actions);
+        }
+        replay
+    }
+
+    fn column<'a>(columns: &'a [Column], name: &str) -> &'a Column {
+        columns.iter().find(|c| c.name == name).unwrap()
+    }
+
+    fn f32_at(column: &Column, row: usize) -> f32 {
+        let bytes = &column.bytes[row * 4..row * 4 + 4];
+        f32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    #[test]
+    fn rows_hold_the_state_before_each_step_and_its_actions() {
+        let replay = recorded(40);
+        let columns = replay_columns(&replay);
+        assert_eq!(columns.len(), 3 + 2 * (SHIP_COLUMNS.len() + ACTION_SIZE));
+        assert!(columns.iter().all(|c| c.len() == 40));
+
+        let start = replay.initial_state();
+        assert_eq!(f32_at(column(&columns, "time"), 0), 0.0);
+        assert_eq!(f32_at(column(&columns, "ship1_x"), 0), start.ships[1].x);
+        assert_eq!(column(&columns, "ship0_alive").bytes[0], 1);
+
+        let mut player = ReplayPlayer::new(replay.clone());
+        player.seek(f32_at(column(&columns, "time"), 25));
+        assert_eq!(player.position(), 25);
+        assert_eq!(
+            f32_at(column(&columns, "ship0_vy"), 25),
+            player.state().ships[0].vy
+        );
+        assert_eq!(
+            f32_at(column(&columns, "ship1_turn_right"), 25),
+            replay.steps[25].actions[1][2]
+        );
+        let step = &column(&columns, "step").bytes[25 * 4..26 * 4];
+        assert_eq!(u32::from_le_bytes(step.try_into().unwrap()), 25);
+    }
+
+    #[test]
+    fn npy_files_carry_an_aligned_header_and_the_raw_values() {
+        let columns = replay_columns(&recorded(5));
+        let schema = schema_text(&columns);
+        assert_eq!(schema.lines().count(), columns.len());
+        assert!(schema.starts_with("step <u4 "));
+
+        for name in ["time", "ship0_alive", "ship1_ammo"] {
+            let column = column(&columns, name);
+            let npy = column.to_npy();
+            assert_eq!(&npy[..8], NPY_MAGIC);
+            let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+            assert_eq!((10 + header_len) % NPY_ALIGNMENT, 0);
+            let header = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+            assert!(header.ends_with('\n'));
+            assert!(header.contains(&format!("'descr': '{}'", column.kind.descr())));
+            assert!(header.contains("'shape': (5,)"));
+            assert_eq!(&npy[10 + header_len..], column.bytes.as_slice());
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use spaceship_duel::events::MatchEvent;
+use spaceship_duel::game::*;
+
+use crate::i18n::{tr, tr_fill, Text};
+
+/// Match time between status lines while both ships are alive
+const STATUS_INTERVAL: f32 = 10.0;
+/// Lines kept in the on-screen log panel
+const PANEL_LINES: usize = 8;
+
+/// Plain-text narration of the showcase match, printed to stdout and shown
+/// in a log panel so a session can be followed without watching the arena
+pub struct Commentary {
+    lines: VecDeque<String>,
+    next_status: f32,
+}
+
+impl Default for Commentary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Commentary {
+    pub fn new() -> Self {
+        Commentary {
+            lines: VecDeque::with_capacity(PANEL_LINES),
+            next_status: STATUS_INTERVAL,
+        }
+    }
+
+    pub fn start_match(&mut self, generation: usize) {
+        self.next_status = STATUS_INTERVAL;
+        self.say(tr_fill(
+            Text::CommentaryMatchStart,
+            &[("gen", generation.to_string())],
+        ));
+    }
+
+    /// Narrate the events of one step, plus a periodic status line
+    pub fn observe(&mut self, events: &[MatchEvent], state: &GameState) {
+        for event in events {
+            match *event {
+                MatchEvent::Destroyed {
+                    shooter,
+                    target,
+                    shots,
+                    time,
+                } => self.say(tr_fill(
+                    Text::CommentaryDestroyed,
+                    &[
+                        ("shooter", ship_name(shooter).to_string()),
+                        ("target", ship_name(target).to_string()),
+                        ("time", format!("{:.1}", time)),
+                        ("shots", shots.to_string()),
+                    ],
+                )),
+                MatchEvent::Crashed { ship, time } => self.say(tr_fill(
+                    Text::CommentaryCrashed,
+                    &[
+                        ("ship", ship_name(ship).to_string()),
+                        ("time", format!("{:.1}", time)),
+                    ],
+                )),
+                MatchEvent::MatchOver { winner: None, time } => self.say(tr_fill(
+                    Text::CommentaryDraw,
+                    &[("time", format!("{:.1}", time))],
+                )),
+                _ => {}
+            }
+        }
+
+        if !state.match_over && state.time >= self.next_status {
+            self.next_status += STATUS_INTERVAL;
+            let (green, blue) = (&state.ships[0], &state.ships[1]);
+            let dx = toroidal_diff(green.x, blue.x, state.arena.width);
+            let dy = toroidal_diff(green.y, blue.y, state.arena.height);
+            self.say(tr_fill(
+                Text::CommentaryStatus,
+                &[
+                    ("time", format!("{:.0}", state.time)),
+                    ("green", green.shots_fired.to_string()),
+                    ("blue", blue.shots_fired.to_string()),
+                    ("distance", format!("{:.0}", (dx * dx + dy * dy).sqrt())),
+                ],
+            ));
+        }
+    }
+
+    fn say(&mut self, line: String) {
+        println!("{}", line);
+        if self.lines.len() == PANEL_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// Log panel in the bottom-right corner, newest line last
+    pub fn render(&self) {
+        let line_height = 18.0;
+        let width = 520.0;
+        let height = PANEL_LINES as f32 * line_height + 10.0;
+        let (x, y) = (ARENA_WIDTH - width - 10.0, ARENA_HEIGHT - height - 10.0);
+        draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.6));
+        for (i, line) in self.lines.iter().enumerate() {
+            draw_text(
+                line,
+                x + 8.0,
+                y + 18.0 + i as f32 * line_height,
+                16.0,
+                Color::new(0.8, 0.8, 0.8, 1.0),
+            );
+        }
+    }
+}
+
+fn ship_name(ship: usize) -> &'static str {
+    tr(if ship == 0 {
+        Text::ShipGreen
+    } else {
+        Text::ShipBlue
+    })
+}
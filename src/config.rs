@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::DuelError;
+use crate::evolution::{Selection, MIN_POPULATION_SIZE, POPULATION_SIZE};
+use crate::game::ArenaLayout;
+use crate::genome::{LayerMutation, NetworkConfig};
+use crate::remote::RemoteStore;
+use crate::simulation::FitnessWeights;
+
+/// Largest population a config may ask for
+const MAX_POPULATION_SIZE: usize = 100_000;
+/// Tables a config file may hold
+const TABLES: [&str; 6] = [
+    "evolution",
+    "mutation",
+    "fitness",
+    "arena",
+    "network",
+    "sync",
+];
+
+/// Hyperparameters of a training run, loadable from a config file so tuning
+/// needs no recompiling. Constants that genomes and exported controllers are
+/// built around (arena size, ship speed, sensor layout, ...) stay compiled in;
+/// the `[arena]` table holds the match rules that may vary between runs,
+/// the `[network]` table the shape and inputs of new networks and the
+/// `[sync]` table the remote store the run mirrors its files into.
+///
+/// ```text
+/// [evolution]
+/// population_size = 150
+/// tournament_size = 3
+/// anneal_generations = 200
+/// final_mutation_strength = 0.25
+/// restart_after = 30
+/// archive_cap = 300
+///
+/// [mutation]
+/// bias_rate = 0.1
+///
+/// [fitness]
+/// win = 150
+///
+/// [arena]
+/// asteroid_count = 2
+/// class_matchups = "mirror"
+///
+/// [network]
+/// hidden = [24, 16]
+/// rays = 8
+///
+/// [sync]
+/// url = "http://storage.local:8080/runs/tuned/"
+/// ```
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub population_size: usize,
+    pub selection: Selection,
+    pub mutation: LayerMutation,
+    pub fitness_weights: FitnessWeights,
+    pub layout: ArenaLayout,
+    pub network: NetworkConfig,
+    pub sync: Option<RemoteStore>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            population_size: POPULATION_SIZE,
+            selection: Selection::default(),
+            mutation: LayerMutation::default(),
+            fitness_weights: FitnessWeights::default(),
+            layout: ArenaLayout::default(),
+            network: NetworkConfig::default(),
+            sync: None,
+        }
+    }
+}
+
+impl Config {
+    /// Parse a config file: `[table]` headers and `key = value` lines
+    /// holding a number, boolean, quoted string or `[a, b]` list of them,
+    /// with `#` comments. The layout looks like TOML but is its own, smaller
+    /// format: each table and each key within it may appear once, and
+    /// nothing else is accepted. Settings left out keep their defaults.
+    pub fn parse(text: &str) -> Result<Self, DuelError> {
+        let mut config = Config::default();
+        let mut table = String::new();
+        let mut tables = HashSet::new();
+        let mut keys = HashSet::new();
+        for (i, raw) in text.lines().enumerate() {
+            let at_line = |error| DuelError::AtLine {
+                line: i + 1,
+                error: Box::new(error),
+            };
+            let line = strip_comment(raw).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                table = name.trim().to_string();
+                if !TABLES.contains(&table.as_str()) {
+                    return Err(at_line(DuelError::UnknownName {
+                        kind: "config table",
+                        name: table,
+                    }));
+                }
+                if !tables.insert(table.clone()) {
+                    return Err(at_line(DuelError::InvalidValue {
+                        what: "repeated config table",
+                        value: table,
+                    }));
+                }
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                at_line(DuelError::InvalidValue {
+                    what: "config line",
+                    value: line.to_string(),
+                })
+            })?;
+            let key = key.trim();
+            if !keys.insert((table.clone(), key.to_string())) {
+                return Err(at_line(DuelError::InvalidValue {
+                    what: "repeated config key",
+                    value: key.to_string(),
+                }));
+            }
+            let value = value_text(value.trim()).map_err(at_line)?;
+            config.set(&table, key, &value).map_err(at_line)?;
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DuelError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Set `key` of `table` from its text value
+    fn set(&mut self, table: &str, key: &str, value: &str) -> Result<(), DuelError> {
+        let number = || {
+            value.parse::<f32>().map_err(|_| DuelError::InvalidValue {
+                what: "config value",
+                value: value.to_string(),
+            })
+        };
+        match table {
+            "evolution" if key == "population_size" => {
+                let size = value.parse().map_err(|_| DuelError::InvalidValue {
+                    what: "population size",
+                    value: value.to_string(),
+                })?;
+                if !(MIN_POPULATION_SIZE..=MAX_POPULATION_SIZE).contains(&size) {
+                    return Err(DuelError::OutOfRange {
+                        what: "population_size".to_string(),
+                        value: size as f64,
+                        min: MIN_POPULATION_SIZE as f64,
+                        max: MAX_POPULATION_SIZE as f64,
+                    });
+                }
+                self.population_size = size;
+                Ok(())
+            }
+            "evolution" => self.selection.set(key, number()?),
+            "mutation" => self.mutation.set(key, number()?),
+            "fitness" => self.fitness_weights.set(key, number()?),
+            "arena" => self.layout.set(key, value),
+            "network" => self.network.set(key, value),
+            "sync" if key == "url" => {
+                self.sync = Some(RemoteStore::parse(value)?);
+                Ok(())
+            }
+            "sync" => Err(DuelError::UnknownName {
+                kind: "sync setting",
+                name: key.to_string(),
+            }),
+            _ => Err(DuelError::UnknownName {
+                kind: "config table",
+                name: table.to_string(),
+            }),
+        }
+    }
+}
+
+/// A value as `set` takes it: unquoted, with the items of a list joined by commas
+fn value_text(value: &str) -> Result<String, DuelError> {
+    let Some(items) = value.strip_prefix('[') else {
+        return Ok(unquote(value).to_string());
+    };
+    let items = items
+        .strip_suffix(']')
+        .ok_or_else(|| DuelError::InvalidValue {
+            what: "config list",
+            value: value.to_string(),
+        })?;
+    let items: Vec<&str> = items.split(',').map(|item| unquote(item.trim())).collect();
+    Ok(items.join(","))
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// `line` up to a `#` that is not inside a quoted string
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configs_set_each_table_and_report_bad_lines() {
+        let config = Config::parse(
+            "# tuned run\n\
+             [evolution]\n\
+             population_size = 40\n\
+             crossover_rate = 0.5 # mostly copies\n\
+             anneal_generations = 200\n\
+             \n\
+             [mutation]\n\
+             bias_rate = 0.1\n\
+             [fitness]\n\
+             win = 150\n\
+             [arena]\n\
+             asteroid_count = 2\n\
+             energy = true\n\
+             class_matchups = \"mirror\"\n\
+             [network]\n\
+             hidden = [24, 16]\n\
+             rays = 8\n",
+        )
+        .unwrap();
+        assert_eq!(config.population_size, 40);
+        assert_eq!(config.selection.crossover_rate, 0.5);
+        assert_eq!(config.selection.anneal_generations, 200);
+        assert_eq!(
+            config.selection.tournament_size,
+            Selection::default().tournament_size
+        );
+        assert_eq!(config.mutation.bias.rate, 0.1);
+        assert_eq!(config.fitness_weights.win, 150.0);
+        assert_eq!(config.layout.asteroid_count, 2);
+        assert!(config.layout.physics.energy);
+        assert_eq!(config.layout.class_matchups.name(), "mirror");
+        assert_eq!(config.network.spec(), "feedforward+rays8:24,16");
+
+        let err = Config::parse("[fitness]\nwin = 1\nwinn = 2\n").unwrap_err();
+        assert!(matches!(err, DuelError::AtLine { line: 3, .. }));
+        assert!(Config::parse("[evolution]\npopulation_size = 2\n").is_err());
+        assert!(Config::parse("[physics]\nrecoil = 1\n").is_err());
+        assert!(Config::parse("[network]\nrays = 5\n").is_err());
+
+        // Lists may also be written as one quoted string
+        let config = Config::parse("[network]\nhidden = \"12,6\"\n").unwrap();
+        assert_eq!(config.network.hidden, [12, 6]);
+        assert!(Config::parse("[network]\nhidden = [12, 6\n").is_err());
+        // Nothing may be set twice, and only the known tables exist
+        let err = Config::parse("[fitness]\nwin = 1\n\n[arena]\n[fitness]\n").unwrap_err();
+        assert!(matches!(err, DuelError::AtLine { line: 5, .. }));
+        let err = Config::parse("[fitness]\nwin = 1\nwin = 2\n").unwrap_err();
+        assert!(matches!(err, DuelError::AtLine { line: 3, .. }));
+        assert!(Config::parse("[[fitness]]\n").is_err());
+
+        let config = Config::parse("[sync]\nurl = \"http://storage.local/runs/a\"\n").unwrap();
+        assert_eq!(
+            config.sync.map(|store| store.url()).as_deref(),
+            Some("http://storage.local:80/runs/a/")
+        );
+        assert!(Config::parse("[sync]\nurl = \"https://storage.local/\"\n").is_err());
+        assert!(Config::parse("[sync]\nbucket = \"runs\"\n").is_err());
+    }
+}
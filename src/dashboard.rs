@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use spaceship_duel::archive::{ArchiveEntry, HallOfFame};
+use spaceship_duel::evolution::Population;
+use spaceship_duel::game::*;
+use spaceship_duel::pacing::{EngagementTimeline, STALL_INTENSITY};
+use spaceship_duel::stats::thin_history;
+
+const PAGE: &str = include_str!("dashboard.html");
+/// Showcase frames kept per second of match time in the published replay
+const REPLAY_FPS: f32 = 30.0;
+/// Highest-rated archived champions viewers can vote for
+const POLL_CANDIDATES: usize = 6;
+/// Longest voter id kept; the page makes up a short random one per browser
+const MAX_VOTER_ID: usize = 64;
+
+/// Figures of one evaluated generation, plotted as the fitness curves
+struct GenerationStats {
+    generation: usize,
+    best: f32,
+    mean: f32,
+    diversity: f32,
+}
+
+/// Everything the dashboard page can ask for
+#[derive(Default)]
+struct DashboardData {
+    history: Vec<GenerationStats>,
+    /// Champion and run figures as a ready-made JSON object
+    champion: String,
+    /// Latest finished showcase match as a ready-made JSON object
+    replay: String,
+    poll: Poll,
+}
+
+/// Viewers' votes on which archived champions fight the next showcase series
+#[derive(Default)]
+struct Poll {
+    candidates: Vec<ArchiveEntry>,
+    votes: Vec<usize>,
+    /// Browsers that have voted this round, so each counts once
+    voters: HashSet<String>,
+}
+
+impl Poll {
+    /// Offer the highest-rated champions of `hall_of_fame`, keeping the votes
+    /// of candidates still on the ballot
+    fn offer(&mut self, hall_of_fame: &HallOfFame) {
+        let mut ranked: Vec<&ArchiveEntry> = hall_of_fame.entries.iter().collect();
+        ranked.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+        let votes = ranked
+            .iter()
+            .take(POLL_CANDIDATES)
+            .map(|entry| {
+                self.candidates
+                    .iter()
+                    .position(|c| c.generation == entry.generation)
+                    .map_or(0, |i| self.votes[i])
+            })
+            .collect();
+        self.candidates = ranked.into_iter().take(POLL_CANDIDATES).cloned().collect();
+        self.votes = votes;
+    }
+
+    /// Count `voter`'s vote for the champion of `generation`; false if there
+    /// is no such candidate or the voter already voted this round
+    fn vote(&mut self, generation: usize, voter: &str) -> bool {
+        let Some(i) = self
+            .candidates
+            .iter()
+            .position(|c| c.generation == generation)
+        else {
+            return false;
+        };
+        if voter.is_empty() || voter.len() > MAX_VOTER_ID || !self.voters.insert(voter.to_string())
+        {
+            return false;
+        }
+        self.votes[i] += 1;
+        true
+    }
+
+    /// The two most-voted candidates, ties going to the higher rating, once
+    /// anyone has voted; closes the round
+    fn close(&mut self) -> Option<[ArchiveEntry; 2]> {
+        if self.candidates.len() < 2 || self.votes.iter().all(|&v| v == 0) {
+            return None;
+        }
+        let mut order: Vec<usize> = (0..self.candidates.len()).collect();
+        order.sort_by(|&a, &b| {
+            let rating = |i: usize| self.candidates[i].rating;
+            self.votes[b]
+                .cmp(&self.votes[a])
+                .then(rating(b).total_cmp(&rating(a)))
+        });
+        self.votes.fill(0);
+        self.voters.clear();
+        Some([0, 1].map(|k| self.candidates[order[k]].clone()))
+    }
+
+    fn json(&self) -> String {
+        let candidates: Vec<String> = self
+            .candidates
+            .iter()
+            .zip(&self.votes)
+            .map(|(c, votes)| {
+                format!(
+                    "{{\"generation\":{},\"rating\":{},\"fitness\":{},\"votes\":{}}}",
+                    c.generation,
+                    json_number(c.rating),
+                    json_number(c.fitness),
+                    votes
+                )
+            })
+            .collect();
+        format!("{{\"candidates\":[{}]}}", candidates.join(","))
+    }
+}
+
+/// Local web page with live training curves, champion stats, a replay of
+/// the latest showcase match and a poll on the next showcase pairing, served
+/// from a background thread
+pub struct Dashboard {
+    data: Arc<Mutex<DashboardData>>,
+    /// Generations the fitness curves keep at full resolution, see `thin_history`
+    history_cap: usize,
+}
+
+impl Dashboard {
+    /// Serve the dashboard on 127.0.0.1:`port`
+    pub fn start(port: u16, history_cap: usize) -> io::Result<Dashboard> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let data = Arc::new(Mutex::new(DashboardData::default()));
+        let shared = Arc::clone(&data);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // A broken connection only affects that one request
+                let _ = respond(stream, &shared);
+            }
+        });
+        Ok(Dashboard { data, history_cap })
+    }
+
+    fn data(&self) -> std::sync::MutexGuard<'_, DashboardData> {
+        self.data.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Add the population's latest evaluation to the curves and champion
+    /// stats, and put its best-rated archived champions up for the vote
+    pub fn record_generation(&self, pop: &Population) {
+        let mean = pop.genomes.iter().map(|g| g.fitness).sum::<f32>() / pop.genomes.len() as f32;
+        let best_age = pop.get_top_two().0.age;
+        let champion = match &pop.champion {
+            Some(entry) => format!(
+                "{{\"generation\":{},\"fitness\":{}}}",
+                entry.generation,
+                json_number(entry.fitness)
+            ),
+            None => "null".to_string(),
+        };
+        let mut data = self.data();
+        data.history.push(GenerationStats {
+            generation: pop.generation,
+            best: pop.best_fitness,
+            mean,
+            diversity: pop.diversity(),
+        });
+        thin_history(&mut data.history, self.history_cap);
+        data.champion = format!(
+            "{{\"champion\":{},\"best_age\":{},\"hall_of_fame\":{},\"matches\":{},\"speedup\":{}}}",
+            champion,
+            best_age,
+            pop.hall_of_fame.len(),
+            pop.matches_played,
+            json_number(pop.speedup() as f32)
+        );
+        data.poll.offer(&pop.hall_of_fame);
+    }
+
+    /// The pair viewers voted for, if anyone voted since the last call,
+    /// opening a fresh round
+    pub fn take_voted_pair(&self) -> Option<[ArchiveEntry; 2]> {
+        self.data().poll.close()
+    }
+
+    /// Replace the replay with a finished showcase match and its engagement timeline
+    pub fn publish_replay(
+        &self,
+        replay: &ReplayRecorder,
+        engagement: &EngagementTimeline,
+        generation: usize,
+    ) {
+        let intensity: Vec<String> = engagement
+            .intensity()
+            .into_iter()
+            .map(json_number)
+            .collect();
+        let json = format!(
+            "{{\"generation\":{},\"width\":{},\"height\":{},\"fps\":{},\"stall_intensity\":{},\"engagement\":[{}],\"frames\":[{}]}}",
+            generation,
+            replay.arena.width,
+            replay.arena.height,
+            REPLAY_FPS,
+            STALL_INTENSITY,
+            intensity.join(","),
+            replay.frames.join(",")
+        );
+        self.data().replay = json;
+    }
+}
+
+/// Samples the showcase match into JSON frames for the dashboard replay
+#[derive(Default)]
+pub struct ReplayRecorder {
+    frames: Vec<String>,
+    next_sample: f32,
+    /// Field of the recorded match, which the page scales the frames to
+    arena: ArenaSize,
+}
+
+impl ReplayRecorder {
+    /// Record `state` if a frame is due; call after each showcase update
+    pub fn record(&mut self, state: &GameState) {
+        if state.time < self.next_sample {
+            return;
+        }
+        self.next_sample = state.time + 1.0 / REPLAY_FPS;
+        self.arena = state.arena;
+        let mut frame = String::from("{\"ships\":[");
+        for (i, ship) in state.ships.iter().enumerate() {
+            let _ = write!(
+                frame,
+                "{}[{:.1},{:.1},{:.3},{}]",
+                if i > 0 { "," } else { "" },
+                ship.x,
+                ship.y,
+                ship.rotation,
+                ship.alive
+            );
+        }
+        frame.push_str("],\"shots\":[");
+        for (i, p) in state.projectiles.iter().enumerate() {
+            let _ = write!(
+                frame,
+                "{}[{:.1},{:.1},{}]",
+                if i > 0 { "," } else { "" },
+                p.x,
+                p.y,
+                p.owner
+            );
+        }
+        frame.push_str("]}");
+        self.frames.push(frame);
+    }
+}
+
+fn respond(mut stream: TcpStream, data: &Mutex<DashboardData>) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // Drain the headers so the browser sees a clean close
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut fields = request.split_whitespace();
+    let method = fields.next().unwrap_or("GET");
+    let target = fields.next().unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (status, content_type, body) = {
+        let mut data = data.lock().unwrap_or_else(|e| e.into_inner());
+        match (method, path) {
+            (_, "/") => ("200 OK", "text/html; charset=utf-8", PAGE.to_string()),
+            (_, "/api/stats") => ("200 OK", "application/json", stats_json(&data)),
+            (_, "/api/replay") if !data.replay.is_empty() => {
+                ("200 OK", "application/json", data.replay.clone())
+            }
+            (_, "/api/replay") => ("200 OK", "application/json", "null".to_string()),
+            (_, "/api/poll") => ("200 OK", "application/json", data.poll.json()),
+            ("POST", "/api/vote") => {
+                let param = |name| {
+                    query
+                        .split('&')
+                        .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+                };
+                let counted = param("generation")
+                    .and_then(|g| g.parse().ok())
+                    .is_some_and(|generation| {
+                        data.poll
+                            .vote(generation, param("voter").unwrap_or_default())
+                    });
+                if counted {
+                    ("200 OK", "application/json", data.poll.json())
+                } else {
+                    ("409 Conflict", "text/plain", "vote not counted".to_string())
+                }
+            }
+            _ => ("404 Not Found", "text/plain", "not found".to_string()),
+        }
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body.as_bytes())
+}
+
+fn stats_json(data: &DashboardData) -> String {
+    let mut history = String::new();
+    for (i, g) in data.history.iter().enumerate() {
+        let _ = write!(
+            history,
+            "{}{{\"generation\":{},\"best\":{},\"mean\":{},\"diversity\":{}}}",
+            if i > 0 { "," } else { "" },
+            g.generation,
+            json_number(g.best),
+            json_number(g.mean),
+            json_number(g.diversity)
+        );
+    }
+    let run = if data.champion.is_empty() {
+        "null"
+    } else {
+        &data.champion
+    };
+    format!("{{\"history\":[{}],\"run\":{}}}", history, run)
+}
+
+/// JSON has no NaN or infinity, so those become null
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        format!("{}", value)
+    } else {
+        "null".to_string()
+    }
+}
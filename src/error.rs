@@ -0,0 +1,143 @@
+use std::fmt;
+use std::io;
+
+/// Error type of every fallible public API in the crate: rejected external
+/// input (genome weights, arena layouts, command-line specs, ...), file I/O,
+/// and failed background work. Loaders validate everything they read and
+/// return one of these instead of panicking.
+#[derive(Debug)]
+pub enum DuelError {
+    /// A vector or record had the wrong number of elements
+    WrongSize {
+        what: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// A number was NaN or infinite
+    NonFinite { what: String },
+    /// A number lay outside the accepted range
+    OutOfRange {
+        what: String,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    /// A name did not match any known option
+    UnknownName { kind: &'static str, name: String },
+    /// Text that could not be parsed as the expected kind of value
+    InvalidValue { what: &'static str, value: String },
+    /// An artifact written by an incompatible format version
+    UnsupportedVersion { found: u32, supported: u32 },
+    /// An artifact saved under different game constants than this build's
+    ConfigMismatch { found: u64, expected: u64 },
+    /// Reading or writing a file, here or on a remote store, failed
+    Io(io::Error),
+    /// A worker thread panicked before finishing its share of `task`
+    WorkerPanicked { task: &'static str },
+    /// `task` was asked to stop before it finished
+    Interrupted { task: &'static str },
+    /// A problem on a given line of a text file such as a config
+    AtLine { line: usize, error: Box<DuelError> },
+}
+
+impl fmt::Display for DuelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DuelError::WrongSize {
+                what,
+                expected,
+                found,
+            } => write!(f, "{} has {} elements, expected {}", what, found, expected),
+            DuelError::NonFinite { what } => write!(f, "{} is not a finite number", what),
+            DuelError::OutOfRange {
+                what,
+                value,
+                min,
+                max,
+            } => write!(f, "{} = {} is outside [{}, {}]", what, value, min, max),
+            DuelError::UnknownName { kind, name } => write!(f, "unknown {} '{}'", kind, name),
+            DuelError::InvalidValue { what, value } => write!(f, "invalid {} '{}'", what, value),
+            DuelError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "format version {} is not supported (expected {})",
+                found, supported
+            ),
+            DuelError::ConfigMismatch { found, expected } => write!(
+                f,
+                "saved under game config {:016x}, but this build uses {:016x}",
+                found, expected
+            ),
+            DuelError::Io(e) => write!(f, "{}", e),
+            DuelError::WorkerPanicked { task } => write!(f, "a {} worker thread panicked", task),
+            DuelError::Interrupted { task } => write!(f, "the {} was interrupted", task),
+            DuelError::AtLine { line, error } => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+impl std::error::Error for DuelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DuelError::Io(e) => Some(e),
+            DuelError::AtLine { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DuelError {
+    fn from(e: io::Error) -> Self {
+        DuelError::Io(e)
+    }
+}
+
+/// Check that `value` is finite and within `[min, max]`
+pub fn check_range(what: &str, value: f32, min: f32, max: f32) -> Result<(), DuelError> {
+    if !value.is_finite() {
+        return Err(DuelError::NonFinite {
+            what: what.to_string(),
+        });
+    }
+    if value < min || value > max {
+        return Err(DuelError::OutOfRange {
+            what: what.to_string(),
+            value: value as f64,
+            min: min as f64,
+            max: max as f64,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn io_and_line_errors_chain_to_their_cause() {
+        let io = DuelError::from(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+        let cause = io.source().expect("I/O errors have a source");
+        assert!(cause.downcast_ref::<io::Error>().is_some());
+
+        let at_line = DuelError::AtLine {
+            line: 3,
+            error: Box::new(io),
+        };
+        assert_eq!(at_line.to_string(), "line 3: no such file");
+        let inner = at_line.source().expect("line errors have a source");
+        assert!(matches!(
+            inner.downcast_ref::<DuelError>(),
+            Some(DuelError::Io(_))
+        ));
+        assert!(inner.source().is_some());
+
+        let leaf = check_range("speed", f32::NAN, 0.0, 1.0).unwrap_err();
+        assert!(matches!(leaf, DuelError::NonFinite { .. }));
+        assert!(leaf.source().is_none());
+        assert!(matches!(
+            check_range("speed", 2.0, 0.0, 1.0),
+            Err(DuelError::OutOfRange { .. })
+        ));
+    }
+}
@@ -0,0 +1,393 @@
+use crate::game::*;
+
+/// A projectile passing this close to the opposing ship without hitting it is a near miss
+pub const NEAR_MISS_DISTANCE: f32 = SHIP_RADIUS * 4.0;
+
+/// Damage dealt to a ship this long before another ship finishes it off
+/// still earns an assist, in simulated seconds
+pub const ASSIST_WINDOW: f32 = 5.0;
+
+/// Notable moment of a match, detected by comparing consecutive states
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatchEvent {
+    /// `ship` launched a projectile
+    ShotFired { ship: usize, time: f32 },
+    /// A projectile of `ship` expired or hit an asteroid instead of a ship
+    ShotMissed { ship: usize, time: f32 },
+    /// Fire from `shooter` passed within `distance` of `target` without hitting it
+    NearMiss {
+        shooter: usize,
+        target: usize,
+        distance: f32,
+        time: f32,
+    },
+    /// A projectile from `shooter` dealt `damage` to `target`
+    Damaged {
+        shooter: usize,
+        target: usize,
+        damage: f32,
+        time: f32,
+    },
+    /// `ship` crossed an arena edge and reappeared on the opposite side,
+    /// `opponent_distance` away from its nearest opponent `opponent`
+    Wrapped {
+        ship: usize,
+        opponent: usize,
+        opponent_distance: f32,
+        time: f32,
+    },
+    /// `shooter` destroyed `target` with its `shots`-th projectile
+    Destroyed {
+        shooter: usize,
+        target: usize,
+        shots: usize,
+        time: f32,
+    },
+    /// `ship` dealt `damage` to `target` within ASSIST_WINDOW before another
+    /// ship destroyed it; follows the `Destroyed` event
+    Assisted {
+        ship: usize,
+        target: usize,
+        damage: f32,
+        time: f32,
+    },
+    /// `ship` flew into an asteroid
+    Crashed { ship: usize, time: f32 },
+    /// The match finished; `winner` is None for a draw
+    MatchOver { winner: Option<usize>, time: f32 },
+}
+
+impl MatchEvent {
+    /// Simulated time the event happened at
+    pub fn time(&self) -> f32 {
+        match *self {
+            MatchEvent::ShotFired { time, .. }
+            | MatchEvent::ShotMissed { time, .. }
+            | MatchEvent::NearMiss { time, .. }
+            | MatchEvent::Damaged { time, .. }
+            | MatchEvent::Wrapped { time, .. }
+            | MatchEvent::Destroyed { time, .. }
+            | MatchEvent::Assisted { time, .. }
+            | MatchEvent::Crashed { time, .. }
+            | MatchEvent::MatchOver { time, .. } => time,
+        }
+    }
+
+    /// Every ship the event names
+    pub fn ships(&self) -> impl Iterator<Item = usize> {
+        let (a, b) = match *self {
+            MatchEvent::ShotFired { ship, .. }
+            | MatchEvent::ShotMissed { ship, .. }
+            | MatchEvent::Crashed { ship, .. } => (Some(ship), None),
+            MatchEvent::Wrapped { ship, opponent, .. } => (Some(ship), Some(opponent)),
+            MatchEvent::NearMiss {
+                shooter, target, ..
+            }
+            | MatchEvent::Damaged {
+                shooter, target, ..
+            }
+            | MatchEvent::Destroyed {
+                shooter, target, ..
+            }
+            | MatchEvent::Assisted {
+                ship: shooter,
+                target,
+                ..
+            } => (Some(shooter), Some(target)),
+            MatchEvent::MatchOver { winner, .. } => (winner, None),
+        };
+        a.into_iter().chain(b)
+    }
+}
+
+/// Damage a ship took from one shooter, kept for assists
+#[derive(Clone, Copy, Debug)]
+struct Wound {
+    shooter: usize,
+    damage: f32,
+    time: f32,
+}
+
+/// Event stream of one match, duel or melee. Like `FitnessTracker` it only
+/// reads the state, so recording costs the simulation nothing when no one is
+/// listening. Per-ship bookkeeping is sized by the first recorded state.
+#[derive(Clone, Debug, Default)]
+pub struct EventLog {
+    pub events: Vec<MatchEvent>,
+    shots: Vec<usize>,
+    hits: Vec<usize>,
+    alive: Vec<bool>,
+    in_flight: Vec<usize>,
+    /// Closest approach of each ship's fire to another ship during the
+    /// current pass, with the ship it passed
+    near_pass: Vec<Option<(usize, f32)>>,
+    /// Damage each ship took within ASSIST_WINDOW, oldest first
+    wounds: Vec<Vec<Wound>>,
+    positions: Vec<(f32, f32)>,
+    finished: bool,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Approximate memory held by the recorded events, in bytes
+    pub fn memory_bytes(&self) -> usize {
+        self.events.capacity() * size_of::<MatchEvent>()
+    }
+
+    /// Closest any projectile has come to a ship other than its owner's in a
+    /// pass still under way, or None if no fire is within NEAR_MISS_DISTANCE
+    pub fn close_pass(&self) -> Option<f32> {
+        self.near_pass
+            .iter()
+            .flatten()
+            .map(|&(_, d)| d)
+            .reduce(f32::min)
+    }
+
+    /// Record one simulation step; call after each `GameState::update`.
+    /// Returns the events the step produced.
+    pub fn record(&mut self, state: &GameState) -> &[MatchEvent] {
+        let start = self.events.len();
+        let time = state.time;
+        let n = state.ships.len();
+        if self.alive.len() != n {
+            self.shots = vec![0; n];
+            self.hits = vec![0; n];
+            self.alive = vec![true; n];
+            self.in_flight = vec![0; n];
+            self.near_pass = vec![None; n];
+            self.wounds = vec![Vec::new(); n];
+        }
+        let new_shots: Vec<usize> = (0..n)
+            .map(|i| state.ships[i].shots_fired - self.shots[i])
+            .collect();
+
+        for (i, ship) in state.ships.iter().enumerate() {
+            for _ in self.shots[i]..ship.shots_fired {
+                self.events.push(MatchEvent::ShotFired { ship: i, time });
+            }
+            self.shots[i] = ship.shots_fired;
+        }
+
+        for wounds in &mut self.wounds {
+            wounds.retain(|w| time - w.time <= ASSIST_WINDOW);
+        }
+        for hit in &state.hits {
+            self.events.push(MatchEvent::Damaged {
+                shooter: hit.shooter,
+                target: hit.target,
+                damage: hit.damage,
+                time,
+            });
+            self.wounds[hit.target].push(Wound {
+                shooter: hit.shooter,
+                damage: hit.damage,
+                time,
+            });
+        }
+
+        for (i, ship) in state.ships.iter().enumerate() {
+            if self.alive[i] && !ship.alive {
+                let wounds = std::mem::take(&mut self.wounds[i]);
+                // The last projectile to strike the ship this step finished it
+                match state.hits.iter().rev().find(|h| h.target == i) {
+                    Some(kill) => {
+                        let shooter = kill.shooter;
+                        self.events.push(MatchEvent::Destroyed {
+                            shooter,
+                            target: i,
+                            shots: state.ships[shooter].shots_fired,
+                            time,
+                        });
+                        let mut helpers: Vec<usize> = Vec::new();
+                        for w in &wounds {
+                            if w.shooter != shooter && !helpers.contains(&w.shooter) {
+                                helpers.push(w.shooter);
+                            }
+                        }
+                        for ship in helpers {
+                            let damage = wounds
+                                .iter()
+                                .filter(|w| w.shooter == ship)
+                                .map(|w| w.damage)
+                                .sum();
+                            self.events.push(MatchEvent::Assisted {
+                                ship,
+                                target: i,
+                                damage,
+                                time,
+                            });
+                        }
+                    }
+                    None => self.events.push(MatchEvent::Crashed { ship: i, time }),
+                }
+            }
+            self.alive[i] = ship.alive;
+        }
+
+        for (i, ship) in state.ships.iter().enumerate() {
+            let in_flight = state.projectiles.iter().filter(|p| p.owner == i).count();
+            let hits = ship.hits_scored - self.hits[i];
+            let resolved = (self.in_flight[i] + new_shots[i]).saturating_sub(in_flight + hits);
+            for _ in 0..resolved {
+                self.events.push(MatchEvent::ShotMissed { ship: i, time });
+            }
+            self.in_flight[i] = in_flight;
+            self.hits[i] = ship.hits_scored;
+        }
+
+        for shooter in 0..n {
+            let closest = state
+                .projectiles
+                .iter()
+                .filter(|p| p.owner == shooter)
+                .flat_map(|p| {
+                    state
+                        .ships
+                        .iter()
+                        .enumerate()
+                        .filter(|&(target, ship)| target != shooter && ship.alive)
+                        .map(move |(target, ship)| {
+                            let dx = toroidal_diff(p.x, ship.x, state.arena.width);
+                            let dy = toroidal_diff(p.y, ship.y, state.arena.height);
+                            (target, (dx * dx + dy * dy).sqrt())
+                        })
+                })
+                .filter(|&(_, d)| d < NEAR_MISS_DISTANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match (closest, self.near_pass[shooter]) {
+                (Some(close), pass) => {
+                    self.near_pass[shooter] = Some(match pass {
+                        Some(p) if p.1 <= close.1 => p,
+                        _ => close,
+                    });
+                }
+                (None, Some((target, distance))) => {
+                    self.near_pass[shooter] = None;
+                    if state.ships[target].alive {
+                        self.events.push(MatchEvent::NearMiss {
+                            shooter,
+                            target,
+                            distance,
+                            time,
+                        });
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        if self.positions.len() == n {
+            for (i, ship) in state.ships.iter().enumerate() {
+                let (px, py) = self.positions[i];
+                let jumped = (ship.x - px).abs() > state.arena.width / 2.0
+                    || (ship.y - py).abs() > state.arena.height / 2.0;
+                if ship.alive && jumped {
+                    let opponent = state.nearest_opponent(i);
+                    let other = &state.ships[opponent];
+                    let dx = toroidal_diff(ship.x, other.x, state.arena.width);
+                    let dy = toroidal_diff(ship.y, other.y, state.arena.height);
+                    self.events.push(MatchEvent::Wrapped {
+                        ship: i,
+                        opponent,
+                        opponent_distance: (dx * dx + dy * dy).sqrt(),
+                        time,
+                    });
+                }
+            }
+        }
+        self.positions = state.ships.iter().map(|s| (s.x, s.y)).collect();
+
+        if state.match_over && !self.finished {
+            self.finished = true;
+            self.events.push(MatchEvent::MatchOver {
+                winner: state.winner,
+                time,
+            });
+        }
+
+        &self.events[start..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strike(state: &mut GameState, owner: usize, target: usize) {
+        let ship = &state.ships[target];
+        state.projectiles.push(Projectile {
+            x: ship.x,
+            y: ship.y,
+            vx: 0.0,
+            vy: 0.0,
+            lifetime: PROJECTILE_LIFETIME,
+            owner,
+        });
+    }
+
+    #[test]
+    fn kills_credit_ships_that_wore_the_target_down() {
+        let mut state = GameState::new();
+        state.physics.hull = 2.0;
+        state.ships = (0..4)
+            .map(|i| Ship {
+                hull: 2.0,
+                ..Ship::new(200.0 + 400.0 * i as f32, 600.0, 0.0)
+            })
+            .collect();
+        let idle = [[0.0; ACTION_SIZE]; 4];
+        let mut log = EventLog::new();
+
+        strike(&mut state, 1, 2);
+        state.update(1.0 / 60.0, &idle);
+        assert_eq!(
+            log.record(&state),
+            [MatchEvent::Damaged {
+                shooter: 1,
+                target: 2,
+                damage: PROJECTILE_DAMAGE,
+                time: state.time,
+            }]
+        );
+
+        strike(&mut state, 0, 2);
+        state.update(1.0 / 60.0, &idle);
+        let events = log.record(&state);
+        assert!(events.contains(&MatchEvent::Destroyed {
+            shooter: 0,
+            target: 2,
+            shots: 0,
+            time: state.time,
+        }));
+        assert!(events.contains(&MatchEvent::Assisted {
+            ship: 1,
+            target: 2,
+            damage: PROJECTILE_DAMAGE,
+            time: state.time,
+        }));
+
+        // Damage older than ASSIST_WINDOW earns nothing
+        strike(&mut state, 1, 3);
+        state.update(1.0 / 60.0, &idle);
+        log.record(&state);
+        state.time += ASSIST_WINDOW;
+        strike(&mut state, 0, 3);
+        state.update(1.0 / 60.0, &idle);
+        let events = log.record(&state);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            MatchEvent::Destroyed {
+                shooter: 0,
+                target: 3,
+                ..
+            }
+        )));
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, MatchEvent::Assisted { .. })));
+    }
+}
@@ -1,51 +1,657 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::archive::{ArchiveEntry, HallOfFame, OpponentSampling};
+use crate::bots::{self, Bot};
+use crate::error::{check_range, DuelError};
+use crate::game::{ArenaLayout, GameState, Loadout};
 use crate::genome::*;
+use crate::novelty::{Behavior, NoveltySearch, BEHAVIOR_SIZE};
+use crate::pareto::{self, Objectives, OBJECTIVE_COUNT};
+use crate::profiling::Span;
+use crate::rating;
 use crate::simulation::*;
+use crate::speciation::Speciation;
+use crate::spectate::{LiveMatch, MatchKind, Spectator, PUBLISH_INTERVAL};
+use crate::surrogate::Surrogate;
 
-const POPULATION_SIZE: usize = 100;
+pub const POPULATION_SIZE: usize = 100;
 const MATCHES_PER_EVAL: usize = 8;
+/// Extra matches per genome against champions drawn from the hall of fame
+pub(crate) const ARCHIVE_MATCHES_PER_EVAL: usize = 2;
+/// Of a new genome's MATCHES_PER_EVAL, how many are played against weak
+/// archived champions instead of the population
+const CURRICULUM_MATCHES: usize = 3;
+/// Curriculum opponents are drawn from this many of the earliest champions
+const CURRICULUM_POOL: usize = 5;
+/// Fitness from curriculum matches is scaled by this, since the opponents are easier
+const CURRICULUM_DISCOUNT: f32 = 0.5;
+/// Head-to-head matches a challenger plays against the reigning champion
+const CHAMPION_SERIES_MATCHES: usize = 20;
+/// Share of series points (win 1, draw 0.5) needed to take the title
+const CHAMPION_WIN_THRESHOLD: f32 = 0.6;
+/// Most times one generation is bred again after falling off a fitness cliff
+const MAX_ROLLBACKS: usize = 3;
+/// Matches the champion plays against each scripted bot every generation
+const BENCHMARK_MATCHES: usize = 4;
 const TOURNAMENT_SIZE: usize = 5;
 const ELITE_COUNT: usize = 5;
-const MUTATION_RATE: f32 = 0.15;
-const MUTATION_STRENGTH: f32 = 0.4;
+/// Smallest population `with_size` creates or a checkpoint may contain
+pub const MIN_POPULATION_SIZE: usize = ELITE_COUNT + 2;
 const CROSSOVER_RATE: f32 = 0.7;
+/// Hall-of-fame champions kept by default before the weakest near-duplicates
+/// are evicted
+const ARCHIVE_CAP: usize = 500;
+/// Largest tournament or elite count `Selection::set` accepts
+const MAX_SELECTION_COUNT: f32 = 1000.0;
+/// Largest mutation strength multiplier an annealing schedule may end on
+const MAX_FINAL_STRENGTH: f32 = 10.0;
+
+/// How parents are picked and how many of the fittest survive unchanged
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selection {
+    /// Genomes drawn per tournament; the fittest of them becomes a parent
+    pub tournament_size: usize,
+    /// Genomes copied into the next generation, the reigning champion first.
+    /// At least two offspring slots are always kept free.
+    pub elite_count: usize,
+    /// Chance a child is a crossover of two parents rather than a copy of one
+    pub crossover_rate: f32,
+    /// Generations over which mutation strength and tournament size move
+    /// from their configured values to the final ones below, trading early
+    /// exploration for late exploitation; 0 keeps them fixed
+    pub anneal_generations: usize,
+    /// Multiplier of every weight group's mutation strength at the end of the schedule
+    pub final_mutation_strength: f32,
+    /// Tournament size at the end of the schedule; 0 keeps `tournament_size`
+    pub final_tournament_size: usize,
+    /// Once the reigning champion has held the title this many generations,
+    /// the schedule restarts from exploration until a new one is crowned; 0
+    /// never restarts
+    pub reheat_after: usize,
+    /// Generations without a new best fitness after which the bottom half
+    /// of the next generation is reseeded with random genomes; 0 never restarts
+    pub restart_after: usize,
+    /// Most champions the hall of fame keeps, see `HallOfFame::evict_to`;
+    /// 0 keeps every generation's
+    pub archive_cap: usize,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Selection {
+            tournament_size: TOURNAMENT_SIZE,
+            elite_count: ELITE_COUNT,
+            crossover_rate: CROSSOVER_RATE,
+            anneal_generations: 0,
+            final_mutation_strength: 1.0,
+            final_tournament_size: 0,
+            reheat_after: 0,
+            restart_after: 0,
+            archive_cap: ARCHIVE_CAP,
+        }
+    }
+}
+
+impl Selection {
+    /// Every setting with the name `set` accepts for it
+    pub fn values(&self) -> [(&'static str, f32); 9] {
+        [
+            ("tournament_size", self.tournament_size as f32),
+            ("elite_count", self.elite_count as f32),
+            ("crossover_rate", self.crossover_rate),
+            ("anneal_generations", self.anneal_generations as f32),
+            ("final_mutation_strength", self.final_mutation_strength),
+            ("final_tournament_size", self.final_tournament_size as f32),
+            ("reheat_after", self.reheat_after as f32),
+            ("restart_after", self.restart_after as f32),
+            ("archive_cap", self.archive_cap as f32),
+        ]
+    }
+
+    /// How far along the annealing schedule generation `generation` is, in
+    /// [0, 1], with the reigning champion crowned `reign` generations ago;
+    /// None when no schedule is set
+    pub fn anneal_progress(&self, generation: usize, reign: Option<usize>) -> Option<f32> {
+        if self.anneal_generations == 0 {
+            return None;
+        }
+        if self.reheat_after > 0 && reign.is_some_and(|r| r >= self.reheat_after) {
+            return Some(0.0);
+        }
+        Some((generation as f32 / self.anneal_generations as f32).min(1.0))
+    }
+
+    /// Mutation strength multiplier and tournament size at `progress` along
+    /// the annealing schedule
+    pub fn annealed(&self, progress: f32) -> (f32, usize) {
+        let strength = 1.0 + (self.final_mutation_strength - 1.0) * progress;
+        let start = self.tournament_size as f32;
+        let end = match self.final_tournament_size {
+            0 => start,
+            size => size as f32,
+        };
+        let tournament = (start + (end - start) * progress).round() as usize;
+        (strength, tournament.max(1))
+    }
+
+    /// Set one value by name, as used in config files
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), DuelError> {
+        match name {
+            "tournament_size" => {
+                check_range(name, value, 1.0, MAX_SELECTION_COUNT)?;
+                self.tournament_size = value as usize;
+            }
+            "elite_count" => {
+                check_range(name, value, 0.0, MAX_SELECTION_COUNT)?;
+                self.elite_count = value as usize;
+            }
+            "crossover_rate" => {
+                check_range(name, value, 0.0, 1.0)?;
+                self.crossover_rate = value;
+            }
+            "anneal_generations" => {
+                check_range(name, value, 0.0, u32::MAX as f32)?;
+                self.anneal_generations = value as usize;
+            }
+            "final_mutation_strength" => {
+                check_range(name, value, 0.0, MAX_FINAL_STRENGTH)?;
+                self.final_mutation_strength = value;
+            }
+            "final_tournament_size" => {
+                check_range(name, value, 0.0, MAX_SELECTION_COUNT)?;
+                self.final_tournament_size = value as usize;
+            }
+            "reheat_after" => {
+                check_range(name, value, 0.0, u32::MAX as f32)?;
+                self.reheat_after = value as usize;
+            }
+            "restart_after" => {
+                check_range(name, value, 0.0, u32::MAX as f32)?;
+                self.restart_after = value as usize;
+            }
+            "archive_cap" => {
+                check_range(name, value, 0.0, u32::MAX as f32)?;
+                self.archive_cap = value as usize;
+            }
+            _ => {
+                return Err(DuelError::UnknownName {
+                    kind: "selection setting",
+                    name: name.to_string(),
+                })
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Match counter for the evaluation in flight, readable from other threads
+#[derive(Debug, Default)]
+pub struct EvalProgress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    /// Start and length of a time-budgeted evaluation, whose progress is its clock
+    budget: Mutex<Option<(Instant, Duration)>>,
+    interrupted: AtomicBool,
+}
+
+impl EvalProgress {
+    /// Completed fraction of the current evaluation in [0, 1]
+    pub fn fraction(&self) -> f32 {
+        if let Some((started, budget)) = *self.budget.lock().unwrap_or_else(|e| e.into_inner()) {
+            return (started.elapsed().as_secs_f32() / budget.as_secs_f32()).min(1.0);
+        }
+        let total = self.total.load(Ordering::Relaxed);
+        if total == 0 {
+            return 0.0;
+        }
+        (self.done.load(Ordering::Relaxed) as f32 / total as f32).min(1.0)
+    }
+
+    /// Ask the evaluation in flight to stop once every worker has finished
+    /// the match or genome it is playing, leaving the rest in
+    /// `Population::partial`. This is for shutting down: the flag is never
+    /// cleared, so every later evaluation reporting here stops at once too.
+    /// A time-budgeted evaluation whose genomes have all played finishes
+    /// the generation early on what they played.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+    }
+
+    fn start(&self, total: usize, done: usize, budget: Option<Duration>) {
+        self.done.store(done, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+        *self.budget.lock().unwrap_or_else(|e| e.into_inner()) =
+            budget.map(|b| (Instant::now(), b));
+    }
+
+    fn advance(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Genomes of an evaluation still to play, handed out to worker threads
+/// through an atomic ticket counter. Ticket `t` is genome `t % len` in round
+/// `t / len`, so time-budgeted evaluations keep cycling through them.
+struct EvalQueue {
+    /// Indices of the genomes not yet evaluated
+    pending: Vec<usize>,
+    next: AtomicUsize,
+    /// Match stream and results of each pending genome, in `pending` order
+    genomes: Vec<Mutex<GenomeEval>>,
+}
+
+/// One genome's evaluation: its own random stream, how many times it has
+/// been taken from the queue, and the matches it played
+struct GenomeEval {
+    rng: StdRng,
+    rounds: usize,
+    tally: EvalTally,
+}
+
+impl EvalQueue {
+    fn new(eval: &EvalState) -> Self {
+        let pending: Vec<usize> = (0..eval.evaluated.len())
+            .filter(|&i| !eval.evaluated[i])
+            .collect();
+        let genomes = pending
+            .iter()
+            .map(|&i| {
+                Mutex::new(GenomeEval {
+                    rng: StdRng::seed_from_u64(eval.seeds[i]),
+                    rounds: 0,
+                    tally: EvalTally::default(),
+                })
+            })
+            .collect();
+        EvalQueue {
+            pending,
+            next: AtomicUsize::new(0),
+            genomes,
+        }
+    }
+}
+
+/// Fitness earned in part of an evaluation, as (genome, fitness)
+/// pairs in match order, so totals do not depend on how genomes were split
+#[derive(Clone, Default)]
+pub(crate) struct EvalTally {
+    pub(crate) scores: Vec<(usize, f32)>,
+    /// Behavior and objectives of the genome in each scored match, in the same order
+    pub(crate) behaviors: Vec<Behavior>,
+    pub(crate) objectives: Vec<Objectives>,
+    pub(crate) matches: usize,
+    pub(crate) simulated_seconds: f64,
+    /// Matches against hall-of-fame champions played and won
+    pub(crate) archive_played: usize,
+    pub(crate) archive_wins: usize,
+}
+
+impl EvalTally {
+    /// Add `other`'s matches after this tally's own
+    fn append(&mut self, other: EvalTally) {
+        self.scores.extend(other.scores);
+        self.behaviors.extend(other.behaviors);
+        self.objectives.extend(other.objectives);
+        self.matches += other.matches;
+        self.simulated_seconds += other.simulated_seconds;
+        self.archive_played += other.archive_played;
+        self.archive_wins += other.archive_wins;
+    }
+}
+
+/// Where an evaluation stands: each genome's match seed, the archive
+/// matches every genome plays, the seed of the champion series that
+/// finishes it, and which genomes have played their whole schedule, with
+/// the results so far
+#[derive(Clone, Default)]
+pub(crate) struct EvalState {
+    pub(crate) seeds: Vec<u64>,
+    pub(crate) archive_matches: usize,
+    pub(crate) finish_seed: u64,
+    pub(crate) evaluated: Vec<bool>,
+    pub(crate) tally: EvalTally,
+}
+
+/// A generation whose evaluation was cut short by `EvalProgress::interrupt`:
+/// the genomes bred for it and every match already played, so a resumed
+/// run finishes the same generation instead of breeding and playing it
+/// again. Evaluations stop between genomes, so each genome's schedule was
+/// either played in full or not started.
+pub struct PartialEvaluation {
+    /// Generation under evaluation, one past the population it was bred from
+    pub generation: usize,
+    /// The population's seed, as changed by any rollback already made
+    pub seed: u64,
+    pub rollbacks: usize,
+    pub restarted: bool,
+    pub genomes: Vec<Genome>,
+    pub(crate) eval: EvalState,
+}
+
+impl PartialEvaluation {
+    /// Genomes whose schedule has been played in full
+    pub fn evaluated(&self) -> usize {
+        self.eval.evaluated.iter().filter(|&&e| e).count()
+    }
+}
+
+/// A generation of `advance` or an `evaluate` that `Population::step`
+/// plays a few matches at a time, for front ends that can't hand a whole
+/// generation to a thread: browsers, or a window that wants steady progress.
+/// It plays the matches and seeds `advance` and `evaluate` play without a
+/// time budget, on one core, so both reach the same population.
+pub struct GenerationStep {
+    rng: StdRng,
+    /// Population before breeding, kept to roll back a fitness cliff
+    before: Option<Snapshot>,
+    eval: EvalState,
+    /// Matches of the schedule played so far, genome by genome
+    played: usize,
+    /// Random stream of the genome being evaluated
+    genome_rng: StdRng,
+    done: bool,
+}
+
+impl GenerationStep {
+    fn new(rng: StdRng) -> Self {
+        GenerationStep {
+            rng,
+            before: None,
+            eval: EvalState::default(),
+            played: 0,
+            genome_rng: StdRng::seed_from_u64(0),
+            done: false,
+        }
+    }
+
+    /// Whether the generation has been evaluated in full
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
 
 pub struct Population {
     pub genomes: Vec<Genome>,
     pub generation: usize,
     pub best_fitness: f32,
+    /// Highest `best_fitness` of any generation and the generation that
+    /// reached it, or the last restart if that came later
+    pub peak_fitness: f32,
+    pub peak_generation: usize,
+    /// Whether the last `evolve` reseeded a stagnant population
+    pub restarted: bool,
+    pub layout: ArenaLayout,
+    pub fitness_weights: FitnessWeights,
+    pub mutation: LayerMutation,
+    pub selection: Selection,
+    /// Champion of every evaluated generation
+    pub hall_of_fame: HallOfFame,
+    /// Reigning champion; only replaced by a challenger that wins a head-to-head series
+    pub champion: Option<ArchiveEntry>,
+    /// Whether the last evaluation crowned a new champion
+    pub champion_changed: bool,
+    /// Share of series points the last generation's best genome took against
+    /// the reigning champion, or None if there was none to challenge
+    pub challenger_score: Option<f32>,
+    /// Optional fitness cliff guard: a generation whose best genome takes a
+    /// smaller share than this against the reigning champion is undone by
+    /// `advance` and bred again under a new seed
+    pub rollback_threshold: Option<f32>,
+    /// Times `advance` rolled the last generation back
+    pub rollbacks: usize,
+    /// Share of the last evaluation's hall-of-fame matches the population
+    /// won, or None if there were none
+    pub archive_win_rate: Option<f32>,
+    pub opponent_sampling: OpponentSampling,
+    /// Optional fitness predictor used to pre-screen offspring
+    pub surrogate: Option<Surrogate>,
+    /// Optional species clustering with fitness sharing for parent selection
+    pub speciation: Option<Speciation>,
+    /// Optional fitness bonus for behaving unlike the rest of the population
+    pub novelty: Option<NoveltySearch>,
+    /// Rank genomes for breeding by Pareto front over their objectives
+    /// instead of by the weighted fitness; the champion is still the fittest
+    pub pareto: bool,
+    /// Each genome's mean objectives over its matches in the last evaluation
+    pub objectives: Vec<Objectives>,
+    pub progress: Arc<EvalProgress>,
+    /// Live view of the evaluation matches for a watching window
+    pub spectator: Arc<Spectator>,
+    /// Root of `generation_rng`, saved with checkpoints so a resumed run
+    /// continues with the same random streams
+    pub seed: u64,
+    /// Matches simulated since the population was created
+    pub matches_played: usize,
+    /// Game time covered by those matches, in simulated seconds
+    pub simulated_seconds: f64,
+    /// Wall-clock time spent inside `evaluate`
+    pub eval_seconds: f64,
+    /// Wall-clock seconds each generation's evaluation matches may take. The
+    /// genomes then play as many matches as fit instead of a fixed schedule,
+    /// so results depend on the machine's speed and seeded runs no longer repeat.
+    pub time_budget: Option<f64>,
+    /// Matches each genome was scored in during the last evaluation
+    pub matches_per_genome: Vec<usize>,
+    /// The champion's share of points against each bot in `Bot::ALL` order,
+    /// an absolute progress measure unaffected by the population's own strength
+    pub bot_scores: Option<[f32; Bot::ALL.len()]>,
+    /// Evaluation cut short by an interrupt, or loaded to resume one; the
+    /// next `advance` finishes it instead of breeding a new generation
+    pub partial: Option<PartialEvaluation>,
 }
 
 impl Population {
-    pub fn new(rng: &mut impl Rng) -> Self {
-        let genomes = (0..POPULATION_SIZE).map(|_| Genome::random(rng)).collect();
+    pub fn new(layout: ArenaLayout, rng: &mut impl Rng) -> Self {
+        Self::with_size(layout, POPULATION_SIZE, rng)
+    }
+
+    /// Random population of `size` genomes (at least MIN_POPULATION_SIZE)
+    pub fn with_size(layout: ArenaLayout, size: usize, rng: &mut impl Rng) -> Self {
+        Self::with_init(layout, size, &Initialization::default(), rng)
+    }
+
+    /// Random population of `size` genomes drawn with the given initialization scheme
+    pub fn with_init(
+        layout: ArenaLayout,
+        size: usize,
+        init: &Initialization,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let size = size.max(MIN_POPULATION_SIZE);
+        let genomes = (0..size).map(|_| Genome::random_with(init, rng)).collect();
         Population {
             genomes,
             generation: 0,
             best_fitness: 0.0,
+            peak_fitness: 0.0,
+            peak_generation: 0,
+            restarted: false,
+            layout,
+            fitness_weights: FitnessWeights::default(),
+            mutation: LayerMutation::default(),
+            selection: Selection::default(),
+            hall_of_fame: HallOfFame::default(),
+            champion: None,
+            champion_changed: false,
+            challenger_score: None,
+            rollback_threshold: None,
+            rollbacks: 0,
+            archive_win_rate: None,
+            opponent_sampling: OpponentSampling::default(),
+            surrogate: None,
+            speciation: None,
+            novelty: None,
+            pareto: false,
+            objectives: Vec::new(),
+            progress: Arc::new(EvalProgress::default()),
+            spectator: Arc::new(Spectator::default()),
+            seed: rng.gen(),
+            matches_played: 0,
+            simulated_seconds: 0.0,
+            eval_seconds: 0.0,
+            time_budget: None,
+            matches_per_genome: Vec::new(),
+            bot_scores: None,
+            partial: None,
         }
     }
 
-    /// Evaluate all genomes by running matches against random opponents
-    pub fn evaluate(&mut self, rng: &mut impl Rng) {
-        // Reset fitness
-        for g in &mut self.genomes {
-            g.fitness = 0.0;
+    /// Evaluate all genomes by running matches against random opponents and
+    /// archived champions, then archive this generation's champion
+    pub fn evaluate(&mut self, rng: &mut impl Rng) -> Result<(), DuelError> {
+        let _span = Span::enter("Population::evaluate");
+        let started = Instant::now();
+
+        let size = self.genomes.len();
+        let budget = self.time_budget.map(Duration::from_secs_f64);
+        let deadline = budget.map(|b| started + b);
+        let mut eval = self.start_evaluation(budget, rng);
+        // Worker threads take genomes from a shared queue as they free up,
+        // so one slow match does not leave the rest of a fixed share waiting.
+        // Each genome keeps its own fitness tally, since pool matches also
+        // score the opponent, and tallies are merged in genome order. Every
+        // genome's matches draw from its own seed, so a seeded run evaluates
+        // the same way on any number of cores.
+        let workers = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(size);
+        let queue = EvalQueue::new(&eval);
+        let this = &*self;
+        let (state, shared) = (&eval, &queue);
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|w| scope.spawn(move || this.evaluate_queue(w, shared, state, deadline)))
+                .collect();
+            handles.into_iter().try_for_each(|h| {
+                h.join()
+                    .map_err(|_| DuelError::WorkerPanicked { task: "evaluation" })
+            })
+        })?;
+        for (i, genome) in queue.pending.into_iter().zip(queue.genomes) {
+            let genome = genome.into_inner().unwrap_or_else(|e| e.into_inner());
+            if genome.rounds > 0 {
+                eval.tally.append(genome.tally);
+                eval.evaluated[i] = true;
+            }
+        }
+        let interrupted = eval.evaluated.contains(&false);
+        if interrupted {
+            self.keep_partial(eval);
+        } else {
+            let mut finish_rng = StdRng::seed_from_u64(eval.finish_seed);
+            self.finish_evaluation(eval.tally, deadline.is_some(), &mut finish_rng);
         }
+        self.eval_seconds += started.elapsed().as_secs_f64();
+        if interrupted {
+            return Err(DuelError::Interrupted { task: "evaluation" });
+        }
+        Ok(())
+    }
 
-        // Each genome plays MATCHES_PER_EVAL matches against random opponents
-        for i in 0..POPULATION_SIZE {
-            for _ in 0..MATCHES_PER_EVAL {
-                let mut j = rng.gen_range(0..POPULATION_SIZE - 1);
-                if j >= i {
-                    j += 1;
+    /// Clear fitness and set up an evaluation of the current genomes: the
+    /// rest of `partial` when it was evaluating this generation, else a new
+    /// one with every genome's match seed drawn from `rng`
+    fn start_evaluation(&mut self, budget: Option<Duration>, rng: &mut impl Rng) -> EvalState {
+        self.reset_fitness();
+        let size = self.genomes.len();
+
+        let eval = match self.partial.take() {
+            Some(partial) if partial.generation == self.generation => partial.eval,
+            _ => {
+                // Each genome plays MATCHES_PER_EVAL matches against random opponents
+                let archive_matches = if self.hall_of_fame.is_empty() {
+                    0
+                } else {
+                    ARCHIVE_MATCHES_PER_EVAL
+                };
+                EvalState {
+                    seeds: (0..size).map(|_| rng.gen()).collect(),
+                    archive_matches,
+                    finish_seed: rng.gen(),
+                    evaluated: vec![false; size],
+                    tally: EvalTally::default(),
                 }
+            }
+        };
+        let schedule = MATCHES_PER_EVAL + eval.archive_matches;
+        let evaluated = eval.evaluated.iter().filter(|&&e| e).count();
+        self.progress
+            .start(size * schedule, evaluated * schedule, budget);
+        eval
+    }
+
+    /// Keep an evaluation cut short as `partial`, with the genomes it evaluates
+    fn keep_partial(&mut self, eval: EvalState) {
+        self.partial = Some(PartialEvaluation {
+            generation: self.generation,
+            seed: self.seed,
+            rollbacks: self.rollbacks,
+            restarted: self.restarted,
+            genomes: self.genomes.clone(),
+            eval,
+        });
+    }
+
+    /// Total the matches in `tally` into fitness, objectives and novelty,
+    /// then archive, rate and benchmark this generation's champion
+    fn finish_evaluation(&mut self, tally: EvalTally, budgeted: bool, rng: &mut impl Rng) {
+        let size = self.genomes.len();
+        self.matches_per_genome = vec![0; size];
+        let mut behaviors = vec![[0.0; BEHAVIOR_SIZE]; size];
+        let mut objectives = vec![[0.0; OBJECTIVE_COUNT]; size];
+        let matches = tally.behaviors.into_iter().zip(tally.objectives);
+        for ((i, fitness), (behavior, objective)) in tally.scores.into_iter().zip(matches) {
+            self.genomes[i].fitness += fitness;
+            self.matches_per_genome[i] += 1;
+            for (sum, value) in behaviors[i].iter_mut().zip(behavior) {
+                *sum += value;
+            }
+            for (sum, value) in objectives[i].iter_mut().zip(objective) {
+                *sum += value;
+            }
+        }
+        self.matches_played += tally.matches;
+        self.simulated_seconds += tally.simulated_seconds;
+        let (archive_played, archive_wins) = (tally.archive_played, tally.archive_wins);
+        self.archive_win_rate =
+            (archive_played > 0).then(|| archive_wins as f32 / archive_played as f32);
+        // Under a time budget match counts differ between genomes, so fitness
+        // becomes the mean per match, scaled to a fixed schedule's length
+        if budgeted {
+            for (genome, &matches) in self.genomes.iter_mut().zip(&self.matches_per_genome) {
+                genome.fitness *= MATCHES_PER_EVAL as f32 / matches.max(1) as f32;
+            }
+        }
+
+        for (objective, &matches) in objectives.iter_mut().zip(&self.matches_per_genome) {
+            objective
+                .iter_mut()
+                .for_each(|v| *v /= matches.max(1) as f32);
+        }
+        self.objectives = objectives;
 
-                let result = run_match(&self.genomes[i], &self.genomes[j], rng);
-                self.genomes[i].fitness += result.fitness[0];
-                self.genomes[j].fitness += result.fitness[1];
+        // Novelty is judged on each genome's mean behavior over its matches
+        if let Some(novelty) = &mut self.novelty {
+            for (behavior, &matches) in behaviors.iter_mut().zip(&self.matches_per_genome) {
+                behavior
+                    .iter_mut()
+                    .for_each(|v| *v /= matches.max(1) as f32);
             }
+            let scores = novelty.scores(&behaviors);
+            for (genome, score) in self.genomes.iter_mut().zip(&scores) {
+                genome.fitness += novelty.weight * score;
+            }
+            novelty.update_archive(&behaviors, &scores);
         }
 
         // Normalize by number of matches played
@@ -54,53 +660,725 @@ impl Population {
         // which is fine since everyone plays roughly the same number
 
         self.best_fitness = self.genomes.iter().map(|g| g.fitness).fold(0.0f32, f32::max);
+        if self.best_fitness > self.peak_fitness {
+            self.peak_fitness = self.best_fitness;
+            self.peak_generation = self.generation;
+        }
+        let (champion, _) = self.get_top_two();
+        self.hall_of_fame.add(&champion, self.generation);
+        // Rating matches draw from a stream of their own, so they leave the
+        // rest of a seeded run unchanged
+        let mut rating_rng = StdRng::seed_from_u64(!self.generation_rng().gen::<u64>());
+        let (matches, seconds) = rating::rate_newest(
+            &mut self.hall_of_fame,
+            &self.layout,
+            &self.fitness_weights,
+            &mut rating_rng,
+        );
+        self.matches_played += matches;
+        self.simulated_seconds += seconds;
+        self.challenge_champion(champion, rng);
+        // The champion's archived copy carries its latest rating and badges
+        if let Some(reigning) = &mut self.champion {
+            let mut archived = self.hall_of_fame.entries.iter();
+            if let Some(entry) = archived.rfind(|e| e.generation == reigning.generation) {
+                reigning.rating = entry.rating;
+                reigning.badges = entry.badges.clone();
+            }
+        }
+        // The earliest champions stay as the curriculum's weak opponents
+        if self.selection.archive_cap > 0 {
+            self.hall_of_fame.evict_to(self.selection.archive_cap, CURRICULUM_POOL);
+        }
+        if let Some(reigning) = &self.champion {
+            let mut bench_rng = StdRng::seed_from_u64(rating_rng.gen());
+            self.bot_scores = Some(bots::benchmark(
+                &reigning.genome,
+                &self.layout,
+                BENCHMARK_MATCHES,
+                &mut bench_rng,
+            ));
+            self.matches_played += BENCHMARK_MATCHES * Bot::ALL.len();
+        }
+        if let Some(surrogate) = &mut self.surrogate {
+            surrogate.observe(&self.genomes);
+        }
+    }
+
+    /// Play the self-play, curriculum and archive matches of genomes taken
+    /// from `queue` on worker `worker`, until the queue runs out. Without a
+    /// deadline each genome plays its whole schedule once, and an interrupt
+    /// stops the worker before its next genome. With a `deadline` the queue
+    /// repeats round-robin, one match per genome at a time, until the
+    /// deadline passes or an interrupt; every genome plays at least once
+    /// unless interrupted.
+    fn evaluate_queue(
+        &self,
+        worker: usize,
+        queue: &EvalQueue,
+        eval: &EvalState,
+        deadline: Option<Instant>,
+    ) {
+        let schedule = MATCHES_PER_EVAL + eval.archive_matches;
+        let count = queue.pending.len();
+        if count == 0 {
+            return;
+        }
+        loop {
+            let ticket = queue.next.fetch_add(1, Ordering::Relaxed);
+            let (round, index) = (ticket / count, ticket % count);
+            let stop = self.progress.is_interrupted()
+                || match deadline {
+                    None => round > 0,
+                    Some(deadline) => round > 0 && Instant::now() >= deadline,
+                };
+            if stop {
+                return;
+            }
+            let i = queue.pending[index];
+            let mut guard = queue.genomes[index]
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            let genome = &mut *guard;
+            let slots = match deadline {
+                None => 0..schedule,
+                Some(_) => {
+                    let slot = genome.rounds % schedule;
+                    slot..slot + 1
+                }
+            };
+            for slot in slots {
+                self.play_slot(worker, i, slot, &mut genome.rng, &mut genome.tally);
+            }
+            genome.rounds += 1;
+        }
+    }
+
+    /// Play match `slot` of genome `i`'s schedule: curriculum matches for
+    /// new genomes first, then pool matches up to MATCHES_PER_EVAL, then
+    /// archive matches
+    fn play_slot(
+        &self,
+        worker: usize,
+        i: usize,
+        slot: usize,
+        rng: &mut StdRng,
+        tally: &mut EvalTally,
+    ) {
+        let size = self.genomes.len();
+        let curriculum = self.hall_of_fame.earliest(CURRICULUM_POOL);
+        // Brand-new offspring start against weak champions so their fitness
+        // still separates them instead of every one of them being wiped out
+        let warm_up = if self.genomes[i].age == 0 && !curriculum.is_empty() {
+            CURRICULUM_MATCHES
+        } else {
+            0
+        };
+        let result = if slot < warm_up {
+            let opponent = &curriculum[rng.gen_range(0..curriculum.len())];
+            let live = (worker, MatchKind::Curriculum, i);
+            let result = self.play(live, &self.genomes[i], &opponent.genome, rng);
+            tally
+                .scores
+                .push((i, CURRICULUM_DISCOUNT * result.fitness[0]));
+            tally.behaviors.push(result.behavior[0]);
+            tally.objectives.push(result.objectives[0]);
+            result
+        } else if slot < MATCHES_PER_EVAL {
+            let mut j = rng.gen_range(0..size - 1);
+            if j >= i {
+                j += 1;
+            }
+
+            let live = (worker, MatchKind::Pool, i);
+            let result = self.play(live, &self.genomes[i], &self.genomes[j], rng);
+            tally.scores.push((i, result.fitness[0]));
+            tally.scores.push((j, result.fitness[1]));
+            tally.behaviors.extend(result.behavior);
+            tally.objectives.extend(result.objectives);
+            result
+        } else {
+            // Matches against past champions only score the current genome
+            let Some(entry) = self.hall_of_fame.sample(&self.opponent_sampling, rng) else {
+                return;
+            };
+            let live = (worker, MatchKind::Archive, i);
+            let result = self.play(live, &self.genomes[i], &entry.genome, rng);
+            tally.scores.push((i, result.fitness[0]));
+            tally.behaviors.push(result.behavior[0]);
+            tally.objectives.push(result.objectives[0]);
+            tally.archive_played += 1;
+            tally.archive_wins += (result.winner == Some(0)) as usize;
+            result
+        };
+        tally.matches += 1;
+        tally.simulated_seconds += result.duration as f64;
+        self.progress.advance();
+    }
+
+    /// `run_match` under this population's rules. While the spectator is
+    /// watching, the match is published to the cell of the worker in `live`,
+    /// labelled with its kind and the genome under evaluation.
+    fn play(
+        &self,
+        live: (usize, MatchKind, usize),
+        a: &Genome,
+        b: &Genome,
+        rng: &mut StdRng,
+    ) -> MatchResult {
+        if !self.spectator.is_watching() {
+            return run_match(a, b, &self.layout, &self.fitness_weights, rng);
+        }
+        let (worker, kind, genome) = live;
+        let cell = self.spectator.cell(worker);
+        let mut steps = 0;
+        let mut publish = |state: &GameState| {
+            steps += 1;
+            if steps % PUBLISH_INTERVAL == 0 || state.match_over {
+                cell.publish(LiveMatch {
+                    state: state.clone(),
+                    generation: self.generation,
+                    genome,
+                    kind,
+                    finished: false,
+                });
+            }
+        };
+        let result =
+            run_match_observed(a, b, &self.layout, &self.fitness_weights, &mut publish, rng);
+        // Matches that run out of time end without a final step to publish
+        if let Some(last) = cell.latest() {
+            cell.publish(LiveMatch {
+                finished: true,
+                ..(*last).clone()
+            });
+        }
+        result
+    }
+
+    /// Add fitness from `matches` matches per genome against opponents drawn
+    /// uniformly from `opponents`. The opponents are not scored, and nothing is
+    /// archived; `best_fitness` is refreshed.
+    pub fn evaluate_against(&mut self, opponents: &[Genome], matches: usize, rng: &mut impl Rng) {
+        if opponents.is_empty() {
+            return;
+        }
+        let started = Instant::now();
+        self.progress.start(self.genomes.len() * matches, 0, None);
+        for genome in &mut self.genomes {
+            for _ in 0..matches {
+                let opponent = &opponents[rng.gen_range(0..opponents.len())];
+                let result = run_match(genome, opponent, &self.layout, &self.fitness_weights, rng);
+                genome.fitness += result.fitness[0];
+                self.matches_played += 1;
+                self.simulated_seconds += result.duration as f64;
+                self.progress.advance();
+            }
+        }
+        self.best_fitness = self
+            .genomes
+            .iter()
+            .map(|g| g.fitness)
+            .fold(0.0f32, f32::max);
+        self.eval_seconds += started.elapsed().as_secs_f64();
+    }
+
+    /// Crown the generation's best if it takes at least CHAMPION_WIN_THRESHOLD
+    /// of the points in a series against the reigning champion, so noise in
+    /// population fitness alone cannot change the title
+    fn challenge_champion(&mut self, challenger: Genome, rng: &mut impl Rng) {
+        self.challenger_score = None;
+        let crowned = match &self.champion {
+            None => true,
+            Some(reigning) => {
+                let mut points = 0.0;
+                for m in 0..CHAMPION_SERIES_MATCHES {
+                    // Alternate starting sides so spawn position cannot decide the series
+                    let side = m % 2;
+                    let result = if side == 0 {
+                        run_match(
+                            &challenger,
+                            &reigning.genome,
+                            &self.layout,
+                            &self.fitness_weights,
+                            rng,
+                        )
+                    } else {
+                        run_match(
+                            &reigning.genome,
+                            &challenger,
+                            &self.layout,
+                            &self.fitness_weights,
+                            rng,
+                        )
+                    };
+                    points += match result.winner {
+                        Some(w) if w == side => 1.0,
+                        Some(_) => 0.0,
+                        None => 0.5,
+                    };
+                    self.matches_played += 1;
+                    self.simulated_seconds += result.duration as f64;
+                }
+                let score = points / CHAMPION_SERIES_MATCHES as f32;
+                self.challenger_score = Some(score);
+                score >= CHAMPION_WIN_THRESHOLD
+            }
+        };
+
+        self.champion_changed = crowned;
+        if crowned {
+            self.champion = Some(ArchiveEntry {
+                fitness: challenger.fitness,
+                genome: challenger,
+                generation: self.generation,
+                rating: rating::INITIAL_RATING,
+                badges: Vec::new(),
+            });
+        }
+    }
+
+    pub fn reset_fitness(&mut self) {
+        for g in &mut self.genomes {
+            g.fitness = 0.0;
+        }
+    }
+
+    /// Random number generator for producing the next generation, derived
+    /// from `seed` and the generation counter
+    pub fn generation_rng(&self) -> StdRng {
+        let mix = (self.generation as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        StdRng::seed_from_u64(self.seed ^ mix)
+    }
+
+    /// Network shape shared by every genome in the population
+    pub fn network(&self) -> NetworkConfig {
+        self.genomes
+            .first()
+            .map_or_else(NetworkConfig::default, |g| g.network.clone())
+    }
+
+    /// Element-wise mean of all genomes' weights
+    pub fn centroid(&self) -> Genome {
+        Genome::average(&self.genomes).unwrap_or_else(|| Genome {
+            weights: vec![0.0; GENOME_SIZE],
+            fitness: 0.0,
+            age: 0,
+            network: NetworkConfig::default(),
+            loadout: Loadout::default(),
+            mutation_scale: MutationScale::default(),
+        })
+    }
+
+    /// Mutation strength multiplier and tournament size the annealing
+    /// schedule sets for breeding the next generation; None without a schedule
+    pub fn annealed_selection(&self) -> Option<(f32, usize)> {
+        let reign = self
+            .champion
+            .as_ref()
+            .map(|c| self.generation.saturating_sub(c.generation));
+        self.selection
+            .anneal_progress(self.generation, reign)
+            .map(|progress| self.selection.annealed(progress))
+    }
+
+    /// Geometric mean of the genomes' mutation scales, where self-adaptation
+    /// has steered the run's exploration
+    pub fn mutation_scale(&self) -> MutationScale {
+        self.centroid().mutation_scale
+    }
+
+    /// Mean Euclidean distance of the genomes' weights from their centroid
+    pub fn diversity(&self) -> f32 {
+        let centroid = self.centroid();
+        let total: f32 = self.genomes.iter().map(|g| g.distance(&centroid)).sum();
+        total / self.genomes.len().max(1) as f32
+    }
+
+    /// Variance of every weight across the population, indexed like `Genome::weights`
+    pub fn weight_variance(&self) -> Vec<f32> {
+        let centroid = self.centroid();
+        let n = self.genomes.len().max(1) as f32;
+        let mut variance = vec![0.0; centroid.weights.len()];
+        for genome in &self.genomes {
+            for ((v, w), mean) in variance
+                .iter_mut()
+                .zip(&genome.weights)
+                .zip(&centroid.weights)
+            {
+                *v += (w - mean) * (w - mean) / n;
+            }
+        }
+        variance
+    }
+
+    /// Simulated game time per second of evaluation wall-clock time
+    pub fn speedup(&self) -> f64 {
+        if self.eval_seconds > 0.0 {
+            self.simulated_seconds / self.eval_seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// Breed and evaluate the next generation. With a rollback threshold, a
+    /// generation that falls off a fitness cliff (its best genome loses the
+    /// series against the reigning champion by more than the threshold) is
+    /// undone and bred again from the same parents under a new seed, up to
+    /// MAX_ROLLBACKS times; matches played in discarded generations still count.
+    /// A generation held in `partial` is finished instead of bred anew.
+    pub fn advance(&mut self, rng: &mut impl Rng) -> Result<(), DuelError> {
+        self.rollbacks = 0;
+        let before = self.rollback_threshold.map(|_| Snapshot::of(self));
+        self.evolve_or_resume(rng);
+        self.evaluate(rng)?;
+        let Some(before) = before else {
+            return Ok(());
+        };
+        while self.rollbacks < MAX_ROLLBACKS && self.fell_off_cliff() {
+            before.clone().restore(self);
+            self.seed = rng.gen();
+            self.rollbacks += 1;
+            self.evolve(rng);
+            self.evaluate(rng)?;
+        }
+        Ok(())
+    }
+
+    /// Breed the next generation and start its evaluation, to be played by
+    /// `step`; the incremental `advance(rng)`, rollbacks included
+    pub fn begin_advance(&mut self, rng: StdRng) -> GenerationStep {
+        self.rollbacks = 0;
+        let mut step = GenerationStep::new(rng);
+        step.before = self.rollback_threshold.map(|_| Snapshot::of(self));
+        self.evolve_or_resume(&mut step.rng);
+        self.restart_evaluation(&mut step);
+        step
+    }
+
+    /// Breed the next generation, or take up the one `partial` was
+    /// evaluating when it was bred from this population
+    fn evolve_or_resume(&mut self, rng: &mut impl Rng) {
+        let next = self.generation + 1;
+        let Some(mut partial) = self.partial.take_if(|p| p.generation == next) else {
+            self.evolve(rng);
+            return;
+        };
+        self.genomes = std::mem::take(&mut partial.genomes);
+        self.generation = partial.generation;
+        self.seed = partial.seed;
+        self.rollbacks = partial.rollbacks;
+        self.restarted = partial.restarted;
+        self.objectives.clear();
+        // Left for `start_evaluation` to continue
+        self.partial = Some(partial);
+    }
+
+    /// Start an evaluation of the current genomes, to be played by `step`;
+    /// the incremental `evaluate(rng)`
+    pub fn begin_evaluate(&mut self, rng: StdRng) -> GenerationStep {
+        let mut step = GenerationStep::new(rng);
+        self.restart_evaluation(&mut step);
+        step
+    }
+
+    fn restart_evaluation(&mut self, step: &mut GenerationStep) {
+        step.eval = self.start_evaluation(None, &mut step.rng);
+        step.played = 0;
+    }
+
+    /// Play up to `matches` more matches of `step`, finishing the evaluation
+    /// once all are played; true once the generation is done. Wall-clock
+    /// time is left for the caller to add to `eval_seconds`, since browsers
+    /// have no `Instant`.
+    pub fn step(&mut self, step: &mut GenerationStep, matches: usize) -> bool {
+        if step.done {
+            return true;
+        }
+        let schedule = MATCHES_PER_EVAL + step.eval.archive_matches;
+        let total = schedule * self.genomes.len();
+        let mut left = matches;
+        while step.played < total && left > 0 {
+            let (i, slot) = (step.played / schedule, step.played % schedule);
+            // Genomes evaluated before an interrupt are not played again
+            if step.eval.evaluated[i] {
+                step.played += schedule;
+                continue;
+            }
+            if slot == 0 {
+                step.genome_rng = StdRng::seed_from_u64(step.eval.seeds[i]);
+            }
+            let tally = &mut step.eval.tally;
+            self.play_slot(0, i, slot, &mut step.genome_rng, tally);
+            if slot + 1 == schedule {
+                step.eval.evaluated[i] = true;
+            }
+            step.played += 1;
+            left -= 1;
+        }
+        if step.played < total {
+            return false;
+        }
+        let tally = std::mem::take(&mut step.eval.tally);
+        let mut finish_rng = StdRng::seed_from_u64(step.eval.finish_seed);
+        self.finish_evaluation(tally, false, &mut finish_rng);
+        // As in `advance`, a generation off a fitness cliff is bred again
+        if let Some(before) = step.before.clone() {
+            if self.rollbacks < MAX_ROLLBACKS && self.fell_off_cliff() {
+                before.restore(self);
+                self.seed = step.rng.gen();
+                self.rollbacks += 1;
+                self.evolve(&mut step.rng);
+                self.restart_evaluation(step);
+                return false;
+            }
+        }
+        step.done = true;
+        true
+    }
+
+    /// Play out the genome `step` is in the middle of, then keep the rest
+    /// of its evaluation as `partial` for a later `advance` to finish, as
+    /// when a window closes mid-generation. A generation that finishes in
+    /// the meantime leaves nothing behind.
+    pub fn suspend(&mut self, mut step: GenerationStep) {
+        let schedule = MATCHES_PER_EVAL + step.eval.archive_matches;
+        let rest = (schedule - step.played % schedule) % schedule;
+        if step.done || self.step(&mut step, rest) {
+            return;
+        }
+        self.keep_partial(step.eval);
+    }
+
+    fn fell_off_cliff(&self) -> bool {
+        match (self.challenger_score, self.rollback_threshold) {
+            (Some(score), Some(threshold)) => score < threshold,
+            _ => false,
+        }
     }
 
     /// Create next generation through selection, crossover, and mutation
     pub fn evolve(&mut self, rng: &mut impl Rng) {
+        // With Pareto selection genomes rank by front and crowding, carried
+        // in copies as their fitness, for both elitism and parent selection
+        let pareto_ranked = self.pareto_ranked();
+
         // Sort by fitness descending
-        self.genomes.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+        self.genomes.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        let ranked = pareto_ranked.as_deref().unwrap_or(&self.genomes);
 
-        let mut new_genomes = Vec::with_capacity(POPULATION_SIZE);
+        let size = self.genomes.len();
+        let mut new_genomes = Vec::with_capacity(size);
+        let elite_count = self.selection.elite_count.min(size - 2);
 
-        // Keep elites
-        for i in 0..ELITE_COUNT {
-            let mut elite = self.genomes[i].clone();
+        // Keep elites, always including the reigning champion
+        if let Some(champion) = &self.champion {
+            let mut elite = champion.genome.clone();
             elite.fitness = 0.0;
+            elite.age += 1;
             new_genomes.push(elite);
         }
+        for genome in ranked {
+            if new_genomes.len() >= elite_count {
+                break;
+            }
+            if new_genomes.iter().any(|e| e.weights == genome.weights) {
+                continue;
+            }
+            let mut elite = genome.clone();
+            elite.fitness = 0.0;
+            elite.age += 1;
+            new_genomes.push(elite);
+        }
+
+        // With speciation, parents compete on fitness shared within their
+        // species; Pareto ranking takes its place when both are on
+        let shared;
+        let parents = match (&pareto_ranked, &mut self.speciation) {
+            (Some(ranked), _) => ranked,
+            (None, Some(speciation)) => {
+                shared = speciation.select_parents(&self.genomes, self.generation);
+                &shared
+            }
+            (None, None) => &self.genomes,
+        };
 
-        // Fill rest with offspring
-        while new_genomes.len() < POPULATION_SIZE {
-            let parent1 = tournament_select(&self.genomes, rng);
-            let parent2 = tournament_select(&self.genomes, rng);
+        // Fill rest with offspring, breeding extra candidates for the surrogate
+        // to screen when it has proven able to rank them
+        let slots = size - new_genomes.len();
+        let oversample = self.surrogate.as_ref().map_or(1, |s| s.oversample());
+        let (mutation, tournament) = match self.annealed_selection() {
+            Some((strength, tournament)) => {
+                (self.mutation.with_weight_strength(strength), tournament)
+            }
+            None => (self.mutation.clone(), self.selection.tournament_size),
+        };
+        let mut offspring: Vec<Genome> = (0..slots * oversample)
+            .map(|_| self.breed(parents, &mutation, tournament, rng))
+            .collect();
+        if let Some(surrogate) = self.surrogate.as_ref().filter(|_| oversample > 1) {
+            let mut scored: Vec<(f32, Genome)> = offspring
+                .into_iter()
+                .map(|child| (surrogate.predict(&child).unwrap_or(0.0), child))
+                .collect();
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+            offspring = scored.into_iter().map(|(_, child)| child).collect();
+        }
+        new_genomes.extend(offspring.into_iter().take(slots));
 
-            let mut child = if rng.gen::<f32>() < CROSSOVER_RATE {
-                Genome::crossover(parent1, parent2, rng)
-            } else {
-                parent1.clone()
+        // A run that has gone `restart_after` generations without a new best
+        // trades the bottom half of its offspring for random genomes
+        self.restarted = self.stagnating();
+        if self.restarted {
+            let init = Initialization {
+                network: self.network(),
+                ..Default::default()
             };
-            child.fitness = 0.0;
-
-            child.mutate(MUTATION_RATE, MUTATION_STRENGTH, rng);
-            new_genomes.push(child);
+            let fresh = (size / 2).min(slots);
+            new_genomes.truncate(size - fresh);
+            new_genomes.extend((0..fresh).map(|_| Genome::random_with(&init, rng)));
+            self.peak_generation = self.generation;
         }
 
         self.genomes = new_genomes;
+        // Objectives belong to the genomes they were measured on
+        self.objectives.clear();
         self.generation += 1;
     }
 
+    /// Whether `restart_after` generations have passed since the best
+    /// fitness last improved or the population was last restarted
+    pub fn stagnating(&self) -> bool {
+        let window = self.selection.restart_after;
+        window > 0 && self.generation >= self.peak_generation + window
+    }
+
+    /// Copies of the genomes best first by NSGA-II's crowded comparison of
+    /// their objectives, scored by it; None unless Pareto selection is on and
+    /// the genomes have been evaluated
+    fn pareto_ranked(&self) -> Option<Vec<Genome>> {
+        if !self.pareto || self.objectives.len() != self.genomes.len() {
+            return None;
+        }
+        let scores = pareto::selection_scores(&self.objectives);
+        let mut ranked: Vec<Genome> = self
+            .genomes
+            .iter()
+            .zip(scores)
+            .map(|(genome, score)| Genome {
+                fitness: score,
+                ..genome.clone()
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        Some(ranked)
+    }
+
+    fn breed(
+        &self,
+        parents: &[Genome],
+        mutation: &LayerMutation,
+        tournament: usize,
+        rng: &mut impl Rng,
+    ) -> Genome {
+        let parent1 = tournament_select(parents, tournament, rng);
+        let parent2 = tournament_select(parents, tournament, rng);
+
+        let mut child = if rng.gen::<f32>() < self.selection.crossover_rate {
+            Genome::crossover(parent1, parent2, rng)
+        } else {
+            parent1.clone()
+        };
+        child.fitness = 0.0;
+        child.age = 0;
+
+        child.mutate(mutation, rng);
+        child
+    }
+
+    /// Write the reigning champion, or the fittest genome if none has been crowned yet
+    pub fn save_champion(&self, path: impl AsRef<Path>) -> Result<(), DuelError> {
+        match &self.champion {
+            Some(champion) => champion.genome.save(path, champion.generation),
+            None => self.get_top_two().0.save(path, self.generation),
+        }
+    }
+
     /// Get the two best genomes for showcase
     pub fn get_top_two(&self) -> (Genome, Genome) {
         let mut sorted: Vec<&Genome> = self.genomes.iter().collect();
-        sorted.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+        sorted.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
         (sorted[0].clone(), sorted[1].clone())
     }
 }
 
-fn tournament_select<'a>(genomes: &'a [Genome], rng: &mut impl Rng) -> &'a Genome {
+/// What `evolve` and `evaluate` change besides the running totals, kept so
+/// `advance` can undo a generation
+#[derive(Clone)]
+struct Snapshot {
+    genomes: Vec<Genome>,
+    generation: usize,
+    best_fitness: f32,
+    peak_fitness: f32,
+    peak_generation: usize,
+    hall_of_fame: HallOfFame,
+    champion: Option<ArchiveEntry>,
+    champion_changed: bool,
+    challenger_score: Option<f32>,
+    archive_win_rate: Option<f32>,
+    surrogate: Option<Surrogate>,
+    speciation: Option<Speciation>,
+    novelty: Option<NoveltySearch>,
+    objectives: Vec<Objectives>,
+    matches_per_genome: Vec<usize>,
+    bot_scores: Option<[f32; Bot::ALL.len()]>,
+}
+
+impl Snapshot {
+    fn of(pop: &Population) -> Self {
+        Snapshot {
+            genomes: pop.genomes.clone(),
+            generation: pop.generation,
+            best_fitness: pop.best_fitness,
+            peak_fitness: pop.peak_fitness,
+            peak_generation: pop.peak_generation,
+            hall_of_fame: pop.hall_of_fame.clone(),
+            champion: pop.champion.clone(),
+            champion_changed: pop.champion_changed,
+            challenger_score: pop.challenger_score,
+            archive_win_rate: pop.archive_win_rate,
+            surrogate: pop.surrogate.clone(),
+            speciation: pop.speciation.clone(),
+            novelty: pop.novelty.clone(),
+            objectives: pop.objectives.clone(),
+            matches_per_genome: pop.matches_per_genome.clone(),
+            bot_scores: pop.bot_scores,
+        }
+    }
+
+    fn restore(self, pop: &mut Population) {
+        pop.genomes = self.genomes;
+        pop.generation = self.generation;
+        pop.best_fitness = self.best_fitness;
+        pop.peak_fitness = self.peak_fitness;
+        pop.peak_generation = self.peak_generation;
+        pop.hall_of_fame = self.hall_of_fame;
+        pop.champion = self.champion;
+        pop.champion_changed = self.champion_changed;
+        pop.challenger_score = self.challenger_score;
+        pop.archive_win_rate = self.archive_win_rate;
+        pop.surrogate = self.surrogate;
+        pop.speciation = self.speciation;
+        pop.novelty = self.novelty;
+        pop.objectives = self.objectives;
+        pop.matches_per_genome = self.matches_per_genome;
+        pop.bot_scores = self.bot_scores;
+    }
+}
+
+fn tournament_select<'a>(genomes: &'a [Genome], size: usize, rng: &mut impl Rng) -> &'a Genome {
     let mut best = &genomes[rng.gen_range(0..genomes.len())];
-    for _ in 1..TOURNAMENT_SIZE {
+    for _ in 1..size {
         let candidate = &genomes[rng.gen_range(0..genomes.len())];
         if candidate.fitness > best.fitness {
             best = candidate;
@@ -108,3 +1386,228 @@ fn tournament_select<'a>(genomes: &'a [Genome], rng: &mut impl Rng) -> &'a Genom
     }
     best
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let run = || {
+            let mut rng = StdRng::seed_from_u64(11);
+            let layout = ArenaLayout {
+                asteroid_count: 2,
+                ..Default::default()
+            };
+            let mut pop = Population::with_size(layout, MIN_POPULATION_SIZE, &mut rng);
+            pop.evaluate(&mut rng).unwrap();
+            let mut rng = pop.generation_rng();
+            pop.evolve(&mut rng);
+            pop.evaluate(&mut rng).unwrap();
+            pop
+        };
+        let (a, b) = (run(), run());
+        for (ga, gb) in a.genomes.iter().zip(&b.genomes) {
+            assert_eq!(ga.weights, gb.weights);
+            assert_eq!(ga.fitness, gb.fitness);
+        }
+        assert_eq!(a.matches_played, b.matches_played);
+    }
+
+    #[test]
+    fn annealing_trades_exploration_for_exploitation() {
+        let mut selection = Selection::default();
+        assert_eq!(selection.anneal_progress(50, Some(0)), None);
+        selection.set("anneal_generations", 100.0).unwrap();
+        selection.set("final_mutation_strength", 0.2).unwrap();
+        selection.set("final_tournament_size", 9.0).unwrap();
+        selection.set("reheat_after", 10.0).unwrap();
+        assert!(selection.set("final_mutation_strength", -1.0).is_err());
+
+        assert_eq!(selection.annealed(0.0), (1.0, TOURNAMENT_SIZE));
+        assert_eq!(selection.anneal_progress(50, Some(2)), Some(0.5));
+        let (strength, tournament) = selection.annealed(1.0);
+        assert!((strength - 0.2).abs() < 1e-6);
+        assert_eq!(tournament, 9);
+        assert_eq!(selection.anneal_progress(500, None), Some(1.0));
+        // A champion unbeaten for too long sends the run back to exploring
+        assert_eq!(selection.anneal_progress(500, Some(10)), Some(0.0));
+
+        let mut rng = StdRng::seed_from_u64(14);
+        let mut pop = Population::with_size(ArenaLayout::default(), MIN_POPULATION_SIZE, &mut rng);
+        assert_eq!(pop.annealed_selection(), None);
+        pop.selection = selection;
+        pop.generation = 100;
+        assert_eq!(pop.annealed_selection().map(|(_, t)| t), Some(9));
+        let weaker = pop.mutation.with_weight_strength(0.5);
+        assert_eq!(weaker.bias.strength, pop.mutation.bias.strength * 0.5);
+        assert_eq!(weaker.loadout, pop.mutation.loadout);
+    }
+
+    #[test]
+    fn generations_that_fall_off_a_cliff_are_bred_again() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let mut pop = Population::with_size(ArenaLayout::default(), MIN_POPULATION_SIZE, &mut rng);
+        pop.evaluate(&mut rng).unwrap();
+        let archived = pop.hall_of_fame.len();
+
+        // A challenger short of a clean sweep counts as a cliff
+        pop.rollback_threshold = Some(1.0);
+        pop.advance(&mut rng).unwrap();
+        let score = pop.challenger_score.unwrap();
+        assert_eq!(pop.generation, 1);
+        // Only the generation finally kept is archived
+        assert_eq!(pop.hall_of_fame.len(), archived + 1);
+        if score < 1.0 {
+            assert_eq!(pop.rollbacks, MAX_ROLLBACKS);
+        }
+    }
+
+    #[test]
+    fn stagnant_populations_reseed_their_bottom_half() {
+        let mut rng = StdRng::seed_from_u64(15);
+        let size = 2 * MIN_POPULATION_SIZE;
+        let mut pop = Population::with_size(ArenaLayout::default(), size, &mut rng);
+        pop.selection.set("restart_after", 3.0).unwrap();
+        pop.evaluate(&mut rng).unwrap();
+        assert_eq!(pop.peak_fitness, pop.best_fitness);
+        pop.evolve(&mut rng);
+        assert!(!pop.restarted);
+
+        pop.evaluate(&mut rng).unwrap();
+        pop.generation = pop.peak_generation + 3;
+        let champion = pop.champion.clone().unwrap().genome.weights;
+        pop.evolve(&mut rng);
+        assert!(pop.restarted);
+        assert_eq!(pop.genomes.len(), size);
+        assert_eq!(pop.genomes[0].weights, champion);
+        // The wait for a new best starts over
+        assert_eq!(pop.peak_generation, pop.generation - 1);
+        assert!(!pop.stagnating());
+    }
+
+    #[test]
+    fn stepped_generations_match_threaded_ones() {
+        let new = || {
+            let mut rng = StdRng::seed_from_u64(16);
+            Population::with_size(ArenaLayout::default(), MIN_POPULATION_SIZE, &mut rng)
+        };
+        let fitness = |pop: &Population| pop.genomes.iter().map(|g| g.fitness).collect::<Vec<_>>();
+        let (mut threaded, mut stepped) = (new(), new());
+
+        threaded.evaluate(&mut StdRng::seed_from_u64(1)).unwrap();
+        let mut step = stepped.begin_evaluate(StdRng::seed_from_u64(1));
+        let mut frames = 1;
+        while !stepped.step(&mut step, 7) {
+            frames += 1;
+        }
+        assert!(frames > 1);
+        assert_eq!(fitness(&stepped), fitness(&threaded));
+
+        threaded.advance(&mut StdRng::seed_from_u64(2)).unwrap();
+        let mut step = stepped.begin_advance(StdRng::seed_from_u64(2));
+        while !stepped.step(&mut step, 7) {}
+        assert!(step.is_done());
+        assert_eq!(stepped.generation, 1);
+        assert_eq!(fitness(&stepped), fitness(&threaded));
+        // A finished generation is not evaluated again
+        let archived = stepped.hall_of_fame.len();
+        assert!(stepped.step(&mut step, 7));
+        assert_eq!(stepped.hall_of_fame.len(), archived);
+    }
+
+    #[test]
+    fn interrupted_generations_resume_where_they_stopped() {
+        let new = || {
+            let mut rng = StdRng::seed_from_u64(17);
+            let mut pop =
+                Population::with_size(ArenaLayout::default(), MIN_POPULATION_SIZE, &mut rng);
+            pop.evaluate(&mut rng).unwrap();
+            pop
+        };
+        let fitness = |pop: &Population| pop.genomes.iter().map(|g| g.fitness).collect::<Vec<_>>();
+        let mut whole = new();
+        let mut step = whole.begin_advance(StdRng::seed_from_u64(3));
+        while !whole.step(&mut step, 7) {}
+
+        // A window closing mid-genome finishes that genome, then stops
+        let mut cut = new();
+        let checkpoint = cut.to_checkpoint_string();
+        let mut step = cut.begin_advance(StdRng::seed_from_u64(3));
+        cut.step(&mut step, 12);
+        cut.suspend(step);
+        let partial = cut.partial.take().unwrap();
+        assert_eq!(partial.generation, 1);
+        assert!(partial.evaluated() > 0 && partial.evaluated() < partial.genomes.len());
+
+        // The threaded evaluation of the resumed run plays only the rest
+        let mut resumed = Population::from_checkpoint_str(&checkpoint).unwrap();
+        let text = partial.to_file_string();
+        resumed.partial = Some(PartialEvaluation::from_file_str(&text, &resumed).unwrap());
+        resumed.advance(&mut StdRng::seed_from_u64(99)).unwrap();
+        assert_eq!(resumed.generation, whole.generation);
+        assert_eq!(resumed.matches_played, whole.matches_played);
+        assert_eq!(fitness(&resumed), fitness(&whole));
+        assert!(resumed.partial.is_none());
+
+        // A stale generation is refused, and an interrupt stops between genomes
+        assert!(PartialEvaluation::from_file_str(&text, &whole).is_err());
+        resumed.progress.interrupt();
+        assert!(resumed.advance(&mut StdRng::seed_from_u64(4)).is_err());
+        let partial = resumed.partial.as_ref().unwrap();
+        assert_eq!((partial.generation, partial.evaluated()), (2, 0));
+        // The flag stays up, and a time budget does not outlast it
+        resumed.time_budget = Some(5.0);
+        assert!(resumed.advance(&mut StdRng::seed_from_u64(4)).is_err());
+        assert_eq!(resumed.partial.as_ref().unwrap().evaluated(), 0);
+    }
+
+    #[test]
+    fn weaker_challengers_leave_the_champion_reigning() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let mut pop = Population::with_size(ArenaLayout::default(), MIN_POPULATION_SIZE, &mut rng);
+        let reigning = pop.genomes[0].clone();
+        pop.challenge_champion(reigning.clone(), &mut rng);
+        assert!(pop.champion_changed);
+
+        // A ship whose fire output never passes 0.5 cannot win a single match
+        let idle = Genome::from_weights(vec![0.0; GENOME_SIZE]).unwrap();
+        pop.generation = 1;
+        pop.challenge_champion(idle, &mut rng);
+        assert!(!pop.champion_changed);
+        assert!(pop.challenger_score.unwrap() < CHAMPION_WIN_THRESHOLD);
+        let champion = pop.champion.as_ref().unwrap();
+        assert_eq!(champion.genome.weights, reigning.weights);
+        assert_eq!(champion.generation, 0);
+
+        // The champion is bred into the next generation unchanged, even
+        // when no genome like it is left in the population
+        pop.genomes[0] = Genome::random(&mut rng);
+        pop.evolve(&mut rng);
+        assert!(pop.genomes.iter().any(|g| g.weights == reigning.weights));
+    }
+
+    #[test]
+    fn time_budget_plays_every_genome_and_averages_fitness() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let mut pop = Population::with_size(ArenaLayout::default(), MIN_POPULATION_SIZE, &mut rng);
+        pop.evaluate(&mut rng).unwrap();
+        // The fixed schedule gives each genome MATCHES_PER_EVAL matches of its
+        // own plus those it is drawn into as an opponent
+        let fixed: usize = pop.matches_per_genome.iter().sum();
+        assert_eq!(fixed, 2 * MIN_POPULATION_SIZE * MATCHES_PER_EVAL);
+
+        // Even a budget too short for a full round still scores every genome
+        pop.time_budget = Some(1e-6);
+        pop.evaluate(&mut rng).unwrap();
+        assert!(pop.matches_per_genome.iter().all(|&n| n > 0));
+        assert!(pop.matches_per_genome.iter().sum::<usize>() < fixed);
+        assert!(pop.progress.fraction() >= 1.0);
+        let best = pop
+            .genomes
+            .iter()
+            .map(|g| g.fitness)
+            .fold(f32::MIN, f32::max);
+        assert_eq!(pop.best_fitness, best.max(0.0));
+    }
+}
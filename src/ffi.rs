@@ -0,0 +1,395 @@
+//! C ABI for the match engine. Every handle returned here is owned by the
+//! caller and must be released with the matching `*_free` function. Functions
+//! returning `i32` use 0 for success and -1 for invalid arguments.
+
+use std::ffi::{c_char, CStr};
+use std::slice;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::game::*;
+use crate::genome::*;
+use crate::simulation::*;
+
+/// Opaque step-wise match handle
+pub struct DuelMatch {
+    state: GameState,
+    /// Memory of the network flying each ship, carried between `duel_genome_act` calls
+    networks: [NetworkState; 2],
+}
+
+/// Plain-data snapshot of one ship
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DuelShip {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub rotation: f32,
+    pub alive: u8,
+    pub cloaked: u8,
+    pub shots_fired: u32,
+    pub hits_scored: u32,
+    /// Shield points left, 0 unless the match rules give ships a shield
+    pub shield: f32,
+    pub hull: f32,
+    /// Energy left in [0, 1]; only drained under energy rules
+    pub energy: f32,
+    /// Shots left; only counts when ammo is limited
+    pub ammo: u32,
+    /// Deflector raised; only under deflector rules
+    pub deflecting: u8,
+}
+
+#[no_mangle]
+pub extern "C" fn duel_genome_size() -> usize {
+    GENOME_SIZE
+}
+
+#[no_mangle]
+pub extern "C" fn duel_action_size() -> usize {
+    ACTION_SIZE
+}
+
+/// Build a genome of the default network from `len` weights; returns null unless `len == duel_genome_size()`
+/// and every weight is finite and within the range mutation keeps weights in.
+///
+/// # Safety
+/// `weights` must point to `len` readable floats.
+#[no_mangle]
+pub unsafe extern "C" fn duel_genome_from_weights(weights: *const f32, len: usize) -> *mut Genome {
+    if weights.is_null() || len != GENOME_SIZE {
+        return std::ptr::null_mut();
+    }
+    let weights = slice::from_raw_parts(weights, len).to_vec();
+    match Genome::from_weights(weights) {
+        Ok(genome) => Box::into_raw(Box::new(genome)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Load a genome saved by the trainer, of any network shape; returns null if
+/// the file cannot be read, is malformed or was saved under other game constants.
+///
+/// # Safety
+/// `path` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn duel_genome_load(path: *const c_char) -> *mut Genome {
+    if path.is_null() {
+        return std::ptr::null_mut();
+    }
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return std::ptr::null_mut();
+    };
+    match Genome::load(path) {
+        Ok((genome, _)) => Box::into_raw(Box::new(genome)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Number of weights in a genome: `duel_genome_size()` for the default
+/// network, other counts for loaded genomes of other shapes; 0 for null.
+///
+/// # Safety
+/// `genome` must come from this library.
+#[no_mangle]
+pub unsafe extern "C" fn duel_genome_weight_count(genome: *const Genome) -> usize {
+    if genome.is_null() {
+        return 0;
+    }
+    (*genome).weights.len()
+}
+
+/// Random genome from a seed
+#[no_mangle]
+pub extern "C" fn duel_genome_random(seed: u64) -> *mut Genome {
+    let mut rng = StdRng::seed_from_u64(seed);
+    Box::into_raw(Box::new(Genome::random(&mut rng)))
+}
+
+/// Copy a genome's weights into `out`, which must hold `duel_genome_weight_count(genome)` floats.
+///
+/// # Safety
+/// `genome` must come from this library; `out` must be writable for `len` floats.
+#[no_mangle]
+pub unsafe extern "C" fn duel_genome_weights(
+    genome: *const Genome,
+    out: *mut f32,
+    len: usize,
+) -> i32 {
+    if genome.is_null() || out.is_null() {
+        return -1;
+    }
+    let weights = &(*genome).weights;
+    if len < weights.len() {
+        return -1;
+    }
+    slice::from_raw_parts_mut(out, weights.len()).copy_from_slice(weights);
+    0
+}
+
+/// # Safety
+/// `genome` must come from this library and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn duel_genome_free(genome: *mut Genome) {
+    if !genome.is_null() {
+        drop(Box::from_raw(genome));
+    }
+}
+
+/// Play a full match at training speed and write both ships' fitness to `out_fitness[0..2]`.
+///
+/// # Safety
+/// Genomes must come from this library; `out_fitness` must be writable for two floats.
+#[no_mangle]
+pub unsafe extern "C" fn duel_run_match(
+    g1: *const Genome,
+    g2: *const Genome,
+    seed: u64,
+    out_fitness: *mut f32,
+) -> i32 {
+    if g1.is_null() || g2.is_null() || out_fitness.is_null() {
+        return -1;
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let result = run_match(
+        &*g1,
+        &*g2,
+        &ArenaLayout::default(),
+        &FitnessWeights::default(),
+        &mut rng,
+    );
+    slice::from_raw_parts_mut(out_fitness, 2).copy_from_slice(&result.fitness);
+    0
+}
+
+/// Start a step-wise duel between ships 0 and 1 with seeded random ship placement
+#[no_mangle]
+pub extern "C" fn duel_match_new(seed: u64) -> *mut DuelMatch {
+    let mut rng = StdRng::seed_from_u64(seed);
+    Box::into_raw(Box::new(DuelMatch {
+        state: ArenaLayout::default().new_match(&mut rng),
+        networks: Default::default(),
+    }))
+}
+
+/// Advance the match by `dt` seconds with explicit controls for both ships.
+///
+/// # Safety
+/// `m` must come from this library; each action pointer must hold `duel_action_size()` floats.
+#[no_mangle]
+pub unsafe extern "C" fn duel_match_step(
+    m: *mut DuelMatch,
+    dt: f32,
+    actions0: *const f32,
+    actions1: *const f32,
+) -> i32 {
+    if m.is_null() || actions0.is_null() || actions1.is_null() || !dt.is_finite() || dt <= 0.0 {
+        return -1;
+    }
+    let mut actions = [[0.0; ACTION_SIZE]; 2];
+    actions[0].copy_from_slice(slice::from_raw_parts(actions0, ACTION_SIZE));
+    actions[1].copy_from_slice(slice::from_raw_parts(actions1, ACTION_SIZE));
+    (*m).state.update(dt, &actions);
+    0
+}
+
+/// Compute the controls a genome chooses for `ship_idx` in the current state.
+/// The match keeps each ship's network memory, which recurrent genomes read
+/// back on the next call, so call once per step with the same genome per ship.
+///
+/// # Safety
+/// Handles must come from this library; `out_actions` must hold `duel_action_size()` floats.
+#[no_mangle]
+pub unsafe extern "C" fn duel_genome_act(
+    genome: *const Genome,
+    m: *mut DuelMatch,
+    ship_idx: u32,
+    out_actions: *mut f32,
+) -> i32 {
+    if genome.is_null() || m.is_null() || out_actions.is_null() {
+        return -1;
+    }
+    let m = &mut *m;
+    let ship_idx = ship_idx as usize;
+    if ship_idx >= m.state.ships.len() {
+        return -1;
+    }
+    let inputs = (*genome).sensors(&m.state, ship_idx);
+    let actions = (*genome).evaluate(&inputs, &mut m.networks[ship_idx]);
+    slice::from_raw_parts_mut(out_actions, ACTION_SIZE).copy_from_slice(&actions);
+    0
+}
+
+/// # Safety
+/// `m` must come from this library; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn duel_match_ship(
+    m: *const DuelMatch,
+    ship_idx: u32,
+    out: *mut DuelShip,
+) -> i32 {
+    if m.is_null() || out.is_null() {
+        return -1;
+    }
+    let ships = &(*m).state.ships;
+    if ship_idx as usize >= ships.len() {
+        return -1;
+    }
+    let ship = &ships[ship_idx as usize];
+    *out = DuelShip {
+        x: ship.x,
+        y: ship.y,
+        vx: ship.vx,
+        vy: ship.vy,
+        rotation: ship.rotation,
+        alive: ship.alive as u8,
+        cloaked: ship.cloaked as u8,
+        shots_fired: ship.shots_fired as u32,
+        hits_scored: ship.hits_scored as u32,
+        shield: ship.shield,
+        hull: ship.hull,
+        energy: ship.energy,
+        ammo: ship.ammo as u32,
+        deflecting: ship.deflecting as u8,
+    };
+    0
+}
+
+/// Elapsed match time in seconds, or a negative value for a null handle.
+///
+/// # Safety
+/// `m` must come from this library.
+#[no_mangle]
+pub unsafe extern "C" fn duel_match_time(m: *const DuelMatch) -> f32 {
+    if m.is_null() {
+        return -1.0;
+    }
+    (*m).state.time
+}
+
+/// 1 if the match has ended, 0 if still running, -1 for a null handle.
+///
+/// # Safety
+/// `m` must come from this library.
+#[no_mangle]
+pub unsafe extern "C" fn duel_match_is_over(m: *const DuelMatch) -> i32 {
+    if m.is_null() {
+        return -1;
+    }
+    (*m).state.match_over as i32
+}
+
+/// Index of the winning ship, or -1 for a draw, unfinished match or null handle.
+///
+/// # Safety
+/// `m` must come from this library.
+#[no_mangle]
+pub unsafe extern "C" fn duel_match_winner(m: *const DuelMatch) -> i32 {
+    if m.is_null() {
+        return -1;
+    }
+    (*m).state.winner.map_or(-1, |w| w as i32)
+}
+
+/// # Safety
+/// `m` must come from this library and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn duel_match_free(m: *mut DuelMatch) {
+    if !m.is_null() {
+        drop(Box::from_raw(m));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_run_through_the_c_functions() {
+        unsafe {
+            let genome = duel_genome_random(3);
+            let m = duel_match_new(5);
+            assert!(!genome.is_null() && !m.is_null());
+
+            let mut weights = vec![0.0; duel_genome_size()];
+            assert_eq!(
+                duel_genome_weights(genome, weights.as_mut_ptr(), weights.len()),
+                0
+            );
+            let copy = duel_genome_from_weights(weights.as_ptr(), weights.len());
+            assert!(!copy.is_null());
+            assert!(duel_genome_from_weights(weights.as_ptr(), weights.len() - 1).is_null());
+
+            let mut start = DuelShip::default();
+            assert_eq!(duel_match_ship(m, 0, &mut start), 0);
+            assert_eq!(start.alive, 1);
+            assert!(start.hull > 0.0);
+            assert_eq!(duel_action_size(), 5);
+            let mut actions = [[0.0; ACTION_SIZE]; 2];
+            for (ship, out) in actions.iter_mut().enumerate() {
+                assert_eq!(duel_genome_act(copy, m, ship as u32, out.as_mut_ptr()), 0);
+            }
+            actions[0] = [1.0, 0.0, 0.0, 0.0, 0.0];
+            for _ in 0..10 {
+                let (a0, a1) = (actions[0].as_ptr(), actions[1].as_ptr());
+                assert_eq!(duel_match_step(m, 1.0 / 60.0, a0, a1), 0);
+            }
+            assert!(duel_match_time(m) > 0.1);
+
+            let mut moved = DuelShip::default();
+            assert_eq!(duel_match_ship(m, 0, &mut moved), 0);
+            assert!(moved.vx != start.vx || moved.vy != start.vy);
+            assert_eq!(duel_match_ship(m, 2, &mut moved), -1);
+            assert_eq!(
+                duel_match_step(m, 0.0, actions[0].as_ptr(), actions[1].as_ptr()),
+                -1
+            );
+
+            duel_match_free(m);
+            duel_genome_free(copy);
+            duel_genome_free(genome);
+            assert_eq!(duel_match_is_over(std::ptr::null()), -1);
+            assert!(duel_genome_load(std::ptr::null()).is_null());
+            assert!(duel_genome_load(c"no/such/champion.genome".as_ptr()).is_null());
+        }
+    }
+
+    #[test]
+    fn recurrent_genomes_keep_their_memory_across_calls() {
+        let init = Initialization {
+            network: NetworkConfig::parse("recurrent:8").unwrap(),
+            ..Initialization::default()
+        };
+        let genome = Genome::random_with(&init, &mut StdRng::seed_from_u64(4));
+        let mut memory = NetworkState::default();
+        unsafe {
+            let handle = Box::into_raw(Box::new(genome.clone()));
+            assert_eq!(duel_genome_weight_count(handle), genome.weights.len());
+            let mut weights = vec![0.0; genome.weights.len()];
+            assert_eq!(
+                duel_genome_weights(handle, weights.as_mut_ptr(), weights.len() - 1),
+                -1
+            );
+            assert_eq!(
+                duel_genome_weights(handle, weights.as_mut_ptr(), weights.len()),
+                0
+            );
+            assert_eq!(weights, genome.weights);
+
+            let m = duel_match_new(9);
+            let inputs = Genome::get_inputs(&(*m).state, 1);
+            let mut calls = [[0.0; ACTION_SIZE]; 2];
+            for out in &mut calls {
+                assert_eq!(duel_genome_act(handle, m, 1, out.as_mut_ptr()), 0);
+                assert_eq!(*out, genome.evaluate(&inputs, &mut memory));
+            }
+            assert_ne!(calls[0], calls[1]);
+
+            duel_match_free(m);
+            duel_genome_free(handle);
+        }
+    }
+}
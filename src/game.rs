@@ -1,10 +1,15 @@
 use rand::Rng;
 
+use crate::error::{check_range, DuelError};
+use crate::profiling::Span;
+
 pub const ARENA_WIDTH: f32 = 1600.0;
 pub const ARENA_HEIGHT: f32 = 1200.0;
 pub const SHIP_ROTATION_SPEED: f32 = 5.0;
 pub const SHIP_THRUST: f32 = 200.0;
-pub const SHIP_DRAG: f32 = 0.98;
+/// Continuous-time drag coefficient (1/s): with no thrust, velocity decays as exp(-SHIP_DRAG * t).
+/// Matches the original 0.98-per-frame damping at 60 Hz.
+pub const SHIP_DRAG: f32 = 1.2122;
 pub const PROJECTILE_SPEED: f32 = 400.0;
 pub const PROJECTILE_LIFETIME: f32 = 0.5;
 pub const FIRE_COOLDOWN: f32 = 0.25;
@@ -13,6 +18,115 @@ pub const SHIP_RADIUS: f32 = 12.0;
 pub const PROJECTILE_RADIUS: f32 = 2.0;
 pub const MAX_PROJECTILES_PER_SHIP: usize = 5;
 pub const MAX_SHIP_SPEED: f32 = 300.0;
+/// Fewest ships in a free-for-all melee; two ships make a duel
+pub const MIN_MELEE_SHIPS: usize = 3;
+/// Most ships in a free-for-all melee
+pub const MAX_MELEE_SHIPS: usize = 8;
+/// Controls per ship per tick: [thrust, turn_left, turn_right, fire, cloak]
+pub const ACTION_SIZE: usize = 5;
+/// Default fraction of the firing ship's velocity carried by a new projectile
+pub const PROJECTILE_INHERITANCE: f32 = 0.3;
+/// Fraction of a full cloak charge used per second while cloaked
+pub const CLOAK_DRAIN_RATE: f32 = 0.25;
+/// Fraction of a full cloak charge regained per second while visible
+pub const CLOAK_RECHARGE_RATE: f32 = 0.1;
+pub const ASTEROID_BASE_RADIUS: f32 = 10.0;
+pub const ASTEROID_MAX_SIZE: u8 = 3;
+pub const ASTEROID_SPEED: f32 = 60.0;
+pub const ASTEROID_SPLIT_SPEED: f32 = 40.0;
+/// Gap kept between a new asteroid and every ship in the stock arena;
+/// smaller arenas keep a proportionally smaller one
+pub const ASTEROID_SPAWN_CLEARANCE: f32 = 150.0;
+/// Random positions tried per asteroid before a match starts without it
+const ASTEROID_SPAWN_ATTEMPTS: usize = 1000;
+/// Most full-size asteroids a layout may request
+pub const MAX_ASTEROIDS: usize = 64;
+/// Largest accepted magnitude for flow-field strengths and accelerations (px/s^2)
+pub const MAX_FLOW_STRENGTH: f32 = 1000.0;
+/// Slowest and fastest turn rate a loadout may choose, as multiples of SHIP_ROTATION_SPEED
+pub const TURN_RATE_RANGE: (f32, f32) = (0.5, 1.5);
+/// Longest time constant (s) a loadout may smooth its turn controls with
+pub const MAX_CONTROL_SMOOTHING: f32 = 0.5;
+/// Damage one projectile deals, in shield or hull points
+pub const PROJECTILE_DAMAGE: f32 = 1.0;
+/// Most shield or hull points a ship may be configured with
+pub const MAX_DEFENSE_POINTS: f32 = 20.0;
+/// Largest magazine a ship may be configured with
+pub const MAX_AMMO: usize = 1000;
+/// Shots an ammo pickup restores, never beyond a full magazine
+pub const PICKUP_AMMO: usize = 5;
+pub const PICKUP_RADIUS: f32 = 8.0;
+/// Pickups in the arena at once; spawning pauses while this many wait
+pub const MAX_PICKUPS: usize = 3;
+/// Fraction of a full energy pool regained per second
+pub const ENERGY_RECHARGE_RATE: f32 = 0.15;
+/// Energy used per second of full thrust
+pub const THRUST_ENERGY_COST: f32 = 0.1;
+/// Energy used by every shot
+pub const SHOT_ENERGY_COST: f32 = 0.08;
+/// Energy used per second while the deflector is raised
+pub const DEFLECTOR_ENERGY_DRAIN: f32 = 0.5;
+/// Share of the arena diagonal within which ships count as engaged (500 px
+/// in the stock arena)
+pub const ENGAGEMENT_SHARE: f32 = 0.25;
+/// Bounds on either side of a non-stock arena (px)
+pub const MIN_ARENA_SIDE: f32 = 200.0;
+pub const MAX_ARENA_SIDE: f32 = 16000.0;
+
+/// Fingerprint of the compiled match rules: arena, engagement, ship, ship
+/// class, loadout, projectile, damage, cloak, asteroid, pickup and energy
+/// constants. Saved genomes record
+/// it, so pilots trained under different rules are not silently pitted
+/// against each other.
+pub fn config_hash() -> u64 {
+    let constants = [
+        ARENA_WIDTH,
+        ARENA_HEIGHT,
+        SHIP_ROTATION_SPEED,
+        SHIP_THRUST,
+        SHIP_DRAG,
+        PROJECTILE_SPEED,
+        PROJECTILE_LIFETIME,
+        FIRE_COOLDOWN,
+        MATCH_DURATION,
+        SHIP_RADIUS,
+        PROJECTILE_RADIUS,
+        MAX_PROJECTILES_PER_SHIP as f32,
+        MAX_SHIP_SPEED,
+        ACTION_SIZE as f32,
+        PROJECTILE_INHERITANCE,
+        CLOAK_DRAIN_RATE,
+        CLOAK_RECHARGE_RATE,
+        ASTEROID_BASE_RADIUS,
+        ASTEROID_MAX_SIZE as f32,
+        ASTEROID_SPEED,
+        ASTEROID_SPLIT_SPEED,
+        ASTEROID_SPAWN_CLEARANCE,
+        TURN_RATE_RANGE.0,
+        TURN_RATE_RANGE.1,
+        PROJECTILE_DAMAGE,
+        PICKUP_AMMO as f32,
+        PICKUP_RADIUS,
+        MAX_PICKUPS as f32,
+        ENERGY_RECHARGE_RATE,
+        THRUST_ENERGY_COST,
+        SHOT_ENERGY_COST,
+        DEFLECTOR_ENERGY_DRAIN,
+        ENGAGEMENT_SHARE,
+    ];
+    let classes = ShipClass::ALL.into_iter().flat_map(|class| {
+        let c = class.config();
+        [c.thrust, c.fire_cooldown, c.projectile_speed, c.hull]
+    });
+    // FNV-1a, which unlike std's hashers is fixed across Rust versions and platforms
+    constants
+        .into_iter()
+        .chain(classes)
+        .flat_map(|c| c.to_bits().to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
 
 #[derive(Clone, Debug)]
 pub struct Ship {
@@ -25,6 +139,238 @@ pub struct Ship {
     pub fire_cooldown: f32,
     pub shots_fired: usize,
     pub hits_scored: usize,
+    /// Hidden from the opponent's sensors; cannot fire while cloaked
+    pub cloaked: bool,
+    /// Remaining cloak charge in [0, 1]
+    pub cloak_energy: f32,
+    pub loadout: Loadout,
+    pub handicap: Handicap,
+    /// Turn command after smoothing, in [-1, 1] (positive turns right)
+    pub turn: f32,
+    /// Remaining shield points, which absorb hits first and regenerate
+    pub shield: f32,
+    /// Remaining hull points; the ship is destroyed when they run out
+    pub hull: f32,
+    /// Shots left in the magazine; only counts when ammo is limited
+    pub ammo: usize,
+    pub class: ShipClass,
+    /// Remaining energy in [0, 1]; only drained when energy is limited
+    pub energy: f32,
+    /// Energy drawn over the match by thrust, shots and the deflector
+    pub energy_spent: f32,
+    /// Deflector raised: projectiles are absorbed harmlessly
+    pub deflecting: bool,
+}
+
+/// Hull design a ship is built to. Each trades speed, firepower and armour
+/// differently; `Standard` is the classic ship every pilot trains on by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShipClass {
+    #[default]
+    Standard,
+    /// Fast and nimble, slow to reload
+    Interceptor,
+    /// Sluggish, armoured and quick firing
+    Gunship,
+    /// Slow firing, with the fastest shots
+    Sniper,
+}
+
+/// Stats of a ship class, as multiples of the stock ship's
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShipConfig {
+    pub thrust: f32,
+    pub fire_cooldown: f32,
+    pub projectile_speed: f32,
+    /// Hull points, on top of whatever the match rules give every ship
+    pub hull: f32,
+}
+
+impl ShipClass {
+    pub const ALL: [ShipClass; 4] = [
+        ShipClass::Standard,
+        ShipClass::Interceptor,
+        ShipClass::Gunship,
+        ShipClass::Sniper,
+    ];
+
+    pub fn config(self) -> ShipConfig {
+        let (thrust, fire_cooldown, projectile_speed, hull) = match self {
+            ShipClass::Standard => (1.0, 1.0, 1.0, 1.0),
+            ShipClass::Interceptor => (1.3, 1.25, 1.0, 1.0),
+            ShipClass::Gunship => (0.75, 0.7, 0.9, 2.0),
+            ShipClass::Sniper => (0.9, 1.6, 1.5, 1.0),
+        };
+        ShipConfig {
+            thrust,
+            fire_cooldown,
+            projectile_speed,
+            hull,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShipClass::Standard => "standard",
+            ShipClass::Interceptor => "interceptor",
+            ShipClass::Gunship => "gunship",
+            ShipClass::Sniper => "sniper",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<ShipClass, DuelError> {
+        ShipClass::ALL
+            .into_iter()
+            .find(|class| class.name() == name)
+            .ok_or_else(|| DuelError::UnknownName {
+                kind: "ship class",
+                name: name.to_string(),
+            })
+    }
+}
+
+/// How ship classes are handed out at the start of each match
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClassMatchups {
+    /// Both ships fly the standard class
+    #[default]
+    Standard,
+    /// Both ships fly the same class, picked at random
+    Mirror,
+    /// Each ship flies a class picked at random
+    Mixed,
+}
+
+impl ClassMatchups {
+    pub fn name(self) -> &'static str {
+        match self {
+            ClassMatchups::Standard => "standard",
+            ClassMatchups::Mirror => "mirror",
+            ClassMatchups::Mixed => "mixed",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<ClassMatchups, DuelError> {
+        [
+            ClassMatchups::Standard,
+            ClassMatchups::Mirror,
+            ClassMatchups::Mixed,
+        ]
+        .into_iter()
+        .find(|matchups| matchups.name() == name)
+        .ok_or_else(|| DuelError::UnknownName {
+            kind: "class matchups",
+            name: name.to_string(),
+        })
+    }
+
+    /// Classes for the two ships of a new match; draws nothing from `rng`
+    /// for standard matchups
+    pub fn pick(self, rng: &mut impl Rng) -> [ShipClass; 2] {
+        let classes = self.pick_for(2, rng);
+        [classes[0], classes[1]]
+    }
+
+    /// Classes for the `count` ships of a new match, mirror matchups giving
+    /// every ship the same one
+    pub fn pick_for(self, count: usize, rng: &mut impl Rng) -> Vec<ShipClass> {
+        let mut random = || ShipClass::ALL[rng.gen_range(0..ShipClass::ALL.len())];
+        match self {
+            ClassMatchups::Standard => vec![ShipClass::Standard; count],
+            ClassMatchups::Mirror => vec![random(); count],
+            ClassMatchups::Mixed => (0..count).map(|_| random()).collect(),
+        }
+    }
+}
+
+/// Handling traits a ship is fitted with, evolvable as part of a genome.
+/// They share one budget: turning faster than stock lengthens the fire
+/// cooldown in proportion and turning slower shortens it, so agility is
+/// paid for with firepower. The default is the stock ship.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Loadout {
+    /// Turn rate as a multiple of SHIP_ROTATION_SPEED, within TURN_RATE_RANGE
+    pub turn_rate: f32,
+    /// Time constant (s) of the low-pass filter on the turn controls; 0 reacts instantly
+    pub smoothing: f32,
+}
+
+impl Default for Loadout {
+    fn default() -> Self {
+        Loadout {
+            turn_rate: 1.0,
+            smoothing: 0.0,
+        }
+    }
+}
+
+impl Loadout {
+    /// Fire cooldown the turn rate costs
+    pub fn fire_cooldown(&self) -> f32 {
+        FIRE_COOLDOWN * self.turn_rate
+    }
+
+    /// The nearest loadout within budget
+    pub fn clamped(self) -> Loadout {
+        Loadout {
+            turn_rate: self.turn_rate.clamp(TURN_RATE_RANGE.0, TURN_RATE_RANGE.1),
+            smoothing: self.smoothing.clamp(0.0, MAX_CONTROL_SMOOTHING),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), DuelError> {
+        check_range(
+            "loadout turn rate",
+            self.turn_rate,
+            TURN_RATE_RANGE.0,
+            TURN_RATE_RANGE.1,
+        )?;
+        check_range(
+            "loadout smoothing",
+            self.smoothing,
+            0.0,
+            MAX_CONTROL_SMOOTHING,
+        )
+    }
+
+    /// "TURN_RATE,SMOOTHING", as `parse` reads it
+    pub fn spec(&self) -> String {
+        format!("{},{}", self.turn_rate, self.smoothing)
+    }
+
+    pub fn parse(spec: &str) -> Result<Loadout, DuelError> {
+        let invalid = || DuelError::InvalidValue {
+            what: "loadout",
+            value: spec.to_string(),
+        };
+        let (turn_rate, smoothing) = spec.split_once(',').ok_or_else(invalid)?;
+        let loadout = Loadout {
+            turn_rate: turn_rate.trim().parse().map_err(|_| invalid())?,
+            smoothing: smoothing.trim().parse().map_err(|_| invalid())?,
+        };
+        loadout.validate()?;
+        Ok(loadout)
+    }
+}
+
+/// Deviation of one ship's weapon from the constants every pilot trains
+/// under, for experiments on how much evolved skill depends on them; the
+/// default is no deviation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Handicap {
+    /// Multiplier on the ship's fire cooldown
+    pub fire_cooldown: f32,
+    /// Projectiles the ship may have in flight at once, instead of MAX_PROJECTILES_PER_SHIP
+    pub max_projectiles: usize,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Handicap {
+            fire_cooldown: 1.0,
+            max_projectiles: MAX_PROJECTILES_PER_SHIP,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -37,13 +383,224 @@ pub struct Projectile {
     pub owner: usize,
 }
 
+/// Drifting rock that splits into smaller fragments when shot
+#[derive(Clone, Debug)]
+pub struct Asteroid {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    /// 1 = smallest fragment, ASTEROID_MAX_SIZE = largest rock
+    pub size: u8,
+    pub angle: f32,
+    pub spin: f32,
+    /// Fixes the jagged outline so a rock keeps its shape between frames
+    pub shape_seed: u32,
+}
+
+/// Ammo crate floating in place until a ship flies over it
+#[derive(Clone, Debug)]
+pub struct Pickup {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A projectile striking a ship that had no deflector raised
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    pub shooter: usize,
+    pub target: usize,
+    pub damage: f32,
+}
+
 #[derive(Clone, Debug)]
 pub struct GameState {
-    pub ships: [Ship; 2],
+    /// Two ships in a duel, up to MAX_MELEE_SHIPS in a free-for-all melee
+    pub ships: Vec<Ship>,
     pub projectiles: Vec<Projectile>,
+    pub asteroids: Vec<Asteroid>,
+    pub pickups: Vec<Pickup>,
+    /// Pickups spawned so far; with `pickup_seed` it fixes where the next one appears
+    pub pickups_spawned: u32,
+    pub pickup_seed: u32,
+    pub flow_field: FlowField,
+    pub physics: PhysicsOptions,
+    pub arena: ArenaSize,
     pub time: f32,
     pub match_over: bool,
     pub winner: Option<usize>,
+    /// Hits landed during the latest update, in the order they struck
+    pub hits: Vec<Hit>,
+}
+
+/// Ambient force field that pushes ships and projectiles every update.
+/// Strengths are accelerations in pixels per second squared.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum FlowField {
+    #[default]
+    None,
+    /// Constant acceleration across the whole arena
+    Wind { ax: f32, ay: f32 },
+    /// Swirl around a center point, fading out with distance
+    Vortex {
+        x: f32,
+        y: f32,
+        strength: f32,
+        radius: f32,
+    },
+    /// Slowly drifting Perlin-noise currents, tiled to match the toroidal arena
+    Noise {
+        seed: u32,
+        cells: u32,
+        strength: f32,
+    },
+}
+
+/// Firing physics that change evolved tactics; defaults reproduce the classic game
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhysicsOptions {
+    /// Fraction of the firing ship's velocity added to a new projectile
+    pub projectile_inheritance: f32,
+    /// Speed (px/s) the firing ship loses along its heading with every shot
+    pub recoil: f32,
+    /// Shield points each ship starts with; 0 leaves ships unshielded
+    pub shield: f32,
+    /// Shield points regenerated per second, up to `shield`
+    pub shield_regen: f32,
+    /// Hull points each ship starts with; 1 makes every unshielded hit fatal
+    pub hull: f32,
+    /// Shots each ship starts with; 0 means unlimited
+    pub ammo: usize,
+    /// Seconds between ammo pickup spawns; 0 spawns none
+    pub pickup_interval: f32,
+    /// Thrust and shots draw on a recharging energy pool
+    pub energy: bool,
+    /// The fifth control raises an energy-hungry deflector instead of the cloak
+    pub deflector: bool,
+    /// Opposing projectiles that touch destroy each other, so shots can be
+    /// fired to intercept incoming ones
+    pub projectile_collisions: bool,
+}
+
+impl Default for PhysicsOptions {
+    fn default() -> Self {
+        PhysicsOptions {
+            projectile_inheritance: PROJECTILE_INHERITANCE,
+            recoil: 0.0,
+            shield: 0.0,
+            shield_regen: 0.0,
+            hull: PROJECTILE_DAMAGE,
+            ammo: 0,
+            pickup_interval: 0.0,
+            energy: false,
+            deflector: false,
+            projectile_collisions: false,
+        }
+    }
+}
+
+impl PhysicsOptions {
+    /// Whether ships survive hits, so shield and hull are worth showing
+    pub fn has_defenses(&self) -> bool {
+        self.shield > 0.0 || self.hull > PROJECTILE_DAMAGE
+    }
+
+    pub fn limits_ammo(&self) -> bool {
+        self.ammo > 0
+    }
+
+    /// Whether ships run on energy, so the pool is worth showing
+    pub fn uses_energy(&self) -> bool {
+        self.energy || self.deflector
+    }
+}
+
+/// Playing field dimensions in pixels. Every genome trains in the stock
+/// ARENA_WIDTH x ARENA_HEIGHT field; other sizes test how well it generalizes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArenaSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for ArenaSize {
+    fn default() -> Self {
+        ArenaSize {
+            width: ARENA_WIDTH,
+            height: ARENA_HEIGHT,
+        }
+    }
+}
+
+impl ArenaSize {
+    /// The stock field with both sides multiplied by `factor`
+    pub fn scaled(factor: f32) -> Self {
+        ArenaSize {
+            width: ARENA_WIDTH * factor,
+            height: ARENA_HEIGHT * factor,
+        }
+    }
+
+    /// Linear size relative to the stock field, by area: 1 for the stock
+    /// field, 2 for one twice as wide and high
+    pub fn scale(&self) -> f32 {
+        (self.width * self.height / (ARENA_WIDTH * ARENA_HEIGHT)).sqrt()
+    }
+
+    pub fn diagonal(&self) -> f32 {
+        (self.width * self.width + self.height * self.height).sqrt()
+    }
+
+    /// Gap kept between a new asteroid and every ship
+    pub fn spawn_clearance(&self) -> f32 {
+        ASTEROID_SPAWN_CLEARANCE * self.scale().min(1.0)
+    }
+
+    /// Distance at which distance sensors saturate and proximity stops
+    /// counting toward fitness, a fixed share of the diagonal so both keep
+    /// their meaning in any arena
+    pub fn engagement_range(&self) -> f32 {
+        self.diagonal() * ENGAGEMENT_SHARE
+    }
+
+    /// Parse "WIDTHxHEIGHT", e.g. "2400x1800"
+    pub fn parse(spec: &str) -> Result<Self, DuelError> {
+        let invalid = || DuelError::InvalidValue {
+            what: "arena size",
+            value: spec.to_string(),
+        };
+        let (width, height) = spec.trim().split_once('x').ok_or_else(invalid)?;
+        let size = ArenaSize {
+            width: width.trim().parse().map_err(|_| invalid())?,
+            height: height.trim().parse().map_err(|_| invalid())?,
+        };
+        size.validate()?;
+        Ok(size)
+    }
+
+    pub fn spec(&self) -> String {
+        format!("{}x{}", self.width, self.height)
+    }
+
+    /// Reject fields too cramped to fit two ships or too large to simulate
+    pub fn validate(&self) -> Result<(), DuelError> {
+        check_range("arena width", self.width, MIN_ARENA_SIDE, MAX_ARENA_SIDE)?;
+        check_range("arena height", self.height, MIN_ARENA_SIDE, MAX_ARENA_SIDE)
+    }
+}
+
+/// Environment settings used to set up every match in a run
+#[derive(Clone, Debug, Default)]
+pub struct ArenaLayout {
+    pub physics: PhysicsOptions,
+    /// Field size; not saved in checkpoints or replays, which always use the stock field
+    pub arena: ArenaSize,
+    pub flow_field: FlowField,
+    /// Pick a fresh random flow field for every match (domain randomization)
+    pub randomize_flow_field: bool,
+    /// Number of full-size asteroids spawned at the start of each match
+    pub asteroid_count: usize,
+    pub class_matchups: ClassMatchups,
 }
 
 impl Ship {
@@ -58,154 +615,321 @@ impl Ship {
             fire_cooldown: 0.0,
             shots_fired: 0,
             hits_scored: 0,
+            cloaked: false,
+            cloak_energy: 1.0,
+            loadout: Loadout::default(),
+            handicap: Handicap::default(),
+            turn: 0.0,
+            shield: 0.0,
+            hull: PROJECTILE_DAMAGE,
+            ammo: 0,
+            class: ShipClass::Standard,
+            energy: 1.0,
+            energy_spent: 0.0,
+            deflecting: false,
+        }
+    }
+
+    /// Draw up to `amount` energy and return the fraction of it available
+    fn spend_energy(&mut self, amount: f32) -> f32 {
+        if amount <= 0.0 {
+            return 1.0;
+        }
+        let drawn = amount.min(self.energy);
+        self.energy -= drawn;
+        self.energy_spent += drawn;
+        drawn / amount
+    }
+
+    /// Seconds between shots under its loadout, class and handicap
+    pub fn fire_interval(&self) -> f32 {
+        self.loadout.fire_cooldown()
+            * self.class.config().fire_cooldown
+            * self.handicap.fire_cooldown
+    }
+
+    /// Hull points the ship starts a match with under `physics`
+    pub fn starting_hull(&self, physics: &PhysicsOptions) -> f32 {
+        physics.hull * self.class.config().hull
+    }
+
+    /// Take a hit: the shield absorbs what it can and the hull the rest
+    pub fn damage(&mut self, amount: f32) {
+        let absorbed = amount.min(self.shield);
+        self.shield -= absorbed;
+        self.hull -= amount - absorbed;
+        if self.hull <= 0.0 {
+            self.hull = 0.0;
+            self.alive = false;
+        }
+    }
+}
+
+impl Asteroid {
+    pub fn random(arena: ArenaSize, rng: &mut impl Rng) -> Self {
+        let heading = rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = rng.gen_range(0.3..1.0) * ASTEROID_SPEED;
+        Asteroid {
+            x: rng.gen_range(0.0..arena.width),
+            y: rng.gen_range(0.0..arena.height),
+            vx: heading.cos() * speed,
+            vy: heading.sin() * speed,
+            size: ASTEROID_MAX_SIZE,
+            angle: rng.gen_range(0.0..std::f32::consts::TAU),
+            spin: rng.gen_range(-1.0..1.0),
+            shape_seed: rng.gen(),
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        ASTEROID_BASE_RADIUS * (1u32 << (self.size - 1)) as f32
+    }
+
+    /// Break into two smaller fragments that inherit this rock's velocity,
+    /// or nothing if already the smallest size
+    fn split(&self, impact_vx: f32, impact_vy: f32) -> Vec<Asteroid> {
+        if self.size <= 1 {
+            return Vec::new();
         }
+        // Fragments fly apart perpendicular to the incoming shot
+        let len = (impact_vx * impact_vx + impact_vy * impact_vy)
+            .sqrt()
+            .max(1.0);
+        let (px, py) = (-impact_vy / len, impact_vx / len);
+        [1.0f32, -1.0]
+            .iter()
+            .enumerate()
+            .map(|(k, &side)| Asteroid {
+                vx: self.vx + px * side * ASTEROID_SPLIT_SPEED,
+                vy: self.vy + py * side * ASTEROID_SPLIT_SPEED,
+                size: self.size - 1,
+                spin: self.spin * -side * 1.5,
+                shape_seed: self.shape_seed.wrapping_mul(31).wrapping_add(k as u32 + 1),
+                ..self.clone()
+            })
+            .collect()
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl GameState {
-    #[allow(dead_code)]
     pub fn new() -> Self {
         GameState {
-            ships: [
+            ships: vec![
                 Ship::new(200.0, 300.0, 0.0),
                 Ship::new(600.0, 300.0, std::f32::consts::PI),
             ],
             projectiles: Vec::new(),
+            asteroids: Vec::new(),
+            pickups: Vec::new(),
+            pickups_spawned: 0,
+            pickup_seed: 0,
+            flow_field: FlowField::None,
+            physics: PhysicsOptions::default(),
+            arena: ArenaSize::default(),
             time: 0.0,
             match_over: false,
             winner: None,
+            hits: Vec::new(),
         }
     }
 
-    pub fn new_random(rng: &mut impl Rng) -> Self {
+    /// Both ships at random positions and headings in `arena`
+    pub fn new_random(arena: ArenaSize, rng: &mut impl Rng) -> Self {
+        Self::with_random_ships(arena, 2, rng)
+    }
+
+    /// `count` ships at random positions and headings in `arena`
+    pub fn with_random_ships(arena: ArenaSize, count: usize, rng: &mut impl Rng) -> Self {
         let tau = std::f32::consts::TAU;
         GameState {
-            ships: [
-                Ship::new(
-                    rng.gen_range(0.0..ARENA_WIDTH),
-                    rng.gen_range(0.0..ARENA_HEIGHT),
-                    rng.gen_range(0.0..tau),
-                ),
-                Ship::new(
-                    rng.gen_range(0.0..ARENA_WIDTH),
-                    rng.gen_range(0.0..ARENA_HEIGHT),
-                    rng.gen_range(0.0..tau),
-                ),
-            ],
+            ships: (0..count)
+                .map(|_| {
+                    Ship::new(
+                        rng.gen_range(0.0..arena.width),
+                        rng.gen_range(0.0..arena.height),
+                        rng.gen_range(0.0..tau),
+                    )
+                })
+                .collect(),
             projectiles: Vec::new(),
+            asteroids: Vec::new(),
+            pickups: Vec::new(),
+            pickups_spawned: 0,
+            pickup_seed: 0,
+            flow_field: FlowField::None,
+            physics: PhysicsOptions::default(),
+            arena,
             time: 0.0,
             match_over: false,
             winner: None,
+            hits: Vec::new(),
         }
     }
 
-    pub fn update(&mut self, dt: f32, actions: &[[f32; 4]; 2]) {
+    /// The nearest other ship still flying, or the nearest other ship once
+    /// none is; in a duel always the opponent
+    pub fn nearest_opponent(&self, ship: usize) -> usize {
+        let me = &self.ships[ship];
+        let distance = |other: &Ship| {
+            let dx = toroidal_diff(other.x, me.x, self.arena.width);
+            let dy = toroidal_diff(other.y, me.y, self.arena.height);
+            dx * dx + dy * dy
+        };
+        (0..self.ships.len())
+            .filter(|&i| i != ship)
+            .min_by(|&a, &b| {
+                let (a, b) = (&self.ships[a], &self.ships[b]);
+                b.alive
+                    .cmp(&a.alive)
+                    .then(distance(a).total_cmp(&distance(b)))
+            })
+            .unwrap_or(ship)
+    }
+
+    /// Advance by `dt` with one set of controls per ship, in ship order.
+    /// Panics unless `actions` holds exactly one set per ship, since a ship
+    /// without controls would silently stop being simulated.
+    pub fn update(&mut self, dt: f32, actions: &[[f32; ACTION_SIZE]]) {
+        let _span = Span::enter("GameState::update");
+        assert_eq!(
+            actions.len(),
+            self.ships.len(),
+            "one set of controls per ship"
+        );
+        self.hits.clear();
         if self.match_over {
             self.time += dt;
             return;
         }
 
         self.time += dt;
+        let arena = self.arena;
 
         // Update ships
-        for i in 0..2 {
+        for (i, a) in actions.iter().enumerate() {
             if !self.ships[i].alive {
                 continue;
             }
 
-            let a = &actions[i];
             let thrust = a[0].clamp(0.0, 1.0);
             let turn_left = a[1].clamp(0.0, 1.0);
             let turn_right = a[2].clamp(0.0, 1.0);
             let fire = a[3];
+            let special = a[4];
 
-            // Rotation
-            self.ships[i].rotation += (turn_right - turn_left) * SHIP_ROTATION_SPEED * dt;
+            // Turn controls pass through the loadout's low-pass filter. A
+            // network's signed turn output arrives split between the two
+            // controls, so their difference is that output unchanged.
+            let ship = &mut self.ships[i];
+            let turn = turn_right - turn_left;
+            ship.turn = if ship.loadout.smoothing > 0.0 {
+                ship.turn + (turn - ship.turn) * (1.0 - (-dt / ship.loadout.smoothing).exp())
+            } else {
+                turn
+            };
 
-            // Thrust
+            // Rotation, thrust, drag and position, integrated exactly for constant inputs
+            let turn_rate = ship.turn * SHIP_ROTATION_SPEED * ship.loadout.turn_rate;
+            let config = ship.class.config();
+            // Without the energy for it, thrust fades to what the pool can supply
+            let thrust = if self.physics.energy {
+                thrust * ship.spend_energy(thrust * THRUST_ENERGY_COST * dt)
+            } else {
+                thrust
+            };
+            integrate_ship(ship, turn_rate, thrust * SHIP_THRUST * config.thrust, dt);
             let cos = self.ships[i].rotation.cos();
             let sin = self.ships[i].rotation.sin();
-            self.ships[i].vx += cos * thrust * SHIP_THRUST * dt;
-            self.ships[i].vy += sin * thrust * SHIP_THRUST * dt;
-
-            // Drag
-            let drag = SHIP_DRAG.powf(dt * 60.0);
-            self.ships[i].vx *= drag;
-            self.ships[i].vy *= drag;
-
-            // Speed cap
-            let speed = (self.ships[i].vx * self.ships[i].vx
-                + self.ships[i].vy * self.ships[i].vy)
-                .sqrt();
-            if speed > MAX_SHIP_SPEED {
-                let scale = MAX_SHIP_SPEED / speed;
-                self.ships[i].vx *= scale;
-                self.ships[i].vy *= scale;
-            }
-
-            // Position
-            self.ships[i].x += self.ships[i].vx * dt;
-            self.ships[i].y += self.ships[i].vy * dt;
 
             // Toroidal wrapping
-            self.ships[i].x = wrap(self.ships[i].x, ARENA_WIDTH);
-            self.ships[i].y = wrap(self.ships[i].y, ARENA_HEIGHT);
+            self.ships[i].x = wrap(self.ships[i].x, arena.width);
+            self.ships[i].y = wrap(self.ships[i].y, arena.height);
 
             // Fire cooldown
             self.ships[i].fire_cooldown = (self.ships[i].fire_cooldown - dt).max(0.0);
 
-            // Fire
-            if fire > 0.5 && self.ships[i].fire_cooldown <= 0.0 {
+            // The fifth control raises the deflector while energy lasts,
+            // when the rules swap it in for the cloak
+            let ship = &mut self.ships[i];
+            ship.deflecting = self.physics.deflector && special > 0.5 && ship.energy > 0.0;
+            if ship.deflecting {
+                ship.spend_energy(DEFLECTOR_ENERGY_DRAIN * dt);
+            }
+
+            // Cloak: drains charge while held, recharges otherwise
+            ship.cloaked = !self.physics.deflector && special > 0.5 && ship.cloak_energy > 0.0;
+            if ship.cloaked {
+                ship.cloak_energy = (ship.cloak_energy - CLOAK_DRAIN_RATE * dt).max(0.0);
+            } else {
+                ship.cloak_energy = (ship.cloak_energy + CLOAK_RECHARGE_RATE * dt).min(1.0);
+            }
+
+            // Shields regenerate; the hull never does
+            ship.shield = (ship.shield + self.physics.shield_regen * dt).min(self.physics.shield);
+            ship.energy = (ship.energy + ENERGY_RECHARGE_RATE * dt).min(1.0);
+
+            // Fire (not possible while cloaked, out of ammo or out of energy)
+            let loaded = (!self.physics.limits_ammo() || self.ships[i].ammo > 0)
+                && (!self.physics.energy || self.ships[i].energy >= SHOT_ENERGY_COST);
+            if fire > 0.5 && !self.ships[i].cloaked && self.ships[i].fire_cooldown <= 0.0 && loaded
+            {
                 let own_projectiles = self.projectiles.iter().filter(|p| p.owner == i).count();
-                if own_projectiles < MAX_PROJECTILES_PER_SHIP {
+                if own_projectiles < self.ships[i].handicap.max_projectiles {
+                    let inheritance = self.physics.projectile_inheritance;
+                    let speed = PROJECTILE_SPEED * config.projectile_speed;
                     self.projectiles.push(Projectile {
                         x: self.ships[i].x + cos * SHIP_RADIUS,
                         y: self.ships[i].y + sin * SHIP_RADIUS,
-                        vx: cos * PROJECTILE_SPEED + self.ships[i].vx * 0.3,
-                        vy: sin * PROJECTILE_SPEED + self.ships[i].vy * 0.3,
+                        vx: cos * speed + self.ships[i].vx * inheritance,
+                        vy: sin * speed + self.ships[i].vy * inheritance,
                         lifetime: PROJECTILE_LIFETIME,
                         owner: i,
                     });
-                    self.ships[i].fire_cooldown = FIRE_COOLDOWN;
+                    self.ships[i].fire_cooldown = self.ships[i].fire_interval();
                     self.ships[i].shots_fired += 1;
+                    self.ships[i].ammo = self.ships[i].ammo.saturating_sub(1);
+                    if self.physics.energy {
+                        self.ships[i].spend_energy(SHOT_ENERGY_COST);
+                    }
+
+                    // Recoil pushes the ship back against its heading
+                    self.ships[i].vx -= cos * self.physics.recoil;
+                    self.ships[i].vy -= sin * self.physics.recoil;
                 }
             }
         }
 
-        // Ship-to-ship collision (elastic bounce)
-        if self.ships[0].alive && self.ships[1].alive {
-            let dx = toroidal_diff(self.ships[0].x, self.ships[1].x, ARENA_WIDTH);
-            let dy = toroidal_diff(self.ships[0].y, self.ships[1].y, ARENA_HEIGHT);
-            let dist_sq = dx * dx + dy * dy;
-            let min_dist = SHIP_RADIUS * 2.0;
-            if dist_sq < min_dist * min_dist && dist_sq > 0.001 {
-                let dist = dist_sq.sqrt();
-                let nx = dx / dist;
-                let ny = dy / dist;
-
-                // Separate ships so they don't overlap
-                let overlap = min_dist - dist;
-                self.ships[0].x += nx * overlap * 0.5;
-                self.ships[0].y += ny * overlap * 0.5;
-                self.ships[1].x -= nx * overlap * 0.5;
-                self.ships[1].y -= ny * overlap * 0.5;
-
-                // Wrap positions after separation
-                self.ships[0].x = wrap(self.ships[0].x, ARENA_WIDTH);
-                self.ships[0].y = wrap(self.ships[0].y, ARENA_HEIGHT);
-                self.ships[1].x = wrap(self.ships[1].x, ARENA_WIDTH);
-                self.ships[1].y = wrap(self.ships[1].y, ARENA_HEIGHT);
-
-                // Elastic velocity exchange along collision normal
-                let rel_vn = (self.ships[0].vx - self.ships[1].vx) * nx
-                    + (self.ships[0].vy - self.ships[1].vy) * ny;
-                if rel_vn < 0.0 {
-                    // Ships are approaching
-                    self.ships[0].vx -= rel_vn * nx;
-                    self.ships[0].vy -= rel_vn * ny;
-                    self.ships[1].vx += rel_vn * nx;
-                    self.ships[1].vy += rel_vn * ny;
-                }
+        // Asteroids drift and spin without drag
+        for a in &mut self.asteroids {
+            a.x = wrap(a.x + a.vx * dt, arena.width);
+            a.y = wrap(a.y + a.vy * dt, arena.height);
+            a.angle += a.spin * dt;
+        }
+
+        // Flow field forces
+        if self.flow_field != FlowField::None {
+            for ship in self.ships.iter_mut().filter(|s| s.alive) {
+                let (ax, ay) = self.flow_field.force_at(arena, ship.x, ship.y, self.time);
+                ship.vx += ax * dt;
+                ship.vy += ay * dt;
+            }
+            for p in &mut self.projectiles {
+                let (ax, ay) = self.flow_field.force_at(arena, p.x, p.y, self.time);
+                p.vx += ax * dt;
+                p.vy += ay * dt;
+            }
+        }
+
+        // Ship-to-ship collisions (elastic bounce), pair by pair
+        for a in 0..self.ships.len() {
+            for b in a + 1..self.ships.len() {
+                self.bounce_ships(a, b);
             }
         }
 
@@ -213,52 +937,616 @@ impl GameState {
         for p in &mut self.projectiles {
             p.x += p.vx * dt;
             p.y += p.vy * dt;
-            p.x = wrap(p.x, ARENA_WIDTH);
-            p.y = wrap(p.y, ARENA_HEIGHT);
+            p.x = wrap(p.x, arena.width);
+            p.y = wrap(p.y, arena.height);
             p.lifetime -= dt;
         }
         self.projectiles.retain(|p| p.lifetime > 0.0);
+        if self.physics.projectile_collisions {
+            self.clash_projectiles(dt);
+        }
 
         // Collision detection
         let mut dead_projectiles = Vec::new();
         for (pi, p) in self.projectiles.iter().enumerate() {
-            let target = 1 - p.owner;
-            if !self.ships[target].alive {
+            // A projectile strikes the first ship in its way other than its owner's
+            let hit_radius = SHIP_RADIUS + PROJECTILE_RADIUS;
+            let target = (0..self.ships.len()).find(|&target| {
+                let ship = &self.ships[target];
+                let dx = toroidal_diff(p.x, ship.x, arena.width);
+                let dy = toroidal_diff(p.y, ship.y, arena.height);
+                target != p.owner && ship.alive && dx * dx + dy * dy < hit_radius * hit_radius
+            });
+            if let Some(target) = target {
+                // A raised deflector absorbs the projectile without harm
+                if !self.ships[target].deflecting {
+                    self.ships[target].damage(PROJECTILE_DAMAGE);
+                    self.ships[p.owner].hits_scored += 1;
+                    self.hits.push(Hit {
+                        shooter: p.owner,
+                        target,
+                        damage: PROJECTILE_DAMAGE,
+                    });
+                }
+                dead_projectiles.push(pi);
+            }
+        }
+
+        // Projectiles that reach an asteroid are absorbed and split it
+        let mut spawned = Vec::new();
+        let mut destroyed = Vec::new();
+        for (pi, p) in self.projectiles.iter().enumerate() {
+            if dead_projectiles.contains(&pi) {
                 continue;
             }
-            let dx = toroidal_diff(p.x, self.ships[target].x, ARENA_WIDTH);
-            let dy = toroidal_diff(p.y, self.ships[target].y, ARENA_HEIGHT);
-            let dist_sq = dx * dx + dy * dy;
-            let hit_radius = SHIP_RADIUS + PROJECTILE_RADIUS;
-            if dist_sq < hit_radius * hit_radius {
-                self.ships[target].alive = false;
-                self.ships[p.owner].hits_scored += 1;
+            let hit = self.asteroids.iter().enumerate().position(|(ai, a)| {
+                if destroyed.contains(&ai) {
+                    return false;
+                }
+                let dx = toroidal_diff(p.x, a.x, arena.width);
+                let dy = toroidal_diff(p.y, a.y, arena.height);
+                let r = a.radius() + PROJECTILE_RADIUS;
+                dx * dx + dy * dy < r * r
+            });
+            if let Some(ai) = hit {
+                spawned.extend(self.asteroids[ai].split(p.vx, p.vy));
+                destroyed.push(ai);
                 dead_projectiles.push(pi);
             }
         }
+        destroyed.sort_unstable();
+        for &ai in destroyed.iter().rev() {
+            self.asteroids.swap_remove(ai);
+        }
+        self.asteroids.extend(spawned);
+
+        // Asteroids destroy any ship they touch
+        for ship in self.ships.iter_mut().filter(|s| s.alive) {
+            let crashed = self.asteroids.iter().any(|a| {
+                let dx = toroidal_diff(ship.x, a.x, arena.width);
+                let dy = toroidal_diff(ship.y, a.y, arena.height);
+                let r = a.radius() + SHIP_RADIUS;
+                dx * dx + dy * dy < r * r
+            });
+            if crashed {
+                ship.alive = false;
+            }
+        }
+
+        self.update_pickups();
+
         // Remove hit projectiles in reverse order
         dead_projectiles.sort_unstable();
         for &pi in dead_projectiles.iter().rev() {
             self.projectiles.remove(pi);
         }
 
-        // Check match end
+        // Check match end: the last ship standing wins
         let alive_count = self.ships.iter().filter(|s| s.alive).count();
         if alive_count <= 1 || self.time >= MATCH_DURATION {
             self.match_over = true;
-            if self.ships[0].alive && !self.ships[1].alive {
-                self.winner = Some(0);
-            } else if self.ships[1].alive && !self.ships[0].alive {
-                self.winner = Some(1);
+            if alive_count == 1 {
+                self.winner = self.ships.iter().position(|s| s.alive);
+            }
+        }
+    }
+}
+
+impl GameState {
+    /// Push ships `a` and `b` apart and exchange their velocity along the
+    /// collision normal if both are flying and overlap
+    fn bounce_ships(&mut self, a: usize, b: usize) {
+        let arena = self.arena;
+        if !self.ships[a].alive || !self.ships[b].alive {
+            return;
+        }
+        let dx = toroidal_diff(self.ships[a].x, self.ships[b].x, arena.width);
+        let dy = toroidal_diff(self.ships[a].y, self.ships[b].y, arena.height);
+        let dist_sq = dx * dx + dy * dy;
+        let min_dist = SHIP_RADIUS * 2.0;
+        if dist_sq >= min_dist * min_dist || dist_sq <= 0.001 {
+            return;
+        }
+        let dist = dist_sq.sqrt();
+        let nx = dx / dist;
+        let ny = dy / dist;
+
+        // Separate ships so they don't overlap
+        let overlap = min_dist - dist;
+        self.ships[a].x += nx * overlap * 0.5;
+        self.ships[a].y += ny * overlap * 0.5;
+        self.ships[b].x -= nx * overlap * 0.5;
+        self.ships[b].y -= ny * overlap * 0.5;
+
+        // Wrap positions after separation
+        for i in [a, b] {
+            self.ships[i].x = wrap(self.ships[i].x, arena.width);
+            self.ships[i].y = wrap(self.ships[i].y, arena.height);
+        }
+
+        // Elastic velocity exchange along collision normal
+        let rel_vn =
+            (self.ships[a].vx - self.ships[b].vx) * nx + (self.ships[a].vy - self.ships[b].vy) * ny;
+        if rel_vn < 0.0 {
+            // Ships are approaching
+            self.ships[a].vx -= rel_vn * nx;
+            self.ships[a].vy -= rel_vn * ny;
+            self.ships[b].vx += rel_vn * nx;
+            self.ships[b].vy += rel_vn * ny;
+        }
+    }
+}
+
+impl GameState {
+    /// Remove every pair of opposing projectiles that passed within touching
+    /// distance during the step of `dt` seconds just taken. Paths are checked
+    /// over the whole step, since shots closing at twice projectile speed
+    /// would otherwise jump past each other between frames.
+    fn clash_projectiles(&mut self, dt: f32) {
+        let arena = self.arena;
+        let touch = 2.0 * PROJECTILE_RADIUS;
+        let mut spent = vec![false; self.projectiles.len()];
+        for (i, a) in self.projectiles.iter().enumerate() {
+            for (j, b) in self.projectiles.iter().enumerate().skip(i + 1) {
+                if a.owner == b.owner || spent[i] || spent[j] {
+                    continue;
+                }
+                let rx = toroidal_diff(a.x, b.x, arena.width);
+                let ry = toroidal_diff(a.y, b.y, arena.height);
+                let (wx, wy) = (a.vx - b.vx, a.vy - b.vy);
+                let w_sq = wx * wx + wy * wy;
+                // Closest approach between the step's start (-dt) and now (0)
+                let t = if w_sq > 0.0 {
+                    (-(rx * wx + ry * wy) / w_sq).clamp(-dt, 0.0)
+                } else {
+                    0.0
+                };
+                let (mx, my) = (rx + wx * t, ry + wy * t);
+                if mx * mx + my * my < touch * touch {
+                    spent[i] = true;
+                    spent[j] = true;
+                }
+            }
+        }
+        let mut spent = spent.into_iter();
+        self.projectiles.retain(|_| !spent.next().unwrap_or(false));
+    }
+
+    /// Spawn ammo pickups on schedule and let ships collect the ones they touch
+    fn update_pickups(&mut self) {
+        let arena = self.arena;
+        let interval = self.physics.pickup_interval;
+        if interval <= 0.0 {
+            return;
+        }
+        // Spawn times are multiples of the interval, so they do not depend on the step size
+        let due = (self.time / interval) as u32;
+        while self.pickups_spawned < due {
+            let k = self.pickups_spawned;
+            self.pickups_spawned += 1;
+            if self.pickups.len() < MAX_PICKUPS {
+                let unit = |axis| hash2(k, axis, self.pickup_seed) as f32 / u32::MAX as f32;
+                self.pickups.push(Pickup {
+                    x: unit(0) * arena.width,
+                    y: unit(1) * arena.height,
+                });
+            }
+        }
+
+        let magazine = self.physics.ammo;
+        for ship in self.ships.iter_mut().filter(|s| s.alive) {
+            self.pickups.retain(|p| {
+                let dx = toroidal_diff(ship.x, p.x, arena.width);
+                let dy = toroidal_diff(ship.y, p.y, arena.height);
+                let r = SHIP_RADIUS + PICKUP_RADIUS;
+                let touched = dx * dx + dy * dy < r * r;
+                if touched {
+                    ship.ammo = (ship.ammo + PICKUP_AMMO).min(magazine);
+                }
+                !touched
+            });
+        }
+    }
+}
+
+impl FlowField {
+    /// Pick a random field of any kind for `arena`, for per-match domain randomization
+    pub fn random(arena: ArenaSize, rng: &mut impl Rng) -> Self {
+        match rng.gen_range(0..4) {
+            0 => FlowField::None,
+            1 => {
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                let strength = rng.gen_range(10.0..60.0);
+                FlowField::Wind {
+                    ax: angle.cos() * strength,
+                    ay: angle.sin() * strength,
+                }
             }
+            2 => FlowField::Vortex {
+                x: rng.gen_range(0.0..arena.width),
+                y: rng.gen_range(0.0..arena.height),
+                strength: rng.gen_range(40.0..120.0) * if rng.gen() { 1.0 } else { -1.0 },
+                radius: rng.gen_range(200.0..500.0),
+            },
+            _ => FlowField::Noise {
+                seed: rng.gen(),
+                cells: rng.gen_range(2..6),
+                strength: rng.gen_range(20.0..80.0),
+            },
         }
     }
+
+    /// Acceleration applied at a point of `arena` at the given match time
+    pub fn force_at(&self, arena: ArenaSize, x: f32, y: f32, time: f32) -> (f32, f32) {
+        match *self {
+            FlowField::None => (0.0, 0.0),
+            FlowField::Wind { ax, ay } => (ax, ay),
+            FlowField::Vortex {
+                x: cx,
+                y: cy,
+                strength,
+                radius,
+            } => {
+                let dx = toroidal_diff(x, cx, arena.width);
+                let dy = toroidal_diff(y, cy, arena.height);
+                let dist = (dx * dx + dy * dy).sqrt().max(1.0);
+                // Tangential push, strongest at the core and fading to zero at the radius
+                let falloff = (1.0 - dist / radius).max(0.0);
+                (
+                    -dy / dist * strength * falloff,
+                    dx / dist * strength * falloff,
+                )
+            }
+            FlowField::Noise {
+                seed,
+                cells,
+                strength,
+            } => {
+                let cells_x = cells.max(1);
+                let cells_y = ((cells_x as f32 * arena.height / arena.width).round() as u32).max(1);
+                let u = x / arena.width * cells_x as f32;
+                let v = y / arena.height * cells_y as f32;
+                // Drift the sampling point over time so currents shift during a match
+                let drift = time * 0.05;
+                let angle = perlin(u + drift, v, cells_x, cells_y, seed) * std::f32::consts::TAU;
+                let magnitude = 0.5 + 0.5 * perlin(u, v + drift, cells_x, cells_y, seed ^ 0x9e37);
+                (
+                    angle.cos() * strength * magnitude,
+                    angle.sin() * strength * magnitude,
+                )
+            }
+        }
+    }
+}
+
+impl ArenaLayout {
+    /// Reject layouts whose values would break or stall the simulation
+    pub fn validate(&self) -> Result<(), DuelError> {
+        self.arena.validate()?;
+        check_range(
+            "projectile inheritance",
+            self.physics.projectile_inheritance,
+            0.0,
+            1.0,
+        )?;
+        check_range("recoil", self.physics.recoil, 0.0, MAX_SHIP_SPEED)?;
+        check_range("shield", self.physics.shield, 0.0, MAX_DEFENSE_POINTS)?;
+        check_range(
+            "shield regeneration",
+            self.physics.shield_regen,
+            0.0,
+            MAX_DEFENSE_POINTS,
+        )?;
+        check_range(
+            "hull",
+            self.physics.hull,
+            PROJECTILE_DAMAGE,
+            MAX_DEFENSE_POINTS,
+        )?;
+        check_range("ammo", self.physics.ammo as f32, 0.0, MAX_AMMO as f32)?;
+        check_range(
+            "pickup interval",
+            self.physics.pickup_interval,
+            0.0,
+            MATCH_DURATION,
+        )?;
+        if self.asteroid_count > MAX_ASTEROIDS {
+            return Err(DuelError::OutOfRange {
+                what: "asteroid count".to_string(),
+                value: self.asteroid_count as f64,
+                min: 0.0,
+                max: MAX_ASTEROIDS as f64,
+            });
+        }
+        // On a torus no point lies further than half the shorter side from a
+        // ship along that axis, so a full-size rock needs room within it
+        let full_size = ASTEROID_BASE_RADIUS * (1u32 << (ASTEROID_MAX_SIZE - 1)) as f32;
+        let half_side = self.arena.width.min(self.arena.height) / 2.0;
+        if self.asteroid_count > 0 && full_size + self.arena.spawn_clearance() >= half_side {
+            return Err(DuelError::InvalidValue {
+                what: "arena for asteroids",
+                value: self.arena.spec(),
+            });
+        }
+        match self.flow_field {
+            FlowField::None => {}
+            FlowField::Wind { ax, ay } => {
+                check_range("wind x", ax, -MAX_FLOW_STRENGTH, MAX_FLOW_STRENGTH)?;
+                check_range("wind y", ay, -MAX_FLOW_STRENGTH, MAX_FLOW_STRENGTH)?;
+            }
+            FlowField::Vortex {
+                x,
+                y,
+                strength,
+                radius,
+            } => {
+                check_range("vortex x", x, 0.0, self.arena.width)?;
+                check_range("vortex y", y, 0.0, self.arena.height)?;
+                check_range(
+                    "vortex strength",
+                    strength,
+                    -MAX_FLOW_STRENGTH,
+                    MAX_FLOW_STRENGTH,
+                )?;
+                check_range("vortex radius", radius, 1.0, self.arena.width)?;
+            }
+            FlowField::Noise {
+                cells, strength, ..
+            } => {
+                check_range("noise cells", cells as f32, 1.0, 64.0)?;
+                check_range("noise strength", strength, 0.0, MAX_FLOW_STRENGTH)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set one match rule by name, as used on the command line, e.g.
+    /// "recoil" to "30" or "class_matchups" to "mixed". The layout is left
+    /// unchanged if the result would not validate.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), DuelError> {
+        fn number<T: std::str::FromStr>(name: &'static str, value: &str) -> Result<T, DuelError> {
+            value.trim().parse().map_err(|_| DuelError::InvalidValue {
+                what: name,
+                value: value.to_string(),
+            })
+        }
+        let mut layout = self.clone();
+        let physics = &mut layout.physics;
+        match name {
+            "projectile_inheritance" => {
+                physics.projectile_inheritance = number("projectile_inheritance", value)?
+            }
+            "recoil" => physics.recoil = number("recoil", value)?,
+            "shield" => physics.shield = number("shield", value)?,
+            "shield_regen" => physics.shield_regen = number("shield_regen", value)?,
+            "hull" => physics.hull = number("hull", value)?,
+            "ammo" => physics.ammo = number("ammo", value)?,
+            "pickup_interval" => physics.pickup_interval = number("pickup_interval", value)?,
+            "energy" => physics.energy = number("energy", value)?,
+            "deflector" => physics.deflector = number("deflector", value)?,
+            "projectile_collisions" => {
+                physics.projectile_collisions = number("projectile_collisions", value)?
+            }
+            "asteroid_count" => layout.asteroid_count = number("asteroid_count", value)?,
+            "randomize_flow_field" => {
+                layout.randomize_flow_field = number("randomize_flow_field", value)?
+            }
+            "class_matchups" => layout.class_matchups = ClassMatchups::parse(value.trim())?,
+            _ => {
+                return Err(DuelError::UnknownName {
+                    kind: "match rule",
+                    name: name.to_string(),
+                })
+            }
+        }
+        layout.validate()?;
+        *self = layout;
+        Ok(())
+    }
+
+    /// Set up a fresh match with random ship placement in this layout
+    pub fn new_match(&self, rng: &mut impl Rng) -> GameState {
+        self.new_melee(2, rng)
+    }
+
+    /// Set up a fresh free-for-all among `count` ships, placed at random
+    /// like a duel's; `new_match` is the two-ship case
+    pub fn new_melee(&self, count: usize, rng: &mut impl Rng) -> GameState {
+        let mut state = GameState::with_random_ships(self.arena, count, rng);
+        state.physics = self.physics.clone();
+        state.flow_field = if self.randomize_flow_field {
+            FlowField::random(self.arena, rng)
+        } else {
+            self.flow_field.clone()
+        };
+        // Keep the spawn area around each ship clear; a field too crowded
+        // with ships for that gets fewer rocks instead of an endless search
+        let clearance = self.arena.spawn_clearance();
+        let mut attempts = self.asteroid_count * ASTEROID_SPAWN_ATTEMPTS;
+        while state.asteroids.len() < self.asteroid_count && attempts > 0 {
+            attempts -= 1;
+            let a = Asteroid::random(self.arena, rng);
+            let clear = state.ships.iter().all(|ship| {
+                let dx = toroidal_diff(ship.x, a.x, self.arena.width);
+                let dy = toroidal_diff(ship.y, a.y, self.arena.height);
+                (dx * dx + dy * dy).sqrt() > a.radius() + clearance
+            });
+            if clear {
+                state.asteroids.push(a);
+            }
+        }
+        if self.physics.pickup_interval > 0.0 {
+            state.pickup_seed = rng.gen();
+        }
+        let classes = self.class_matchups.pick_for(count, rng);
+        for (ship, class) in state.ships.iter_mut().zip(classes) {
+            ship.class = class;
+            ship.shield = self.physics.shield;
+            ship.hull = ship.starting_hull(&self.physics);
+            ship.ammo = self.physics.ammo;
+        }
+        state
+    }
+}
+
+/// Periodic 2D gradient noise in roughly [-1, 1], repeating every `period_x` x `period_y` cells
+fn perlin(u: f32, v: f32, period_x: u32, period_y: u32, seed: u32) -> f32 {
+    let x0 = u.floor();
+    let y0 = v.floor();
+    let fx = u - x0;
+    let fy = v - y0;
+
+    let corner = |ix: i64, iy: i64, ox: f32, oy: f32| {
+        let gx = ix.rem_euclid(period_x as i64) as u32;
+        let gy = iy.rem_euclid(period_y as i64) as u32;
+        let angle = hash2(gx, gy, seed) as f32 / u32::MAX as f32 * std::f32::consts::TAU;
+        angle.cos() * ox + angle.sin() * oy
+    };
+
+    let (ix, iy) = (x0 as i64, y0 as i64);
+    let n00 = corner(ix, iy, fx, fy);
+    let n10 = corner(ix + 1, iy, fx - 1.0, fy);
+    let n01 = corner(ix, iy + 1, fx, fy - 1.0);
+    let n11 = corner(ix + 1, iy + 1, fx - 1.0, fy - 1.0);
+
+    let sx = fx * fx * (3.0 - 2.0 * fx);
+    let sy = fy * fy * (3.0 - 2.0 * fy);
+    let top = n00 + (n10 - n00) * sx;
+    let bottom = n01 + (n11 - n01) * sx;
+    (top + (bottom - top) * sy) * std::f32::consts::SQRT_2
+}
+
+fn hash2(x: u32, y: u32, seed: u32) -> u32 {
+    let mut h =
+        x.wrapping_mul(0x85eb_ca6b) ^ y.wrapping_mul(0xc2b2_ae35) ^ seed.wrapping_mul(0x27d4_eb2f);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297a_2d39);
+    h ^= h >> 15;
+    h
+}
+
+/// Advance a ship's heading, velocity and position by `dt`.
+///
+/// Velocity follows dv/dt = a - SHIP_DRAG * v, which has a closed-form solution
+/// for constant acceleration, so the result does not depend on how a time span
+/// is split into steps. Thrust is applied along the mid-step heading, keeping the
+/// error from turning while thrusting second-order in `dt`.
+fn integrate_ship(ship: &mut Ship, turn_rate: f32, accel: f32, dt: f32) {
+    let mid_rotation = ship.rotation + turn_rate * dt * 0.5;
+    ship.rotation += turn_rate * dt;
+
+    let ax = mid_rotation.cos() * accel;
+    let ay = mid_rotation.sin() * accel;
+
+    let decay = (-SHIP_DRAG * dt).exp();
+    // Fraction of the drag-free displacement actually covered during the step
+    let travel = (1.0 - decay) / SHIP_DRAG;
+    // Velocity converges toward terminal velocity a / k
+    let (tx, ty) = (ax / SHIP_DRAG, ay / SHIP_DRAG);
+
+    ship.x += tx * dt + (ship.vx - tx) * travel;
+    ship.y += ty * dt + (ship.vy - ty) * travel;
+    ship.vx = tx + (ship.vx - tx) * decay;
+    ship.vy = ty + (ship.vy - ty) * decay;
+
+    // Speed cap
+    let speed = (ship.vx * ship.vx + ship.vy * ship.vy).sqrt();
+    if speed > MAX_SHIP_SPEED {
+        let scale = MAX_SHIP_SPEED / speed;
+        ship.vx *= scale;
+        ship.vy *= scale;
+    }
+}
+
+/// Lead-pursuit solution for `shooter` firing at `target`.
+#[derive(Clone, Copy, Debug)]
+pub struct Intercept {
+    /// Where the target will be when a shot fired now reaches it (wrapped to the arena)
+    pub x: f32,
+    pub y: f32,
+    /// Heading the shooter must face to hit, in world radians
+    pub aim_angle: f32,
+    /// Projectile flight time to the intercept point
+    pub time: f32,
+}
+
+/// Solve for the heading that makes a projectile fired now meet the target,
+/// assuming both ships keep their current velocity. Returns None when the
+/// target outruns the projectile.
+pub fn lead_intercept(state: &GameState, shooter: usize, target: usize) -> Option<Intercept> {
+    let s = &state.ships[shooter];
+    let t = &state.ships[target];
+    let dx = toroidal_diff(t.x, s.x, state.arena.width);
+    let dy = toroidal_diff(t.y, s.y, state.arena.height);
+    // Target velocity relative to the part of the projectile's motion inherited from the shooter
+    let inheritance = state.physics.projectile_inheritance;
+    let wx = t.vx - s.vx * inheritance;
+    let wy = t.vy - s.vy * inheritance;
+
+    // |d + w*t| = speed * t  =>  (w.w - speed^2) t^2 + 2 d.w t + d.d = 0
+    let speed = PROJECTILE_SPEED * s.class.config().projectile_speed;
+    let a = wx * wx + wy * wy - speed * speed;
+    let b = 2.0 * (dx * wx + dy * wy);
+    let c = dx * dx + dy * dy;
+    let time = if a.abs() < 1e-6 {
+        if b >= 0.0 {
+            return None;
+        }
+        -c / b
+    } else {
+        let disc = b * b - 4.0 * a * c;
+        if disc < 0.0 {
+            return None;
+        }
+        let sq = disc.sqrt();
+        let (t1, t2) = ((-b - sq) / (2.0 * a), (-b + sq) / (2.0 * a));
+        let (lo, hi) = (t1.min(t2), t1.max(t2));
+        if lo > 0.0 {
+            lo
+        } else if hi > 0.0 {
+            hi
+        } else {
+            return None;
+        }
+    };
+
+    Some(Intercept {
+        x: wrap(t.x + t.vx * time, state.arena.width),
+        y: wrap(t.y + t.vy * time, state.arena.height),
+        aim_angle: (dy + wy * time).atan2(dx + wx * time),
+        time,
+    })
 }
 
 pub fn wrap(val: f32, max: f32) -> f32 {
     ((val % max) + max) % max
 }
 
+/// `(x, y)` followed by the copies of an object reaching `reach` from it
+/// that cross an edge of `arena`, shifted to the opposite edge(s): up to
+/// three ghosts in a corner. Drawing every position keeps objects whole as
+/// they wrap.
+pub fn wrap_copies(
+    x: f32,
+    y: f32,
+    reach: f32,
+    arena: ArenaSize,
+) -> impl Iterator<Item = (f32, f32)> {
+    let shifts = |p: f32, size: f32| {
+        [
+            Some(0.0),
+            (p < reach).then_some(size),
+            (p > size - reach).then_some(-size),
+        ]
+    };
+    let (xs, ys) = (shifts(x, arena.width), shifts(y, arena.height));
+    xs.into_iter()
+        .flatten()
+        .flat_map(move |dx| ys.into_iter().flatten().map(move |dy| (x + dx, y + dy)))
+}
+
 pub fn toroidal_diff(a: f32, b: f32, max: f32) -> f32 {
     let d = a - b;
     if d > max / 2.0 {
@@ -269,3 +1557,436 @@ pub fn toroidal_diff(a: f32, b: f32, max: f32) -> f32 {
         d
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome::Genome;
+    use crate::simulation::{run_melee, MELEE_ASSIST_SCORE, MELEE_KILL_SCORE};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const RATES: [f32; 3] = [30.0, 60.0, 120.0];
+
+    /// Fly ship 0 with fixed controls for `seconds` at the given tick rate
+    fn fly(hz: f32, seconds: f32, action: [f32; ACTION_SIZE], start_vx: f32) -> Ship {
+        let mut state = GameState::new();
+        state.ships[0] = Ship::new(400.0, 600.0, 0.0);
+        state.ships[0].vx = start_vx;
+        state.ships[1] = Ship::new(1200.0, 100.0, 0.0);
+        let idle = [0.0; ACTION_SIZE];
+        let steps = (seconds * hz).round() as usize;
+        for _ in 0..steps {
+            state.update(1.0 / hz, &[action, idle]);
+        }
+        state.ships[0].clone()
+    }
+
+    fn assert_close(ships: &[Ship], tolerance: f32) {
+        for pair in ships.windows(2) {
+            let dx = toroidal_diff(pair[0].x, pair[1].x, ARENA_WIDTH);
+            let dy = toroidal_diff(pair[0].y, pair[1].y, ARENA_HEIGHT);
+            let dv = ((pair[0].vx - pair[1].vx).powi(2) + (pair[0].vy - pair[1].vy).powi(2)).sqrt();
+            assert!(
+                (dx * dx + dy * dy).sqrt() < tolerance,
+                "position diverged: {:?} vs {:?}",
+                pair[0],
+                pair[1]
+            );
+            assert!(
+                dv < tolerance,
+                "velocity diverged: {:?} vs {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn straight_thrust_is_dt_invariant() {
+        let ships: Vec<Ship> = RATES
+            .iter()
+            .map(|&hz| fly(hz, 2.0, [1.0, 0.0, 0.0, 0.0, 0.0], 0.0))
+            .collect();
+        assert_close(&ships, 0.05);
+    }
+
+    #[test]
+    fn coasting_drag_is_dt_invariant() {
+        let ships: Vec<Ship> = RATES
+            .iter()
+            .map(|&hz| fly(hz, 1.0, [0.0; ACTION_SIZE], 100.0))
+            .collect();
+        assert_close(&ships, 0.05);
+        let expected = 100.0 * (-SHIP_DRAG).exp();
+        assert!((ships[1].vx - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn turning_thrust_is_approximately_dt_invariant() {
+        let ships: Vec<Ship> = RATES
+            .iter()
+            .map(|&hz| fly(hz, 2.0, [1.0, 0.0, 0.6, 0.0, 0.0], 0.0))
+            .collect();
+        assert_close(&ships, 1.0);
+    }
+    #[test]
+    fn loadout_sets_turn_rate_and_control_lag() {
+        let turn = |turn_rate, smoothing| {
+            let mut state = GameState::new();
+            state.ships[0] = Ship::new(400.0, 600.0, 0.0);
+            state.ships[0].loadout = Loadout {
+                turn_rate,
+                smoothing,
+            };
+            state.ships[1] = Ship::new(1200.0, 100.0, 0.0);
+            let right = [0.0, 0.0, 1.0, 1.0, 0.0];
+            for _ in 0..15 {
+                state.update(1.0 / 60.0, &[right, [0.0; ACTION_SIZE]]);
+            }
+            state.ships[0].clone()
+        };
+        let stock = turn(1.0, 0.0);
+        assert!((stock.rotation - SHIP_ROTATION_SPEED * 0.25).abs() < 1e-4);
+        let agile = turn(1.5, 0.0);
+        assert!((agile.rotation - stock.rotation * 1.5).abs() < 1e-4);
+        // Agility costs firepower
+        assert!(agile.fire_cooldown > stock.fire_cooldown);
+        let smoothed = turn(1.0, 0.2);
+        assert!(smoothed.rotation > 0.0 && smoothed.rotation < stock.rotation * 0.6);
+        assert!(Loadout::parse("2,0").is_err());
+        assert_eq!(Loadout::parse("1.5,0.2").unwrap().spec(), "1.5,0.2");
+    }
+
+    #[test]
+    fn shields_absorb_hits_and_regenerate_but_hull_does_not() {
+        let mut state = GameState {
+            physics: PhysicsOptions {
+                shield: 2.0,
+                shield_regen: 0.5,
+                hull: 2.0,
+                ..Default::default()
+            },
+            ..GameState::new()
+        };
+        state.ships[0] = Ship {
+            shield: 2.0,
+            hull: 2.0,
+            ..Ship::new(400.0, 600.0, 0.0)
+        };
+        state.ships[1] = Ship {
+            shield: 2.0,
+            hull: 2.0,
+            ..Ship::new(1200.0, 100.0, 0.0)
+        };
+        let idle = [[0.0; ACTION_SIZE]; 2];
+
+        state.ships[0].damage(PROJECTILE_DAMAGE);
+        assert_eq!((state.ships[0].shield, state.ships[0].hull), (1.0, 2.0));
+        // A hit bigger than the shield spills over onto the hull
+        state.ships[0].damage(1.5);
+        assert_eq!((state.ships[0].shield, state.ships[0].hull), (0.0, 1.5));
+        for _ in 0..60 {
+            state.update(1.0 / 60.0, &idle);
+        }
+        assert!((state.ships[0].shield - 0.5).abs() < 1e-4);
+        assert_eq!(state.ships[0].hull, 1.5);
+        for _ in 0..600 {
+            state.update(1.0 / 60.0, &idle);
+        }
+        assert_eq!(state.ships[0].shield, 2.0);
+
+        state.ships[1].damage(3.0);
+        assert!(state.ships[1].alive);
+        state.ships[1].damage(PROJECTILE_DAMAGE);
+        assert!(!state.ships[1].alive);
+        state.update(1.0 / 60.0, &idle);
+        assert_eq!(state.winner, Some(0));
+
+        // The default rules keep one hit, one kill
+        let mut stock = Ship::new(0.0, 0.0, 0.0);
+        stock.damage(PROJECTILE_DAMAGE);
+        assert!(!stock.alive);
+        assert!(!PhysicsOptions::default().has_defenses());
+    }
+
+    #[test]
+    fn ammo_runs_out_and_pickups_refill_it() {
+        let layout = ArenaLayout {
+            physics: PhysicsOptions {
+                ammo: 3,
+                pickup_interval: 2.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let run = |hz: f32| {
+            let mut state = layout.new_match(&mut StdRng::seed_from_u64(5));
+            // Park ship 1 far away and keep ship 0 firing
+            let fire = [0.0, 0.0, 0.0, 1.0, 0.0];
+            for _ in 0..(3.0 * hz) as usize {
+                state.ships[1].x = wrap(state.ships[0].x + ARENA_WIDTH / 2.0, ARENA_WIDTH);
+                state.update(1.0 / hz, &[fire, [0.0; ACTION_SIZE]]);
+            }
+            state
+        };
+        let state = run(60.0);
+        assert_eq!(state.ships[0].ammo, 0);
+        assert_eq!(state.ships[0].shots_fired, 3);
+        // Pickups appear on a fixed schedule wherever the step size puts the frames
+        assert_eq!(state.pickups_spawned, 1);
+        let coarse = run(30.0);
+        assert_eq!(coarse.pickups.len(), state.pickups.len());
+        assert_eq!(
+            (coarse.pickups[0].x, coarse.pickups[0].y),
+            (state.pickups[0].x, state.pickups[0].y)
+        );
+
+        // Flying over a pickup reloads, up to a full magazine
+        let mut state = state;
+        let pickup = state.pickups[0].clone();
+        state.ships[0].x = pickup.x;
+        state.ships[0].y = pickup.y;
+        state.update(1.0 / 60.0, &[[0.0; ACTION_SIZE]; 2]);
+        assert!(state.pickups.is_empty());
+        assert_eq!(state.ships[0].ammo, 3);
+    }
+
+    #[test]
+    fn class_matchups_deal_classes_with_their_stats() {
+        let deal = |class_matchups, seed| {
+            let layout = ArenaLayout {
+                class_matchups,
+                ..Default::default()
+            };
+            let state = layout.new_match(&mut StdRng::seed_from_u64(seed));
+            [state.ships[0].class, state.ships[1].class]
+        };
+        // Standard matchups draw nothing, so existing seeds set up the same arenas
+        assert_eq!(deal(ClassMatchups::Standard, 3), [ShipClass::Standard; 2]);
+        let mut rng = StdRng::seed_from_u64(3);
+        ArenaLayout::default().new_match(&mut rng);
+        let mut bare = StdRng::seed_from_u64(3);
+        GameState::new_random(ArenaSize::default(), &mut bare);
+        assert_eq!(rng.gen::<u64>(), bare.gen::<u64>());
+
+        let mirrors: Vec<_> = (0..20)
+            .map(|seed| deal(ClassMatchups::Mirror, seed))
+            .collect();
+        assert!(mirrors.iter().all(|[a, b]| a == b));
+        assert!(mirrors.iter().any(|[a, _]| *a != mirrors[0][0]));
+        let mixed: Vec<_> = (0..20)
+            .map(|seed| deal(ClassMatchups::Mixed, seed))
+            .collect();
+        assert!(mixed.iter().any(|[a, b]| a != b));
+
+        // A gunship's armour takes a hit that destroys the standard ship
+        let mut gunship = Ship {
+            class: ShipClass::Gunship,
+            ..Ship::new(0.0, 0.0, 0.0)
+        };
+        gunship.hull = gunship.starting_hull(&PhysicsOptions::default());
+        gunship.damage(PROJECTILE_DAMAGE);
+        assert!(gunship.alive);
+
+        // A sniper's shots fly faster
+        let mut state = GameState::new();
+        state.ships[0].class = ShipClass::Sniper;
+        let fire = [0.0, 0.0, 0.0, 1.0, 0.0];
+        state.update(1.0 / 60.0, &[fire, fire]);
+        let speeds: Vec<f32> = state.projectiles.iter().map(|p| p.vx.abs()).collect();
+        assert!((speeds[0] / speeds[1] - 1.5).abs() < 1e-4);
+
+        assert_eq!(ShipClass::parse("sniper").unwrap(), ShipClass::Sniper);
+        assert!(ClassMatchups::parse("random").is_err());
+    }
+
+    #[test]
+    fn objects_near_an_edge_get_ghosts_beyond_the_opposite_edge() {
+        let arena = ArenaSize::default();
+        let copies = |x, y| wrap_copies(x, y, 10.0, arena).collect::<Vec<_>>();
+        assert_eq!(copies(800.0, 600.0), [(800.0, 600.0)]);
+        assert_eq!(
+            copies(5.0, 600.0),
+            [(5.0, 600.0), (5.0 + ARENA_WIDTH, 600.0)]
+        );
+        assert_eq!(
+            copies(800.0, ARENA_HEIGHT - 3.0),
+            [(800.0, ARENA_HEIGHT - 3.0), (800.0, -3.0)]
+        );
+        // A corner shows the object in all four corners
+        let corner = copies(ARENA_WIDTH - 2.0, 4.0);
+        assert_eq!(corner.len(), 4);
+        assert!(corner.contains(&(-2.0, 4.0 + ARENA_HEIGHT)));
+        // Ghosts wrap at the edges of the arena being played
+        let small = ArenaSize::parse("400x300").unwrap();
+        let ghosts: Vec<_> = wrap_copies(395.0, 150.0, 10.0, small).collect();
+        assert_eq!(ghosts, [(395.0, 150.0), (-5.0, 150.0)]);
+    }
+
+    #[test]
+    fn energy_limits_thrust_and_fire_and_powers_the_deflector() {
+        let mut state = GameState {
+            physics: PhysicsOptions {
+                energy: true,
+                deflector: true,
+                ..Default::default()
+            },
+            ..GameState::new()
+        };
+        state.ships[1] = Ship::new(1200.0, 100.0, 0.0);
+        let idle = [0.0; ACTION_SIZE];
+
+        // Shots stop once the pool runs dry, then resume as it recharges
+        let fire = [0.0, 0.0, 0.0, 1.0, 0.0];
+        for _ in 0..600 {
+            state.projectiles.clear();
+            state.update(1.0 / 60.0, &[fire, idle]);
+        }
+        assert!(state.ships[0].energy < SHOT_ENERGY_COST);
+        let fired = state.ships[0].shots_fired;
+        assert!(fired < 600 / 15);
+        assert!((state.ships[0].energy_spent - fired as f32 * SHOT_ENERGY_COST).abs() < 1e-4);
+
+        // An empty pool gives no thrust
+        state.ships[0].energy = 0.0;
+        state.ships[0].vx = 0.0;
+        state.ships[0].vy = 0.0;
+        state.update(1.0 / 60.0, &[[1.0, 0.0, 0.0, 0.0, 0.0], idle]);
+        assert_eq!((state.ships[0].vx, state.ships[0].vy), (0.0, 0.0));
+
+        // A raised deflector swallows a hit instead of the cloak hiding the ship
+        state.ships[1].energy = 1.0;
+        let raise = [0.0, 0.0, 0.0, 0.0, 1.0];
+        state.projectiles.push(Projectile {
+            x: state.ships[1].x - SHIP_RADIUS,
+            y: state.ships[1].y,
+            vx: 0.0,
+            vy: 0.0,
+            lifetime: PROJECTILE_LIFETIME,
+            owner: 0,
+        });
+        state.update(1.0 / 60.0, &[idle, raise]);
+        assert!(state.ships[1].deflecting && !state.ships[1].cloaked);
+        assert!(state.ships[1].alive);
+        assert!(state.projectiles.is_empty());
+        assert!(state.ships[1].energy < 1.0);
+
+        // Without the rules nothing costs energy
+        let mut stock = GameState::new();
+        stock.update(1.0 / 60.0, &[[1.0, 0.0, 0.0, 1.0, 1.0]; 2]);
+        assert_eq!(stock.ships[0].energy, 1.0);
+        assert!(stock.ships[0].cloaked && !stock.ships[0].deflecting);
+    }
+
+    #[test]
+    fn opposing_projectiles_destroy_each_other_when_collisions_are_on() {
+        let shot = |x: f32, vx: f32, owner| Projectile {
+            x,
+            y: 100.0,
+            vx,
+            vy: 0.0,
+            lifetime: PROJECTILE_LIFETIME,
+            owner,
+        };
+        let run = |collisions| {
+            let mut state = GameState::new();
+            state.physics.projectile_collisions = collisions;
+            // Head-on at 800 px/s, closer than one frame's travel: they would
+            // jump past each other if only end positions were compared
+            state.projectiles = vec![
+                shot(795.0, PROJECTILE_SPEED, 0),
+                shot(805.0, -PROJECTILE_SPEED, 1),
+                // A ship's own shots pass through each other
+                shot(400.0, PROJECTILE_SPEED, 0),
+                shot(410.0, -PROJECTILE_SPEED, 0),
+            ];
+            state.update(1.0 / 60.0, &[[0.0; ACTION_SIZE]; 2]);
+            state.projectiles.len()
+        };
+        assert_eq!(run(false), 4);
+        assert_eq!(run(true), 2);
+
+        let mut layout = ArenaLayout::default();
+        layout.set("projectile_collisions", "true").unwrap();
+        assert!(layout.physics.projectile_collisions);
+    }
+    #[test]
+    fn melees_are_won_by_the_last_ship_standing() {
+        let layout = ArenaLayout::default();
+        let mut state = layout.new_melee(4, &mut StdRng::seed_from_u64(8));
+        assert_eq!(state.ships.len(), 4);
+        for (i, ship) in state.ships.iter_mut().enumerate() {
+            *ship = Ship::new(200.0 + 300.0 * i as f32, 600.0, 0.0);
+        }
+        assert_eq!(state.nearest_opponent(1), 0);
+        state.ships[0].alive = false;
+        assert_eq!(state.nearest_opponent(1), 2);
+
+        // Fire strikes whichever opponent is in its way, not just ship 1
+        state.projectiles.push(Projectile {
+            x: state.ships[3].x,
+            y: state.ships[3].y,
+            vx: 0.0,
+            vy: 0.0,
+            lifetime: PROJECTILE_LIFETIME,
+            owner: 1,
+        });
+        let idle = [[0.0; ACTION_SIZE]; 4];
+        state.update(1.0 / 60.0, &idle);
+        assert_eq!(state.ships[1].hits_scored, 1);
+        assert!(state.ships[3].hull < state.ships[3].starting_hull(&state.physics));
+
+        state.ships[3].alive = false;
+        state.update(1.0 / 60.0, &idle);
+        assert!(!state.match_over);
+        state.ships[2].alive = false;
+        state.update(1.0 / 60.0, &idle);
+        assert!(state.match_over);
+        assert_eq!(state.winner, Some(1));
+
+        let mut rng = StdRng::seed_from_u64(2);
+        let genomes: Vec<Genome> = (0..5).map(|_| Genome::random(&mut rng)).collect();
+        let pilots: Vec<&Genome> = genomes.iter().collect();
+        let result = run_melee(&pilots, &layout, &mut StdRng::seed_from_u64(3)).unwrap();
+        assert_eq!(result.scores.len(), 5);
+        for i in 0..5 {
+            let credit = result.kills[i] as f32 * MELEE_KILL_SCORE
+                + result.assists[i] as f32 * MELEE_ASSIST_SCORE;
+            let placement = result.scores[i] - credit / 4.0;
+            assert!((-1e-6..=1.0 + 1e-6).contains(&placement));
+        }
+        if let Some(winner) = result.winner {
+            assert!(result.scores[winner] >= 1.0);
+        }
+        assert!(run_melee(&pilots[..2], &layout, &mut StdRng::seed_from_u64(3)).is_err());
+    }
+
+    #[test]
+    fn small_arenas_spawn_asteroids_without_stalling() {
+        let mut layout = ArenaLayout {
+            arena: ArenaSize::parse("200x200").unwrap(),
+            asteroid_count: MAX_ASTEROIDS,
+            ..Default::default()
+        };
+        layout.validate().unwrap();
+        // However crowded with ships, the search for clear spots ends
+        let state = layout.new_melee(8, &mut StdRng::seed_from_u64(1));
+        assert!(state.asteroids.len() <= MAX_ASTEROIDS);
+        let state = layout.new_match(&mut StdRng::seed_from_u64(1));
+        assert!(!state.asteroids.is_empty());
+
+        // A strip too narrow for a clear spawn area is refused outright
+        layout.arena = ArenaSize::parse("200x8000").unwrap();
+        assert!(layout.validate().is_err());
+        layout.asteroid_count = 0;
+        assert!(layout.validate().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "one set of controls per ship")]
+    fn every_ship_needs_controls() {
+        let mut state = ArenaLayout::default().new_melee(3, &mut StdRng::seed_from_u64(8));
+        state.update(1.0 / 60.0, &[[0.0; ACTION_SIZE]; 2]);
+    }
+}
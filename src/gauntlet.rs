@@ -0,0 +1,93 @@
+use rand::Rng;
+
+use crate::error::DuelError;
+use crate::evolution::*;
+use crate::game::ArenaLayout;
+use crate::genome::*;
+use crate::simulation::*;
+
+/// Frozen set of opponents for scoring genomes on an absolute scale, independent
+/// of the population they came from and of the fitness shaping they evolved under
+#[derive(Clone, Debug)]
+pub struct Gauntlet {
+    pub opponents: Vec<Genome>,
+}
+
+impl Gauntlet {
+    /// Evolve a throwaway population for `generations` under the default fitness
+    /// weights and freeze its `size` best genomes
+    pub fn warm_up(
+        layout: &ArenaLayout,
+        generations: usize,
+        size: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Self, DuelError> {
+        let mut pop = Population::new(layout.clone(), rng);
+        pop.evaluate(rng)?;
+        for _ in 0..generations {
+            pop.evolve(rng);
+            pop.evaluate(rng)?;
+        }
+        let mut opponents = pop.genomes;
+        opponents.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        opponents.truncate(size);
+        Ok(Gauntlet { opponents })
+    }
+
+    /// Share of available points (1 per win, 0.5 per draw) `genome` earns over
+    /// `matches_each` matches against every opponent, alternating sides
+    pub fn score(
+        &self,
+        genome: &Genome,
+        layout: &ArenaLayout,
+        matches_each: usize,
+        rng: &mut impl Rng,
+    ) -> f32 {
+        let weights = FitnessWeights::default();
+        let mut points = 0.0;
+        let mut played = 0;
+        for opponent in &self.opponents {
+            for m in 0..matches_each {
+                let side = m % 2;
+                let result = if side == 0 {
+                    run_match(genome, opponent, layout, &weights, rng)
+                } else {
+                    run_match(opponent, genome, layout, &weights, rng)
+                };
+                points += match result.winner {
+                    Some(w) if w == side => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+                played += 1;
+            }
+        }
+        if played == 0 {
+            0.0
+        } else {
+            points / played as f32
+        }
+    }
+
+    /// Gauntlet score of `steps + 1` evenly spaced blends from `a` (t = 0) to
+    /// `b` (t = 1), as (t, score) pairs. A dip between two strong genomes means
+    /// they sit on separate optima.
+    pub fn score_blends(
+        &self,
+        a: &Genome,
+        b: &Genome,
+        steps: usize,
+        layout: &ArenaLayout,
+        matches_each: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<(f32, f32)> {
+        let steps = steps.max(1);
+        (0..=steps)
+            .map(|i| {
+                let t = i as f32 / steps as f32;
+                let blend = Genome::interpolate(a, b, t);
+                (t, self.score(&blend, layout, matches_each, rng))
+            })
+            .collect()
+    }
+}
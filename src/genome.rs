@@ -1,73 +1,956 @@
 use rand::Rng;
+use std::path::Path;
 
+use crate::error::{check_range, DuelError};
 use crate::game::*;
+use crate::profiling::Span;
 
-pub const INPUT_SIZE: usize = 14;
+/// Relative sensors, read by every network
+pub const INPUT_SIZE: usize = 19;
+/// Absolute position sensors (own x and y as fractions of the arena), read
+/// only by networks configured with `position_inputs`
+pub const POSITION_INPUTS: usize = 2;
+/// Shield and hull sensors (own, then the opponent's, as fractions of their
+/// starting points), read only by networks configured with `defense_inputs`
+pub const DEFENSE_INPUTS: usize = 4;
+/// Remaining ammo and the nearest pickup's distance and bearing, read only by
+/// networks configured with `ammo_inputs`
+pub const AMMO_INPUTS: usize = 4;
+/// Own energy and whether the opponent's deflector is up, read only by
+/// networks configured with `energy_inputs`
+pub const ENERGY_INPUTS: usize = 2;
+/// Rays cast from each ship at evenly spaced angles to its heading, starting
+/// dead ahead; a network with `rays` set reads every (MAX_RAYS / rays)-th
+pub const MAX_RAYS: usize = 16;
+/// Values each ray reads: the distance along it to the opponent, to a hostile
+/// projectile and to an asteroid, 1 when nothing is hit within range
+pub const RAY_CHANNELS: usize = 3;
+/// Raycast sensors, read in place of the relative sensors by networks
+/// configured with `rays`
+pub const RAY_INPUTS: usize = MAX_RAYS * RAY_CHANNELS;
+/// Values `get_inputs` produces: the relative sensors, then the position,
+/// defense, ammo, energy and raycast sensors
+pub const SENSOR_COUNT: usize =
+    INPUT_SIZE + POSITION_INPUTS + DEFENSE_INPUTS + AMMO_INPUTS + ENERGY_INPUTS + RAY_INPUTS;
+/// Width of the single hidden layer of the default network
 pub const HIDDEN_SIZE: usize = 20;
-pub const OUTPUT_SIZE: usize = 4;
-// Weights: (INPUT+1)*HIDDEN + (HIDDEN+1)*OUTPUT = 15*20 + 21*4 = 300+84 = 384
+pub const OUTPUT_SIZE: usize = ACTION_SIZE;
+/// Outputs of a `signed_turn` network: thrust, turn, fire and cloak
+pub const SIGNED_TURN_OUTPUTS: usize = OUTPUT_SIZE - 1;
+/// Output of a `signed_turn` network that steers
+const TURN_OUTPUT: usize = 1;
+// Weights of the default network: (INPUT+1)*HIDDEN + (HIDDEN+1)*OUTPUT = 20*20 + 21*5 = 400+105 = 505
 pub const GENOME_SIZE: usize = (INPUT_SIZE + 1) * HIDDEN_SIZE + (HIDDEN_SIZE + 1) * OUTPUT_SIZE;
+/// Widest hidden layer a network may have; evaluation keeps activations on the stack
+pub const MAX_LAYER_WIDTH: usize = 64;
+pub const MAX_HIDDEN_LAYERS: usize = 4;
+/// Weights are kept within +-WEIGHT_LIMIT by mutation and validation
+pub const WEIGHT_LIMIT: f32 = 3.0;
+/// Version written in the header of saved genome files
+pub const GENOME_FILE_VERSION: u32 = 4;
+const GENOME_FILE_MAGIC: &str = "spaceship-duel genome";
+/// Whether loading a genome file insists it was saved under this build's game constants
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigCheck {
+    Require,
+    /// Accept a genome from different rules, e.g. to see how it copes
+    Ignore,
+}
+
+/// Short name of every sensor input, in the order `get_inputs` fills them
+pub const INPUT_NAMES: [&str; SENSOR_COUNT] = [
+    "opponent_distance",
+    "opponent_angle_sin",
+    "opponent_angle_cos",
+    "opponent_facing_sin",
+    "opponent_facing_cos",
+    "own_speed",
+    "opponent_speed",
+    "bullet_distance",
+    "bullet_angle_sin",
+    "bullet_angle_cos",
+    "drift_sin",
+    "drift_cos",
+    "fire_cooldown",
+    "own_projectiles",
+    "cloak_charge",
+    "in_crosshair",
+    "crosshair_offset",
+    "threat_time",
+    "threat_miss",
+    "own_x",
+    "own_y",
+    "own_shield",
+    "own_hull",
+    "opponent_shield",
+    "opponent_hull",
+    "own_ammo",
+    "pickup_distance",
+    "pickup_angle_sin",
+    "pickup_angle_cos",
+    "own_energy",
+    "opponent_deflecting",
+    "ray0_opponent",
+    "ray0_projectile",
+    "ray0_asteroid",
+    "ray1_opponent",
+    "ray1_projectile",
+    "ray1_asteroid",
+    "ray2_opponent",
+    "ray2_projectile",
+    "ray2_asteroid",
+    "ray3_opponent",
+    "ray3_projectile",
+    "ray3_asteroid",
+    "ray4_opponent",
+    "ray4_projectile",
+    "ray4_asteroid",
+    "ray5_opponent",
+    "ray5_projectile",
+    "ray5_asteroid",
+    "ray6_opponent",
+    "ray6_projectile",
+    "ray6_asteroid",
+    "ray7_opponent",
+    "ray7_projectile",
+    "ray7_asteroid",
+    "ray8_opponent",
+    "ray8_projectile",
+    "ray8_asteroid",
+    "ray9_opponent",
+    "ray9_projectile",
+    "ray9_asteroid",
+    "ray10_opponent",
+    "ray10_projectile",
+    "ray10_asteroid",
+    "ray11_opponent",
+    "ray11_projectile",
+    "ray11_asteroid",
+    "ray12_opponent",
+    "ray12_projectile",
+    "ray12_asteroid",
+    "ray13_opponent",
+    "ray13_projectile",
+    "ray13_asteroid",
+    "ray14_opponent",
+    "ray14_projectile",
+    "ray14_asteroid",
+    "ray15_opponent",
+    "ray15_projectile",
+    "ray15_asteroid",
+];
+/// Half-angle (radians) of the cone in front of the nose that counts as "in the crosshair"
+pub const CROSSHAIR_HALF_ANGLE: f32 = 0.1;
+/// Projectiles whose closest approach passes within this distance count as threats
+pub const THREAT_RADIUS: f32 = SHIP_RADIUS * 3.0;
+
+/// Distribution random genomes draw their weights from
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WeightInit {
+    /// Every weight uniform in (-1, 1)
+    #[default]
+    Uniform,
+    /// Uniform in +-sqrt(6 / (fan_in + fan_out)), suited to the tanh hidden layer
+    Xavier,
+    /// Uniform with variance 2 / fan_in
+    He,
+    /// Uniform in (-1, 1) for a `density` fraction of weights, zero elsewhere
+    Sparse { density: f32 },
+}
+
+impl WeightInit {
+    /// Parse "uniform", "xavier", "he" or "sparse:DENSITY"
+    pub fn parse(spec: &str) -> Result<Self, DuelError> {
+        let (kind, param) = match spec.split_once(':') {
+            Some((kind, param)) => (kind, Some(param)),
+            None => (spec, None),
+        };
+        match (kind, param) {
+            ("uniform", None) => Ok(WeightInit::Uniform),
+            ("xavier", None) => Ok(WeightInit::Xavier),
+            ("he", None) => Ok(WeightInit::He),
+            ("sparse", None) => Ok(WeightInit::Sparse { density: 0.2 }),
+            ("sparse", Some(param)) => {
+                let density = param.parse::<f32>().map_err(|_| DuelError::InvalidValue {
+                    what: "sparse density",
+                    value: param.to_string(),
+                })?;
+                check_range("sparse density", density, 0.0, 1.0)?;
+                Ok(WeightInit::Sparse { density })
+            }
+            _ => Err(DuelError::InvalidValue {
+                what: "initialization",
+                value: spec.to_string(),
+            }),
+        }
+    }
+
+    fn sample(self, fan_in: usize, fan_out: usize, rng: &mut impl Rng) -> f32 {
+        match self {
+            WeightInit::Uniform => rng.gen_range(-1.0..1.0),
+            WeightInit::Xavier => {
+                let limit = (6.0 / (fan_in + fan_out) as f32).sqrt();
+                rng.gen_range(-limit..limit)
+            }
+            WeightInit::He => {
+                let limit = (6.0 / fan_in as f32).sqrt();
+                rng.gen_range(-limit..limit)
+            }
+            WeightInit::Sparse { density } => {
+                if rng.gen::<f32>() < density {
+                    rng.gen_range(-1.0..1.0)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// How `Genome::random_with` fills a new genome
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Initialization {
+    pub weights: WeightInit,
+    /// Start every bias at zero instead of sampling it
+    pub zero_bias: bool,
+    /// Shape of the network to draw weights for
+    pub network: NetworkConfig,
+}
+
+/// Shape of a genome's network. The inputs are the relative sensors, plus
+/// the position sensors if enabled, and the outputs are the ship controls;
+/// the hidden layers can be chosen freely.
+///
+/// Weights are laid out layer by layer, nearest the inputs first, each neuron
+/// a row of incoming weights followed by its bias. A recurrent network then
+/// adds, per hidden layer, one row per neuron of weights from that layer's
+/// activations on the previous tick.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NetworkConfig {
+    /// Width of each hidden layer, nearest the inputs first
+    pub hidden: Vec<usize>,
+    /// Hidden layers keep state between ticks
+    pub recurrent: bool,
+    /// Also read the ship's absolute position, so positional play can be learned
+    pub position_inputs: bool,
+    /// Also read both ships' shield and hull, so retreating to recharge can be learned
+    pub defense_inputs: bool,
+    /// Also read the ammo left and where to find more, so shots can be rationed
+    pub ammo_inputs: bool,
+    /// Also read own energy and the opponent's deflector, so energy can be budgeted
+    pub energy_inputs: bool,
+    /// Steer with one tanh output in [-1, 1] (positive turns right) instead
+    /// of competing left and right outputs
+    pub signed_turn: bool,
+    /// Read this many raycast sensors instead of the relative sensors; 0
+    /// keeps the relative sensors
+    pub rays: usize,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            hidden: vec![HIDDEN_SIZE],
+            recurrent: false,
+            position_inputs: false,
+            defense_inputs: false,
+            ammo_inputs: false,
+            energy_inputs: false,
+            signed_turn: false,
+            rays: 0,
+        }
+    }
+}
+
+impl NetworkConfig {
+    pub fn spec(&self) -> String {
+        let widths: Vec<String> = self.hidden.iter().map(|w| w.to_string()).collect();
+        let kind = if self.recurrent {
+            "recurrent"
+        } else {
+            "feedforward"
+        };
+        let position = if self.position_inputs {
+            "+position"
+        } else {
+            ""
+        };
+        let defense = if self.defense_inputs { "+defense" } else { "" };
+        let ammo = if self.ammo_inputs { "+ammo" } else { "" };
+        let energy = if self.energy_inputs { "+energy" } else { "" };
+        let steer = if self.signed_turn { "+steer" } else { "" };
+        let rays = match self.rays {
+            0 => String::new(),
+            n => format!("+rays{}", n),
+        };
+        format!(
+            "{}{}{}{}{}{}{}:{}",
+            kind,
+            rays,
+            position,
+            defense,
+            ammo,
+            energy,
+            steer,
+            widths.join(",")
+        )
+    }
+
+    /// Parse "feedforward:WIDTHS" or "recurrent:WIDTHS", either optionally with
+    /// "+raysN", "+position", "+defense", "+ammo", "+energy" and "+steer" in
+    /// that order before the colon; a bare WIDTHS list is feedforward
+    pub fn parse(spec: &str) -> Result<Self, DuelError> {
+        let (kind, widths) = spec.split_once(':').unwrap_or(("feedforward", spec));
+        let (kind, signed_turn) = match kind.strip_suffix("+steer") {
+            Some(kind) => (kind, true),
+            None => (kind, false),
+        };
+        let (kind, energy_inputs) = match kind.strip_suffix("+energy") {
+            Some(kind) => (kind, true),
+            None => (kind, false),
+        };
+        let (kind, ammo_inputs) = match kind.strip_suffix("+ammo") {
+            Some(kind) => (kind, true),
+            None => (kind, false),
+        };
+        let (kind, defense_inputs) = match kind.strip_suffix("+defense") {
+            Some(kind) => (kind, true),
+            None => (kind, false),
+        };
+        let (kind, position_inputs) = match kind.strip_suffix("+position") {
+            Some(kind) => (kind, true),
+            None => (kind, false),
+        };
+        let (kind, rays) = match kind.split_once("+rays") {
+            Some((kind, count)) => {
+                let count = count.parse().map_err(|_| DuelError::InvalidValue {
+                    what: "ray count",
+                    value: excerpt(count),
+                })?;
+                (kind, count)
+            }
+            None => (kind, 0),
+        };
+        let recurrent = match kind {
+            "feedforward" => false,
+            "recurrent" => true,
+            _ => {
+                return Err(DuelError::UnknownName {
+                    kind: "network",
+                    name: kind.to_string(),
+                })
+            }
+        };
+        let network = NetworkConfig {
+            hidden: Self::parse_widths(widths)?,
+            recurrent,
+            position_inputs,
+            defense_inputs,
+            ammo_inputs,
+            energy_inputs,
+            signed_turn,
+            rays,
+        };
+        network.validate()?;
+        Ok(network)
+    }
+
+    /// Set one setting by its `[network]` config key, e.g. "hidden" to "24,16"
+    /// or "rays" to "8"
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), DuelError> {
+        fn parse<T: std::str::FromStr>(name: &'static str, value: &str) -> Result<T, DuelError> {
+            value.trim().parse().map_err(|_| DuelError::InvalidValue {
+                what: name,
+                value: excerpt(value),
+            })
+        }
+        let mut network = self.clone();
+        match name {
+            "hidden" => network.hidden = Self::parse_widths(value)?,
+            "recurrent" => network.recurrent = parse("recurrent", value)?,
+            "position_inputs" => network.position_inputs = parse("position_inputs", value)?,
+            "defense_inputs" => network.defense_inputs = parse("defense_inputs", value)?,
+            "ammo_inputs" => network.ammo_inputs = parse("ammo_inputs", value)?,
+            "energy_inputs" => network.energy_inputs = parse("energy_inputs", value)?,
+            "signed_turn" => network.signed_turn = parse("signed_turn", value)?,
+            "rays" => network.rays = parse("rays", value)?,
+            _ => {
+                return Err(DuelError::UnknownName {
+                    kind: "network setting",
+                    name: name.to_string(),
+                })
+            }
+        }
+        network.validate()?;
+        *self = network;
+        Ok(())
+    }
+
+    /// Parse comma-separated hidden layer widths such as "24,16"
+    pub fn parse_widths(spec: &str) -> Result<Vec<usize>, DuelError> {
+        spec.split(',')
+            .map(|width| {
+                width.trim().parse().map_err(|_| DuelError::InvalidValue {
+                    what: "hidden layer width",
+                    value: excerpt(width),
+                })
+            })
+            .collect()
+    }
+
+    /// Check there are 1 to MAX_HIDDEN_LAYERS hidden layers of 1 to
+    /// MAX_LAYER_WIDTH neurons, and that the rays, if any, divide MAX_RAYS so
+    /// they are evenly spaced
+    pub fn validate(&self) -> Result<(), DuelError> {
+        if self.rays > MAX_RAYS || (self.rays > 0 && !MAX_RAYS.is_multiple_of(self.rays)) {
+            return Err(DuelError::InvalidValue {
+                what: "ray count",
+                value: self.rays.to_string(),
+            });
+        }
+        check_range(
+            "hidden layers",
+            self.hidden.len() as f32,
+            1.0,
+            MAX_HIDDEN_LAYERS as f32,
+        )?;
+        for &width in &self.hidden {
+            check_range(
+                "hidden layer width",
+                width as f32,
+                1.0,
+                MAX_LAYER_WIDTH as f32,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Number of sensor values the network reads
+    pub fn inputs(&self) -> usize {
+        self.sensors().count()
+    }
+
+    /// Indices into `get_inputs` of the sensors the network reads, in input order
+    pub fn sensors(&self) -> impl Iterator<Item = usize> + '_ {
+        let position = INPUT_SIZE..INPUT_SIZE + POSITION_INPUTS;
+        let defense = position.end..position.end + DEFENSE_INPUTS;
+        let ammo = defense.end..defense.end + AMMO_INPUTS;
+        let energy = ammo.end..ammo.end + ENERGY_INPUTS;
+        let rays = energy.end..SENSOR_COUNT;
+        let first_ray = rays.start;
+        let stride = MAX_RAYS / self.rays.max(1);
+        (0..INPUT_SIZE)
+            .filter(move |_| self.rays == 0)
+            .chain(rays.filter(move |&i| {
+                self.rays > 0 && ((i - first_ray) / RAY_CHANNELS).is_multiple_of(stride)
+            }))
+            .chain(position.filter(move |_| self.position_inputs))
+            .chain(defense.filter(move |_| self.defense_inputs))
+            .chain(ammo.filter(move |_| self.ammo_inputs))
+            .chain(energy.filter(move |_| self.energy_inputs))
+    }
+
+    /// Number of outputs: one per ship control, or one fewer when a single
+    /// signed output steers
+    pub fn outputs(&self) -> usize {
+        if self.signed_turn {
+            SIGNED_TURN_OUTPUTS
+        } else {
+            OUTPUT_SIZE
+        }
+    }
+
+    /// (fan_in, width) of every weighted layer, hidden layers first and the output layer last
+    pub fn layers(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let fan_ins = std::iter::once(self.inputs()).chain(self.hidden.iter().copied());
+        fan_ins.zip(
+            self.hidden
+                .iter()
+                .copied()
+                .chain(std::iter::once(self.outputs())),
+        )
+    }
+
+    /// Weights of the feedforward layers, which recurrent weights follow
+    pub fn feedforward_len(&self) -> usize {
+        self.layers()
+            .map(|(fan_in, width)| (fan_in + 1) * width)
+            .sum()
+    }
+
+    pub fn genome_size(&self) -> usize {
+        let recurrent: usize = if self.recurrent {
+            self.hidden.iter().map(|w| w * w).sum()
+        } else {
+            0
+        };
+        self.feedforward_len() + recurrent
+    }
+
+    /// Hidden neurons across all layers, the length of a recurrent `NetworkState`
+    pub fn hidden_units(&self) -> usize {
+        self.hidden.iter().sum()
+    }
+
+    pub fn group(&self, index: usize) -> WeightGroup {
+        self.locate(index).0
+    }
+
+    /// Group of a flat genome index and the fan-in and fan-out of its layer
+    fn locate(&self, index: usize) -> (WeightGroup, usize, usize) {
+        let output_layer = self.hidden.len();
+        let mut start = 0;
+        for (layer, (fan_in, width)) in self.layers().enumerate() {
+            let len = (fan_in + 1) * width;
+            if index < start + len {
+                let group = if (index - start) % (fan_in + 1) == fan_in {
+                    WeightGroup::Bias
+                } else if layer == output_layer {
+                    WeightGroup::HiddenOutput
+                } else {
+                    WeightGroup::InputHidden
+                };
+                return (group, fan_in, width);
+            }
+            start += len;
+        }
+        let mut rest = index - start;
+        for &width in &self.hidden {
+            if rest < width * width {
+                return (WeightGroup::Recurrent, width, width);
+            }
+            rest -= width * width;
+        }
+        // Past the end of the genome; no caller indexes there
+        (WeightGroup::Recurrent, 1, 1)
+    }
+}
+
+/// Which part of the network a flat genome index belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeightGroup {
+    /// Weights into a hidden layer, from the inputs or the hidden layer before it
+    InputHidden,
+    HiddenOutput,
+    Bias,
+    /// Previous hidden state -> hidden, only present in recurrent genomes
+    Recurrent,
+}
+
+/// Chance of perturbing a weight and the size of the perturbation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MutationParams {
+    pub rate: f32,
+    pub strength: f32,
+}
+
+/// A genome's own multipliers of the configured weight mutation rates and
+/// strengths, handed down to its offspring. They stay at 1 unless
+/// `LayerMutation::self_adaptation` is raised, and then evolve alongside the
+/// weights, so a run can turn its own exploration up or down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MutationScale {
+    pub rate: f32,
+    pub strength: f32,
+}
+
+impl Default for MutationScale {
+    fn default() -> Self {
+        MutationScale {
+            rate: 1.0,
+            strength: 1.0,
+        }
+    }
+}
+
+impl MutationScale {
+    pub const MIN: f32 = 0.01;
+    pub const MAX: f32 = 10.0;
+
+    pub fn validate(&self) -> Result<(), DuelError> {
+        check_range("mutation rate scale", self.rate, Self::MIN, Self::MAX)?;
+        check_range(
+            "mutation strength scale",
+            self.strength,
+            Self::MIN,
+            Self::MAX,
+        )
+    }
+
+    /// `params` scaled, the rate capped at certainty and the strength at the
+    /// full weight range
+    pub fn apply(&self, params: MutationParams) -> MutationParams {
+        MutationParams {
+            rate: (params.rate * self.rate).min(1.0),
+            strength: (params.strength * self.strength).min(2.0 * WEIGHT_LIMIT),
+        }
+    }
+
+    /// Geometric blend `a^(1-t) * b^t`, the scales being log-normally mutated
+    pub fn blend(a: MutationScale, b: MutationScale, t: f32) -> MutationScale {
+        let mix = |a: f32, b: f32| (a.ln() + t * (b.ln() - a.ln())).exp();
+        MutationScale {
+            rate: mix(a.rate, b.rate),
+            strength: mix(a.strength, b.strength),
+        }
+        .clamped()
+    }
+
+    fn clamped(self) -> Self {
+        MutationScale {
+            rate: self.rate.clamp(Self::MIN, Self::MAX),
+            strength: self.strength.clamp(Self::MIN, Self::MAX),
+        }
+    }
+}
+
+/// Mutation settings per weight group. Output weights steer the ship directly,
+/// so by default they mutate less often and by less than the hidden layer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerMutation {
+    pub input_hidden: MutationParams,
+    pub hidden_output: MutationParams,
+    pub bias: MutationParams,
+    /// Loadout traits; strength is a fraction of each trait's range
+    pub loadout: MutationParams,
+    /// Learning rate of log-normal self-adaptation: each genome's
+    /// `MutationScale` is multiplied by exp(self_adaptation * N(0, 1)) before
+    /// its weights mutate. 0 keeps every genome on the configured values.
+    pub self_adaptation: f32,
+}
+
+impl Default for LayerMutation {
+    fn default() -> Self {
+        LayerMutation {
+            input_hidden: MutationParams {
+                rate: 0.15,
+                strength: 0.4,
+            },
+            hidden_output: MutationParams {
+                rate: 0.08,
+                strength: 0.25,
+            },
+            bias: MutationParams {
+                rate: 0.15,
+                strength: 0.4,
+            },
+            loadout: MutationParams {
+                rate: 0.0,
+                strength: 0.1,
+            },
+            self_adaptation: 0.0,
+        }
+    }
+}
+
+impl LayerMutation {
+    /// The same rate and strength for every weight; the loadout keeps its default
+    pub fn uniform(rate: f32, strength: f32) -> Self {
+        let params = MutationParams { rate, strength };
+        LayerMutation {
+            input_hidden: params,
+            hidden_output: params,
+            bias: params,
+            ..Self::default()
+        }
+    }
+
+    /// These settings with every weight group's strength multiplied by `factor`
+    pub fn with_weight_strength(&self, factor: f32) -> Self {
+        let scaled = |params: MutationParams| MutationParams {
+            strength: params.strength * factor,
+            ..params
+        };
+        LayerMutation {
+            input_hidden: scaled(self.input_hidden),
+            hidden_output: scaled(self.hidden_output),
+            bias: scaled(self.bias),
+            ..self.clone()
+        }
+    }
+
+    pub fn params(&self, group: WeightGroup) -> MutationParams {
+        match group {
+            // Recurrent weights feed the hidden layer alongside the inputs
+            WeightGroup::InputHidden | WeightGroup::Recurrent => self.input_hidden,
+            WeightGroup::HiddenOutput => self.hidden_output,
+            WeightGroup::Bias => self.bias,
+        }
+    }
+
+    /// Every setting with the name `set` accepts for it
+    pub fn values(&self) -> [(&'static str, f32); 9] {
+        [
+            ("input_hidden_rate", self.input_hidden.rate),
+            ("input_hidden_strength", self.input_hidden.strength),
+            ("hidden_output_rate", self.hidden_output.rate),
+            ("hidden_output_strength", self.hidden_output.strength),
+            ("bias_rate", self.bias.rate),
+            ("bias_strength", self.bias.strength),
+            ("loadout_rate", self.loadout.rate),
+            ("loadout_strength", self.loadout.strength),
+            ("self_adaptation", self.self_adaptation),
+        ]
+    }
+
+    /// Set one value by name ("input_hidden_rate", "bias_strength", ...), as used on the command line
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), DuelError> {
+        let max = if name.ends_with("_rate") || name == "self_adaptation" {
+            1.0
+        } else {
+            2.0 * WEIGHT_LIMIT
+        };
+        let field = match name {
+            "input_hidden_rate" => &mut self.input_hidden.rate,
+            "input_hidden_strength" => &mut self.input_hidden.strength,
+            "hidden_output_rate" => &mut self.hidden_output.rate,
+            "hidden_output_strength" => &mut self.hidden_output.strength,
+            "bias_rate" => &mut self.bias.rate,
+            "bias_strength" => &mut self.bias.strength,
+            "loadout_rate" => &mut self.loadout.rate,
+            "loadout_strength" => &mut self.loadout.strength,
+            "self_adaptation" => &mut self.self_adaptation,
+            _ => {
+                return Err(DuelError::UnknownName {
+                    kind: "mutation setting",
+                    name: name.to_string(),
+                })
+            }
+        };
+        check_range(name, value, 0.0, max)?;
+        *field = value;
+        Ok(())
+    }
+}
+
+/// Hidden activations a genome carries from one tick to the next, all hidden
+/// layers back to back. Recurrent genomes read them back on the following
+/// tick; keep one per ship and reset it at the start of every match.
+#[derive(Clone, Debug, Default)]
+pub struct NetworkState {
+    pub hidden: Vec<f32>,
+}
+
+impl NetworkState {
+    pub fn reset(&mut self) {
+        self.hidden.clear();
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Genome {
     pub weights: Vec<f32>,
     pub fitness: f32,
+    /// Generations this genome has survived as an elite (0 for new offspring)
+    pub age: usize,
+    pub network: NetworkConfig,
+    /// Handling traits of the ship this genome flies
+    pub loadout: Loadout,
+    pub mutation_scale: MutationScale,
 }
 
 impl Genome {
+    /// Genome of the default network shape from externally supplied weights,
+    /// which must number GENOME_SIZE and lie within +-WEIGHT_LIMIT
+    pub fn from_weights(weights: Vec<f32>) -> Result<Self, DuelError> {
+        Self::with_network(NetworkConfig::default(), weights)
+    }
+
+    /// Genome with the given network shape; the weights must number
+    /// `network.genome_size()` and lie within +-WEIGHT_LIMIT
+    pub fn with_network(network: NetworkConfig, weights: Vec<f32>) -> Result<Self, DuelError> {
+        network.validate()?;
+        if weights.len() != network.genome_size() {
+            return Err(DuelError::WrongSize {
+                what: "genome",
+                expected: network.genome_size(),
+                found: weights.len(),
+            });
+        }
+        let limits = -WEIGHT_LIMIT..=WEIGHT_LIMIT;
+        if let Some(i) = weights.iter().position(|w| !limits.contains(w)) {
+            check_range(
+                &format!("weight {}", i),
+                weights[i],
+                -WEIGHT_LIMIT,
+                WEIGHT_LIMIT,
+            )?;
+        }
+        Ok(Genome {
+            weights,
+            fitness: 0.0,
+            age: 0,
+            network,
+            loadout: Loadout::default(),
+            mutation_scale: MutationScale::default(),
+        })
+    }
+
     pub fn random(rng: &mut impl Rng) -> Self {
+        Self::random_with(&Initialization::default(), rng)
+    }
+
+    pub fn random_with(init: &Initialization, rng: &mut impl Rng) -> Self {
+        let network = init.network.clone();
+        let weights = (0..network.genome_size())
+            .map(|i| {
+                let (group, fan_in, fan_out) = network.locate(i);
+                if init.zero_bias && group == WeightGroup::Bias {
+                    return 0.0;
+                }
+                init.weights
+                    .sample(fan_in, fan_out, rng)
+                    .clamp(-WEIGHT_LIMIT, WEIGHT_LIMIT)
+            })
+            .collect();
         Genome {
-            weights: (0..GENOME_SIZE).map(|_| rng.gen_range(-1.0..1.0)).collect(),
+            weights,
             fitness: 0.0,
+            age: 0,
+            network,
+            loadout: Loadout::default(),
+            mutation_scale: MutationScale::default(),
         }
     }
 
-    /// Evaluate the neural network given sensor inputs, returning [thrust, turn_left, turn_right, fire]
-    pub fn evaluate(&self, inputs: &[f32; INPUT_SIZE]) -> [f32; OUTPUT_SIZE] {
-        let mut idx = 0;
+    pub fn is_recurrent(&self) -> bool {
+        self.network.recurrent
+    }
 
-        // Hidden layer
-        let mut hidden = [0.0f32; HIDDEN_SIZE];
-        for h in hidden.iter_mut() {
-            let mut sum = 0.0;
-            for &inp in inputs.iter() {
-                sum += inp * self.weights[idx];
-                idx += 1;
-            }
-            sum += self.weights[idx]; // bias
-            idx += 1;
-            *h = sum.tanh();
+    /// Evaluate the neural network given sensor inputs, returning [thrust, turn_left, turn_right, fire, cloak].
+    /// A `signed_turn` network's turn output fills turn_left when negative and turn_right when positive.
+    /// `state` holds the hidden activations of the previous tick and is updated to this tick's.
+    pub fn evaluate(
+        &self,
+        inputs: &[f32; SENSOR_COUNT],
+        state: &mut NetworkState,
+    ) -> [f32; OUTPUT_SIZE] {
+        let _span = Span::enter("Genome::evaluate");
+        self.forward(inputs, state, None)
+    }
+
+    /// Activation of every neuron as `evaluate` would compute it next: the
+    /// sensors the network reads, each hidden layer, then the outputs.
+    /// `state` is left as it is.
+    pub fn activations(&self, inputs: &[f32; SENSOR_COUNT], state: &NetworkState) -> Vec<Vec<f32>> {
+        let mut layers = Vec::with_capacity(self.network.hidden.len() + 2);
+        self.forward(inputs, &mut state.clone(), Some(&mut layers));
+        layers
+    }
+
+    /// The forward pass of `evaluate`, also copying each layer's activations
+    /// into `trace` if given
+    fn forward(
+        &self,
+        inputs: &[f32; SENSOR_COUNT],
+        state: &mut NetworkState,
+        mut trace: Option<&mut Vec<Vec<f32>>>,
+    ) -> [f32; OUTPUT_SIZE] {
+        let recurrent = self.network.recurrent;
+        if recurrent && state.hidden.len() != self.network.hidden_units() {
+            state.hidden = vec![0.0; self.network.hidden_units()];
         }
+        let output_layer = self.network.hidden.len();
+        let mut idx = 0;
+        let mut recurrent_idx = self.network.feedforward_len();
+        let mut state_idx = 0;
 
-        // Output layer
-        let mut output = [0.0f32; OUTPUT_SIZE];
-        for o in output.iter_mut() {
-            let mut sum = 0.0;
-            for &h in hidden.iter() {
-                sum += h * self.weights[idx];
-                idx += 1;
+        // Each layer reads one buffer and writes the other
+        let mut buffers = [[0.0f32; MAX_LAYER_WIDTH]; 2];
+        for (slot, sensor) in buffers[0].iter_mut().zip(self.network.sensors()) {
+            *slot = inputs[sensor];
+        }
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.push(buffers[0][..self.network.inputs()].to_vec());
+        }
+        for (layer, (fan_in, width)) in self.network.layers().enumerate() {
+            let hidden = layer < output_layer;
+            let [even, odd] = &mut buffers;
+            let (current, next) = if layer % 2 == 0 {
+                (&*even, odd)
+            } else {
+                (&*odd, even)
+            };
+            for (i, n) in next[..width].iter_mut().enumerate() {
+                let row = &self.weights[idx..idx + fan_in + 1];
+                idx += fan_in + 1;
+                let mut sum = 0.0;
+                for (&x, &w) in current[..fan_in].iter().zip(row) {
+                    sum += x * w;
+                }
+                sum += row[fan_in]; // bias
+                if hidden && recurrent {
+                    let row = &self.weights[recurrent_idx + i * width..][..width];
+                    for (&prev, &w) in state.hidden[state_idx..][..width].iter().zip(row) {
+                        sum += prev * w;
+                    }
+                }
+                let signed = !hidden && self.network.signed_turn && i == TURN_OUTPUT;
+                *n = if hidden || signed {
+                    sum.tanh()
+                } else {
+                    sigmoid(sum)
+                };
+            }
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.push(next[..width].to_vec());
+            }
+            if hidden && recurrent {
+                state.hidden[state_idx..][..width].copy_from_slice(&next[..width]);
+                state_idx += width;
+                recurrent_idx += width * width;
             }
-            sum += self.weights[idx]; // bias
-            idx += 1;
-            *o = sigmoid(sum);
         }
 
+        let raw = &buffers[(output_layer + 1) % 2];
+        if self.network.signed_turn {
+            // The game steers by turn_right - turn_left, which gives back the signed turn
+            let turn = raw[TURN_OUTPUT];
+            return [raw[0], (-turn).max(0.0), turn.max(0.0), raw[2], raw[3]];
+        }
+        let mut output = [0.0f32; OUTPUT_SIZE];
+        output.copy_from_slice(&raw[..OUTPUT_SIZE]);
         output
     }
 
-    /// Build sensor inputs for a ship from the current game state
-    pub fn get_inputs(state: &GameState, ship_idx: usize) -> [f32; INPUT_SIZE] {
+    /// Build sensor inputs for a ship from the current game state. The
+    /// opponent is the nearest ship still flying, which in a duel is the
+    /// other one. A cloaked opponent reads as "unknown": maximum distance,
+    /// zero speed, shield, hull and deflector, and sin/cos pairs of (0, 0),
+    /// which no real angle can produce.
+    pub fn get_inputs(state: &GameState, ship_idx: usize) -> [f32; SENSOR_COUNT] {
+        Self::read_sensors(state, ship_idx, true)
+    }
+
+    /// The inputs of `get_inputs` this genome's network can read: the rays
+    /// are only cast, at a cost per ship every tick, for ray networks, and
+    /// read 0 otherwise
+    pub fn sensors(&self, state: &GameState, ship_idx: usize) -> [f32; SENSOR_COUNT] {
+        Self::read_sensors(state, ship_idx, self.network.rays > 0)
+    }
+
+    fn read_sensors(state: &GameState, ship_idx: usize, rays: bool) -> [f32; SENSOR_COUNT] {
+        let _span = Span::enter("Genome::get_inputs");
         let ship = &state.ships[ship_idx];
-        let opp = &state.ships[1 - ship_idx];
+        let opp = &state.ships[state.nearest_opponent(ship_idx)];
 
         // Relative position using toroidal distance
-        let dx = toroidal_diff(opp.x, ship.x, ARENA_WIDTH);
-        let dy = toroidal_diff(opp.y, ship.y, ARENA_HEIGHT);
+        let dx = toroidal_diff(opp.x, ship.x, state.arena.width);
+        let dy = toroidal_diff(opp.y, ship.y, state.arena.height);
         let dist = (dx * dx + dy * dy).sqrt().max(1.0);
+        let range = state.arena.engagement_range();
 
         // Angle from our ship to opponent, relative to our heading
         let angle_to_opp = dy.atan2(dx) - ship.rotation;
 
+        // Is the opponent inside the firing cone, and where within it
+        let aim_error = angle_to_opp.sin().atan2(angle_to_opp.cos());
+        let (in_crosshair, crosshair_offset) = if aim_error.abs() <= CROSSHAIR_HALF_ANGLE {
+            (1.0, aim_error / CROSSHAIR_HALF_ANGLE)
+        } else {
+            (0.0, 0.0)
+        };
+
         // Opponent heading relative to vector from them to us
         let angle_opp_to_us = (-dy).atan2(-dx);
         let opp_facing_angle = opp.rotation - angle_opp_to_us;
@@ -85,6 +968,9 @@ impl Genome {
         // Nearest enemy bullet
         let (bullet_dist, bullet_angle) = nearest_enemy_bullet(state, ship_idx);
 
+        // Most threatening incoming projectile by time to closest approach
+        let (impact_time, impact_miss) = incoming_threat(state, ship_idx);
+
         // Fire cooldown (0 = ready, 1 = max cooldown)
         let cooldown_norm = (ship.fire_cooldown / FIRE_COOLDOWN).min(1.0);
 
@@ -92,14 +978,33 @@ impl Genome {
         let own_projectiles = state.projectiles.iter().filter(|p| p.owner == ship_idx).count();
         let projectile_norm = own_projectiles as f32 / MAX_PROJECTILES_PER_SHIP as f32;
 
-        [
-            (dist / 500.0).min(1.0),      // 0: distance to opponent (normalized)
+        // Defenses as fractions of the starting points; shields read 0 when disabled
+        let physics = &state.physics;
+        let shield_norm = |s: &Ship| {
+            if physics.shield > 0.0 {
+                s.shield / physics.shield
+            } else {
+                0.0
+            }
+        };
+
+        // Magazine fill (full when ammo is unlimited) and the nearest pickup
+        let ammo_norm = if physics.limits_ammo() {
+            ship.ammo as f32 / physics.ammo as f32
+        } else {
+            1.0
+        };
+        let (pickup_dist, pickup_angle) = nearest_pickup(state, ship_idx);
+        let (pickup_sin, pickup_cos) = pickup_angle.map_or((0.0, 0.0), |a| (a.sin(), a.cos()));
+
+        let relative = [
+            (dist / range).min(1.0),       // 0: distance to opponent (normalized)
             angle_to_opp.sin(),            // 1: angle to opponent (sin)
             angle_to_opp.cos(),            // 2: angle to opponent (cos)
             opp_facing_angle.sin(),        // 3: opponent facing direction (sin)
             opp_facing_angle.cos(),        // 4: opponent facing direction (cos)
-            (own_speed / 300.0).min(1.0),  // 5: own speed normalized
-            (opp_speed / 300.0).min(1.0),  // 6: opponent speed normalized
+            (own_speed / MAX_SHIP_SPEED).min(1.0), // 5: own speed normalized
+            (opp_speed / MAX_SHIP_SPEED).min(1.0), // 6: opponent speed normalized
             bullet_dist,                   // 7: nearest bullet distance
             bullet_angle.sin(),            // 8: nearest bullet angle (sin)
             bullet_angle.cos(),            // 9: nearest bullet angle (cos)
@@ -107,31 +1012,341 @@ impl Genome {
             own_vel_angle.cos(),           // 11: own drift direction (cos)
             cooldown_norm,                 // 12: fire cooldown (0=ready)
             projectile_norm,               // 13: own projectile count (normalized)
-        ]
+            ship.cloak_energy,             // 14: own cloak charge
+            in_crosshair,                  // 15: opponent inside firing cone
+            crosshair_offset,              // 16: offset within firing cone (-1..1)
+            impact_time,                   // 17: time to closest approach of threat (1=none)
+            impact_miss,                   // 18: predicted miss distance of threat (1=none)
+            ship.x / state.arena.width,         // 19: own x position (position networks only)
+            ship.y / state.arena.height,        // 20: own y position (position networks only)
+            shield_norm(ship),             // 21: own shield (defense networks only)
+            ship.hull / ship.starting_hull(physics), // 22: own hull (defense networks only)
+            shield_norm(opp),           // 23: opponent shield (defense networks only)
+            opp.hull / opp.starting_hull(physics), // 24: opponent hull (defense networks only)
+            ammo_norm,                  // 25: own ammo left (ammo networks only)
+            pickup_dist,                // 26: nearest pickup distance (1=none)
+            pickup_sin,                 // 27: nearest pickup angle (sin)
+            pickup_cos,                 // 28: nearest pickup angle (cos)
+            ship.energy,                // 29: own energy (energy networks only)
+            opp.deflecting as u8 as f32, // 30: opponent deflector raised
+        ];
+        let mut inputs = [0.0; SENSOR_COUNT];
+        inputs[..relative.len()].copy_from_slice(&relative);
+        // 31..: rays (ray networks only)
+        if rays {
+            inputs[relative.len()..].copy_from_slice(&cast_rays(state, ship_idx));
+        }
+
+        if opp.cloaked {
+            inputs[0] = 1.0;
+            inputs[1..5].fill(0.0);
+            inputs[6] = 0.0;
+            inputs[15..17].fill(0.0);
+            inputs[23..25].fill(0.0);
+            inputs[30] = 0.0;
+        }
+
+        inputs
+    }
+
+    /// Text file form: a versioned header, generation, fitness, network shape
+    /// and game config hash, then one weight per line
+    pub fn to_file_string(&self, generation: usize) -> String {
+        let mut out = format!(
+            "{} {}\ngeneration {}\nfitness {}\nnetwork {}\nconfig {:016x}\nloadout {}\nweights {}\n",
+            GENOME_FILE_MAGIC,
+            GENOME_FILE_VERSION,
+            generation,
+            self.fitness,
+            self.network.spec(),
+            config_hash(),
+            self.loadout.spec(),
+            self.weights.len()
+        );
+        for w in &self.weights {
+            out.push_str(&format!("{}\n", w));
+        }
+        out
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>, generation: usize) -> Result<(), DuelError> {
+        std::fs::write(path, self.to_file_string(generation))?;
+        Ok(())
+    }
+
+    /// Parse the output of `to_file_string`, returning the genome (with its
+    /// saved fitness) and the generation it was saved in. Files saved under
+    /// other game constants are rejected.
+    pub fn from_file_str(text: &str) -> Result<(Genome, usize), DuelError> {
+        Self::from_file_str_with(text, ConfigCheck::Require)
+    }
+
+    /// `from_file_str`, optionally accepting files saved under other game
+    /// constants. Files from before config hashes were recorded are always accepted.
+    pub fn from_file_str_with(
+        text: &str,
+        check: ConfigCheck,
+    ) -> Result<(Genome, usize), DuelError> {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default();
+        let version: u32 = header
+            .strip_prefix(GENOME_FILE_MAGIC)
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| DuelError::InvalidValue {
+                what: "genome file header",
+                value: excerpt(header),
+            })?;
+        if version == 0 || version > GENOME_FILE_VERSION {
+            return Err(DuelError::UnsupportedVersion {
+                found: version,
+                supported: GENOME_FILE_VERSION,
+            });
+        }
+
+        let generation: usize = header_field(lines.next(), "generation")?;
+        let fitness: f32 = header_field(lines.next(), "fitness")?;
+        check_range("fitness", fitness, f32::MIN, f32::MAX)?;
+        // Version 1 files predate configurable networks and always hold the default shape
+        let network = if version >= 2 {
+            let spec: String = header_field(lines.next(), "network")?;
+            NetworkConfig::parse(&spec)?
+        } else {
+            NetworkConfig::default()
+        };
+        if version >= 3 {
+            let config: String = header_field(lines.next(), "config")?;
+            let found = u64::from_str_radix(&config, 16).map_err(|_| DuelError::InvalidValue {
+                what: "config",
+                value: excerpt(&config),
+            })?;
+            if check == ConfigCheck::Require && found != config_hash() {
+                return Err(DuelError::ConfigMismatch {
+                    found,
+                    expected: config_hash(),
+                });
+            }
+        }
+        // Files before version 4 were flown with the stock loadout
+        let loadout = if version >= 4 {
+            let spec: String = header_field(lines.next(), "loadout")?;
+            Loadout::parse(&spec)?
+        } else {
+            Loadout::default()
+        };
+        let count: usize = header_field(lines.next(), "weights")?;
+        if count != network.genome_size() {
+            return Err(DuelError::WrongSize {
+                what: "genome",
+                expected: network.genome_size(),
+                found: count,
+            });
+        }
+
+        // One past the expected count, so trailing extra weights are reported
+        let weights = lines
+            .filter(|line| !line.trim().is_empty())
+            .take(count + 1)
+            .map(|line| {
+                line.trim()
+                    .parse::<f32>()
+                    .map_err(|_| DuelError::InvalidValue {
+                        what: "weight",
+                        value: excerpt(line),
+                    })
+            })
+            .collect::<Result<Vec<f32>, _>>()?;
+        let mut genome = Genome::with_network(network, weights)?;
+        genome.fitness = fitness;
+        genome.loadout = loadout;
+        Ok((genome, generation))
+    }
+
+    /// Read a genome file written by `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<(Genome, usize), DuelError> {
+        Self::load_with(path, ConfigCheck::Require)
+    }
+
+    pub fn load_with(
+        path: impl AsRef<Path>,
+        check: ConfigCheck,
+    ) -> Result<(Genome, usize), DuelError> {
+        Self::from_file_str_with(&std::fs::read_to_string(path)?, check)
     }
 
     pub fn crossover(a: &Genome, b: &Genome, rng: &mut impl Rng) -> Genome {
-        let point = rng.gen_range(0..GENOME_SIZE);
-        let mut weights = Vec::with_capacity(GENOME_SIZE);
-        for i in 0..GENOME_SIZE {
-            weights.push(if i < point { a.weights[i] } else { b.weights[i] });
+        // The child takes a's shape; weights of a partner with a different
+        // network mean nothing in it, so such a child is a copy of a
+        let point = if a.network == b.network {
+            rng.gen_range(0..a.network.feedforward_len())
+        } else {
+            a.weights.len()
+        };
+        let weights = a
+            .weights
+            .iter()
+            .zip(&b.weights)
+            .enumerate()
+            .map(|(i, (&wa, &wb))| if i < point { wa } else { wb })
+            .collect();
+        Genome {
+            weights,
+            fitness: 0.0,
+            age: 0,
+            network: a.network.clone(),
+            loadout: Loadout {
+                turn_rate: (a.loadout.turn_rate + b.loadout.turn_rate) / 2.0,
+                smoothing: (a.loadout.smoothing + b.loadout.smoothing) / 2.0,
+            },
+            mutation_scale: MutationScale::blend(a.mutation_scale, b.mutation_scale, 0.5),
         }
+    }
+
+    /// Element-wise mean of the weights of `genomes`, or None if there are none
+    pub fn average(genomes: &[Genome]) -> Option<Genome> {
+        let n = genomes.len() as f32;
+        let first = genomes.first()?;
+        let mut weights = vec![0.0; first.weights.len()];
+        let mut loadout = Loadout {
+            turn_rate: 0.0,
+            smoothing: 0.0,
+        };
+        let mut log_scale = (0.0, 0.0);
+        for genome in genomes {
+            log_scale.0 += genome.mutation_scale.rate.ln() / n;
+            log_scale.1 += genome.mutation_scale.strength.ln() / n;
+            for (sum, w) in weights.iter_mut().zip(&genome.weights) {
+                *sum += w / n;
+            }
+            loadout.turn_rate += genome.loadout.turn_rate / n;
+            loadout.smoothing += genome.loadout.smoothing / n;
+        }
+        Some(Genome {
+            weights,
+            fitness: 0.0,
+            age: 0,
+            network: first.network.clone(),
+            loadout: loadout.clamped(),
+            mutation_scale: MutationScale {
+                rate: log_scale.0.exp(),
+                strength: log_scale.1.exp(),
+            }
+            .clamped(),
+        })
+    }
+
+    /// Linear blend `a + t * (b - a)`: t = 0 gives `a`, t = 1 gives `b`. Values
+    /// outside [0, 1] extrapolate, clamped to +-WEIGHT_LIMIT.
+    pub fn interpolate(a: &Genome, b: &Genome, t: f32) -> Genome {
+        let weights = a
+            .weights
+            .iter()
+            .zip(&b.weights)
+            .map(|(wa, wb)| (wa + t * (wb - wa)).clamp(-WEIGHT_LIMIT, WEIGHT_LIMIT))
+            .collect();
+        let (la, lb) = (a.loadout, b.loadout);
+        let loadout = Loadout {
+            turn_rate: la.turn_rate + t * (lb.turn_rate - la.turn_rate),
+            smoothing: la.smoothing + t * (lb.smoothing - la.smoothing),
+        };
         Genome {
             weights,
             fitness: 0.0,
+            age: 0,
+            network: a.network.clone(),
+            loadout: loadout.clamped(),
+            mutation_scale: MutationScale::blend(a.mutation_scale, b.mutation_scale, t),
+        }
+    }
+
+    /// Copy that ignores sensor input `input`: its weights into the first
+    /// layer are zeroed, exactly as if the input always read 0
+    pub fn without_input(&self, input: usize) -> Genome {
+        let mut genome = Genome {
+            fitness: 0.0,
+            age: 0,
+            ..self.clone()
+        };
+        let row = self.network.inputs() + 1;
+        for neuron in 0..self.network.hidden[0] {
+            genome.weights[neuron * row + input] = 0.0;
         }
+        genome
+    }
+
+    /// Euclidean distance between the weight vectors
+    pub fn distance(&self, other: &Genome) -> f32 {
+        self.weights
+            .iter()
+            .zip(&other.weights)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>()
+            .sqrt()
     }
 
-    pub fn mutate(&mut self, rate: f32, strength: f32, rng: &mut impl Rng) {
-        for w in &mut self.weights {
-            if rng.gen::<f32>() < rate {
+    /// Perturb the weights and loadout. Under self-adaptation the mutation
+    /// scale changes first, so the new scale is judged by the weights it
+    /// produces; it scales the weight mutations only, not the loadout's.
+    pub fn mutate(&mut self, mutation: &LayerMutation, rng: &mut impl Rng) {
+        let tau = mutation.self_adaptation;
+        if tau > 0.0 {
+            let scale = &mut self.mutation_scale;
+            scale.rate *= (tau * standard_normal(rng)).exp();
+            scale.strength *= (tau * standard_normal(rng)).exp();
+            *scale = scale.clamped();
+        }
+        let scale = self.mutation_scale;
+        for (i, w) in self.weights.iter_mut().enumerate() {
+            let MutationParams { rate, strength } =
+                scale.apply(mutation.params(self.network.group(i)));
+            if strength > 0.0 && rng.gen::<f32>() < rate {
                 *w += rng.gen_range(-strength..strength);
-                *w = w.clamp(-3.0, 3.0);
+                *w = w.clamp(-WEIGHT_LIMIT, WEIGHT_LIMIT);
+            }
+        }
+
+        // Loadout traits stay fixed unless their rate is raised from the default 0
+        let MutationParams { rate, strength } = mutation.loadout;
+        if rate > 0.0 && strength > 0.0 {
+            let loadout = &mut self.loadout;
+            for (trait_value, scale) in [
+                (&mut loadout.turn_rate, 1.0),
+                (&mut loadout.smoothing, MAX_CONTROL_SMOOTHING),
+            ] {
+                if rng.gen::<f32>() < rate {
+                    *trait_value += rng.gen_range(-strength..strength) * scale;
+                }
             }
+            *loadout = loadout.clamped();
         }
     }
 }
 
+/// Value of a "name value" header line of a genome file
+fn header_field<T: std::str::FromStr>(
+    line: Option<&str>,
+    name: &'static str,
+) -> Result<T, DuelError> {
+    let line = line.unwrap_or_default();
+    line.strip_prefix(name)
+        .and_then(|value| value.strip_prefix(' '))
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| DuelError::InvalidValue {
+            what: name,
+            value: excerpt(line),
+        })
+}
+
+/// Start of a rejected line, short enough to quote in an error message
+fn excerpt(line: &str) -> String {
+    line.chars().take(40).collect()
+}
+
+/// Sample of N(0, 1) by the Box-Muller transform
+fn standard_normal(rng: &mut impl Rng) -> f32 {
+    let u = rng.gen::<f32>().max(f32::MIN_POSITIVE);
+    (-2.0 * u.ln()).sqrt() * (std::f32::consts::TAU * rng.gen::<f32>()).cos()
+}
+
 fn sigmoid(x: f32) -> f32 {
     1.0 / (1.0 + (-x).exp())
 }
@@ -145,8 +1360,8 @@ fn nearest_enemy_bullet(state: &GameState, ship_idx: usize) -> (f32, f32) {
         if p.owner == ship_idx {
             continue;
         }
-        let dx = toroidal_diff(p.x, ship.x, ARENA_WIDTH);
-        let dy = toroidal_diff(p.y, ship.y, ARENA_HEIGHT);
+        let dx = toroidal_diff(p.x, ship.x, state.arena.width);
+        let dy = toroidal_diff(p.y, ship.y, state.arena.height);
         let dist = (dx * dx + dy * dy).sqrt();
         if dist < min_dist {
             min_dist = dist;
@@ -157,6 +1372,481 @@ fn nearest_enemy_bullet(state: &GameState, ship_idx: usize) -> (f32, f32) {
     if min_dist == f32::MAX {
         (1.0, 0.0)
     } else {
-        ((min_dist / 500.0).min(1.0), best_angle)
+        (
+            (min_dist / state.arena.engagement_range()).min(1.0),
+            best_angle,
+        )
+    }
+}
+
+/// Normalized distance to the nearest ammo pickup and its bearing relative
+/// to the ship's heading; (1, None) when there is none
+fn nearest_pickup(state: &GameState, ship_idx: usize) -> (f32, Option<f32>) {
+    let ship = &state.ships[ship_idx];
+    state
+        .pickups
+        .iter()
+        .map(|p| {
+            let dx = toroidal_diff(p.x, ship.x, state.arena.width);
+            let dy = toroidal_diff(p.y, ship.y, state.arena.height);
+            ((dx * dx + dy * dy).sqrt(), dy.atan2(dx) - ship.rotation)
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or((1.0, None), |(dist, angle)| {
+            (
+                (dist / state.arena.engagement_range()).min(1.0),
+                Some(angle),
+            )
+        })
+}
+
+/// Distance from the ship along each of MAX_RAYS rays to the nearest
+/// opponent, enemy projectile and asteroid, as fractions of the engagement
+/// range with 1 meaning nothing is hit. Every object is seen at its nearest
+/// toroidal image. Projectiles count while passing within a ship radius of
+/// a ray, so the rays report fire that would strike a ship flying along
+/// them; cloaked and destroyed opponents are invisible.
+fn cast_rays(state: &GameState, ship_idx: usize) -> [f32; RAY_INPUTS] {
+    let ship = &state.ships[ship_idx];
+    let range = state.arena.engagement_range();
+    let directions: [(f32, f32); MAX_RAYS] = std::array::from_fn(|k| {
+        let angle = ship.rotation + k as f32 * std::f32::consts::TAU / MAX_RAYS as f32;
+        (angle.cos(), angle.sin())
+    });
+    let mut rays = [1.0f32; RAY_INPUTS];
+    let mut cast = |channel: usize, x: f32, y: f32, radius: f32| {
+        let dx = toroidal_diff(x, ship.x, state.arena.width);
+        let dy = toroidal_diff(y, ship.y, state.arena.height);
+        let dist_sq = dx * dx + dy * dy;
+        if dist_sq.sqrt() - radius > range {
+            return;
+        }
+        for (ray, &(cos, sin)) in rays.chunks_exact_mut(RAY_CHANNELS).zip(&directions) {
+            // Distance along the ray to the circle's near edge, if the ray meets it
+            let along = dx * cos + dy * sin;
+            let off_sq = dist_sq - along * along;
+            let hit = if dist_sq <= radius * radius {
+                0.0
+            } else if along > 0.0 && off_sq <= radius * radius {
+                along - (radius * radius - off_sq).sqrt()
+            } else {
+                continue;
+            };
+            ray[channel] = ray[channel].min((hit / range).min(1.0));
+        }
+    };
+    for (i, opp) in state.ships.iter().enumerate() {
+        if i != ship_idx && opp.alive && !opp.cloaked {
+            cast(0, opp.x, opp.y, SHIP_RADIUS);
+        }
+    }
+    for p in state.projectiles.iter().filter(|p| p.owner != ship_idx) {
+        cast(1, p.x, p.y, PROJECTILE_RADIUS + SHIP_RADIUS);
+    }
+    for a in &state.asteroids {
+        cast(2, a.x, a.y, a.radius());
+    }
+    rays
+}
+
+/// Time to closest approach and miss distance of the enemy projectile that
+/// will pass nearest to the ship soonest, using relative velocity. Both are
+/// normalized to [0, 1], with (1, 1) meaning nothing is on a collision course.
+fn incoming_threat(state: &GameState, ship_idx: usize) -> (f32, f32) {
+    let ship = &state.ships[ship_idx];
+    let mut best = (1.0f32, 1.0f32);
+
+    for p in &state.projectiles {
+        if p.owner == ship_idx {
+            continue;
+        }
+        let rx = toroidal_diff(p.x, ship.x, state.arena.width);
+        let ry = toroidal_diff(p.y, ship.y, state.arena.height);
+        let wx = p.vx - ship.vx;
+        let wy = p.vy - ship.vy;
+        let w_sq = wx * wx + wy * wy;
+        if w_sq < 1e-6 {
+            continue;
+        }
+
+        // Closing only if the projectile reaches its closest point in the future, before expiring
+        let t = -(rx * wx + ry * wy) / w_sq;
+        if t < 0.0 || t > p.lifetime {
+            continue;
+        }
+        let mx = rx + wx * t;
+        let my = ry + wy * t;
+        let miss = (mx * mx + my * my).sqrt();
+        if miss > THREAT_RADIUS {
+            continue;
+        }
+
+        let time_norm = t / PROJECTILE_LIFETIME;
+        if time_norm < best.0 {
+            best = (time_norm, miss / THREAT_RADIUS);
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn file_round_trip_preserves_weights_and_metadata() {
+        let mut genome = Genome::random(&mut StdRng::seed_from_u64(3));
+        genome.fitness = 1234.5;
+        genome.loadout = Loadout {
+            turn_rate: 0.75,
+            smoothing: 0.3,
+        };
+        let (loaded, generation) = Genome::from_file_str(&genome.to_file_string(42)).unwrap();
+        assert_eq!(loaded.weights, genome.weights);
+        assert_eq!(loaded.fitness, genome.fitness);
+        assert_eq!(loaded.loadout, genome.loadout);
+        assert_eq!(generation, 42);
+
+        // Version 1 files have no network line and hold the default network
+        let config = format!("config {:016x}\n", config_hash());
+        let v1 = genome
+            .to_file_string(42)
+            .replacen("genome 4", "genome 1", 1)
+            .replacen("network feedforward:20\n", "", 1)
+            .replacen(&config, "", 1)
+            .replacen("loadout 0.75,0.3\n", "", 1);
+        let (old, _) = Genome::from_file_str(&v1).unwrap();
+        assert_eq!(old.weights, genome.weights);
+        assert_eq!(old.loadout, Loadout::default());
+    }
+
+    #[test]
+    fn blends_run_between_their_endpoints() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let (a, b) = (Genome::random(&mut rng), Genome::random(&mut rng));
+        assert_eq!(Genome::interpolate(&a, &b, 0.0).weights, a.weights);
+        assert_eq!(Genome::interpolate(&a, &b, 1.0).weights, b.weights);
+
+        let mid = Genome::interpolate(&a, &b, 0.5);
+        let mean = Genome::average(&[a.clone(), b.clone()]).unwrap();
+        assert!(mid.distance(&mean) < 1e-4);
+        assert!((mid.distance(&a) - mid.distance(&b)).abs() < 1e-4);
+        assert!(Genome::average(&[]).is_none());
+    }
+
+    #[test]
+    fn recurrent_genomes_remember_the_previous_tick() {
+        let init = Initialization {
+            network: NetworkConfig::parse("recurrent:12,8").unwrap(),
+            ..Initialization::default()
+        };
+        let genome = Genome::random_with(&init, &mut StdRng::seed_from_u64(6));
+        assert!(genome.is_recurrent());
+        assert_eq!(
+            genome.weights.len(),
+            20 * 12 + 13 * 8 + 9 * 5 + 12 * 12 + 8 * 8
+        );
+        let (loaded, _) = Genome::from_file_str(&genome.to_file_string(1)).unwrap();
+        assert_eq!(loaded.weights, genome.weights);
+
+        let inputs = [0.5; SENSOR_COUNT];
+        let mut state = NetworkState::default();
+        let first = genome.evaluate(&inputs, &mut state);
+        let second = genome.evaluate(&inputs, &mut state);
+        assert_ne!(first, second);
+        state.reset();
+        // Tracing a tick shows every layer and leaves the state alone
+        let layers = genome.activations(&inputs, &state);
+        let widths: Vec<usize> = layers.iter().map(Vec::len).collect();
+        assert_eq!(widths, [INPUT_SIZE, 12, 8, OUTPUT_SIZE]);
+        assert_eq!(layers[3], first);
+        assert_eq!(genome.evaluate(&inputs, &mut state), first);
+    }
+
+    #[test]
+    fn custom_networks_evaluate_mutate_and_round_trip() {
+        let network = NetworkConfig::parse("feedforward+position+ammo:16,6").unwrap();
+        assert_ne!(network, NetworkConfig::default());
+        let init = Initialization {
+            network: network.clone(),
+            ..Initialization::default()
+        };
+        let mut rng = StdRng::seed_from_u64(8);
+        let mut genome = Genome::random_with(&init, &mut rng);
+        assert_eq!(genome.network, network);
+        assert_eq!(genome.weights.len(), network.genome_size());
+
+        let outputs = genome.evaluate(&[0.25; SENSOR_COUNT], &mut NetworkState::default());
+        assert!(outputs.iter().all(|o| o.is_finite()));
+
+        let parent = genome.weights.clone();
+        genome.mutate(&LayerMutation::default(), &mut rng);
+        assert_eq!(genome.weights.len(), network.genome_size());
+        assert_ne!(genome.weights, parent);
+
+        let (loaded, generation) = Genome::from_file_str(&genome.to_file_string(3)).unwrap();
+        assert_eq!(loaded.network, network);
+        assert_eq!(loaded.weights, genome.weights);
+        assert_eq!(generation, 3);
+    }
+
+    #[test]
+    fn signed_turn_networks_steer_with_one_output() {
+        let network = NetworkConfig::parse("feedforward+energy+steer:8").unwrap();
+        assert!(network.signed_turn && network.energy_inputs);
+        assert_eq!(network.spec(), "feedforward+energy+steer:8");
+        let init = Initialization {
+            network,
+            ..Initialization::default()
+        };
+        let mut rng = StdRng::seed_from_u64(9);
+        let genome = Genome::random_with(&init, &mut rng);
+        assert_eq!(genome.weights.len(), 22 * 8 + 9 * SIGNED_TURN_OUTPUTS);
+
+        let mut inputs = [0.0; SENSOR_COUNT];
+        let mut state = NetworkState::default();
+        let mut turned = [false; 2];
+        for _ in 0..50 {
+            inputs
+                .iter_mut()
+                .for_each(|x| *x = rng.gen_range(-1.0..1.0));
+            let action = genome.evaluate(&inputs, &mut state);
+            let raw = genome.activations(&inputs, &state);
+            let turn = raw[2][1];
+            // Never both directions at once, and right minus left is the turn itself
+            assert!(action[1] == 0.0 || action[2] == 0.0);
+            assert_eq!(action[2] - action[1], turn);
+            assert_eq!(
+                [action[0], action[3], action[4]],
+                [raw[2][0], raw[2][2], raw[2][3]]
+            );
+            turned[(turn > 0.0) as usize] = true;
+        }
+        assert_eq!(turned, [true; 2]);
+    }
+
+    #[test]
+    fn self_adaptation_evolves_each_genomes_mutation_scale() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let mut mutation = LayerMutation::uniform(1.0, 0.4);
+        let mut genome = Genome::random(&mut rng);
+        genome.mutate(&mutation, &mut rng);
+        assert_eq!(genome.mutation_scale, MutationScale::default());
+
+        mutation.set("self_adaptation", 0.5).unwrap();
+        assert!(mutation.set("self_adaptation", 2.0).is_err());
+        genome.mutate(&mutation, &mut rng);
+        assert_ne!(genome.mutation_scale, MutationScale::default());
+        assert!(genome.mutation_scale.validate().is_ok());
+
+        // A genome that has scaled its rate down keeps most weights as they are
+        mutation.self_adaptation = 0.0;
+        let mut cautious = genome.clone();
+        cautious.mutation_scale.rate = 0.05;
+        cautious.mutate(&mutation, &mut rng);
+        let changed = cautious
+            .weights
+            .iter()
+            .zip(&genome.weights)
+            .filter(|(a, b)| a != b)
+            .count();
+        assert!(changed < genome.weights.len() / 5);
+
+        // Offspring inherit the geometric mean of their parents' scales
+        let mut a = Genome::random(&mut rng);
+        let mut b = a.clone();
+        a.mutation_scale.rate = 0.5;
+        b.mutation_scale.rate = 2.0;
+        let child = Genome::crossover(&a, &b, &mut rng);
+        assert!((child.mutation_scale.rate - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ray_networks_see_through_the_arena_edge() {
+        let network = NetworkConfig::parse("feedforward+rays4+ammo:8").unwrap();
+        assert_eq!(network.spec(), "feedforward+rays4+ammo:8");
+        // Four rays of three channels replace the relative sensors
+        let first_ray = SENSOR_COUNT - RAY_INPUTS;
+        let sensors: Vec<usize> = network.sensors().collect();
+        assert_eq!(sensors.len(), 4 * RAY_CHANNELS + AMMO_INPUTS);
+        assert_eq!(
+            sensors[..4],
+            [first_ray, first_ray + 1, first_ray + 2, first_ray + 12]
+        );
+        assert!(NetworkConfig::parse("feedforward+rays5:8").is_err());
+        assert!(NetworkConfig::parse("feedforward+raysmany:8").is_err());
+
+        // The opponent sits dead ahead across the right edge, 200 away
+        let mut state = GameState::new();
+        state.ships[0] = Ship::new(ARENA_WIDTH - 100.0, 400.0, 0.0);
+        state.ships[1] = Ship::new(100.0, 400.0, 0.0);
+        let range = state.arena.engagement_range();
+        let inputs = Genome::get_inputs(&state, 0);
+        let ahead = &inputs[first_ray..first_ray + RAY_CHANNELS];
+        assert!((ahead[0] - (200.0 - SHIP_RADIUS) / range).abs() < 1e-4);
+        assert_eq!(ahead[1..], [1.0, 1.0]);
+        // Nothing behind, and nothing at all once the opponent cloaks
+        let behind = first_ray + MAX_RAYS / 2 * RAY_CHANNELS;
+        assert_eq!(inputs[behind], 1.0);
+        // Only ray networks pay for casting them
+        let mut rng = StdRng::seed_from_u64(2);
+        let init = Initialization {
+            network,
+            ..Initialization::default()
+        };
+        let with_rays = Genome::random_with(&init, &mut rng);
+        assert_eq!(with_rays.sensors(&state, 0), inputs);
+        let plain = Genome::random(&mut rng).sensors(&state, 0);
+        assert_eq!(plain[..first_ray], inputs[..first_ray]);
+        assert!(plain[first_ray..].iter().all(|&ray| ray == 0.0));
+        state.ships[1].cloaked = true;
+        assert_eq!(Genome::get_inputs(&state, 0)[first_ray], 1.0);
+    }
+
+    #[test]
+    fn networks_read_only_their_optional_sensors() {
+        let init = Initialization {
+            network: NetworkConfig::parse("feedforward+position:8").unwrap(),
+            ..Initialization::default()
+        };
+        let genome = Genome::random_with(&init, &mut StdRng::seed_from_u64(8));
+        assert_eq!(genome.weights.len(), 22 * 8 + 9 * 5);
+        assert_eq!(genome.network.spec(), "feedforward+position:8");
+        let (loaded, _) = Genome::from_file_str(&genome.to_file_string(1)).unwrap();
+        assert_eq!(loaded.network, genome.network);
+
+        let inputs = [0.5; SENSOR_COUNT];
+        let mut moved = inputs;
+        moved[INPUT_SIZE] = 0.9;
+        let mut state = NetworkState::default();
+        assert_ne!(
+            genome.evaluate(&inputs, &mut state),
+            genome.evaluate(&moved, &mut state)
+        );
+        let plain = Genome::random(&mut StdRng::seed_from_u64(8));
+        assert_eq!(
+            plain.evaluate(&inputs, &mut state),
+            plain.evaluate(&moved, &mut state)
+        );
+
+        // Defense sensors follow the position sensors, whether or not those are read
+        let init = Initialization {
+            network: NetworkConfig::parse("recurrent+defense:8").unwrap(),
+            ..Initialization::default()
+        };
+        let defense = Genome::random_with(&init, &mut StdRng::seed_from_u64(8));
+        assert_eq!(defense.network.inputs(), INPUT_SIZE + DEFENSE_INPUTS);
+        assert_eq!(defense.network.spec(), "recurrent+defense:8");
+        let mut damaged = inputs;
+        damaged[INPUT_SIZE + POSITION_INPUTS + DEFENSE_INPUTS - 1] = 0.1;
+        assert_eq!(
+            defense.evaluate(&inputs, &mut NetworkState::default()),
+            defense.evaluate(&moved, &mut NetworkState::default())
+        );
+        assert_ne!(
+            defense.evaluate(&inputs, &mut NetworkState::default()),
+            defense.evaluate(&damaged, &mut NetworkState::default())
+        );
+        assert_eq!(
+            genome.evaluate(&inputs, &mut state),
+            genome.evaluate(&damaged, &mut state)
+        );
+        assert_eq!(
+            NetworkConfig::parse("feedforward+position+defense+ammo+energy:8")
+                .unwrap()
+                .inputs(),
+            SENSOR_COUNT - RAY_INPUTS
+        );
+    }
+
+    #[test]
+    fn removing_an_input_matches_zeroing_it() {
+        let genome = Genome::random(&mut StdRng::seed_from_u64(7));
+        let inputs = [0.7; SENSOR_COUNT];
+        let mut zeroed = inputs;
+        zeroed[5] = 0.0;
+        let mut state = NetworkState::default();
+        assert_eq!(
+            genome.without_input(5).evaluate(&inputs, &mut state),
+            genome.evaluate(&zeroed, &mut state)
+        );
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        let genome = Genome::random(&mut StdRng::seed_from_u64(4));
+        let text = genome.to_file_string(1);
+
+        let newer = text.replacen("genome 4", "genome 5", 1);
+        assert!(matches!(
+            Genome::from_file_str(&newer),
+            Err(DuelError::UnsupportedVersion { found: 5, .. })
+        ));
+
+        let config = format!("config {:016x}", config_hash());
+        let foreign = text.replacen(&config, "config 0123456789abcdef", 1);
+        assert!(matches!(
+            Genome::from_file_str(&foreign),
+            Err(DuelError::ConfigMismatch { .. })
+        ));
+        assert!(Genome::from_file_str_with(&foreign, ConfigCheck::Ignore).is_ok());
+
+        let truncated: String = text.lines().take(100).map(|l| format!("{}\n", l)).collect();
+        assert!(matches!(
+            Genome::from_file_str(&truncated),
+            Err(DuelError::WrongSize { .. })
+        ));
+
+        let with_last_weight = |weight: &str| {
+            let (kept, _) = text.trim_end().rsplit_once('\n').unwrap();
+            format!("{}\n{}\n", kept, weight)
+        };
+        assert!(Genome::from_file_str(&with_last_weight("0.5")).is_ok());
+        assert!(matches!(
+            Genome::from_file_str(&with_last_weight("99")),
+            Err(DuelError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            Genome::from_file_str(&with_last_weight("NaN")),
+            Err(DuelError::NonFinite { .. })
+        ));
+        assert!(matches!(
+            Genome::from_file_str(""),
+            Err(DuelError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn foreign_weights_are_rejected_with_the_reason() {
+        let weights = Genome::random(&mut StdRng::seed_from_u64(9)).weights;
+        assert!(matches!(
+            Genome::from_weights(weights[1..].to_vec()),
+            Err(DuelError::WrongSize {
+                expected: GENOME_SIZE,
+                found,
+                ..
+            }) if found == GENOME_SIZE - 1
+        ));
+        let with = |i: usize, w: f32| {
+            let mut weights = weights.clone();
+            weights[i] = w;
+            Genome::from_weights(weights)
+        };
+        assert!(matches!(
+            with(3, f32::NAN),
+            Err(DuelError::NonFinite { what }) if what == "weight 3"
+        ));
+        assert!(matches!(
+            with(0, f32::NEG_INFINITY),
+            Err(DuelError::NonFinite { .. })
+        ));
+        assert!(matches!(
+            with(GENOME_SIZE - 1, 99.0),
+            Err(DuelError::OutOfRange { value, .. }) if value == 99.0
+        ));
+        assert!(Genome::from_weights(weights).is_ok());
     }
 }
@@ -0,0 +1,481 @@
+use std::sync::OnceLock;
+
+use spaceship_duel::achievements::Badge;
+
+/// Language of HUD and menu text
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    /// Parse a code such as "en", "de" or a locale like "de_DE.UTF-8"
+    pub fn parse(code: &str) -> Option<Self> {
+        let lang = code.split(['_', '-', '.']).next()?.to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            _ => None,
+        }
+    }
+
+    /// Language from the LANG environment variable, falling back to English
+    pub fn from_env() -> Self {
+        std::env::var("LANG")
+            .ok()
+            .and_then(|lang| Language::parse(&lang))
+            .unwrap_or_default()
+    }
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// Choose the UI language; only the first call has any effect
+pub fn set_language(language: Language) {
+    let _ = LANGUAGE.set(language);
+}
+
+fn language() -> Language {
+    LANGUAGE.get().copied().unwrap_or_default()
+}
+
+/// Every translatable UI string. Templates name their arguments in braces,
+/// filled in with `tr_fill`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Text {
+    MenuTitle,
+    MenuHint,
+    PresetClassic,
+    PresetClassicDescription,
+    PresetWindy,
+    PresetWindyDescription,
+    PresetAsteroidField,
+    PresetAsteroidFieldDescription,
+    MenuContinue,
+    MenuContinueDescription,
+    MenuWatchReplay,
+    MenuWatchReplayDescription,
+    MenuChallenge,
+    MenuChallengeDescription,
+    /// {gen} {best} {percent}
+    HudGeneration,
+    /// {time} {duration}
+    HudTime,
+    HudChampionElo,
+    /// {badges}
+    HudChampionBadges,
+    BadgeWrapKill,
+    BadgeBlitz,
+    BadgeMarksman,
+    /// {shots} {hits}
+    HudGreenShots,
+    /// {shots} {hits}
+    HudBlueShots,
+    GreenWins,
+    BlueWins,
+    Draw,
+    PlayerWins,
+    ChampionWins,
+    /// {length} {match}
+    SeriesTitle,
+    SeriesGreenWins,
+    SeriesBlueWins,
+    SeriesDrawn,
+    PlayBanner,
+    /// {difficulty} {wins} {losses} {draws}
+    LadderStatus,
+    DifficultyEasy,
+    DifficultyMedium,
+    DifficultyHard,
+    /// {stalled}
+    EngagementSummary,
+    EvolutionPaused,
+    EvolutionPausing,
+    /// {matches} {hours} {speedup} {rate} {memory}
+    SessionSummary,
+    /// {best} {mean}
+    FitnessGraphLegend,
+    /// {gen} {genome} {kind} {worker}
+    SpectateBanner,
+    MatchCurriculum,
+    MatchPool,
+    MatchArchive,
+    ClassStandard,
+    ClassInterceptor,
+    ClassGunship,
+    ClassSniper,
+    /// {speed}
+    ShowcaseSpeed,
+    ShowcasePaused,
+    /// {time} {duration}
+    ReplayStatus,
+    ReplayPaused,
+    ReplayNotesHelp,
+    ReplayNotePrompt,
+    ReplayBranchHelp,
+    /// {count} {round} {time}
+    MeleeStatus,
+    /// {rank} {points} {wins}
+    MeleeStanding,
+    /// {rank}
+    MeleeWinner,
+    /// {ship} {from} {time} {path}
+    ReplayBranchStatus,
+    /// {gen} {percent} {best}
+    ProgressTitle,
+    /// {gen} {best} {summary}
+    GenerationLog,
+    NewChampion,
+    /// {badge} {gen}
+    FirstBadgeLog,
+    /// {a} {b}
+    VotedShowcaseLog,
+    /// {count}
+    RollbackLog,
+    /// {window}
+    RestartLog,
+    /// {accuracy} {factor}
+    SurrogateLog,
+    /// {count}, {largest}, {culled}
+    SpeciesLog,
+    /// {scores}
+    BotBenchmarkLog,
+    ShipGreen,
+    ShipBlue,
+    /// {gen}
+    CommentaryMatchStart,
+    /// {shooter} {target} {time} {shots}
+    CommentaryDestroyed,
+    /// {ship} {time}
+    CommentaryCrashed,
+    /// {time}
+    CommentaryDraw,
+    /// {time} {green} {blue} {distance}
+    CommentaryStatus,
+    /// Ticker lines list alternative phrasings separated by '|'.
+    /// {shooter} {target} {distance}
+    TickerNearMiss,
+    /// {ship} {count}
+    TickerMissStreak,
+    /// {ship} {opponent}
+    TickerDaringWrap,
+    /// {shooter} {target} {count}
+    TickerComeback,
+    FitnessWin,
+    FitnessDeath,
+    FitnessHits,
+    FitnessAccuracy,
+    FitnessShots,
+    FitnessAmmo,
+    FitnessProximity,
+    FitnessSurvival,
+    FitnessEnergy,
+    FitnessTotal,
+}
+
+/// The current language's text for `key`
+pub fn tr(key: Text) -> &'static str {
+    match language() {
+        Language::English => english(key),
+        Language::German => german(key),
+    }
+}
+
+/// `tr(key)` with each `{name}` replaced by its value
+pub fn tr_fill(key: Text, args: &[(&str, String)]) -> String {
+    fill(tr(key), args)
+}
+
+/// `template` with each `{name}` replaced by its value
+pub fn fill(template: &str, args: &[(&str, String)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+/// Display name of a champion badge
+pub fn badge_label(badge: Badge) -> &'static str {
+    tr(match badge {
+        Badge::WrapKill => Text::BadgeWrapKill,
+        Badge::Blitz => Text::BadgeBlitz,
+        Badge::Marksman => Text::BadgeMarksman,
+    })
+}
+
+/// Label of a `FitnessBreakdown::components` entry
+pub fn fitness_label(component: &str) -> &'static str {
+    tr(match component {
+        "win" => Text::FitnessWin,
+        "death" => Text::FitnessDeath,
+        "hits" => Text::FitnessHits,
+        "accuracy" => Text::FitnessAccuracy,
+        "shots" => Text::FitnessShots,
+        "ammo" => Text::FitnessAmmo,
+        "proximity" => Text::FitnessProximity,
+        "survival" => Text::FitnessSurvival,
+        "energy" => Text::FitnessEnergy,
+        _ => Text::FitnessTotal,
+    })
+}
+
+fn english(key: Text) -> &'static str {
+    match key {
+        Text::MenuTitle => "EVOLVED SPACESHIP DUEL",
+        Text::MenuHint => "Up/Down + Enter or a number key to choose",
+        Text::PresetClassic => "Classic duel",
+        Text::PresetClassicDescription => "Open arena, no hazards",
+        Text::PresetWindy => "Shifting currents",
+        Text::PresetWindyDescription => "A new random flow field every match",
+        Text::PresetAsteroidField => "Asteroid field",
+        Text::PresetAsteroidFieldDescription => "Six destructible asteroids",
+        Text::MenuContinue => "Continue last run",
+        Text::MenuContinueDescription => "Pick up training where it stopped",
+        Text::MenuWatchReplay => "Watch saved replay",
+        Text::MenuWatchReplayDescription => "The showcase match last saved with R",
+        Text::MenuChallenge => "Challenge the champions",
+        Text::MenuChallengeDescription => "Fly against the last run's pilots yourself",
+        Text::HudGeneration => "Gen: {gen}  Best: {best}  Next gen: {percent}%",
+        Text::HudTime => "Time: {time}s / {duration}s",
+        Text::HudChampionElo => "Champion Elo: {elo}",
+        Text::HudChampionBadges => "Badges: {badges}",
+        Text::BadgeWrapKill => "Wrap-around kill",
+        Text::BadgeBlitz => "Blitz",
+        Text::BadgeMarksman => "Marksman",
+        Text::HudGreenShots => "Green - Shots: {shots} Hits: {hits}",
+        Text::HudBlueShots => "Blue  - Shots: {shots} Hits: {hits}",
+        Text::GreenWins => "GREEN WINS!",
+        Text::BlueWins => "BLUE WINS!",
+        Text::Draw => "DRAW!",
+        Text::PlayerWins => "YOU WIN!",
+        Text::ChampionWins => "THE CHAMPION WINS!",
+        Text::SeriesTitle => "Best of {length} - match {match}",
+        Text::SeriesGreenWins => "GREEN TAKES THE SERIES",
+        Text::SeriesBlueWins => "BLUE TAKES THE SERIES",
+        Text::SeriesDrawn => "THE SERIES ENDS LEVEL",
+        Text::PlayBanner => {
+            "YOU ARE GREEN: arrows steer, Space fires, Down cloaks (H hands back to the AI)"
+        }
+        Text::LadderStatus => {
+            "Opponent: {difficulty} (1 easy, 2 medium, 3 hard) | your record {wins}-{losses}-{draws}"
+        }
+        Text::DifficultyEasy => "early champion",
+        Text::DifficultyMedium => "mid-ranked champion",
+        Text::DifficultyHard => "current champion",
+        Text::EngagementSummary => "Engagement per second: {stalled}% stalled",
+        Text::EvolutionPaused => "EVOLUTION PAUSED (P to resume)",
+        Text::EvolutionPausing => "EVOLUTION PAUSING AFTER THIS GENERATION",
+        Text::SessionSummary => {
+            "{matches} matches | {hours}h simulated | {speedup}x realtime | {rate} gen/h | {memory} MB held"
+        }
+        Text::FitnessGraphLegend => "best {best}  mean {mean}",
+        Text::SpectateBanner => {
+            "LIVE: generation {gen}, genome #{genome} {kind} on worker {worker} (L for the showcase)"
+        }
+        Text::MatchCurriculum => "vs an early champion",
+        Text::MatchPool => "vs the population",
+        Text::MatchArchive => "vs the hall of fame",
+        Text::ClassStandard => "standard",
+        Text::ClassInterceptor => "interceptor",
+        Text::ClassGunship => "gunship",
+        Text::ClassSniper => "sniper",
+        Text::ShowcaseSpeed => "Speed {speed}x (+/- to change)",
+        Text::ShowcasePaused => "MATCH PAUSED (Space resumes, F steps)",
+        Text::ReplayStatus => {
+            "REPLAY {time}s / {duration}s (Space pauses, . steps, Left/Right seek, Esc quits)"
+        }
+        Text::ReplayPaused => "PAUSED",
+        Text::ReplayNotesHelp => "B bookmarks, N adds a note, [ / ] jump between bookmarks",
+        Text::ReplayNotePrompt => "Note: {text}_ (Enter saves, Esc cancels)",
+        Text::ReplayBranchHelp => "1 / 2 take over Green / Blue from here",
+        Text::MeleeStatus => "MELEE of the top {count} genomes, match {round} at {time}s (Esc quits)",
+        Text::MeleeStanding => "#{rank}: {points} points, {wins} wins",
+        Text::MeleeWinner => "#{rank} IS THE LAST SHIP STANDING!",
+        Text::ReplayBranchStatus => {
+            "BRANCH: {ship} taken over at {from}s, now {time}s (S saves to {path}, Esc returns to the replay)"
+        }
+        Text::ProgressTitle => {
+            "Evolved Spaceship Duel - Gen {gen} ({percent}% evaluated) - Best {best}"
+        }
+        Text::GenerationLog => "Generation {gen} | Best fitness: {best} | {summary}",
+        Text::NewChampion => "  New champion crowned after a head-to-head series",
+        Text::FirstBadgeLog => "  First badge of the run: {badge} (generation {gen})",
+        Text::VotedShowcaseLog => "Viewers voted for the next showcase: generation {a} vs generation {b}",
+        Text::RollbackLog => "  Fitness cliff: generation rolled back and bred again {count}x",
+        Text::RestartLog => {
+            "  Stagnation: no new best in {window} generations, half the population reseeded"
+        }
+        Text::SurrogateLog => {
+            "  Surrogate rank accuracy: {accuracy} | screening {factor}x offspring"
+        }
+        Text::SpeciesLog => "  Species: {count} | largest {largest} | culled {culled}",
+        Text::BotBenchmarkLog => "  Champion vs bots: {scores}",
+        Text::ShipGreen => "Green",
+        Text::ShipBlue => "Blue",
+        Text::CommentaryMatchStart => "Generation {gen}: new showcase match",
+        Text::CommentaryDestroyed => "{shooter} destroyed {target} at {time}s after {shots} shots",
+        Text::CommentaryCrashed => "{ship} crashed into an asteroid at {time}s",
+        Text::CommentaryDraw => "Draw: time ran out at {time}s",
+        Text::CommentaryStatus => {
+            "{time}s: Green {green} shots, Blue {blue} shots, {distance} px apart"
+        }
+        Text::TickerNearMiss => {
+            "{shooter}'s shot grazes {target} by {distance} px!|So close! {target} slips past {shooter}'s fire|{target} feels the heat of that one"
+        }
+        Text::TickerMissStreak => {
+            "{ship} has missed {count} in a row|{ship} can't buy a hit: {count} straight misses|{count} shots and nothing for {ship}"
+        }
+        Text::TickerDaringWrap => {
+            "{ship} dives through the edge of space with {opponent} right behind!|Daring! {ship} wraps around under {opponent}'s nose"
+        }
+        Text::TickerComeback => {
+            "{shooter} shakes off {count} misses and takes down {target}!|Persistence pays: {shooter} finally finds {target}"
+        }
+        Text::FitnessWin => "win",
+        Text::FitnessDeath => "death",
+        Text::FitnessHits => "hits",
+        Text::FitnessAccuracy => "accuracy",
+        Text::FitnessShots => "shots",
+        Text::FitnessAmmo => "ammo",
+        Text::FitnessProximity => "proximity",
+        Text::FitnessSurvival => "survival",
+        Text::FitnessEnergy => "energy",
+        Text::FitnessTotal => "total",
+    }
+}
+
+fn german(key: Text) -> &'static str {
+    match key {
+        Text::MenuTitle => "EVOLVIERTES RAUMSCHIFF-DUELL",
+        Text::MenuHint => "Hoch/Runter + Enter oder eine Zifferntaste wählt",
+        Text::PresetClassic => "Klassisches Duell",
+        Text::PresetClassicDescription => "Offene Arena, keine Hindernisse",
+        Text::PresetWindy => "Wechselnde Strömungen",
+        Text::PresetWindyDescription => "Jedes Match ein neues zufälliges Strömungsfeld",
+        Text::PresetAsteroidField => "Asteroidenfeld",
+        Text::PresetAsteroidFieldDescription => "Sechs zerstörbare Asteroiden",
+        Text::MenuContinue => "Letzten Lauf fortsetzen",
+        Text::MenuContinueDescription => "Das Training dort aufnehmen, wo es aufgehört hat",
+        Text::MenuWatchReplay => "Gespeicherte Wiederholung ansehen",
+        Text::MenuWatchReplayDescription => "Das zuletzt mit R gespeicherte Showcase-Match",
+        Text::MenuChallenge => "Die Champions herausfordern",
+        Text::MenuChallengeDescription => "Selbst gegen die Piloten des letzten Laufs fliegen",
+        Text::HudGeneration => "Gen: {gen}  Beste: {best}  Nächste Gen: {percent}%",
+        Text::HudTime => "Zeit: {time}s / {duration}s",
+        Text::HudChampionElo => "Champion-Elo: {elo}",
+        Text::HudChampionBadges => "Abzeichen: {badges}",
+        Text::BadgeWrapKill => "Abschuss über den Rand",
+        Text::BadgeBlitz => "Blitzsieg",
+        Text::BadgeMarksman => "Scharfschütze",
+        Text::HudGreenShots => "Grün - Schüsse: {shots} Treffer: {hits}",
+        Text::HudBlueShots => "Blau - Schüsse: {shots} Treffer: {hits}",
+        Text::GreenWins => "GRÜN GEWINNT!",
+        Text::BlueWins => "BLAU GEWINNT!",
+        Text::Draw => "UNENTSCHIEDEN!",
+        Text::PlayerWins => "DU GEWINNST!",
+        Text::ChampionWins => "DER CHAMPION GEWINNT!",
+        Text::SeriesTitle => "Best of {length} - Match {match}",
+        Text::SeriesGreenWins => "GRÜN GEWINNT DIE SERIE",
+        Text::SeriesBlueWins => "BLAU GEWINNT DIE SERIE",
+        Text::SeriesDrawn => "DIE SERIE ENDET UNENTSCHIEDEN",
+        Text::PlayBanner => {
+            "DU BIST GRÜN: Pfeiltasten lenken, Leertaste feuert, Runter tarnt (H übergibt an die KI)"
+        }
+        Text::LadderStatus => {
+            "Gegner: {difficulty} (1 leicht, 2 mittel, 3 schwer) | deine Bilanz {wins}-{losses}-{draws}"
+        }
+        Text::DifficultyEasy => "früher Champion",
+        Text::DifficultyMedium => "Champion aus dem Mittelfeld",
+        Text::DifficultyHard => "aktueller Champion",
+        Text::EngagementSummary => "Gefechtsintensität pro Sekunde: {stalled}% Leerlauf",
+        Text::EvolutionPaused => "EVOLUTION PAUSIERT (P zum Fortsetzen)",
+        Text::EvolutionPausing => "EVOLUTION PAUSIERT NACH DIESER GENERATION",
+        Text::SessionSummary => {
+            "{matches} Matches | {hours}h simuliert | {speedup}x Echtzeit | {rate} Gen/h | {memory} MB belegt"
+        }
+        Text::FitnessGraphLegend => "beste {best}  Mittel {mean}",
+        Text::SpectateBanner => {
+            "LIVE: Generation {gen}, Genom #{genome} {kind} auf Worker {worker} (L für die Vorführung)"
+        }
+        Text::MatchCurriculum => "gegen einen frühen Champion",
+        Text::MatchPool => "gegen die Population",
+        Text::MatchArchive => "gegen die Ruhmeshalle",
+        Text::ClassStandard => "Standard",
+        Text::ClassInterceptor => "Abfangjäger",
+        Text::ClassGunship => "Kanonenboot",
+        Text::ClassSniper => "Scharfschütze",
+        Text::ShowcaseSpeed => "Tempo {speed}x (+/- zum Ändern)",
+        Text::ShowcasePaused => "MATCH PAUSIERT (Leertaste setzt fort, F Einzelschritt)",
+        Text::ReplayStatus => {
+            "WIEDERHOLUNG {time}s / {duration}s (Leertaste pausiert, . Einzelschritt, Links/Rechts spulen, Esc beendet)"
+        }
+        Text::ReplayPaused => "PAUSIERT",
+        Text::ReplayNotesHelp => {
+            "B setzt Lesezeichen, N fügt Notiz hinzu, [ / ] springen zwischen Lesezeichen"
+        }
+        Text::ReplayNotePrompt => "Notiz: {text}_ (Enter speichert, Esc bricht ab)",
+        Text::ReplayBranchHelp => "1 / 2 übernehmen Grün / Blau ab hier",
+        Text::MeleeStatus => {
+            "GETÜMMEL der besten {count} Genome, Match {round} bei {time}s (Esc beendet)"
+        }
+        Text::MeleeStanding => "#{rank}: {points} Punkte, {wins} Siege",
+        Text::MeleeWinner => "#{rank} BLEIBT ALS LETZTES SCHIFF ÜBRIG!",
+        Text::ReplayBranchStatus => {
+            "ABZWEIGUNG: {ship} übernommen bei {from}s, jetzt {time}s (S speichert nach {path}, Esc zurück zur Wiederholung)"
+        }
+        Text::ProgressTitle => {
+            "Evolviertes Raumschiff-Duell - Gen {gen} ({percent}% bewertet) - Beste {best}"
+        }
+        Text::GenerationLog => "Generation {gen} | Beste Fitness: {best} | {summary}",
+        Text::NewChampion => "  Neuer Champion nach gewonnener Duellserie",
+        Text::FirstBadgeLog => "  Erstes Abzeichen des Laufs: {badge} (Generation {gen})",
+        Text::VotedShowcaseLog => "Zuschauerwahl für die nächste Vorführung: Generation {a} gegen Generation {b}",
+        Text::RollbackLog => {
+            "  Fitness-Klippe: Generation {count}x zurückgesetzt und neu gezüchtet"
+        }
+        Text::RestartLog => {
+            "  Stillstand: seit {window} Generationen keine neue Bestleistung, halbe Population neu gesät"
+        }
+        Text::SurrogateLog => {
+            "  Surrogat-Rangkorrelation: {accuracy} | {factor}x Nachkommen vorsortiert"
+        }
+        Text::SpeciesLog => "  Arten: {count} | größte {largest} | ausgesondert {culled}",
+        Text::BotBenchmarkLog => "  Champion gegen Bots: {scores}",
+        Text::ShipGreen => "Grün",
+        Text::ShipBlue => "Blau",
+        Text::CommentaryMatchStart => "Generation {gen}: neues Showcase-Match",
+        Text::CommentaryDestroyed => {
+            "{shooter} zerstört {target} bei {time}s nach {shots} Schüssen"
+        }
+        Text::CommentaryCrashed => "{ship} kollidiert bei {time}s mit einem Asteroiden",
+        Text::CommentaryDraw => "Unentschieden: Zeit abgelaufen bei {time}s",
+        Text::CommentaryStatus => {
+            "{time}s: Grün {green} Schüsse, Blau {blue} Schüsse, {distance} px Abstand"
+        }
+        Text::TickerNearMiss => {
+            "{shooter} verfehlt {target} um {distance} px!|Knapp! {target} entkommt dem Feuer von {shooter}|Das war heiß für {target}"
+        }
+        Text::TickerMissStreak => {
+            "{ship} hat {count}-mal in Folge verfehlt|{ship} trifft einfach nicht: {count} Fehlschüsse|{count} Schüsse und nichts für {ship}"
+        }
+        Text::TickerDaringWrap => {
+            "{ship} taucht durch den Rand des Alls, {opponent} dicht dahinter!|Gewagt! {ship} wechselt direkt vor {opponent} die Seite"
+        }
+        Text::TickerComeback => {
+            "{shooter} schüttelt {count} Fehlschüsse ab und erwischt {target}!|Beharrlichkeit zahlt sich aus: {shooter} trifft {target}"
+        }
+        Text::FitnessWin => "Sieg",
+        Text::FitnessDeath => "Tod",
+        Text::FitnessHits => "Treffer",
+        Text::FitnessAccuracy => "Präzision",
+        Text::FitnessShots => "Schüsse",
+        Text::FitnessAmmo => "Munition",
+        Text::FitnessProximity => "Nähe",
+        Text::FitnessSurvival => "Überleben",
+        Text::FitnessEnergy => "Energie",
+        Text::FitnessTotal => "gesamt",
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::archive::HallOfFame;
+use crate::error::DuelError;
+use crate::genome::Genome;
+
+/// Version written in the header of player record files
+pub const RECORD_FILE_VERSION: u32 = 1;
+const RECORD_FILE_MAGIC: &str = "spaceship-duel player record";
+
+/// Rung of the human challenge ladder. Opponents come from the hall of
+/// fame by archived Elo rating, which tracks early generations at the
+/// bottom and the current champion at the top.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Difficulty {
+    /// The lowest-rated archived champion
+    #[default]
+    Easy,
+    /// The median-rated archived champion
+    Medium,
+    /// The current champion
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, DuelError> {
+        Self::ALL
+            .into_iter()
+            .find(|d| d.name() == name)
+            .ok_or_else(|| DuelError::UnknownName {
+                kind: "difficulty",
+                name: name.to_string(),
+            })
+    }
+
+    /// Opponent at this rung: an archived champion picked by rating, or
+    /// `champion` at the top and whenever the archive is still empty
+    pub fn opponent(self, hall: &HallOfFame, champion: &Genome) -> Genome {
+        let mut ranked: Vec<_> = hall.entries.iter().collect();
+        // Earlier generations go first among equal ratings
+        ranked.sort_by(|a, b| {
+            a.rating
+                .total_cmp(&b.rating)
+                .then(a.generation.cmp(&b.generation))
+        });
+        let entry = match self {
+            Difficulty::Easy => ranked.first(),
+            Difficulty::Medium => ranked.get(ranked.len() / 2),
+            Difficulty::Hard => None,
+        };
+        entry.map_or_else(|| champion.clone(), |e| e.genome.clone())
+    }
+}
+
+/// Wins, losses and draws of one rung
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tally {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+}
+
+/// The player's personal results against the ladder, kept across sessions
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerRecord {
+    /// One tally per rung, in `Difficulty::ALL` order
+    pub tallies: [Tally; 3],
+}
+
+impl PlayerRecord {
+    pub fn tally(&self, difficulty: Difficulty) -> Tally {
+        self.tallies[difficulty as usize]
+    }
+
+    /// Count a finished match; `winner` is the ship index, the player flying ship 0
+    pub fn record(&mut self, difficulty: Difficulty, winner: Option<usize>) {
+        let tally = &mut self.tallies[difficulty as usize];
+        // Counts read back from an edited file may already be at the limit
+        let count = match winner {
+            Some(0) => &mut tally.wins,
+            Some(_) => &mut tally.losses,
+            None => &mut tally.draws,
+        };
+        *count = count.saturating_add(1);
+    }
+
+    /// Text form: header, then one "RUNG WINS LOSSES DRAWS" line per rung
+    pub fn to_file_string(&self) -> String {
+        let mut out = format!("{} {}\n", RECORD_FILE_MAGIC, RECORD_FILE_VERSION);
+        for (difficulty, t) in Difficulty::ALL.iter().zip(&self.tallies) {
+            let _ = writeln!(
+                out,
+                "{} {} {} {}",
+                difficulty.name(),
+                t.wins,
+                t.losses,
+                t.draws
+            );
+        }
+        out
+    }
+
+    /// Parse the output of `to_file_string`
+    pub fn from_file_str(text: &str) -> Result<PlayerRecord, DuelError> {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default();
+        let version: u32 = header
+            .strip_prefix(RECORD_FILE_MAGIC)
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| DuelError::InvalidValue {
+                what: "player record header",
+                value: excerpt(header),
+            })?;
+        if version == 0 || version > RECORD_FILE_VERSION {
+            return Err(DuelError::UnsupportedVersion {
+                found: version,
+                supported: RECORD_FILE_VERSION,
+            });
+        }
+        let mut record = PlayerRecord::default();
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            let mut parts = line.split_whitespace();
+            let difficulty = Difficulty::parse(parts.next().unwrap_or_default())?;
+            let mut count = || {
+                parts
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| DuelError::InvalidValue {
+                        what: "player record line",
+                        value: excerpt(line),
+                    })
+            };
+            record.tallies[difficulty as usize] = Tally {
+                wins: count()?,
+                losses: count()?,
+                draws: count()?,
+            };
+        }
+        Ok(record)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DuelError> {
+        std::fs::write(path, self.to_file_string())?;
+        Ok(())
+    }
+
+    /// The record at `path`; an empty one if no match has been played yet
+    pub fn load(path: impl AsRef<Path>) -> Result<PlayerRecord, DuelError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_file_str(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(PlayerRecord::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Start of a rejected line, short enough to quote in an error message
+fn excerpt(line: &str) -> String {
+    line.chars().take(40).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn rungs_climb_from_the_weakest_archived_champion_to_the_current_one() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let champion = Genome::random(&mut rng);
+        let mut hall = HallOfFame::default();
+        let pick = |d: Difficulty, hall: &HallOfFame| d.opponent(hall, &champion).weights;
+        assert_eq!(pick(Difficulty::Easy, &hall), champion.weights);
+
+        let genomes: Vec<Genome> = (0..3).map(|_| Genome::random(&mut rng)).collect();
+        for (generation, (genome, rating)) in
+            genomes.iter().zip([1100.0, 900.0, 1000.0]).enumerate()
+        {
+            hall.add(genome, generation);
+            hall.entries[generation].rating = rating;
+        }
+        assert_eq!(pick(Difficulty::Easy, &hall), genomes[1].weights);
+        assert_eq!(pick(Difficulty::Medium, &hall), genomes[2].weights);
+        assert_eq!(pick(Difficulty::Hard, &hall), champion.weights);
+    }
+
+    #[test]
+    fn player_records_round_trip_and_reject_garbage() {
+        let mut record = PlayerRecord::default();
+        record.record(Difficulty::Easy, Some(0));
+        record.record(Difficulty::Easy, Some(0));
+        record.record(Difficulty::Hard, Some(1));
+        record.record(Difficulty::Medium, None);
+        assert_eq!(
+            record.tally(Difficulty::Easy),
+            Tally {
+                wins: 2,
+                losses: 0,
+                draws: 0
+            }
+        );
+        let text = record.to_file_string();
+        assert_eq!(PlayerRecord::from_file_str(&text).unwrap(), record);
+
+        assert!(PlayerRecord::from_file_str("spaceship-duel player record 9").is_err());
+        let garbled = text.replace("hard 0 1 0", "hard 0 x 0");
+        assert!(PlayerRecord::from_file_str(&garbled).is_err());
+        assert!(PlayerRecord::from_file_str(&text.replace("medium", "brutal")).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use rand::Rng;
+
+use crate::game::ArenaLayout;
+use crate::gauntlet::Gauntlet;
+use crate::genome::*;
+
+/// Gauntlet scores of genomes perturbed by one magnitude in random directions
+#[derive(Clone, Debug)]
+pub struct LandscapePoint {
+    /// Euclidean length of the perturbation
+    pub magnitude: f32,
+    /// Weights in the probed genome
+    pub dimensions: usize,
+    pub mean: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl LandscapePoint {
+    /// Per-weight RMS change, comparable to a mutation strength
+    pub fn per_weight(&self) -> f32 {
+        self.magnitude / (self.dimensions as f32).sqrt()
+    }
+}
+
+/// Probe the fitness landscape around `genome`: for every magnitude, score
+/// `directions` perturbations of that length against the gauntlet. A score
+/// that falls off at small magnitudes means a brittle, narrow optimum.
+pub fn probe(
+    genome: &Genome,
+    gauntlet: &Gauntlet,
+    layout: &ArenaLayout,
+    magnitudes: &[f32],
+    directions: usize,
+    matches_each: usize,
+    rng: &mut impl Rng,
+) -> Vec<LandscapePoint> {
+    let directions = directions.max(1);
+    magnitudes
+        .iter()
+        .map(|&magnitude| {
+            let scores: Vec<f32> = (0..directions)
+                .map(|_| {
+                    let probe = perturb(genome, magnitude, rng);
+                    gauntlet.score(&probe, layout, matches_each, rng)
+                })
+                .collect();
+            LandscapePoint {
+                magnitude,
+                dimensions: genome.weights.len(),
+                mean: scores.iter().sum::<f32>() / scores.len() as f32,
+                min: scores.iter().copied().fold(f32::INFINITY, f32::min),
+                max: scores.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            }
+        })
+        .collect()
+}
+
+/// `genome` moved `magnitude` along a uniformly random direction
+fn perturb(genome: &Genome, magnitude: f32, rng: &mut impl Rng) -> Genome {
+    // Normalized Gaussian samples are uniform on the sphere; Box-Muller keeps
+    // this free of an extra distributions crate
+    let direction: Vec<f32> = (0..genome.weights.len())
+        .map(|_| {
+            let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+            let u2: f32 = rng.gen();
+            (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos()
+        })
+        .collect();
+    let norm = direction
+        .iter()
+        .map(|d| d * d)
+        .sum::<f32>()
+        .sqrt()
+        .max(f32::EPSILON);
+    let weights = genome
+        .weights
+        .iter()
+        .zip(&direction)
+        .map(|(w, d)| (w + d / norm * magnitude).clamp(-WEIGHT_LIMIT, WEIGHT_LIMIT))
+        .collect();
+    Genome {
+        weights,
+        fitness: 0.0,
+        age: 0,
+        network: genome.network.clone(),
+        loadout: genome.loadout,
+        mutation_scale: genome.mutation_scale,
+    }
+}
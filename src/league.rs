@@ -0,0 +1,126 @@
+use rand::Rng;
+
+use crate::archive::OpponentSampling;
+use crate::error::DuelError;
+use crate::evolution::*;
+use crate::game::ArenaLayout;
+use crate::genome::Genome;
+
+/// Sizes and match mix for league training
+#[derive(Clone, Debug)]
+pub struct LeagueConfig {
+    pub exploiter_population: usize,
+    /// Matches per main genome against the exploiters' current champions
+    pub main_vs_exploiters: usize,
+    /// Matches per main-exploiter genome against the main champion
+    pub main_exploiter_matches: usize,
+    /// Matches per league-exploiter genome against hall-of-fame champions
+    pub league_exploiter_matches: usize,
+    /// Restart the main exploiters from scratch every N generations (0 = never)
+    pub exploiter_reset_every: usize,
+}
+
+impl Default for LeagueConfig {
+    fn default() -> Self {
+        LeagueConfig {
+            exploiter_population: 30,
+            main_vs_exploiters: 2,
+            main_exploiter_matches: 6,
+            league_exploiter_matches: 6,
+            exploiter_reset_every: 25,
+        }
+    }
+}
+
+/// AlphaStar-style league: a main population trained by self-play and
+/// against two exploiter populations, which in turn train only against the
+/// current main champion (main exploiters) or the whole archive (league
+/// exploiters) to expose weaknesses the main population must then cover.
+pub struct League {
+    pub config: LeagueConfig,
+    pub main: Population,
+    pub main_exploiters: Population,
+    pub league_exploiters: Population,
+}
+
+impl League {
+    pub fn new(
+        layout: &ArenaLayout,
+        config: LeagueConfig,
+        rng: &mut impl Rng,
+    ) -> Result<Self, DuelError> {
+        let mut league = League {
+            main: Population::new(layout.clone(), rng),
+            main_exploiters: Population::with_size(
+                layout.clone(),
+                config.exploiter_population,
+                rng,
+            ),
+            league_exploiters: Population::with_size(
+                layout.clone(),
+                config.exploiter_population,
+                rng,
+            ),
+            config,
+        };
+        league.evaluate(rng)?;
+        Ok(league)
+    }
+
+    /// Evolve every population by one generation and re-evaluate them
+    pub fn step(&mut self, rng: &mut impl Rng) -> Result<(), DuelError> {
+        self.main.evolve(rng);
+        let reset = self.config.exploiter_reset_every;
+        if reset > 0 && self.main.generation.is_multiple_of(reset) {
+            let layout = self.main_exploiters.layout.clone();
+            self.main_exploiters =
+                Population::with_size(layout, self.config.exploiter_population, rng);
+        } else {
+            self.main_exploiters.evolve(rng);
+        }
+        self.league_exploiters.evolve(rng);
+        self.evaluate(rng)
+    }
+
+    fn evaluate(&mut self, rng: &mut impl Rng) -> Result<(), DuelError> {
+        // Exploiters are scored against the main population as it currently stands
+        let (main_champion, _) = self.main.get_top_two();
+        self.main_exploiters.reset_fitness();
+        self.main_exploiters.evaluate_against(
+            std::slice::from_ref(&main_champion),
+            self.config.main_exploiter_matches,
+            rng,
+        );
+
+        let archive = self.archive_opponents(rng);
+        self.league_exploiters.reset_fitness();
+        self.league_exploiters.evaluate_against(
+            &archive,
+            self.config.league_exploiter_matches,
+            rng,
+        );
+
+        // Main agents play themselves and the archive, then the exploiters' best
+        self.main.evaluate(rng)?;
+        let exploiters = [
+            self.main_exploiters.get_top_two().0,
+            self.league_exploiters.get_top_two().0,
+        ];
+        self.main
+            .evaluate_against(&exploiters, self.config.main_vs_exploiters, rng);
+        Ok(())
+    }
+
+    /// Opponents for the league exploiters: the main population's hall of fame,
+    /// or its current champion before anything has been archived
+    fn archive_opponents(&self, rng: &mut impl Rng) -> Vec<Genome> {
+        let hof = &self.main.hall_of_fame;
+        if hof.is_empty() {
+            return vec![self.main.get_top_two().0];
+        }
+        (0..hof.len().min(16))
+            .filter_map(|_| hof.sample(&OpponentSampling::Uniform, rng))
+            .map(|entry| entry.genome.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,66 @@
+//! Evolved spaceship duel: the match engine, genome networks and evolution,
+//! shared by the visual front end and the optional C interface.
+
+pub mod ablation;
+pub mod abtest;
+pub mod achievements;
+pub mod archive;
+pub mod asymmetry;
+pub mod balance;
+pub mod bots;
+pub mod checkpoint;
+pub mod codegen;
+pub mod columns;
+pub mod config;
+pub mod error;
+pub mod events;
+pub mod evolution;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod game;
+pub mod gauntlet;
+pub mod genome;
+pub mod ladder;
+pub mod landscape;
+pub mod league;
+pub mod merge;
+pub mod novelty;
+pub mod pacing;
+pub mod pareto;
+pub mod pressure;
+pub mod profiling;
+pub mod rating;
+pub mod remote;
+pub mod replay;
+pub mod scaling;
+pub mod simulation;
+pub mod snapshot;
+pub mod speciation;
+pub mod spectate;
+pub mod stats;
+pub mod surrogate;
+pub mod sweep;
+pub mod watermark;
+
+// Browsers give rand no OS entropy, so unseeded runs draw theirs from the
+// clock. Nothing here needs to be unpredictable; --seed still repeats a run.
+#[cfg(target_arch = "wasm32")]
+getrandom::register_custom_getrandom!(clock_entropy);
+
+#[cfg(target_arch = "wasm32")]
+fn clock_entropy(buf: &mut [u8]) -> Result<(), getrandom::Error> {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static CALLS: AtomicU64 = AtomicU64::new(0);
+    let mut state = macroquad::miniquad::date::now().to_bits()
+        ^ CALLS.fetch_add(1, Ordering::Relaxed).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    for chunk in buf.chunks_mut(8) {
+        // splitmix64
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        chunk.copy_from_slice(&z.to_le_bytes()[..chunk.len()]);
+    }
+    Ok(())
+}
@@ -1,255 +1,2553 @@
+use ::rand::rngs::StdRng;
+use ::rand::{Rng, SeedableRng};
 use macroquad::prelude::*;
+use std::io::{IsTerminal, Write};
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
 use std::thread::{self, JoinHandle};
 
-mod evolution;
-mod game;
-mod genome;
-mod simulation;
+mod brain;
+mod commentary;
+mod dashboard;
+mod i18n;
+mod menu;
+mod photo;
+mod schedule;
+mod series;
+mod ticker;
 
-use evolution::*;
-use game::*;
-use genome::*;
+use brain::render_brain;
+use commentary::Commentary;
+use dashboard::{Dashboard, ReplayRecorder};
+use i18n::{badge_label, fitness_label, tr, tr_fill, Language, Text};
+use menu::MenuChoice;
+use photo::{PhotoMode, RenderStyle};
+use schedule::PauseSchedule;
+use series::Series;
+use spaceship_duel::achievements::Badge;
+use spaceship_duel::archive::OpponentSampling;
+use spaceship_duel::bots::{Bot, Controller, GenomeController};
+use spaceship_duel::checkpoint::{in_flight_path, Retention};
+use spaceship_duel::codegen;
+use spaceship_duel::config::Config;
+use spaceship_duel::error::DuelError;
+use spaceship_duel::events::{EventLog, MatchEvent};
+use spaceship_duel::evolution::*;
+use spaceship_duel::game::*;
+use spaceship_duel::genome::*;
+use spaceship_duel::ladder::{Difficulty, PlayerRecord};
+use spaceship_duel::novelty::NoveltySearch;
+use spaceship_duel::pacing::{EngagementTimeline, STALL_INTENSITY};
+use spaceship_duel::pressure::{PressureGrid, PRESSURE_CELL_SIZE};
+use spaceship_duel::replay::{AnnotationKind, Branch, Replay, ReplayNotes, ReplayPlayer};
+use spaceship_duel::simulation::{
+    FitnessBreakdown, FitnessTracker, FitnessWeights, MeleeSession, SIM_DT,
+};
+use spaceship_duel::speciation::Speciation;
+use spaceship_duel::spectate::{LiveMatch, MatchKind};
+use spaceship_duel::stats::{thin_history, GenerationStats, StatsLog, HISTORY_CAP};
+use spaceship_duel::surrogate::Surrogate;
+use ticker::Ticker;
 
 const END_DELAY: f32 = 2.0;
+/// Ship colors by index: the duel's green and blue, then the rest of a melee
+const SHIP_COLORS: [Color; MAX_MELEE_SHIPS] = [
+    Color::new(0.0, 1.0, 0.4, 1.0),
+    Color::new(0.4, 0.6, 1.0, 1.0),
+    Color::new(1.0, 0.4, 0.3, 1.0),
+    Color::new(1.0, 0.85, 0.2, 1.0),
+    Color::new(0.8, 0.4, 1.0, 1.0),
+    Color::new(0.2, 0.9, 0.9, 1.0),
+    Color::new(1.0, 0.55, 0.8, 1.0),
+    Color::new(0.85, 0.85, 0.85, 1.0),
+];
+const CHAMPION_EXPORT_PATH: &str = "champion.rs";
+/// Files the S hotkey writes the two showcase genomes to
+const SHOWCASE_SAVE_PATHS: [&str; 2] = ["showcase_green.genome", "showcase_blue.genome"];
+/// File the player's win/loss record against the difficulty ladder is kept in
+const PLAYER_RECORD_PATH: &str = "player.record";
+/// Checkpoint of runs started from the menu without --checkpoint, which
+/// the menu's continue entry resumes
+const LAST_RUN_CHECKPOINT: &str = "last-run.checkpoint";
+/// File the R hotkey writes the showcase match replay to
+const SHOWCASE_REPLAY_PATH: &str = "showcase.replay";
+/// File the S hotkey writes a branched replay to
+const BRANCH_REPLAY_PATH: &str = "branch.replay";
+/// Seconds the arrow keys seek during replay playback
+const REPLAY_SEEK_SECONDS: f32 = 5.0;
+/// Seconds a live match is watched before switching to a more interesting one
+const SPECTATE_DWELL: f32 = 3.0;
+/// Showcase speeds the +/- keys step through, as multiples of real time
+const SHOWCASE_SPEEDS: [f32; 7] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+/// Showcase time scale during slow motion
+const SLOW_MOTION_SCALE: f32 = 0.2;
+/// Fire passing this close to a ship (px from its centre) cues slow motion
+const SLOW_MOTION_DISTANCE: f32 = SHIP_RADIUS * 2.0;
+/// Wall-clock seconds slow motion lasts after its last cue
+const SLOW_MOTION_HOLD: f32 = 0.6;
+/// Largest change of the time scale per wall-clock second, so slow motion eases in and out
+const SLOW_MOTION_RAMP: f32 = 4.0;
+const FLOW_GRID_SPACING: f32 = 80.0;
+/// Height of the engagement timeline strip below the arena
+const ENGAGEMENT_STRIP_HEIGHT: f32 = 60.0;
+/// Heading error (radians) under which the aim assist reports a lock
+const LOCK_TOLERANCE: f32 = 0.08;
+/// Size of the fitness graph in the top-right corner
+const FITNESS_GRAPH_SIZE: (f32, f32) = (240.0, 100.0);
+
+/// Command-line options
+struct Options {
+    layout: ArenaLayout,
+    opponent_sampling: OpponentSampling,
+    /// Pre-screen offspring with a learned fitness predictor
+    surrogate: bool,
+    pareto: bool,
+    rollback_threshold: Option<f32>,
+    /// Cluster the population into species that share fitness
+    speciation: Option<Speciation>,
+    /// Reward genomes for behaving unlike the rest of the population
+    novelty: Option<NoveltySearch>,
+    /// Wall-clock seconds per generation's evaluation instead of a fixed match count
+    time_budget: Option<f64>,
+    /// Evaluation matches played per rendered frame instead of a generation
+    /// per background thread
+    matches_per_frame: Option<usize>,
+    population_size: usize,
+    selection: Selection,
+    mutation: LayerMutation,
+    fitness_weights: FitnessWeights,
+    initialization: Initialization,
+    language: Language,
+    /// Genome file to place in the initial population
+    seed_genome: Option<String>,
+    /// Accept a seed genome saved under other game constants
+    allow_config_mismatch: bool,
+    /// Checkpoint to continue from instead of starting a new population
+    resume: Option<String>,
+    /// Where to write a checkpoint after every evaluated generation, and the
+    /// generation in flight beside it when the window closes; runs started
+    /// from the menu default to LAST_RUN_CHECKPOINT
+    checkpoint: Option<String>,
+    /// Per-generation copies of the checkpoint to keep beside it
+    keep_checkpoints: Option<Retention>,
+    /// CSV or JSONL file that per-generation fitness statistics are appended to
+    stats: Option<String>,
+    /// Generations the live fitness graphs keep before thinning out old ones
+    history_cap: usize,
+    /// Replay file to play back instead of training
+    replay: Option<String>,
+    /// Genome that takes over a ship when a replay is branched, instead of
+    /// the keyboard
+    branch_genome: Option<String>,
+    /// Show free-for-all melees among this many of the best genomes instead
+    /// of training
+    melee: Option<usize>,
+    pause: PauseSchedule,
+    /// Seed for every random choice, making evolution reproducible
+    seed: Option<u64>,
+    /// Narrate the showcase match as text (stdout and a log panel)
+    commentary: bool,
+    /// Fly the green ship from the keyboard against the best genome
+    play: bool,
+    /// Ladder rung the opponent in play mode is picked from
+    difficulty: Difficulty,
+    /// Slow the showcase down around close calls and kills
+    slow_motion: bool,
+    /// Show live evaluation matches instead of the showcase
+    spectate: bool,
+    /// Port of the local web dashboard, if enabled
+    dashboard: Option<u16>,
+    /// Any arena option on the command line skips the start-up menu
+    skip_menu: bool,
+}
+
+impl Options {
+    fn from_args() -> Self {
+        let mut layout = ArenaLayout::default();
+        let mut skip_menu = false;
+        let mut pause = PauseSchedule::default();
+        let mut opponent_sampling = OpponentSampling::default();
+        let mut surrogate = false;
+        let mut pareto = false;
+        let mut rollback_threshold = None;
+        let mut speciation = None;
+        let mut novelty = None;
+        let mut time_budget = None;
+        let mut matches_per_frame = None;
+        let mut population_size = POPULATION_SIZE;
+        let mut selection = Selection::default();
+        let mut mutation = LayerMutation::default();
+        let mut fitness_weights = FitnessWeights::default();
+        let mut initialization = Initialization::default();
+        let mut language = Language::from_env();
+        let mut seed_genome = None;
+        let mut allow_config_mismatch = false;
+        let mut resume = None;
+        let mut checkpoint = None;
+        let mut keep_checkpoints = None;
+        let mut stats = None;
+        let mut history_cap = HISTORY_CAP;
+        let mut replay = None;
+        let mut branch_genome = None;
+        let mut melee = None;
+        let mut commentary = false;
+        let mut play = false;
+        let mut difficulty = Difficulty::default();
+        let mut slow_motion = false;
+        let mut spectate = false;
+        let mut dashboard = None;
+        let mut seed = None;
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                // Replaces the settings it covers; flags after it override them
+                "--config" => {
+                    skip_menu = true;
+                    let path = args.next().unwrap_or_default();
+                    let config = Config::load(&path)
+                        .unwrap_or_else(|e| usage_error(&format!("cannot load {}: {}", path, e)));
+                    population_size = config.population_size;
+                    selection = config.selection;
+                    mutation = config.mutation;
+                    fitness_weights = config.fitness_weights;
+                    initialization.network = config.network;
+                    layout = ArenaLayout {
+                        flow_field: layout.flow_field,
+                        ..config.layout
+                    };
+                }
+                "--flow-field" => {
+                    skip_menu = true;
+                    let kind = args.next().unwrap_or_default();
+                    layout.flow_field = match kind.as_str() {
+                        "none" => FlowField::None,
+                        "wind" => FlowField::Wind { ax: 40.0, ay: 0.0 },
+                        "vortex" => FlowField::Vortex {
+                            x: ARENA_WIDTH / 2.0,
+                            y: ARENA_HEIGHT / 2.0,
+                            strength: 80.0,
+                            radius: 400.0,
+                        },
+                        "noise" => FlowField::Noise {
+                            seed: 1,
+                            cells: 4,
+                            strength: 50.0,
+                        },
+                        _ => usage_error(&format!("unknown flow field '{}'", kind)),
+                    };
+                }
+                "--randomize-flow-field" => {
+                    skip_menu = true;
+                    layout.randomize_flow_field = true;
+                }
+                "--asteroids" => {
+                    skip_menu = true;
+                    let count = args.next().unwrap_or_default();
+                    layout.asteroid_count = count.parse().unwrap_or_else(|_| {
+                        usage_error(&format!("invalid asteroid count '{}'", count))
+                    });
+                }
+                "--projectile-inheritance" => {
+                    layout.physics.projectile_inheritance = parse_number(&arg, args.next());
+                }
+                "--recoil" => layout.physics.recoil = parse_number(&arg, args.next()),
+                "--shield" => layout.physics.shield = parse_number(&arg, args.next()),
+                "--shield-regen" => layout.physics.shield_regen = parse_number(&arg, args.next()),
+                "--hull" => layout.physics.hull = parse_number(&arg, args.next()),
+                "--ammo" => {
+                    let count = args.next().unwrap_or_default();
+                    layout.physics.ammo = count.parse().unwrap_or_else(|_| {
+                        usage_error(&format!("invalid ammo count '{}'", count))
+                    });
+                }
+                "--ship-classes" => {
+                    let name = args.next().unwrap_or_default();
+                    layout.class_matchups =
+                        ClassMatchups::parse(&name).unwrap_or_else(|e| usage_error(&e.to_string()));
+                }
+                "--pickup-interval" => {
+                    layout.physics.pickup_interval = parse_number(&arg, args.next());
+                }
+                "--energy" => layout.physics.energy = true,
+                "--deflector" => layout.physics.deflector = true,
+                "--projectile-collisions" => layout.physics.projectile_collisions = true,
+                "--opponent-sampling" => {
+                    let spec = args.next().unwrap_or_default();
+                    opponent_sampling = OpponentSampling::parse(&spec)
+                        .unwrap_or_else(|e| usage_error(&e.to_string()));
+                }
+                "--surrogate" => surrogate = true,
+                "--pareto" => pareto = true,
+                "--rollback" => {
+                    let share = parse_number(&arg, args.next());
+                    if !(0.0..=1.0).contains(&share) {
+                        usage_error("--rollback must be a share between 0 and 1");
+                    }
+                    rollback_threshold = Some(share);
+                }
+                "--time-budget" => {
+                    let seconds = parse_number(&arg, args.next());
+                    if seconds <= 0.0 {
+                        usage_error("--time-budget must be positive");
+                    }
+                    time_budget = Some(seconds as f64);
+                }
+                "--matches-per-frame" => {
+                    let count = args.next().unwrap_or_default();
+                    matches_per_frame = match count.parse() {
+                        Ok(0) | Err(_) => {
+                            usage_error(&format!("invalid matches per frame: {}", count))
+                        }
+                        Ok(count) => Some(count),
+                    };
+                }
+                "--speciation" => {
+                    let spec = args.next().unwrap_or_default();
+                    speciation = Some(
+                        Speciation::parse(&spec).unwrap_or_else(|e| usage_error(&e.to_string())),
+                    );
+                }
+                "--novelty" => {
+                    let spec = args.next().unwrap_or_default();
+                    novelty = Some(
+                        NoveltySearch::parse(&spec).unwrap_or_else(|e| usage_error(&e.to_string())),
+                    );
+                }
+                "--mutation" => {
+                    let spec = args.next().unwrap_or_default();
+                    let (name, value) = spec.split_once('=').unwrap_or_else(|| {
+                        usage_error(&format!("expected NAME=VALUE, got '{}'", spec))
+                    });
+                    mutation
+                        .set(name, parse_number(name, Some(value.to_string())))
+                        .unwrap_or_else(|e| usage_error(&e.to_string()));
+                }
+                "--fitness" => {
+                    let spec = args.next().unwrap_or_default();
+                    let (name, value) = spec.split_once('=').unwrap_or_else(|| {
+                        usage_error(&format!("expected NAME=VALUE, got '{}'", spec))
+                    });
+                    fitness_weights
+                        .set(name, parse_number(name, Some(value.to_string())))
+                        .unwrap_or_else(|e| usage_error(&e.to_string()));
+                }
+                "--init" => {
+                    let spec = args.next().unwrap_or_default();
+                    initialization.weights =
+                        WeightInit::parse(&spec).unwrap_or_else(|e| usage_error(&e.to_string()));
+                }
+                "--zero-bias" => initialization.zero_bias = true,
+                "--hidden" => {
+                    let spec = args.next().unwrap_or_default();
+                    initialization.network.hidden = NetworkConfig::parse_widths(&spec)
+                        .unwrap_or_else(|e| usage_error(&e.to_string()));
+                }
+                "--recurrent" => initialization.network.recurrent = true,
+                "--position-inputs" => initialization.network.position_inputs = true,
+                "--defense-inputs" => initialization.network.defense_inputs = true,
+                "--ammo-inputs" => initialization.network.ammo_inputs = true,
+                "--energy-inputs" => initialization.network.energy_inputs = true,
+                "--signed-turn" => initialization.network.signed_turn = true,
+                "--rays" => {
+                    let count = args.next().unwrap_or_default();
+                    initialization.network.rays = count
+                        .parse()
+                        .unwrap_or_else(|_| usage_error(&format!("invalid ray count: {}", count)));
+                }
+                "--seed-genome" => seed_genome = args.next(),
+                "--allow-config-mismatch" => allow_config_mismatch = true,
+                "--resume" => {
+                    skip_menu = true;
+                    resume = args.next();
+                }
+                "--checkpoint" => checkpoint = args.next(),
+                "--keep-checkpoints" => {
+                    let spec = args.next().unwrap_or_default();
+                    keep_checkpoints = Some(
+                        Retention::parse(&spec).unwrap_or_else(|e| usage_error(&e.to_string())),
+                    );
+                }
+                "--stats" => stats = args.next(),
+                "--history-cap" => {
+                    let count = args.next().unwrap_or_default();
+                    history_cap = count.parse().unwrap_or_else(|_| {
+                        usage_error(&format!("invalid history cap: {}", count))
+                    });
+                }
+                "--replay" => replay = args.next(),
+                "--branch-genome" => branch_genome = args.next(),
+                "--melee" => {
+                    let count = args.next().unwrap_or_default();
+                    melee = match count.parse() {
+                        Ok(count @ MIN_MELEE_SHIPS..=MAX_MELEE_SHIPS) => Some(count),
+                        _ => usage_error(&format!(
+                            "invalid melee size '{}' ({} to {} ships)",
+                            count, MIN_MELEE_SHIPS, MAX_MELEE_SHIPS
+                        )),
+                    };
+                }
+                "--language" => {
+                    let code = args.next().unwrap_or_default();
+                    language = Language::parse(&code).unwrap_or_else(|| {
+                        usage_error(&format!("unsupported language '{}' (en, de)", code))
+                    });
+                }
+                "--pause-hours" => {
+                    let spec = args.next().unwrap_or_default();
+                    pause.quiet_hours_utc =
+                        Some(schedule::parse_hours(&spec).unwrap_or_else(|| {
+                            usage_error(&format!("invalid hour range '{}'", spec))
+                        }));
+                }
+                "--pause-on-battery" => pause.on_battery = true,
+                "--seed" => {
+                    let value = args.next().unwrap_or_default();
+                    seed = Some(
+                        value
+                            .parse()
+                            .unwrap_or_else(|_| usage_error(&format!("invalid seed '{}'", value))),
+                    );
+                }
+                "--commentary" => commentary = true,
+                "--play" => play = true,
+                "--difficulty" => {
+                    let name = args.next().unwrap_or_default();
+                    difficulty = Difficulty::parse(&name)
+                        .unwrap_or_else(|e| usage_error(&format!("{} (easy, medium, hard)", e)));
+                }
+                "--slow-motion" => slow_motion = true,
+                "--spectate" => spectate = true,
+                "--dashboard" => {
+                    let port = args.next().unwrap_or_default();
+                    dashboard = Some(port.parse().unwrap_or_else(|_| {
+                        usage_error(&format!("invalid dashboard port '{}'", port))
+                    }));
+                }
+                "--no-menu" => skip_menu = true,
+                _ => usage_error(&format!("unknown argument '{}'", arg)),
+            }
+        }
+        if let Err(e) = layout.validate() {
+            usage_error(&e.to_string());
+        }
+        if let Err(e) = initialization.network.validate() {
+            usage_error(&e.to_string());
+        }
+        if keep_checkpoints.is_some() && checkpoint.is_none() {
+            usage_error("--keep-checkpoints needs --checkpoint");
+        }
+        Options {
+            layout,
+            opponent_sampling,
+            surrogate,
+            pareto,
+            rollback_threshold,
+            speciation,
+            novelty,
+            time_budget,
+            matches_per_frame,
+            population_size,
+            selection,
+            mutation,
+            fitness_weights,
+            initialization,
+            language,
+            seed_genome,
+            allow_config_mismatch,
+            resume,
+            checkpoint,
+            keep_checkpoints,
+            stats,
+            history_cap,
+            replay,
+            branch_genome,
+            melee,
+            pause,
+            seed,
+            commentary,
+            play,
+            difficulty,
+            slow_motion,
+            spectate,
+            dashboard,
+            skip_menu,
+        }
+    }
+}
+
+fn parse_number(flag: &str, value: Option<String>) -> f32 {
+    let value = value.unwrap_or_default();
+    value
+        .parse()
+        .unwrap_or_else(|_| usage_error(&format!("invalid value '{}' for {}", value, flag)))
+}
+
+fn usage_error(msg: &str) -> ! {
+    eprintln!("error: {}", msg);
+    eprintln!("usage: spaceship-duel [--config FILE.conf] [--flow-field none|wind|vortex|noise] [--randomize-flow-field] [--asteroids N]");
+    eprintln!("                      [--shield POINTS] [--shield-regen POINTS/S] [--hull POINTS] [--ammo N] [--pickup-interval SECONDS]");
+    eprintln!(
+        "                      [--ship-classes standard|mirror|mixed] [--energy] [--deflector] [--projectile-collisions]"
+    );
+    eprintln!("                      [--time-budget SECONDS] [--matches-per-frame N] [--surrogate] [--speciation THRESHOLD[:STAGNATION]] [--novelty WEIGHT[:K]] [--pareto] [--rollback SHARE] [--mutation NAME=VALUE] [--fitness NAME=VALUE] [--init uniform|xavier|he|sparse:DENSITY] [--zero-bias]");
+    eprintln!("                      [--hidden WIDTHS] [--recurrent] [--position-inputs] [--defense-inputs] [--ammo-inputs] [--energy-inputs] [--signed-turn] [--rays N]");
+    eprintln!(
+        "                      [--pause-hours START-END (UTC)] [--pause-on-battery] [--language en|de] [--commentary] [--play] [--difficulty easy|medium|hard] [--slow-motion] [--spectate] [--dashboard PORT] [--no-menu]"
+    );
+    eprintln!("                      [--seed-genome FILE] [--allow-config-mismatch]");
+    eprintln!("                      [--resume CHECKPOINT] [--checkpoint FILE [--keep-checkpoints LAST[:EVERY]]] [--stats FILE.csv|FILE.jsonl] [--history-cap N] [--seed N]");
+    eprintln!(
+        "       spaceship-duel --replay FILE [--branch-genome FILE] [--allow-config-mismatch]"
+    );
+    eprintln!("       spaceship-duel --melee SHIPS [--resume CHECKPOINT] [--seed N]");
+    std::process::exit(2);
+}
+
+/// Cumulative throughput figures for the whole session
+struct SessionStats {
+    /// macroquad's clock, which unlike `Instant` also runs in browsers
+    started: f64,
+    generations: usize,
+    matches: usize,
+    simulated_seconds: f64,
+    speedup: f64,
+    /// Bytes held by what grows over a run: the hall of fame, and once
+    /// `main` adds them the fitness history and the showcase match's
+    /// replay and event log
+    memory_bytes: usize,
+}
+
+impl SessionStats {
+    fn new() -> Self {
+        SessionStats {
+            started: get_time(),
+            generations: 0,
+            matches: 0,
+            simulated_seconds: 0.0,
+            speedup: 0.0,
+            memory_bytes: 0,
+        }
+    }
+
+    fn update(&mut self, pop: &Population) {
+        self.generations = pop.generation;
+        self.matches = pop.matches_played;
+        self.simulated_seconds = pop.simulated_seconds;
+        self.speedup = pop.speedup();
+        self.memory_bytes = pop.hall_of_fame.memory_bytes();
+    }
+
+    fn generations_per_hour(&self) -> f64 {
+        let hours = (get_time() - self.started) / 3600.0;
+        if hours > 0.0 {
+            self.generations as f64 / hours
+        } else {
+            0.0
+        }
+    }
+
+    fn summary(&self) -> String {
+        tr_fill(
+            Text::SessionSummary,
+            &[
+                ("matches", self.matches.to_string()),
+                ("hours", format!("{:.1}", self.simulated_seconds / 3600.0)),
+                ("speedup", format!("{:.0}", self.speedup)),
+                ("rate", format!("{:.1}", self.generations_per_hour())),
+                ("memory", format!("{:.1}", self.memory_bytes as f64 / 1e6)),
+            ],
+        )
+    }
+}
+
+fn champion_badges_of(pop: &Population) -> Vec<Badge> {
+    pop.champion
+        .as_ref()
+        .map_or_else(Vec::new, |c| c.badges.clone())
+}
+
+/// Announce badges the newest archived champion is the first of the run to earn
+fn log_first_badges(pop: &Population) {
+    let Some(newest) = pop.hall_of_fame.entries.last() else {
+        return;
+    };
+    for &badge in &newest.badges {
+        if pop.hall_of_fame.first_earned(badge) == Some(newest.generation) {
+            let text = tr_fill(
+                Text::FirstBadgeLog,
+                &[
+                    ("badge", badge_label(badge).to_string()),
+                    ("gen", newest.generation.to_string()),
+                ],
+            );
+            println!("{}", text);
+        }
+    }
+}
+
+fn generation_log(generation: usize, best_fitness: f32, session: &SessionStats) -> String {
+    tr_fill(
+        Text::GenerationLog,
+        &[
+            ("gen", generation.to_string()),
+            ("best", format!("{:.1}", best_fitness)),
+            ("summary", session.summary()),
+        ],
+    )
+}
+
+fn progress_title(generation: usize, eval_percent: u32, best_fitness: f32) -> String {
+    tr_fill(
+        Text::ProgressTitle,
+        &[
+            ("gen", generation.to_string()),
+            ("percent", eval_percent.to_string()),
+            ("best", format!("{:.0}", best_fitness)),
+        ],
+    )
+}
+
+/// Set the title of the controlling terminal. miniquad cannot retitle the
+/// game window after creation, so the terminal carries the live progress.
+fn set_terminal_title(title: &str) {
+    let mut stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        return;
+    }
+    let _ = write!(stdout, "\x1b]0;{}\x07", title);
+    let _ = stdout.flush();
+}
 
 fn window_conf() -> Conf {
     Conf {
         window_title: "Evolved Spaceship Duel".to_string(),
         window_width: ARENA_WIDTH as i32,
-        window_height: ARENA_HEIGHT as i32,
+        window_height: (ARENA_HEIGHT + ENGAGEMENT_STRIP_HEIGHT) as i32,
         window_resizable: false,
         ..Default::default()
     }
 }
 
-/// Spawn evolution (evolve + evaluate) on a background thread.
-/// Returns a join handle that yields the updated population and top two genomes.
-fn spawn_evolution(mut pop: Population) -> JoinHandle<(Population, Genome, Genome)> {
-    thread::spawn(move || {
-        let mut rng = ::rand::thread_rng();
-        pop.evolve(&mut rng);
-        pop.evaluate(&mut rng);
-        let (g1, g2) = pop.get_top_two();
-        (pop, g1, g2)
-    })
+/// Evolved population and its two best genomes, from one background generation
+type EvolutionResult = Result<(Population, Genome, Genome), DuelError>;
+
+/// The next generation in the making
+enum Evolution {
+    /// Bred and evaluated on a background thread, which hands the population
+    /// back even when interrupted
+    #[cfg(not(target_arch = "wasm32"))]
+    Thread(JoinHandle<(Population, Result<(), DuelError>)>),
+    /// Evaluated a few matches per frame: a fixed number of them, or as many
+    /// as fit in FRAME_EVOLUTION_BUDGET. Browsers have no threads, and a
+    /// window shows steady progress this way instead of a generation at once.
+    Frames(Box<Population>, Box<GenerationStep>, Option<usize>),
+}
+
+/// Seconds of each frame given to evaluation matches when they run in frames
+/// without a match count
+const FRAME_EVOLUTION_BUDGET: f64 = 0.008;
+
+/// Start breeding and evaluating the next generation of `pop`, in frames
+/// with `matches_per_frame` or always in browsers, else on a thread
+fn spawn_evolution(mut pop: Population, matches_per_frame: Option<usize>) -> Evolution {
+    let rng = pop.generation_rng();
+    #[cfg(not(target_arch = "wasm32"))]
+    if matches_per_frame.is_none() {
+        return Evolution::Thread(thread::spawn(move || {
+            let mut rng = rng;
+            let result = pop.advance(&mut rng);
+            (pop, result)
+        }));
+    }
+    let step = pop.begin_advance(rng);
+    Evolution::Frames(Box::new(pop), Box::new(step), matches_per_frame)
+}
+
+impl Evolution {
+    /// Play this frame's share of matches when evolution runs in frames
+    fn run_frame(&mut self) {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Evolution::Thread(_) => {}
+            Evolution::Frames(pop, step, matches) => {
+                let started = get_time();
+                match *matches {
+                    Some(count) => {
+                        pop.step(step, count);
+                    }
+                    None => {
+                        while !pop.step(step, 1) && get_time() - started < FRAME_EVOLUTION_BUDGET {}
+                    }
+                }
+                pop.eval_seconds += get_time() - started;
+            }
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Evolution::Thread(handle) => handle.is_finished(),
+            Evolution::Frames(_, step, _) => step.is_done(),
+        }
+    }
+
+    fn join(self) -> EvolutionResult {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Evolution::Thread(handle) => match handle.join() {
+                Ok((pop, Ok(()))) => {
+                    let (g1, g2) = pop.get_top_two();
+                    Ok((pop, g1, g2))
+                }
+                Ok((_, Err(e))) => Err(e),
+                Err(_) => Err(DuelError::WorkerPanicked { task: "evolution" }),
+            },
+            Evolution::Frames(pop, _, _) => {
+                let (g1, g2) = pop.get_top_two();
+                Ok((*pop, g1, g2))
+            }
+        }
+    }
+
+    /// Stop once the genomes in play have finished their matches and hand
+    /// back the population, holding the rest of its evaluation in `partial`
+    /// unless the generation finished first; None if evolution failed
+    fn interrupt(self, progress: &EvalProgress) -> Option<Population> {
+        progress.interrupt();
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Evolution::Thread(handle) => match handle.join() {
+                Ok((pop, Ok(()) | Err(DuelError::Interrupted { .. }))) => Some(pop),
+                _ => None,
+            },
+            Evolution::Frames(mut pop, step, _) => {
+                pop.suspend(*step);
+                Some(*pop)
+            }
+        }
+    }
+}
+
+/// Save the evolution in progress when the window closes: a generation
+/// that finished in the meantime as the checkpoint, or the genomes and
+/// matches of one still being evaluated next to it, for `--resume` to
+/// finish instead of starting that generation over
+fn shut_down(evolution: Option<Evolution>, progress: &EvalProgress, options: &Options) {
+    let Some(path) = &options.checkpoint else {
+        return;
+    };
+    let Some(pop) = evolution.and_then(|e| e.interrupt(progress)) else {
+        return;
+    };
+    let Some(partial) = &pop.partial else {
+        save_checkpoint(&pop, options);
+        let _ = std::fs::remove_file(in_flight_path(path));
+        return;
+    };
+    let in_flight = in_flight_path(path);
+    match partial.save(&in_flight) {
+        Ok(()) => println!(
+            "Saved generation {} with {} of {} genomes evaluated to {}",
+            partial.generation,
+            partial.evaluated(),
+            partial.genomes.len(),
+            in_flight
+        ),
+        Err(e) => eprintln!("Failed to write {}: {}", in_flight, e),
+    }
+}
+
+/// Random population configured from the command line, evaluated once
+fn new_population(options: &Options, rng: &mut impl ::rand::Rng) -> Population {
+    let mut pop = Population::with_init(
+        options.layout.clone(),
+        options.population_size,
+        &options.initialization,
+        rng,
+    );
+    pop.opponent_sampling = options.opponent_sampling.clone();
+    pop.selection = options.selection.clone();
+    pop.mutation = options.mutation.clone();
+    pop.fitness_weights = options.fitness_weights.clone();
+    if options.surrogate {
+        pop.surrogate = Some(Surrogate::new(&options.initialization.network));
+    }
+    pop.speciation = options.speciation.clone();
+    pop.novelty = options.novelty.clone();
+    pop.pareto = options.pareto;
+    pop.rollback_threshold = options.rollback_threshold;
+    pop.time_budget = options.time_budget;
+    if let Some(path) = &options.seed_genome {
+        let check = if options.allow_config_mismatch {
+            ConfigCheck::Ignore
+        } else {
+            ConfigCheck::Require
+        };
+        match Genome::load_with(path, check) {
+            Ok((genome, _)) if genome.network != options.initialization.network => {
+                usage_error(&format!(
+                    "{} has network {} but the population uses {}; match it with --hidden, --recurrent, --rays and the --*-inputs flags",
+                    path,
+                    genome.network.spec(),
+                    options.initialization.network.spec()
+                ))
+            }
+            Ok((genome, generation)) => {
+                println!(
+                    "Seeded population with {} (generation {})",
+                    path, generation
+                );
+                pop.genomes[0] = Genome {
+                    fitness: 0.0,
+                    ..genome
+                };
+            }
+            Err(e) => usage_error(&format!("cannot load {}: {}", path, e)),
+        }
+    }
+    // Browsers have no threads for `evaluate`, so the first generation is
+    // played in one go on the main thread
+    #[cfg(target_arch = "wasm32")]
+    {
+        let mut step = pop.begin_evaluate(StdRng::seed_from_u64(rng.gen()));
+        pop.step(&mut step, usize::MAX);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(e) = pop.evaluate(rng) {
+        eprintln!("error: initial evaluation failed: {}", e);
+        std::process::exit(1);
+    }
+    pop
+}
+
+/// Population saved by `--checkpoint`, already evaluated, with the
+/// evaluation of the next generation if the window closed during it
+fn resume_population(path: &str) -> Population {
+    match Population::load(path) {
+        Ok(mut pop) => {
+            println!("Resumed {} at generation {}", path, pop.generation);
+            let in_flight = in_flight_path(path);
+            if std::path::Path::new(&in_flight).exists() {
+                match PartialEvaluation::load(&in_flight, &pop) {
+                    Ok(partial) => {
+                        println!(
+                            "Continuing generation {} with {} of {} genomes already evaluated",
+                            partial.generation,
+                            partial.evaluated(),
+                            partial.genomes.len()
+                        );
+                        pop.partial = Some(partial);
+                    }
+                    Err(e) => eprintln!("Ignoring {}: {}", in_flight, e),
+                }
+            }
+            pop
+        }
+        Err(e) => usage_error(&format!("cannot resume from {}: {}", path, e)),
+    }
+}
+
+/// Write the `--checkpoint`, plus its per-generation copy under `--keep-checkpoints`
+fn save_checkpoint(pop: &Population, options: &Options) {
+    let Some(path) = &options.checkpoint else {
+        return;
+    };
+    if let Err(e) = pop.save(path) {
+        eprintln!("Failed to write checkpoint {}: {}", path, e);
+    }
+    if let Some(retention) = &options.keep_checkpoints {
+        if let Err(e) = pop.save_retained(path, retention) {
+            eprintln!("Failed to keep a copy of checkpoint {}: {}", path, e);
+        }
+    }
+}
+
+fn record_stats(pop: &Population, log: &mut Option<StatsLog>) {
+    if let Some(log) = log {
+        if let Err(e) = log.record(pop) {
+            eprintln!("Failed to write generation stats: {}", e);
+        }
+    }
 }
 
 #[macroquad::main(window_conf)]
 async fn main() {
-    let mut rng = ::rand::thread_rng();
+    let mut options = Options::from_args();
+    i18n::set_language(options.language);
+    if !options.skip_menu && options.replay.is_none() {
+        // Browsers have no files to keep a run in
+        if cfg!(not(target_arch = "wasm32")) && options.checkpoint.is_none() {
+            options.checkpoint = Some(LAST_RUN_CHECKPOINT.to_string());
+        }
+        let last_run = options
+            .checkpoint
+            .clone()
+            .filter(|path| std::path::Path::new(path).exists());
+        let saved_replay = std::path::Path::new(SHOWCASE_REPLAY_PATH).exists();
+        match menu::choose(last_run.is_some(), saved_replay).await {
+            MenuChoice::NewRun(preset) => {
+                let physics = options.layout.physics.clone();
+                options.layout = ArenaLayout {
+                    physics,
+                    ..preset.layout()
+                };
+            }
+            MenuChoice::Continue => options.resume = last_run,
+            MenuChoice::WatchReplay => options.replay = Some(SHOWCASE_REPLAY_PATH.to_string()),
+            MenuChoice::Challenge => {
+                options.play = true;
+                options.resume = last_run;
+            }
+        }
+    }
+    if let Some(path) = &options.replay {
+        let replay = Replay::load(path)
+            .unwrap_or_else(|e| usage_error(&format!("cannot load {}: {}", path, e)));
+        let notes = ReplayNotes::load_for(path).unwrap_or_else(|e| {
+            usage_error(&format!(
+                "cannot load {}: {}",
+                ReplayNotes::path_for(path).display(),
+                e
+            ))
+        });
+        let branch_genome = options.branch_genome.as_ref().map(|path| {
+            let check = if options.allow_config_mismatch {
+                ConfigCheck::Ignore
+            } else {
+                ConfigCheck::Require
+            };
+            Genome::load_with(path, check)
+                .unwrap_or_else(|e| usage_error(&format!("cannot load {}: {}", path, e)))
+                .0
+        });
+        play_replay(replay, notes, path, branch_genome).await;
+        return;
+    }
+    let mut rng = options
+        .seed
+        .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
 
-    // Initialize population and run first evaluation synchronously
-    let mut pop = Population::new(&mut rng);
-    pop.evaluate(&mut rng);
+    let mut pop = match &options.resume {
+        Some(path) => resume_population(path),
+        None => new_population(&options, &mut rng),
+    };
+    // The budget suits this machine, so it applies to resumed runs too
+    pop.time_budget = options.time_budget;
+    // A resumed run keeps the arena it was trained in
+    options.layout = pop.layout.clone();
+    if let Some(count) = options.melee {
+        play_melee(&pop, count, &mut rng).await;
+        return;
+    }
+    save_checkpoint(&pop, &options);
+    let mut stats_log = options.stats.as_ref().map(|path| {
+        StatsLog::open(path)
+            .unwrap_or_else(|e| usage_error(&format!("cannot open {}: {}", path, e)))
+    });
+    // A resumed generation was already logged by the run that saved it
+    if options.resume.is_none() {
+        record_stats(&pop, &mut stats_log);
+    }
     let (g1, g2) = pop.get_top_two();
+    // Play mode opponents, one per rung, refreshed every generation
+    let mut ladder = Difficulty::ALL.map(|d| d.opponent(&pop.hall_of_fame, &g1));
+    let mut player_record = PlayerRecord::load(PLAYER_RECORD_PATH).unwrap_or_else(|e| {
+        eprintln!("Failed to load {}: {}", PLAYER_RECORD_PATH, e);
+        PlayerRecord::default()
+    });
+    // The showcase scores with the run's weights; resumed runs keep their own
+    let fitness_weights = pop.fitness_weights.clone();
+    // Fitness of every generation this session, for the live graph
+    let mut fitness_history = vec![GenerationStats::of(&pop)];
 
     let mut current_gen = pop.generation;
     let mut current_best = pop.best_fitness;
-    println!("Generation {} | Best fitness: {:.1}", current_gen, current_best);
+    let mut champion_elo = pop.champion.as_ref().map(|c| c.rating);
+    let mut champion_badges = champion_badges_of(&pop);
+    let mut session = SessionStats::new();
+    session.update(&pop);
+    let dashboard =
+        options
+            .dashboard
+            .and_then(|port| match Dashboard::start(port, options.history_cap) {
+                Ok(dashboard) => {
+                    println!("Dashboard at http://127.0.0.1:{}/", port);
+                    dashboard.record_generation(&pop);
+                    Some(dashboard)
+                }
+                Err(e) => {
+                    eprintln!("Failed to start dashboard on port {}: {}", port, e);
+                    None
+                }
+            });
+    println!("{}", generation_log(current_gen, current_best, &session));
 
     // Start first background evolution
-    let mut evo_handle: Option<JoinHandle<(Population, Genome, Genome)>> =
-        Some(spawn_evolution(pop));
+    let progress = Arc::clone(&pop.progress);
+    let spectator = Arc::clone(&pop.spectator);
+    spectator.set_watching(options.spectate);
+    let mut evo_handle: Option<Evolution> = Some(spawn_evolution(pop, options.matches_per_frame));
+    // Population held back while evolution is paused
+    let mut parked: Option<Population> = None;
 
     // Showcase state
     let mut showcase_genomes = [g1, g2];
-    let mut match_state = GameState::new_random(&mut rng);
+    // The showcase pair plays out its series; newer genomes wait for the next one
+    let mut series = Series::default();
+    let mut next_genomes: Option<[Genome; 2]> = None;
+    // Each showcase match is set up from its own seed, so it can be replayed
+    let mut showcase_replay = Replay::new(options.layout.clone(), rng.gen());
+    let mut match_state = showcase_replay.initial_state();
+    let mut pilots = showcase_pilots(&showcase_genomes, ladder_opponent(&ladder, &options));
+    let mut fitness_tracker = FitnessTracker::new(fitness_weights.clone());
+    let mut event_log = EventLog::new();
+    let mut engagement = EngagementTimeline::default();
+    let mut commentary = Commentary::new();
+    let mut replay = ReplayRecorder::default();
+    let mut ticker = Ticker::new();
+    if options.commentary {
+        commentary.start_match(current_gen);
+    }
+    let mut pressure = PressureGrid::new();
     let mut end_timer = END_DELAY;
+    let mut show_flow_field = false;
+    let mut show_aim_assist = false;
+    let mut show_fitness = false;
+    let mut show_pressure = false;
+    let mut show_ticker = true;
+    let mut show_fitness_graph = true;
+    let mut show_brains = false;
+    let mut shown_percent = None;
+    let mut clock = ShowcaseClock::new(options.slow_motion);
+    // Worker whose live match is on screen while spectating
+    let mut spectated: Option<usize> = None;
+    let mut spectate_timer = 0.0;
+    let mut photo = PhotoMode::default();
+    // Closing the window saves a generation in flight instead of losing it
+    if options.checkpoint.is_some() {
+        prevent_quit();
+    }
 
     loop {
+        if is_quit_requested() {
+            shut_down(evo_handle.take(), &progress, &options);
+            return;
+        }
         let dt = get_frame_time().min(1.0 / 30.0);
 
-        if !match_state.match_over {
-            // Step the showcase match
-            let inputs0 = Genome::get_inputs(&match_state, 0);
-            let inputs1 = Genome::get_inputs(&match_state, 1);
-            let actions0 = showcase_genomes[0].evaluate(&inputs0);
-            let actions1 = showcase_genomes[1].evaluate(&inputs1);
-            match_state.update(dt, &[actions0, actions1]);
+        if is_key_pressed(KeyCode::V) {
+            show_flow_field = !show_flow_field;
+        }
+        if is_key_pressed(KeyCode::A) {
+            show_aim_assist = !show_aim_assist;
+        }
+        if is_key_pressed(KeyCode::B) {
+            show_fitness = !show_fitness;
+        }
+        if is_key_pressed(KeyCode::E) {
+            let source =
+                codegen::champion_source(&showcase_genomes[0], current_gen, options.layout.arena);
+            match std::fs::write(CHAMPION_EXPORT_PATH, source) {
+                Ok(()) => println!("Exported champion controller to {}", CHAMPION_EXPORT_PATH),
+                Err(e) => eprintln!("Failed to export champion: {}", e),
+            }
+        }
+        if is_key_pressed(KeyCode::S) {
+            for (genome, path) in showcase_genomes.iter().zip(SHOWCASE_SAVE_PATHS) {
+                match genome.save(path, current_gen) {
+                    Ok(()) => println!("Saved showcase genome to {}", path),
+                    Err(e) => eprintln!("Failed to save {}: {}", path, e),
+                }
+            }
+        }
+        if is_key_pressed(KeyCode::R) {
+            match showcase_replay.save(SHOWCASE_REPLAY_PATH) {
+                Ok(()) => println!("Saved showcase replay to {}", SHOWCASE_REPLAY_PATH),
+                Err(e) => eprintln!("Failed to save {}: {}", SHOWCASE_REPLAY_PATH, e),
+            }
+        }
+        if is_key_pressed(KeyCode::M) {
+            show_pressure = !show_pressure;
+        }
+        if is_key_pressed(KeyCode::T) {
+            show_ticker = !show_ticker;
+        }
+        if is_key_pressed(KeyCode::G) {
+            show_fitness_graph = !show_fitness_graph;
+        }
+        if is_key_pressed(KeyCode::N) {
+            show_brains = !show_brains;
+        }
+        if is_key_pressed(KeyCode::C) {
+            options.commentary = !options.commentary;
+        }
+        if is_key_pressed(KeyCode::Z) {
+            clock.slow_motion = !clock.slow_motion;
+        }
+        if is_key_pressed(KeyCode::H) {
+            options.play = !options.play;
+            pilots = showcase_pilots(&showcase_genomes, ladder_opponent(&ladder, &options));
+            // A series is between two pilots; a new one starts with the next match
+            series = Series::default();
+        }
+        if options.play && !photo.is_active() {
+            let keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3];
+            if let Some(rung) = keys.iter().position(|&key| is_key_pressed(key)) {
+                options.difficulty = Difficulty::ALL[rung];
+                pilots = showcase_pilots(&showcase_genomes, ladder_opponent(&ladder, &options));
+                series = Series::default();
+            }
+        }
+        // Space fires in play mode, so the match only pauses on it while watching
+        if is_key_pressed(KeyCode::Space) && !options.play {
+            clock.paused = !clock.paused;
+        }
+        if is_key_pressed(KeyCode::F) {
+            clock.step_once();
+        }
+        if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+            clock.change_speed(1);
+        }
+        if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+            clock.change_speed(-1);
+        }
+        if is_key_pressed(KeyCode::L) {
+            options.spectate = !options.spectate;
+            spectator.set_watching(options.spectate);
+            spectated = None;
+        }
+        if is_key_pressed(KeyCode::P) {
+            options.pause.manual = !options.pause.manual;
+        }
+        let paused = options.pause.is_paused();
+        if !paused {
+            if let Some(pop) = parked.take() {
+                evo_handle = Some(spawn_evolution(pop, options.matches_per_frame));
+            }
+        }
+        if let Some(evolution) = &mut evo_handle {
+            evolution.run_frame();
+        }
+
+        // The showcase runs fixed steps at the chosen speed, however fast frames
+        // come, and stands still while being photographed
+        let steps = if photo.is_active() {
+            0
         } else {
-            end_timer -= dt;
-            match_state.time += dt;
-
-            if end_timer <= 0.0 {
-                // Check if background evolution has completed
-                let evo_done = evo_handle
-                    .as_ref()
-                    .map_or(false, |h| h.is_finished());
-
-                if evo_done {
-                    let (new_pop, g1, g2) = evo_handle.take().unwrap().join().unwrap();
-                    current_gen = new_pop.generation;
-                    current_best = new_pop.best_fitness;
-                    showcase_genomes = [g1, g2];
-                    println!(
-                        "Generation {} | Best fitness: {:.1}",
-                        current_gen, current_best
-                    );
+            clock.steps(dt)
+        };
+        for _ in 0..steps {
+            if !match_state.match_over {
+                // Step the showcase match
+                let actions = [0, 1].map(|i| pilots[i].act(&match_state, i));
+                // Pilots can change mid-match, so each ship takes its pilot's loadout every step
+                for (ship, pilot) in match_state.ships.iter_mut().zip(&pilots) {
+                    ship.loadout = pilot.loadout();
+                }
+                showcase_replay.record(&match_state, SIM_DT, &actions);
+                match_state.update(SIM_DT, &actions);
+                fitness_tracker.record(&match_state);
+                engagement.record(&match_state);
+                let events = event_log.record(&match_state);
+                let close_call = events.iter().any(|e| {
+                    matches!(e, MatchEvent::Destroyed { .. })
+                        || matches!(e, MatchEvent::NearMiss { distance, .. } if *distance < SLOW_MOTION_DISTANCE)
+                });
+                if options.commentary {
+                    commentary.observe(events, &match_state);
+                }
+                ticker.observe(events);
+                if close_call
+                    || event_log
+                        .close_pass()
+                        .is_some_and(|d| d < SLOW_MOTION_DISTANCE)
+                {
+                    clock.cue_slow_motion();
+                }
+                if dashboard.is_some() {
+                    replay.record(&match_state);
+                }
+                pressure.update(&match_state, SIM_DT);
+                if match_state.match_over {
+                    series.record(match_state.winner);
+                    if options.play {
+                        player_record.record(options.difficulty, match_state.winner);
+                        if let Err(e) = player_record.save(PLAYER_RECORD_PATH) {
+                            eprintln!("Failed to save {}: {}", PLAYER_RECORD_PATH, e);
+                        }
+                    }
+                }
+            } else {
+                end_timer -= SIM_DT;
+                match_state.time += SIM_DT;
+
+                if end_timer <= 0.0 {
+                    if let Some(dashboard) = &dashboard {
+                        dashboard.publish_replay(&replay, &engagement, current_gen);
+                    }
+                    replay = ReplayRecorder::default();
+
+                    // Check if background evolution has completed
+                    let evo_done = evo_handle.as_ref().is_some_and(|h| h.is_finished());
 
-                    // Start next background evolution
-                    evo_handle = Some(spawn_evolution(new_pop));
+                    // Keep showcasing the last genomes if background evolution fails
+                    let finished = evo_handle.take_if(|_| evo_done).map(Evolution::join);
+                    match finished {
+                        None => {}
+                        Some(Err(e)) => eprintln!("Background evolution stopped: {}", e),
+                        Some(Ok((new_pop, g1, g2))) => {
+                            current_gen = new_pop.generation;
+                            current_best = new_pop.best_fitness;
+                            champion_elo = new_pop.champion.as_ref().map(|c| c.rating);
+                            champion_badges = champion_badges_of(&new_pop);
+                            session.update(&new_pop);
+                            session.memory_bytes += fitness_history.capacity()
+                                * size_of::<GenerationStats>()
+                                + showcase_replay.memory_bytes()
+                                + event_log.memory_bytes();
+                            if let Some(dashboard) = &dashboard {
+                                dashboard.record_generation(&new_pop);
+                            }
+                            ladder =
+                                Difficulty::ALL.map(|d| d.opponent(&new_pop.hall_of_fame, &g1));
+                            next_genomes = Some([g1, g2]);
+                            println!("{}", generation_log(current_gen, current_best, &session));
+                            save_checkpoint(&new_pop, &options);
+                            // A generation in flight at the last shutdown is done now
+                            if let Some(path) = &options.checkpoint {
+                                let _ = std::fs::remove_file(in_flight_path(path));
+                            }
+                            record_stats(&new_pop, &mut stats_log);
+                            fitness_history.push(GenerationStats::of(&new_pop));
+                            thin_history(&mut fitness_history, options.history_cap);
+                            if new_pop.champion_changed {
+                                println!("{}", tr(Text::NewChampion));
+                            }
+                            log_first_badges(&new_pop);
+                            if new_pop.rollbacks > 0 {
+                                println!(
+                                    "{}",
+                                    tr_fill(
+                                        Text::RollbackLog,
+                                        &[("count", new_pop.rollbacks.to_string())]
+                                    )
+                                );
+                            }
+                            if new_pop.restarted {
+                                let window = new_pop.selection.restart_after;
+                                println!(
+                                    "{}",
+                                    tr_fill(Text::RestartLog, &[("window", window.to_string())])
+                                );
+                            }
+                            if let Some(surrogate) = &new_pop.surrogate {
+                                println!(
+                                    "{}",
+                                    tr_fill(
+                                        Text::SurrogateLog,
+                                        &[
+                                            ("accuracy", format!("{:.2}", surrogate.accuracy)),
+                                            ("factor", surrogate.oversample().to_string()),
+                                        ],
+                                    )
+                                );
+                            }
+                            if let Some(speciation) = &new_pop.speciation {
+                                let largest = speciation.species.iter().map(|s| s.size).max();
+                                println!(
+                                    "{}",
+                                    tr_fill(
+                                        Text::SpeciesLog,
+                                        &[
+                                            ("count", speciation.species.len().to_string()),
+                                            ("largest", largest.unwrap_or(0).to_string()),
+                                            ("culled", speciation.culled.to_string()),
+                                        ],
+                                    )
+                                );
+                            }
+                            if let Some(scores) = new_pop.bot_scores {
+                                let scores: Vec<String> = Bot::ALL
+                                    .iter()
+                                    .zip(scores)
+                                    .map(|(bot, s)| format!("{} {:.0}%", bot.name(), s * 100.0))
+                                    .collect();
+                                println!(
+                                    "{}",
+                                    tr_fill(
+                                        Text::BotBenchmarkLog,
+                                        &[("scores", scores.join(", "))]
+                                    )
+                                );
+                            }
+
+                            // Start next background evolution, unless paused
+                            if paused {
+                                parked = Some(new_pop);
+                            } else {
+                                evo_handle =
+                                    Some(spawn_evolution(new_pop, options.matches_per_frame));
+                            }
+                        }
+                    }
+
+                    // Start a new showcase match, with updated genomes once a series
+                    // is over; a pairing dashboard viewers voted for goes first, and
+                    // the pair it displaces comes back after it
+                    if series.is_over() {
+                        series = Series::default();
+                        let voted = dashboard.as_ref().and_then(|d| d.take_voted_pair());
+                        if let Some([a, b]) = voted {
+                            println!(
+                                "{}",
+                                tr_fill(
+                                    Text::VotedShowcaseLog,
+                                    &[
+                                        ("a", a.generation.to_string()),
+                                        ("b", b.generation.to_string()),
+                                    ]
+                                )
+                            );
+                            let displaced =
+                                std::mem::replace(&mut showcase_genomes, [a.genome, b.genome]);
+                            next_genomes.get_or_insert(displaced);
+                        } else if let Some(genomes) = next_genomes.take() {
+                            showcase_genomes = genomes;
+                        }
+                    }
+                    showcase_replay = Replay::new(options.layout.clone(), rng.gen());
+                    match_state = showcase_replay.initial_state();
+                    pilots = showcase_pilots(&showcase_genomes, ladder_opponent(&ladder, &options));
+                    fitness_tracker = FitnessTracker::new(fitness_weights.clone());
+                    event_log = EventLog::new();
+                    engagement = EngagementTimeline::default();
+                    ticker.start_match();
+                    if options.commentary {
+                        commentary.start_match(current_gen);
+                    }
+                    pressure.clear();
+                    end_timer = END_DELAY;
                 }
+            }
+        }
 
-                // Start a new showcase match (with current or updated genomes)
-                match_state = GameState::new_random(&mut rng);
-                end_timer = END_DELAY;
+        // Mirror progress into the terminal title so it stays visible while the window is hidden
+        let percent = (progress.fraction() * 100.0) as u32;
+        if shown_percent != Some(percent) {
+            shown_percent = Some(percent);
+            set_terminal_title(&progress_title(current_gen, percent, current_best));
+        }
+
+        // Follow the most interesting live match, staying with one for a while
+        // unless it ends. The showcase keeps running underneath, unseen.
+        if options.spectate {
+            spectate_timer -= dt;
+            let ended = spectated
+                .and_then(|worker| spectator.cell(worker).latest())
+                .is_none_or(|live| live.finished);
+            if spectate_timer <= 0.0 || ended {
+                if let Some(worker) = spectator.most_interesting() {
+                    spectated = Some(worker);
+                    spectate_timer = SPECTATE_DWELL;
+                }
             }
         }
+        let live = spectated
+            .filter(|_| options.spectate)
+            .and_then(|worker| Some((worker, spectator.cell(worker).latest()?)));
+        let shown = live.as_ref().map_or(&match_state, |(_, live)| &live.state);
+        if is_key_pressed(KeyCode::F2) {
+            photo.toggle(shown);
+        }
+        photo.handle_input(dt);
+        let shown = photo.frame().unwrap_or(shown);
+        let style = photo.style();
 
         // Render
         clear_background(BLACK);
-        render_arena();
-        render_projectiles(&match_state.projectiles);
-        render_ship(&match_state.ships[0], Color::new(0.0, 1.0, 0.4, 1.0));
-        render_ship(&match_state.ships[1], Color::new(0.4, 0.6, 1.0, 1.0));
-        render_hud(&match_state, current_gen, current_best);
+        if photo.is_active() {
+            set_camera(&photo.camera());
+        }
+        render_arena(style);
+        if show_pressure && live.is_none() {
+            render_pressure(&pressure);
+        }
+        if show_flow_field {
+            render_flow_field(&shown.flow_field, shown.time);
+        }
+        render_asteroids(&shown.asteroids, style, shown.arena);
+        render_pickups(&shown.pickups, style, shown.arena);
+        render_projectiles(&shown.projectiles, style, shown.arena);
+        render_ship(
+            &shown.ships[0],
+            Color::new(0.0, 1.0, 0.4, 1.0),
+            style,
+            shown.arena,
+        );
+        render_ship(
+            &shown.ships[1],
+            Color::new(0.4, 0.6, 1.0, 1.0),
+            style,
+            shown.arena,
+        );
+        if show_aim_assist {
+            render_aim_assist(shown, 0, 1);
+        }
+        // Photos show only the arena
+        if photo.is_active() {
+            set_default_camera();
+            photo.capture();
+            next_frame().await;
+            continue;
+        }
+        render_hud(
+            shown,
+            current_gen,
+            current_best,
+            champion_elo,
+            &champion_badges,
+            percent,
+        );
+        if let Some((worker, live)) = &live {
+            render_spectate_banner(live, *worker);
+        }
+        if show_fitness && live.is_none() {
+            render_fitness_breakdown(&fitness_tracker.breakdown(&match_state));
+        }
+        if show_brains && live.is_none() {
+            render_brains(&pilots, &match_state);
+        }
+        if paused {
+            render_paused_banner(parked.is_some());
+        }
+        if options.play {
+            render_play_banner();
+            render_ladder(options.difficulty, &player_record);
+        }
+        render_showcase_speed(&clock);
+        render_session_stats(&session);
+        if show_fitness_graph {
+            render_fitness_graph(&fitness_history);
+        }
+        if options.commentary {
+            commentary.render();
+        }
+        ticker.update(dt);
+        if show_ticker {
+            ticker.render();
+        }
 
-        if match_state.match_over {
-            render_match_result(&match_state);
+        if live.is_none() {
+            series.render([
+                Color::new(0.0, 1.0, 0.4, 1.0),
+                Color::new(0.4, 0.6, 1.0, 1.0),
+            ]);
+        }
+        if match_state.match_over && live.is_none() {
+            render_match_result(&match_state, options.play);
+            render_engagement(&engagement);
         }
 
         next_frame().await;
     }
 }
 
-fn render_arena() {
+fn render_arena(style: RenderStyle) {
     let border_color = Color::new(0.15, 0.15, 0.25, 1.0);
-    let t = 1.0;
+    let t = style.line_scale;
     draw_line(0.0, 0.0, ARENA_WIDTH, 0.0, t, border_color);
     draw_line(ARENA_WIDTH, 0.0, ARENA_WIDTH, ARENA_HEIGHT, t, border_color);
-    draw_line(ARENA_WIDTH, ARENA_HEIGHT, 0.0, ARENA_HEIGHT, t, border_color);
+    draw_line(
+        ARENA_WIDTH,
+        ARENA_HEIGHT,
+        0.0,
+        ARENA_HEIGHT,
+        t,
+        border_color,
+    );
     draw_line(0.0, ARENA_HEIGHT, 0.0, 0.0, t, border_color);
 }
 
-fn render_ship(ship: &Ship, color: Color) {
+/// Shade each grid cell by which ship's projectiles have recently dominated it
+fn render_pressure(grid: &PressureGrid) {
+    for row in 0..grid.rows {
+        for col in 0..grid.cols {
+            let [green, blue] = grid.cell(col, row);
+            let total = green + blue;
+            if total < 0.01 {
+                continue;
+            }
+            // Hue from the owner balance, opacity from the overall amount of fire
+            let share = green / total;
+            let alpha = (total * 0.15).min(0.35);
+            let color = Color::new(
+                0.4 * (1.0 - share),
+                0.6 + 0.4 * share,
+                1.0 - 0.6 * share,
+                alpha,
+            );
+            draw_rectangle(
+                col as f32 * PRESSURE_CELL_SIZE,
+                row as f32 * PRESSURE_CELL_SIZE,
+                PRESSURE_CELL_SIZE,
+                PRESSURE_CELL_SIZE,
+                color,
+            );
+        }
+    }
+}
+
+/// Debug view of the flow field: a grid of short lines pointing along the local force
+fn render_flow_field(field: &FlowField, time: f32) {
+    if *field == FlowField::None {
+        return;
+    }
+    let color = Color::new(0.3, 0.3, 0.5, 0.6);
+    let mut y = FLOW_GRID_SPACING / 2.0;
+    while y < ARENA_HEIGHT {
+        let mut x = FLOW_GRID_SPACING / 2.0;
+        while x < ARENA_WIDTH {
+            let (ax, ay) = field.force_at(ArenaSize::default(), x, y, time);
+            // Scale so that a 100 px/s^2 push spans most of a grid cell
+            let scale = FLOW_GRID_SPACING * 0.6 / 100.0;
+            draw_line(x, y, x + ax * scale, y + ay * scale, 1.0, color);
+            draw_circle(x, y, 1.0, color);
+            x += FLOW_GRID_SPACING;
+        }
+        y += FLOW_GRID_SPACING;
+    }
+}
+
+fn render_ship(ship: &Ship, color: Color, style: RenderStyle, arena: ArenaSize) {
     if !ship.alive {
-        render_explosion(ship.x, ship.y, color);
+        render_explosion(ship.x, ship.y, color, style, arena);
         return;
     }
+    let color = if ship.cloaked {
+        Color::new(color.r, color.g, color.b, 0.25)
+    } else {
+        color
+    };
 
     let cos = ship.rotation.cos();
     let sin = ship.rotation.sin();
+    let speed = (ship.vx * ship.vx + ship.vy * ship.vy).sqrt();
+    for (x, y) in wrap_copies(ship.x, ship.y, SHIP_RADIUS * 1.5, arena) {
+        // Triangle vertices (nose forward)
+        let nose = (x + cos * SHIP_RADIUS, y + sin * SHIP_RADIUS);
+        let left = (
+            x + (-cos * 0.7 - sin * 0.7) * SHIP_RADIUS,
+            y + (-sin * 0.7 + cos * 0.7) * SHIP_RADIUS,
+        );
+        let right = (
+            x + (-cos * 0.7 + sin * 0.7) * SHIP_RADIUS,
+            y + (-sin * 0.7 - cos * 0.7) * SHIP_RADIUS,
+        );
 
-    // Triangle vertices (nose forward)
-    let nose = (ship.x + cos * SHIP_RADIUS, ship.y + sin * SHIP_RADIUS);
-    let left = (
-        ship.x + (-cos * 0.7 - sin * 0.7) * SHIP_RADIUS,
-        ship.y + (-sin * 0.7 + cos * 0.7) * SHIP_RADIUS,
-    );
-    let right = (
-        ship.x + (-cos * 0.7 + sin * 0.7) * SHIP_RADIUS,
-        ship.y + (-sin * 0.7 - cos * 0.7) * SHIP_RADIUS,
-    );
+        let t = 2.0 * style.line_scale;
+        if style.glow {
+            draw_circle(
+                x,
+                y,
+                SHIP_RADIUS * 2.0,
+                Color::new(color.r, color.g, color.b, 0.08),
+            );
+            let halo = Color::new(color.r, color.g, color.b, 0.2 * color.a);
+            draw_line(nose.0, nose.1, left.0, left.1, t * 3.0, halo);
+            draw_line(left.0, left.1, right.0, right.1, t * 3.0, halo);
+            draw_line(right.0, right.1, nose.0, nose.1, t * 3.0, halo);
+        }
+        draw_line(nose.0, nose.1, left.0, left.1, t, color);
+        draw_line(left.0, left.1, right.0, right.1, t, color);
+        draw_line(right.0, right.1, nose.0, nose.1, t, color);
 
-    let t = 2.0;
-    draw_line(nose.0, nose.1, left.0, left.1, t, color);
-    draw_line(left.0, left.1, right.0, right.1, t, color);
-    draw_line(right.0, right.1, nose.0, nose.1, t, color);
+        // Draw thrust flame when moving fast enough
+        if speed > 30.0 {
+            let tail = (x - cos * SHIP_RADIUS * 1.3, y - sin * SHIP_RADIUS * 1.3);
+            let flame_color = Color::new(1.0, 0.6, 0.1, 0.7 * color.a);
+            let t = 1.5 * style.line_scale;
+            draw_line(left.0, left.1, tail.0, tail.1, t, flame_color);
+            draw_line(right.0, right.1, tail.0, tail.1, t, flame_color);
+        }
 
-    // Draw thrust flame when moving fast enough
-    let speed = (ship.vx * ship.vx + ship.vy * ship.vy).sqrt();
-    if speed > 30.0 {
-        let tail = (
-            ship.x - cos * SHIP_RADIUS * 1.3,
-            ship.y - sin * SHIP_RADIUS * 1.3,
-        );
-        let flame_color = Color::new(1.0, 0.6, 0.1, 0.7);
-        draw_line(left.0, left.1, tail.0, tail.1, 1.5, flame_color);
-        draw_line(right.0, right.1, tail.0, tail.1, 1.5, flame_color);
+        // Raised deflector: a bubble around the hull
+        if ship.deflecting {
+            let bubble = Color::new(0.8, 0.5, 1.0, 0.6);
+            draw_circle_lines(x, y, SHIP_RADIUS * 1.5, 1.5 * style.line_scale, bubble);
+        }
+    }
+}
+
+/// Lead-pursuit aim point and lock-on reticle for `shooter` against `target`
+fn render_aim_assist(state: &GameState, shooter: usize, target: usize) {
+    let (s, t) = (&state.ships[shooter], &state.ships[target]);
+    if !s.alive || !t.alive || t.cloaked {
+        return;
+    }
+    let Some(intercept) = lead_intercept(state, shooter, target) else {
+        return;
+    };
+    let in_range = intercept.time <= PROJECTILE_LIFETIME;
+    let error = (intercept.aim_angle - s.rotation)
+        .sin()
+        .atan2((intercept.aim_angle - s.rotation).cos());
+    let locked = in_range && error.abs() < LOCK_TOLERANCE;
+
+    // Aim point: small diamond where the shot would meet the target
+    let aim_color = if in_range {
+        Color::new(1.0, 0.9, 0.3, 0.8)
+    } else {
+        Color::new(0.5, 0.5, 0.5, 0.5)
+    };
+    let r = 5.0;
+    for (ax, ay) in wrap_copies(intercept.x, intercept.y, r, state.arena) {
+        draw_line(ax - r, ay, ax, ay - r, 1.0, aim_color);
+        draw_line(ax, ay - r, ax + r, ay, 1.0, aim_color);
+        draw_line(ax + r, ay, ax, ay + r, 1.0, aim_color);
+        draw_line(ax, ay + r, ax - r, ay, 1.0, aim_color);
+    }
+
+    // Reticle around the target, closing in and turning red when locked
+    let (reticle_color, gap) = if locked {
+        (Color::new(1.0, 0.2, 0.2, 0.9), SHIP_RADIUS * 1.4)
+    } else {
+        (Color::new(1.0, 0.9, 0.3, 0.5), SHIP_RADIUS * 2.2)
+    };
+    for (x, y) in wrap_copies(t.x, t.y, gap + 6.0, state.arena) {
+        draw_circle_lines(x, y, gap, 1.0, reticle_color);
+        for k in 0..4 {
+            let angle = k as f32 * std::f32::consts::FRAC_PI_2;
+            let (c, sn) = (angle.cos(), angle.sin());
+            draw_line(
+                x + c * gap,
+                y + sn * gap,
+                x + c * (gap + 6.0),
+                y + sn * (gap + 6.0),
+                1.0,
+                reticle_color,
+            );
+        }
     }
 }
 
-fn render_explosion(x: f32, y: f32, color: Color) {
+fn render_explosion(x: f32, y: f32, color: Color, style: RenderStyle, arena: ArenaSize) {
     let faded = Color::new(color.r, color.g, color.b, 0.5);
-    for i in 0..6 {
-        let angle = i as f32 * std::f32::consts::PI / 3.0;
-        let len = 8.0 + (i as f32 * 3.0) % 7.0;
-        draw_line(
-            x,
-            y,
-            x + angle.cos() * len,
-            y + angle.sin() * len,
-            1.5,
-            faded,
-        );
+    for (x, y) in wrap_copies(x, y, 15.0, arena) {
+        for i in 0..6 {
+            let angle = i as f32 * std::f32::consts::PI / 3.0;
+            let len = 8.0 + (i as f32 * 3.0) % 7.0;
+            draw_line(
+                x,
+                y,
+                x + angle.cos() * len,
+                y + angle.sin() * len,
+                1.5 * style.line_scale,
+                faded,
+            );
+        }
+    }
+}
+
+fn render_asteroids(asteroids: &[Asteroid], style: RenderStyle, arena: ArenaSize) {
+    let color = Color::new(0.7, 0.7, 0.7, 1.0);
+    const VERTICES: u32 = 10;
+    for a in asteroids {
+        let radius = a.radius();
+        for (x, y) in wrap_copies(a.x, a.y, radius, arena) {
+            let point = |k: u32| {
+                // Per-vertex jitter derived from the shape seed gives each rock a fixed jagged outline
+                let jitter =
+                    (a.shape_seed.wrapping_mul(2_654_435_761).rotate_left(k * 3) % 100) as f32;
+                let r = radius * (0.75 + jitter / 400.0);
+                let angle = a.angle + k as f32 / VERTICES as f32 * std::f32::consts::TAU;
+                (x + angle.cos() * r, y + angle.sin() * r)
+            };
+            for k in 0..VERTICES {
+                let (x1, y1) = point(k);
+                let (x2, y2) = point((k + 1) % VERTICES);
+                draw_line(x1, y1, x2, y2, 1.5 * style.line_scale, color);
+            }
+        }
     }
 }
 
-fn render_projectiles(projectiles: &[Projectile]) {
+/// Ammo crates: a yellow square with a cross
+fn render_pickups(pickups: &[Pickup], style: RenderStyle, arena: ArenaSize) {
+    let color = Color::new(1.0, 0.85, 0.2, 0.9);
+    let r = PICKUP_RADIUS * 0.7;
+    let t = 1.5 * style.line_scale;
+    for p in pickups {
+        for (x, y) in wrap_copies(p.x, p.y, r, arena) {
+            draw_rectangle_lines(x - r, y - r, r * 2.0, r * 2.0, t, color);
+            draw_line(x - r * 0.5, y, x + r * 0.5, y, t, color);
+            draw_line(x, y - r * 0.5, x, y + r * 0.5, t, color);
+        }
+    }
+}
+
+fn render_projectiles(projectiles: &[Projectile], style: RenderStyle, arena: ArenaSize) {
     for p in projectiles {
-        let color = if p.owner == 0 {
-            Color::new(0.0, 1.0, 0.4, 0.9)
-        } else {
-            Color::new(0.4, 0.6, 1.0, 0.9)
+        let color = Color {
+            a: 0.9,
+            ..SHIP_COLORS[p.owner % SHIP_COLORS.len()]
         };
-        draw_circle(p.x, p.y, PROJECTILE_RADIUS, color);
         // Small tail
         let speed = (p.vx * p.vx + p.vy * p.vy).sqrt().max(1.0);
         let dx = -p.vx / speed * 4.0;
         let dy = -p.vy / speed * 4.0;
-        draw_line(
-            p.x,
-            p.y,
-            p.x + dx,
-            p.y + dy,
-            1.0,
-            Color::new(color.r, color.g, color.b, 0.4),
-        );
+        for (x, y) in wrap_copies(p.x, p.y, 4.0, arena) {
+            if style.glow {
+                let halo = Color::new(color.r, color.g, color.b, 0.15);
+                draw_circle(x, y, PROJECTILE_RADIUS * 3.0, halo);
+            }
+            draw_circle(x, y, PROJECTILE_RADIUS * style.line_scale.sqrt(), color);
+            draw_line(
+                x,
+                y,
+                x + dx,
+                y + dy,
+                style.line_scale,
+                Color::new(color.r, color.g, color.b, 0.4),
+            );
+        }
     }
 }
 
-fn render_hud(state: &GameState, generation: usize, best_fitness: f32) {
+fn render_hud(
+    state: &GameState,
+    generation: usize,
+    best_fitness: f32,
+    champion_elo: Option<f32>,
+    champion_badges: &[Badge],
+    eval_percent: u32,
+) {
     let text_color = Color::new(0.5, 0.5, 0.5, 1.0);
     draw_text(
-        &format!("Gen: {}  Best: {:.0}", generation, best_fitness),
+        &tr_fill(
+            Text::HudGeneration,
+            &[
+                ("gen", generation.to_string()),
+                ("best", format!("{:.0}", best_fitness)),
+                ("percent", eval_percent.to_string()),
+            ],
+        ),
         10.0,
         20.0,
         20.0,
         text_color,
     );
     draw_text(
-        &format!(
-            "Time: {:.1}s / {:.0}s",
-            state.time.min(MATCH_DURATION),
-            MATCH_DURATION
+        &tr_fill(
+            Text::HudTime,
+            &[
+                ("time", format!("{:.1}", state.time.min(MATCH_DURATION))),
+                ("duration", format!("{:.0}", MATCH_DURATION)),
+            ],
         ),
         10.0,
         40.0,
         20.0,
         text_color,
     );
+    if let Some(elo) = champion_elo {
+        draw_text(
+            &tr_fill(Text::HudChampionElo, &[("elo", format!("{:.0}", elo))]),
+            10.0,
+            60.0,
+            20.0,
+            text_color,
+        );
+    }
+    if !champion_badges.is_empty() {
+        let names: Vec<&str> = champion_badges.iter().map(|&b| badge_label(b)).collect();
+        draw_text(
+            &tr_fill(Text::HudChampionBadges, &[("badges", names.join(", "))]),
+            10.0,
+            80.0,
+            20.0,
+            text_color,
+        );
+    }
 
     let green = Color::new(0.0, 1.0, 0.4, 1.0);
     let blue = Color::new(0.4, 0.6, 1.0, 1.0);
 
-    draw_text(
-        &format!(
-            "Green - Shots: {} Hits: {}",
-            state.ships[0].shots_fired, state.ships[0].hits_scored
+    let lines = [
+        (Text::HudGreenShots, ARENA_HEIGHT - 30.0, green),
+        (Text::HudBlueShots, ARENA_HEIGHT - 10.0, blue),
+    ];
+    // Class names only matter once some ship flies something other than the classic ship
+    let classes_differ = state.ships.iter().any(|s| s.class != ShipClass::Standard);
+    for (ship, (key, y, color)) in state.ships.iter().zip(lines) {
+        let text = tr_fill(
+            key,
+            &[
+                ("shots", ship.shots_fired.to_string()),
+                ("hits", ship.hits_scored.to_string()),
+            ],
+        );
+        draw_text(&text, 10.0, y, 18.0, color);
+        render_status_bars(ship, &state.physics, 230.0, y - 12.0, color);
+        if classes_differ {
+            draw_text(tr(class_text(ship.class)), 360.0, y, 18.0, color);
+        }
+    }
+}
+
+/// Each genome pilot's network as it reads the current state, green's on
+/// the left and blue's on the right; a human pilot has none to show
+fn render_brains(pilots: &[Box<dyn Controller>; 2], state: &GameState) {
+    let (width, height) = (440.0, 340.0);
+    let areas = [
+        Rect::new(10.0, 150.0, width, height),
+        Rect::new(ARENA_WIDTH - width - 10.0, 150.0, width, height),
+    ];
+    let colors = [
+        Color::new(0.0, 1.0, 0.4, 1.0),
+        Color::new(0.4, 0.6, 1.0, 1.0),
+    ];
+    for (i, pilot) in pilots.iter().enumerate() {
+        if let Some((genome, network)) = pilot.brain() {
+            let layers = genome.activations(&genome.sensors(state, i), network);
+            render_brain(genome, &layers, areas[i], colors[i]);
+        }
+    }
+}
+
+fn class_text(class: ShipClass) -> Text {
+    match class {
+        ShipClass::Standard => Text::ClassStandard,
+        ShipClass::Interceptor => Text::ClassInterceptor,
+        ShipClass::Gunship => Text::ClassGunship,
+        ShipClass::Sniper => Text::ClassSniper,
+    }
+}
+
+/// Shield, hull and ammo bars, each as a fraction of what ships start with;
+/// bars for unlimited or disabled resources are left out
+fn render_status_bars(ship: &Ship, physics: &PhysicsOptions, x: f32, y: f32, hull_color: Color) {
+    const WIDTH: f32 = 120.0;
+    const HEIGHT: f32 = 4.0;
+    let shield_color = Color::new(0.3, 0.9, 1.0, 1.0);
+    let ammo_color = Color::new(1.0, 0.85, 0.2, 1.0);
+    let energy_color = Color::new(0.8, 0.5, 1.0, 1.0);
+    // A hull that dies to the first hit is not worth a bar
+    let hull = ship.starting_hull(physics);
+    let hull = if hull > PROJECTILE_DAMAGE { hull } else { 0.0 };
+    let bars = [
+        (ship.shield, physics.shield, shield_color),
+        (ship.hull, hull, hull_color),
+        (ship.ammo as f32, physics.ammo as f32, ammo_color),
+        (
+            ship.energy,
+            physics.uses_energy() as u8 as f32,
+            energy_color,
         ),
-        10.0,
-        ARENA_HEIGHT - 30.0,
+    ];
+    let shown = bars.into_iter().filter(|&(_, max, _)| max > 0.0);
+    for (row, (points, max, color)) in shown.enumerate() {
+        let top = y + row as f32 * (HEIGHT + 1.0);
+        let faded = Color::new(color.r, color.g, color.b, 0.25);
+        draw_rectangle(x, top, WIDTH, HEIGHT, faded);
+        draw_rectangle(
+            x,
+            top,
+            WIDTH * (points / max).clamp(0.0, 1.0),
+            HEIGHT,
+            color,
+        );
+    }
+}
+
+/// Live per-component fitness for both ships, using the same formulas as evaluation
+fn render_fitness_breakdown(breakdown: &[FitnessBreakdown; 2]) {
+    let colors = [
+        Color::new(0.0, 1.0, 0.4, 1.0),
+        Color::new(0.4, 0.6, 1.0, 1.0),
+    ];
+    let top = ARENA_HEIGHT - 220.0;
+    for (i, (fitness, color)) in breakdown.iter().zip(colors).enumerate() {
+        let x = 10.0 + i as f32 * 160.0;
+        let mut y = top;
+        for (label, value) in fitness.components() {
+            let label = fitness_label(label);
+            draw_text(&format!("{:<10}{:>6.1}", label, value), x, y, 16.0, color);
+            y += 16.0;
+        }
+        draw_text(
+            &format!("{:<10}{:>6.1}", tr(Text::FitnessTotal), fitness.total()),
+            x,
+            y + 4.0,
+            18.0,
+            color,
+        );
+    }
+}
+
+fn render_paused_banner(idle: bool) {
+    let text = tr(if idle {
+        Text::EvolutionPaused
+    } else {
+        Text::EvolutionPausing
+    });
+    draw_text(text, 10.0, 60.0, 18.0, Color::new(1.0, 0.6, 0.1, 1.0));
+}
+
+fn render_play_banner() {
+    let text = tr(Text::PlayBanner);
+    let width = measure_text(text, None, 18, 1.0).width;
+    draw_text(
+        text,
+        (ARENA_WIDTH - width) / 2.0,
+        60.0,
         18.0,
-        green,
+        Color::new(0.0, 1.0, 0.4, 1.0),
     );
+}
+
+/// Difficulty menu of play mode with the player's record at the chosen rung
+fn render_ladder(difficulty: Difficulty, record: &PlayerRecord) {
+    let tally = record.tally(difficulty);
+    let text = tr_fill(
+        Text::LadderStatus,
+        &[
+            ("difficulty", tr(difficulty_label(difficulty)).to_string()),
+            ("wins", tally.wins.to_string()),
+            ("losses", tally.losses.to_string()),
+            ("draws", tally.draws.to_string()),
+        ],
+    );
+    let width = measure_text(&text, None, 18, 1.0).width;
     draw_text(
-        &format!(
-            "Blue  - Shots: {} Hits: {}",
-            state.ships[1].shots_fired, state.ships[1].hits_scored
-        ),
-        10.0,
-        ARENA_HEIGHT - 10.0,
+        &text,
+        (ARENA_WIDTH - width) / 2.0,
+        80.0,
+        18.0,
+        Color::new(0.0, 1.0, 0.4, 1.0),
+    );
+}
+
+fn difficulty_label(difficulty: Difficulty) -> Text {
+    match difficulty {
+        Difficulty::Easy => Text::DifficultyEasy,
+        Difficulty::Medium => Text::DifficultyMedium,
+        Difficulty::Hard => Text::DifficultyHard,
+    }
+}
+
+/// Turns frame time into fixed showcase steps at an adjustable speed, so
+/// the match plays the same whatever the frame rate
+struct ShowcaseClock {
+    /// Index into SHOWCASE_SPEEDS
+    speed: usize,
+    paused: bool,
+    /// Steps owed to the next frame while paused
+    single_steps: usize,
+    /// Scaled frame time not yet simulated
+    pending: f32,
+    /// Whether close calls may cue slow motion
+    slow_motion: bool,
+    /// Time dilation on top of the speed: 1 normally, down to SLOW_MOTION_SCALE
+    dilation: f32,
+    /// Wall-clock seconds of slow motion still owed to the last cue
+    slow_motion_hold: f32,
+}
+
+impl ShowcaseClock {
+    fn new(slow_motion: bool) -> Self {
+        ShowcaseClock {
+            speed: SHOWCASE_SPEEDS.iter().position(|&s| s == 1.0).unwrap_or(0),
+            paused: false,
+            single_steps: 0,
+            pending: 0.0,
+            slow_motion,
+            dilation: 1.0,
+            slow_motion_hold: 0.0,
+        }
+    }
+
+    /// Hold slow motion for a while, if it is enabled
+    fn cue_slow_motion(&mut self) {
+        if self.slow_motion {
+            self.slow_motion_hold = SLOW_MOTION_HOLD;
+        }
+    }
+
+    fn speed(&self) -> f32 {
+        SHOWCASE_SPEEDS[self.speed]
+    }
+
+    /// Move `delta` levels through SHOWCASE_SPEEDS, stopping at either end
+    fn change_speed(&mut self, delta: isize) {
+        self.speed = self
+            .speed
+            .saturating_add_signed(delta)
+            .min(SHOWCASE_SPEEDS.len() - 1);
+    }
+
+    /// Pause, then advance a single step
+    fn step_once(&mut self) {
+        self.paused = true;
+        self.single_steps += 1;
+    }
+
+    /// Steps to simulate for a frame that took `frame_dt` seconds
+    fn steps(&mut self, frame_dt: f32) -> usize {
+        if self.paused {
+            self.pending = 0.0;
+            return std::mem::take(&mut self.single_steps);
+        }
+        // Dilation only changes how much time is owed; physics still runs in
+        // whole SIM_DT steps, so a slowed match plays out exactly the same
+        self.slow_motion_hold = (self.slow_motion_hold - frame_dt).max(0.0);
+        let target = if self.slow_motion_hold > 0.0 {
+            SLOW_MOTION_SCALE
+        } else {
+            1.0
+        };
+        let ramp = SLOW_MOTION_RAMP * frame_dt;
+        self.dilation += (target - self.dilation).clamp(-ramp, ramp);
+        self.pending += frame_dt * self.speed() * self.dilation;
+        let steps = (self.pending / SIM_DT) as usize;
+        self.pending -= steps as f32 * SIM_DT;
+        steps
+    }
+}
+
+/// What the live match on screen is: generation, genome and opponent
+fn render_spectate_banner(live: &LiveMatch, worker: usize) {
+    let kind = tr(match live.kind {
+        MatchKind::Curriculum => Text::MatchCurriculum,
+        MatchKind::Pool => Text::MatchPool,
+        MatchKind::Archive => Text::MatchArchive,
+    });
+    let text = tr_fill(
+        Text::SpectateBanner,
+        &[
+            ("gen", live.generation.to_string()),
+            ("genome", live.genome.to_string()),
+            ("kind", kind.to_string()),
+            ("worker", worker.to_string()),
+        ],
+    );
+    let width = measure_text(&text, None, 18, 1.0).width;
+    draw_text(
+        &text,
+        (ARENA_WIDTH - width) / 2.0,
+        60.0,
         18.0,
-        blue,
+        Color::new(1.0, 0.3, 0.3, 1.0),
+    );
+}
+
+/// Playback speed below the HUD, unless the match runs at real time
+fn render_showcase_speed(clock: &ShowcaseClock) {
+    let text = if clock.paused {
+        tr(Text::ShowcasePaused).to_string()
+    } else if clock.speed() != 1.0 {
+        tr_fill(Text::ShowcaseSpeed, &[("speed", clock.speed().to_string())])
+    } else {
+        return;
+    };
+    draw_text(&text, 10.0, 80.0, 18.0, Color::new(0.6, 0.6, 0.6, 1.0));
+}
+
+/// The player's ship: arrows to thrust and turn, Space to fire, Down to cloak
+struct KeyboardPilot;
+
+impl Controller for KeyboardPilot {
+    fn act(&mut self, _state: &GameState, _ship_idx: usize) -> [f32; ACTION_SIZE] {
+        let key = |code| if is_key_down(code) { 1.0 } else { 0.0 };
+        [
+            key(KeyCode::Up),
+            key(KeyCode::Left),
+            key(KeyCode::Right),
+            key(KeyCode::Space),
+            key(KeyCode::Down),
+        ]
+    }
+}
+
+/// Pilots of the showcase ships: the two best genomes, or the player
+/// against `opponent` in play mode. Networks start over whenever the
+/// pilots change.
+fn showcase_pilots(genomes: &[Genome; 2], opponent: Option<&Genome>) -> [Box<dyn Controller>; 2] {
+    match opponent {
+        Some(opponent) => [
+            Box::new(KeyboardPilot),
+            Box::new(GenomeController::new(opponent.clone())),
+        ],
+        None => [
+            Box::new(GenomeController::new(genomes[0].clone())),
+            Box::new(GenomeController::new(genomes[1].clone())),
+        ],
+    }
+}
+
+/// The ladder opponent at the chosen rung in play mode, else None
+fn ladder_opponent<'a>(ladder: &'a [Genome; 3], options: &Options) -> Option<&'a Genome> {
+    options.play.then(|| &ladder[options.difficulty as usize])
+}
+
+fn render_session_stats(session: &SessionStats) {
+    let text = session.summary();
+    let width = measure_text(&text, None, 16, 1.0).width;
+    draw_text(
+        &text,
+        ARENA_WIDTH - width - 10.0,
+        20.0,
+        16.0,
+        Color::new(0.4, 0.4, 0.4, 1.0),
     );
 }
 
-fn render_match_result(state: &GameState) {
-    let msg = match state.winner {
-        Some(0) => "GREEN WINS!",
-        Some(1) => "BLUE WINS!",
-        _ => "DRAW!",
+/// Best and mean fitness per generation as a line chart below the session
+/// stats, scaled to the range seen so far
+fn render_fitness_graph(history: &[GenerationStats]) {
+    let (width, height) = FITNESS_GRAPH_SIZE;
+    let left = ARENA_WIDTH - width - 10.0;
+    let top = 32.0;
+    draw_rectangle(left, top, width, height, Color::new(0.0, 0.0, 0.0, 0.6));
+    draw_rectangle_lines(
+        left,
+        top,
+        width,
+        height,
+        1.0,
+        Color::new(0.3, 0.3, 0.3, 1.0),
+    );
+
+    let (low, high) = history
+        .iter()
+        .flat_map(|s| [s.best, s.mean])
+        .filter(|v| v.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+    let span = (high - low).max(1.0);
+    let step = width / (history.len().max(2) - 1) as f32;
+    let point = |i: usize, value: f32| {
+        let y = top + height - 4.0 - (value - low) / span * (height - 20.0);
+        (left + i as f32 * step, y)
     };
+    let best = Color::new(0.0, 1.0, 0.4, 1.0);
+    let mean = Color::new(1.0, 0.6, 0.1, 1.0);
+    for (i, pair) in history.windows(2).enumerate() {
+        let lines = [
+            (pair[0].best, pair[1].best, best),
+            (pair[0].mean, pair[1].mean, mean),
+        ];
+        for (from, to, color) in lines {
+            let (x1, y1) = point(i, from);
+            let (x2, y2) = point(i + 1, to);
+            draw_line(x1, y1, x2, y2, 1.5, color);
+        }
+    }
+
+    if let Some(last) = history.last() {
+        let text = tr_fill(
+            Text::FitnessGraphLegend,
+            &[
+                ("best", format!("{:.0}", last.best)),
+                ("mean", format!("{:.0}", last.mean)),
+            ],
+        );
+        draw_text(
+            &text,
+            left + 6.0,
+            top + 14.0,
+            16.0,
+            Color::new(0.6, 0.6, 0.6, 1.0),
+        );
+    }
+}
+
+/// Engagement timeline of the finished match in the strip below the arena,
+/// one bar per second on the full match duration, stalled seconds dimmed
+fn render_engagement(timeline: &EngagementTimeline) {
+    let intensity = timeline.intensity();
+    let peak = intensity.iter().copied().fold(1.0f32, f32::max);
+    let top = ARENA_HEIGHT + 22.0;
+    let height = ENGAGEMENT_STRIP_HEIGHT - 26.0;
+    let bar_width = ARENA_WIDTH / MATCH_DURATION.ceil();
+    for (i, &value) in intensity.iter().enumerate() {
+        let bar = (value / peak * height).max(1.0);
+        let color = if value < STALL_INTENSITY {
+            Color::new(0.35, 0.35, 0.35, 1.0)
+        } else {
+            Color::new(1.0, 0.6, 0.1, 1.0)
+        };
+        draw_rectangle(
+            i as f32 * bar_width + 1.0,
+            top + height - bar,
+            bar_width - 2.0,
+            bar,
+            color,
+        );
+    }
+    let text = tr_fill(
+        Text::EngagementSummary,
+        &[(
+            "stalled",
+            format!("{:.0}", timeline.stall_fraction() * 100.0),
+        )],
+    );
+    draw_text(
+        &text,
+        10.0,
+        ARENA_HEIGHT + 16.0,
+        16.0,
+        Color::new(0.6, 0.6, 0.6, 1.0),
+    );
+}
+
+/// Window playback of a saved replay: Space pauses, Period steps one tick
+/// while paused, Left and Right seek, Escape quits. B bookmarks the current
+/// moment, N types a note for it and the bracket keys jump between
+/// bookmarks; notes are saved next to the replay at `path` as they are made.
+/// While paused, 1 or 2 branches off from the current moment with the green
+/// or blue ship flown by `branch_genome`, or by the keyboard without one.
+async fn play_replay(
+    replay: Replay,
+    mut notes: ReplayNotes,
+    path: &str,
+    branch_genome: Option<Genome>,
+) {
+    let duration = replay.duration();
+    let mut player = ReplayPlayer::new(replay);
+    let mut clock = 0.0;
+    let mut paused = false;
+    // Text of the note being typed; playback keys are off while typing
+    let mut typing: Option<String> = None;
+    loop {
+        if let Some(text) = &mut typing {
+            while let Some(c) = get_char_pressed() {
+                if !c.is_control() {
+                    text.push(c);
+                }
+            }
+            if is_key_pressed(KeyCode::Backspace) {
+                text.pop();
+            }
+            if is_key_pressed(KeyCode::Enter) {
+                if !text.trim().is_empty() {
+                    notes.add(player.state().time, AnnotationKind::Note, text);
+                    save_replay_notes(&notes, path);
+                }
+                typing = None;
+            } else if is_key_pressed(KeyCode::Escape) {
+                typing = None;
+            }
+        } else {
+            if is_key_pressed(KeyCode::Escape) {
+                break;
+            }
+            if is_key_pressed(KeyCode::B) {
+                notes.add(player.state().time, AnnotationKind::Bookmark, "");
+                save_replay_notes(&notes, path);
+            }
+            if is_key_pressed(KeyCode::N) {
+                // Drop the N itself and anything typed before
+                while get_char_pressed().is_some() {}
+                typing = Some(String::new());
+                paused = true;
+            }
+            let time = player.state().time;
+            let jump = if is_key_pressed(KeyCode::LeftBracket) {
+                notes.previous_bookmark(time).or(Some(0.0))
+            } else if is_key_pressed(KeyCode::RightBracket) {
+                notes.next_bookmark(time)
+            } else {
+                None
+            };
+            if let Some(target) = jump {
+                clock = target;
+                player.seek(clock);
+            }
+        }
+        if typing.is_none() && is_key_pressed(KeyCode::Space) {
+            paused = !paused;
+        }
+        if typing.is_none() && is_key_pressed(KeyCode::Left) {
+            clock = (player.state().time - REPLAY_SEEK_SECONDS).max(0.0);
+            player.seek(clock);
+        }
+        if typing.is_none() && is_key_pressed(KeyCode::Right) {
+            clock = player.state().time + REPLAY_SEEK_SECONDS;
+            player.seek(clock);
+        }
+        if paused {
+            if typing.is_none() && is_key_pressed(KeyCode::Period) {
+                player.step();
+            }
+            let takeover = if typing.is_some() {
+                None
+            } else if is_key_pressed(KeyCode::Key1) {
+                Some(0)
+            } else if is_key_pressed(KeyCode::Key2) {
+                Some(1)
+            } else {
+                None
+            };
+            if let Some(ship) = takeover {
+                let pilot: Box<dyn Controller> = match &branch_genome {
+                    Some(genome) => Box::new(GenomeController::new(genome.clone())),
+                    None => Box::new(KeyboardPilot),
+                };
+                let mut pilots = [None, None];
+                pilots[ship] = Some(pilot);
+                fly_branch(player.branch(), pilots, ship).await;
+            }
+            clock = player.state().time;
+        } else {
+            // Steps rarely line up with frames; never seek behind the last step
+            clock += get_frame_time();
+            player.seek(clock.max(player.state().time));
+        }
+
+        let state = player.state();
+        render_replay_state(state);
+        let status = tr_fill(
+            Text::ReplayStatus,
+            &[
+                ("time", format!("{:.1}", state.time)),
+                ("duration", format!("{:.1}", duration)),
+            ],
+        );
+        draw_text(&status, 10.0, 20.0, 20.0, Color::new(0.5, 0.5, 0.5, 1.0));
+        draw_text(
+            tr(Text::ReplayNotesHelp),
+            10.0,
+            38.0,
+            16.0,
+            Color::new(0.5, 0.5, 0.5, 1.0),
+        );
+        if paused {
+            draw_text(
+                tr(Text::ReplayPaused),
+                10.0,
+                58.0,
+                18.0,
+                Color::new(1.0, 0.6, 0.1, 1.0),
+            );
+            draw_text(
+                tr(Text::ReplayBranchHelp),
+                10.0,
+                76.0,
+                16.0,
+                Color::new(0.5, 0.5, 0.5, 1.0),
+            );
+        }
+        render_replay_notes(&notes, state.time, duration);
+        if let Some(text) = &typing {
+            let prompt = tr_fill(Text::ReplayNotePrompt, &[("text", text.clone())]);
+            draw_text(
+                &prompt,
+                10.0,
+                ARENA_HEIGHT - 30.0,
+                20.0,
+                Color::new(1.0, 1.0, 0.6, 1.0),
+            );
+        }
+        if state.match_over {
+            render_match_result(state, false);
+        }
+        next_frame().await;
+    }
+}
+
+/// Fly a branch of a replay in real time until Escape returns to the replay;
+/// S saves the branch, recording included, to BRANCH_REPLAY_PATH
+async fn fly_branch(mut branch: Branch, mut pilots: [Option<Box<dyn Controller>>; 2], ship: usize) {
+    let from = branch.state().time;
+    let mut clock = from;
+    loop {
+        if is_key_pressed(KeyCode::Escape) {
+            break;
+        }
+        if is_key_pressed(KeyCode::S) {
+            match branch.replay().save(BRANCH_REPLAY_PATH) {
+                Ok(()) => println!("Saved branch replay to {}", BRANCH_REPLAY_PATH),
+                Err(e) => eprintln!("Failed to save branch replay: {}", e),
+            }
+        }
+        clock += get_frame_time();
+        while branch.state().time < clock {
+            let pilots = pilots
+                .each_mut()
+                .map(|pilot| pilot.as_deref_mut().map(|p| p as &mut dyn Controller));
+            if !branch.step(pilots) {
+                break;
+            }
+        }
+
+        let state = branch.state();
+        render_replay_state(state);
+        let ship_name = tr(if ship == 0 {
+            Text::ShipGreen
+        } else {
+            Text::ShipBlue
+        });
+        let status = tr_fill(
+            Text::ReplayBranchStatus,
+            &[
+                ("ship", ship_name.to_string()),
+                ("from", format!("{:.1}", from)),
+                ("time", format!("{:.1}", state.time)),
+                ("path", BRANCH_REPLAY_PATH.to_string()),
+            ],
+        );
+        draw_text(&status, 10.0, 20.0, 20.0, Color::new(1.0, 0.6, 0.1, 1.0));
+        if state.match_over {
+            render_match_result(state, false);
+        }
+        next_frame().await;
+    }
+}
+
+/// Window showcase of free-for-all melees among the `count` fittest genomes
+/// of `pop`, one after another, with their standings over all matches so
+/// far: melee points for placement, kills and assists, and wins. Escape quits.
+async fn play_melee(pop: &Population, count: usize, rng: &mut StdRng) {
+    let mut ranked: Vec<&Genome> = pop.genomes.iter().collect();
+    ranked.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    ranked.truncate(count);
+    let mut points = vec![0.0f32; ranked.len()];
+    let mut wins = vec![0usize; ranked.len()];
+    let mut round = 0;
+    loop {
+        round += 1;
+        let controllers = ranked
+            .iter()
+            .map(|&g| Box::new(GenomeController::new(g.clone())) as Box<dyn Controller>)
+            .collect();
+        let mut session = match MeleeSession::new(controllers, &pop.layout, rng) {
+            Ok(session) => session,
+            Err(e) => usage_error(&e.to_string()),
+        };
+        let mut clock = 0.0;
+        let mut end_timer = END_DELAY;
+        while end_timer > 0.0 {
+            if is_key_pressed(KeyCode::Escape) {
+                return;
+            }
+            let dt = get_frame_time().min(1.0 / 30.0);
+            if session.is_over() {
+                end_timer -= dt;
+            } else {
+                clock += dt;
+                while session.state().time < clock && session.step() {}
+                if session.is_over() {
+                    let result = session.result();
+                    for (total, score) in points.iter_mut().zip(&result.scores) {
+                        *total += score;
+                    }
+                    if let Some(winner) = result.winner {
+                        wins[winner] += 1;
+                    }
+                }
+            }
+
+            let state = session.state();
+            render_replay_state(state);
+            let status = tr_fill(
+                Text::MeleeStatus,
+                &[
+                    ("count", ranked.len().to_string()),
+                    ("round", round.to_string()),
+                    ("time", format!("{:.1}", state.time)),
+                ],
+            );
+            draw_text(&status, 10.0, 20.0, 20.0, Color::new(0.5, 0.5, 0.5, 1.0));
+            for (rank, color) in SHIP_COLORS.iter().enumerate().take(ranked.len()) {
+                let standing = tr_fill(
+                    Text::MeleeStanding,
+                    &[
+                        ("rank", (rank + 1).to_string()),
+                        ("points", format!("{:.1}", points[rank])),
+                        ("wins", wins[rank].to_string()),
+                    ],
+                );
+                draw_text(&standing, 10.0, 44.0 + 18.0 * rank as f32, 16.0, *color);
+            }
+            if session.is_over() {
+                let (msg, color) = match state.winner {
+                    Some(winner) => (
+                        tr_fill(Text::MeleeWinner, &[("rank", (winner + 1).to_string())]),
+                        SHIP_COLORS[winner],
+                    ),
+                    None => (tr(Text::Draw).to_string(), WHITE),
+                };
+                let width = measure_text(&msg, None, 40, 1.0).width;
+                draw_text(
+                    &msg,
+                    (ARENA_WIDTH - width) / 2.0,
+                    ARENA_HEIGHT / 2.0,
+                    40.0,
+                    color,
+                );
+            }
+            next_frame().await;
+        }
+    }
+}
+
+/// Arena, asteroids, pickups, projectiles and ships of a replayed or melee state
+fn render_replay_state(state: &GameState) {
+    let style = RenderStyle::default();
+    clear_background(BLACK);
+    render_arena(style);
+    render_asteroids(&state.asteroids, style, state.arena);
+    render_pickups(&state.pickups, style, state.arena);
+    render_projectiles(&state.projectiles, style, state.arena);
+    for (ship, color) in state.ships.iter().zip(SHIP_COLORS) {
+        render_ship(ship, color, style, state.arena);
+    }
+}
+
+fn save_replay_notes(notes: &ReplayNotes, path: &str) {
+    if let Err(e) = notes.save_for(path) {
+        eprintln!("Failed to save replay notes: {}", e);
+    }
+}
+
+/// Timeline along the bottom of the arena with a tick per bookmark, and the
+/// notes made in the last few seconds of play
+fn render_replay_notes(notes: &ReplayNotes, time: f32, duration: f32) {
+    let y = ARENA_HEIGHT - 8.0;
+    let width = ARENA_WIDTH - 20.0;
+    let x_at = |t: f32| 10.0 + width * (t / duration.max(f32::EPSILON)).clamp(0.0, 1.0);
+    let dim = Color::new(0.35, 0.35, 0.35, 1.0);
+    draw_line(10.0, y, 10.0 + width, y, 2.0, dim);
+    for bookmark in notes.bookmarks() {
+        let x = x_at(bookmark.time);
+        draw_line(x, y - 6.0, x, y + 6.0, 2.0, Color::new(1.0, 0.8, 0.2, 1.0));
+    }
+    draw_circle(x_at(time), y, 4.0, WHITE);
+
+    let showing = notes
+        .showing_at(time)
+        .filter(|a| a.kind == AnnotationKind::Note);
+    for (i, note) in showing.enumerate() {
+        draw_text(
+            &note.text,
+            10.0,
+            ARENA_HEIGHT - 60.0 - 22.0 * i as f32,
+            20.0,
+            Color::new(1.0, 1.0, 0.6, 1.0),
+        );
+    }
+}
+
+fn render_match_result(state: &GameState, play: bool) {
+    let msg = tr(match (state.winner, play) {
+        (Some(0), false) => Text::GreenWins,
+        (Some(1), false) => Text::BlueWins,
+        (Some(0), true) => Text::PlayerWins,
+        (Some(1), true) => Text::ChampionWins,
+        _ => Text::Draw,
+    });
 
     let color = match state.winner {
         Some(0) => Color::new(0.0, 1.0, 0.4, 1.0),
@@ -0,0 +1,163 @@
+use macroquad::prelude::*;
+
+use spaceship_duel::game::*;
+
+use crate::i18n::{tr, Text};
+
+/// Named starting configurations offered by the start-up menu
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Preset {
+    Classic,
+    Windy,
+    AsteroidField,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Classic, Preset::Windy, Preset::AsteroidField];
+
+    pub fn name(self) -> &'static str {
+        tr(match self {
+            Preset::Classic => Text::PresetClassic,
+            Preset::Windy => Text::PresetWindy,
+            Preset::AsteroidField => Text::PresetAsteroidField,
+        })
+    }
+
+    pub fn description(self) -> &'static str {
+        tr(match self {
+            Preset::Classic => Text::PresetClassicDescription,
+            Preset::Windy => Text::PresetWindyDescription,
+            Preset::AsteroidField => Text::PresetAsteroidFieldDescription,
+        })
+    }
+
+    pub fn layout(self) -> ArenaLayout {
+        match self {
+            Preset::Classic => ArenaLayout::default(),
+            Preset::Windy => ArenaLayout {
+                randomize_flow_field: true,
+                ..Default::default()
+            },
+            Preset::AsteroidField => ArenaLayout {
+                asteroid_count: 6,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// What the start-up menu starts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuChoice {
+    /// Train a new population in a preset arena
+    NewRun(Preset),
+    /// Resume the last run from its checkpoint
+    Continue,
+    /// Play back the last saved showcase replay
+    WatchReplay,
+    /// Fly against the champions of the last run, or of a new one if there is none
+    Challenge,
+}
+
+impl MenuChoice {
+    pub fn name(self) -> &'static str {
+        match self {
+            MenuChoice::NewRun(preset) => preset.name(),
+            MenuChoice::Continue => tr(Text::MenuContinue),
+            MenuChoice::WatchReplay => tr(Text::MenuWatchReplay),
+            MenuChoice::Challenge => tr(Text::MenuChallenge),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            MenuChoice::NewRun(preset) => preset.description(),
+            MenuChoice::Continue => tr(Text::MenuContinueDescription),
+            MenuChoice::WatchReplay => tr(Text::MenuWatchReplayDescription),
+            MenuChoice::Challenge => tr(Text::MenuChallengeDescription),
+        }
+    }
+}
+
+/// Show the start-up menu until the user picks an entry (arrow keys +
+/// Enter, or a number key). Continue and the replay are only offered when
+/// there is a last run or a saved replay to go back to.
+pub async fn choose(last_run: bool, saved_replay: bool) -> MenuChoice {
+    let mut choices: Vec<MenuChoice> = Preset::ALL.into_iter().map(MenuChoice::NewRun).collect();
+    if last_run {
+        choices.push(MenuChoice::Continue);
+    }
+    if saved_replay {
+        choices.push(MenuChoice::WatchReplay);
+    }
+    choices.push(MenuChoice::Challenge);
+    let mut selected = 0;
+    let number_keys = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+    ];
+
+    loop {
+        if is_key_pressed(KeyCode::Up) {
+            selected = (selected + choices.len() - 1) % choices.len();
+        }
+        if is_key_pressed(KeyCode::Down) {
+            selected = (selected + 1) % choices.len();
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            return choices[selected];
+        }
+        for (key, &choice) in number_keys.iter().zip(&choices) {
+            if is_key_pressed(*key) {
+                return choice;
+            }
+        }
+
+        clear_background(BLACK);
+        let title = tr(Text::MenuTitle);
+        let title_width = measure_text(title, None, 48, 1.0).width;
+        draw_text(
+            title,
+            (ARENA_WIDTH - title_width) / 2.0,
+            ARENA_HEIGHT * 0.3,
+            48.0,
+            WHITE,
+        );
+
+        let dim = Color::new(0.5, 0.5, 0.5, 1.0);
+        let highlight = Color::new(0.0, 1.0, 0.4, 1.0);
+        for (i, choice) in choices.iter().enumerate() {
+            let y = ARENA_HEIGHT * 0.4 + i as f32 * 60.0;
+            let color = if i == selected { highlight } else { dim };
+            let marker = if i == selected { ">" } else { " " };
+            draw_text(
+                &format!("{} {}. {}", marker, i + 1, choice.name()),
+                ARENA_WIDTH * 0.35,
+                y,
+                30.0,
+                color,
+            );
+            draw_text(
+                choice.description(),
+                ARENA_WIDTH * 0.35 + 40.0,
+                y + 22.0,
+                18.0,
+                dim,
+            );
+        }
+
+        draw_text(
+            tr(Text::MenuHint),
+            ARENA_WIDTH * 0.35,
+            ARENA_HEIGHT * 0.8,
+            18.0,
+            dim,
+        );
+
+        next_frame().await;
+    }
+}
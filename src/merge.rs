@@ -0,0 +1,123 @@
+use rand::Rng;
+
+use crate::archive::HallOfFame;
+use crate::error::{check_range, DuelError};
+use crate::evolution::*;
+use crate::genome::Genome;
+use crate::speciation::Speciation;
+use crate::surrogate::Surrogate;
+
+/// Pool two runs into the starting population of a new one: the fittest
+/// `share` of each population's genomes (at least one from each), topped up
+/// to `size` with mutated copies of them, then evaluated together. The new
+/// run keeps `base`'s rules and hyperparameters but starts at generation 0
+/// with its own seed, an empty hall of fame and no champion, since ratings
+/// and generation numbers of separate runs don't compare. Both populations
+/// must share one network shape.
+pub fn merge(
+    base: Population,
+    other: &Population,
+    share: f32,
+    size: usize,
+    rng: &mut impl Rng,
+) -> Result<Population, DuelError> {
+    check_range("merge share", share, 0.0, 1.0)?;
+    if other.network() != base.network() {
+        return Err(DuelError::InvalidValue {
+            what: "network of merged population",
+            value: other.network().spec(),
+        });
+    }
+    let mut pool = fittest(&base.genomes, share);
+    pool.extend(fittest(&other.genomes, share));
+    let kept = pool.len();
+    let size = size.max(kept).max(MIN_POPULATION_SIZE);
+    for k in 0..size - kept {
+        let mut child = pool[k % kept].clone();
+        child.age = 0;
+        child.mutate(&base.mutation, rng);
+        pool.push(child);
+    }
+
+    let mut merged = base;
+    merged.genomes = pool;
+    merged.reset_fitness();
+    merged.generation = 0;
+    merged.best_fitness = 0.0;
+    merged.peak_fitness = 0.0;
+    merged.peak_generation = 0;
+    merged.hall_of_fame = HallOfFame::default();
+    merged.champion = None;
+    merged.champion_changed = false;
+    merged.archive_win_rate = None;
+    merged.surrogate = merged
+        .surrogate
+        .as_ref()
+        .map(|_| Surrogate::new(&merged.network()));
+    merged.speciation = merged
+        .speciation
+        .as_ref()
+        .map(|s| Speciation::new(s.threshold, s.stagnation));
+    if let Some(novelty) = &mut merged.novelty {
+        novelty.archive.clear();
+    }
+    merged.objectives.clear();
+    merged.seed = rng.gen();
+    merged.matches_played = 0;
+    merged.simulated_seconds = 0.0;
+    merged.eval_seconds = 0.0;
+    merged.matches_per_genome.clear();
+    merged.bot_scores = None;
+    merged.evaluate(rng)?;
+    Ok(merged)
+}
+
+/// The fittest `share` of `genomes`, best first, and never none
+fn fittest(genomes: &[Genome], share: f32) -> Vec<Genome> {
+    let mut sorted = genomes.to_vec();
+    sorted.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    let keep = (sorted.len() as f32 * share).ceil() as usize;
+    sorted.truncate(keep.max(1));
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ArenaLayout;
+    use crate::genome::{Initialization, NetworkConfig};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn merging_keeps_both_runs_best_and_starts_a_new_run() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut a = Population::with_size(ArenaLayout::default(), 10, &mut rng);
+        let mut b = Population::with_size(ArenaLayout::default(), 10, &mut rng);
+        a.genomes[3].fitness = 50.0;
+        b.genomes[7].fitness = 60.0;
+        a.generation = 12;
+        let (best_a, best_b) = (a.genomes[3].weights.clone(), b.genomes[7].weights.clone());
+
+        let merged = merge(a, &b, 0.2, 12, &mut rng).unwrap();
+        assert_eq!(merged.genomes.len(), 12);
+        assert_eq!(merged.generation, 0);
+        for best in [best_a, best_b] {
+            assert!(merged.genomes.iter().any(|g| g.weights == best));
+        }
+        assert_eq!(merged.matches_per_genome.len(), 12);
+        assert_eq!(merged.hall_of_fame.len(), 1);
+
+        let narrow = NetworkConfig {
+            hidden: vec![4],
+            ..Default::default()
+        };
+        let init = Initialization {
+            network: narrow,
+            ..Default::default()
+        };
+        let other = Population::with_init(ArenaLayout::default(), 10, &init, &mut rng);
+        let base = Population::with_size(ArenaLayout::default(), 10, &mut rng);
+        assert!(merge(base, &other, 0.5, 10, &mut rng).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::error::{check_range, DuelError};
+use crate::game::*;
+
+/// Columns and rows of the coarse position heat map in a behavior
+const HEAT_GRID: (usize, usize) = (4, 3);
+const HEAT_CELLS: usize = HEAT_GRID.0 * HEAT_GRID.1;
+/// Equal slices of the match that shots are counted in
+const SHOT_BINS: usize = 4;
+/// Values in a behavior descriptor: the heat map, the shot timeline and the
+/// mean distance to the opponent
+pub const BEHAVIOR_SIZE: usize = HEAT_CELLS + SHOT_BINS + 1;
+/// Default neighbours a behavior is compared against
+const DEFAULT_K: usize = 15;
+/// Most novel behaviors archived each generation
+const ARCHIVE_ADDS: usize = 3;
+/// Archived behaviors kept; the oldest are dropped first
+const ARCHIVE_CAPACITY: usize = 500;
+
+/// What a ship did in a match, independent of whether it worked: where it
+/// spent its time, when it fired and how close it kept, each in [0, 1]
+pub type Behavior = [f32; BEHAVIOR_SIZE];
+
+/// Accumulates both ships' behavior over the steps of a match
+#[derive(Clone, Debug, Default)]
+pub struct BehaviorRecorder {
+    heat: [[u32; HEAT_CELLS]; 2],
+    shots: [[usize; SHOT_BINS]; 2],
+    distance: f32,
+    steps: u32,
+}
+
+impl BehaviorRecorder {
+    pub fn record(&mut self, state: &GameState) {
+        self.steps += 1;
+        let bin = ((state.time / MATCH_DURATION * SHOT_BINS as f32) as usize).min(SHOT_BINS - 1);
+        for (i, ship) in state.ships.iter().enumerate() {
+            let col =
+                ((ship.x / state.arena.width * HEAT_GRID.0 as f32) as usize).min(HEAT_GRID.0 - 1);
+            let row =
+                ((ship.y / state.arena.height * HEAT_GRID.1 as f32) as usize).min(HEAT_GRID.1 - 1);
+            self.heat[i][row * HEAT_GRID.0 + col] += 1;
+            // Cumulative counts; the timeline keeps each bin's last value
+            self.shots[i][bin] = ship.shots_fired;
+        }
+        let (a, b) = (&state.ships[0], &state.ships[1]);
+        let dx = toroidal_diff(b.x, a.x, state.arena.width);
+        let dy = toroidal_diff(b.y, a.y, state.arena.height);
+        self.distance += dx.hypot(dy);
+    }
+
+    /// Both ships' behavior over the steps recorded so far
+    pub fn behaviors(&self) -> [Behavior; 2] {
+        let steps = self.steps.max(1) as f32;
+        let max_distance = (ARENA_WIDTH / 2.0).hypot(ARENA_HEIGHT / 2.0);
+        let max_shots = MATCH_DURATION / SHOT_BINS as f32 / FIRE_COOLDOWN;
+        [0, 1].map(|i| {
+            let mut behavior = [0.0; BEHAVIOR_SIZE];
+            for (cell, &count) in behavior.iter_mut().zip(&self.heat[i]) {
+                *cell = count as f32 / steps;
+            }
+            // Bins the match never reached carry the previous total forward
+            let mut fired = 0;
+            for bin in 0..SHOT_BINS {
+                let total = self.shots[i][bin].max(fired);
+                behavior[HEAT_CELLS + bin] = ((total - fired) as f32 / max_shots).min(1.0);
+                fired = total;
+            }
+            behavior[BEHAVIOR_SIZE - 1] = self.distance / steps / max_distance;
+            behavior
+        })
+    }
+}
+
+fn distance(a: &Behavior, b: &Behavior) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Novelty search: each genome earns a bonus for behaving unlike its k
+/// nearest neighbours among the current population and an archive of past
+/// novel behaviors, so strategies other than the dominant one survive long
+/// enough to be refined.
+#[derive(Clone, Debug)]
+pub struct NoveltySearch {
+    /// Fitness added per unit of novelty
+    pub weight: f32,
+    /// Neighbours a behavior's novelty is averaged over
+    pub k: usize,
+    pub archive: Vec<Behavior>,
+}
+
+impl NoveltySearch {
+    pub fn new(weight: f32, k: usize) -> Self {
+        NoveltySearch {
+            weight,
+            k,
+            archive: Vec::new(),
+        }
+    }
+
+    /// The spec `parse` turns back into these settings
+    pub fn spec(&self) -> String {
+        format!("{}:{}", self.weight, self.k)
+    }
+
+    /// Parse "WEIGHT" or "WEIGHT:K"
+    pub fn parse(spec: &str) -> Result<Self, DuelError> {
+        let (weight, k) = match spec.split_once(':') {
+            Some((weight, k)) => (weight, Some(k)),
+            None => (spec, None),
+        };
+        let weight = weight.parse::<f32>().map_err(|_| DuelError::InvalidValue {
+            what: "novelty weight",
+            value: weight.to_string(),
+        })?;
+        check_range("novelty weight", weight, 0.0, 1e6)?;
+        let k = match k {
+            Some(value) => value
+                .parse::<usize>()
+                .map_err(|_| DuelError::InvalidValue {
+                    what: "novelty neighbours",
+                    value: value.to_string(),
+                })?,
+            None => DEFAULT_K,
+        };
+        check_range("novelty neighbours", k as f32, 1.0, 1e4)?;
+        Ok(Self::new(weight, k))
+    }
+
+    /// Novelty of every behavior in `population`: the mean distance to its
+    /// k nearest neighbours among the others and the archive
+    pub fn scores(&self, population: &[Behavior]) -> Vec<f32> {
+        population
+            .iter()
+            .enumerate()
+            .map(|(i, behavior)| {
+                let mut distances: Vec<f32> = population
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, other)| other)
+                    .chain(&self.archive)
+                    .map(|other| distance(behavior, other))
+                    .collect();
+                let k = self.k.min(distances.len());
+                if k == 0 {
+                    return 0.0;
+                }
+                distances.select_nth_unstable_by(k - 1, f32::total_cmp);
+                distances[..k].iter().sum::<f32>() / k as f32
+            })
+            .collect()
+    }
+
+    /// Archive the most novel of this generation's behaviors
+    pub fn update_archive(&mut self, population: &[Behavior], scores: &[f32]) {
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+        self.archive
+            .extend(order.iter().take(ARCHIVE_ADDS).map(|&i| population[i]));
+        let excess = self.archive.len().saturating_sub(ARCHIVE_CAPACITY);
+        self.archive.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outlying_behaviors_score_highest_and_get_archived() {
+        let mut state = GameState::new();
+        state.ships[0] = Ship::new(10.0, 10.0, 0.0);
+        state.ships[1] = Ship::new(ARENA_WIDTH - 10.0, ARENA_HEIGHT - 10.0, 0.0);
+        let mut recorder = BehaviorRecorder::default();
+        recorder.record(&state);
+        state.ships[1].shots_fired = 2;
+        recorder.record(&state);
+        let [first, second] = recorder.behaviors();
+        assert_eq!(first[0], 1.0);
+        assert_eq!(second[HEAT_CELLS - 1], 1.0);
+        assert!(second[HEAT_CELLS] > 0.0 && first[HEAT_CELLS] == 0.0);
+        assert!(first.iter().all(|v| (0.0..=1.0).contains(v)));
+
+        let mut novelty = NoveltySearch::parse("10:2").unwrap();
+        assert_eq!(novelty.spec(), "10:2");
+        let population = [first, first, first, second];
+        let scores = novelty.scores(&population);
+        assert!(scores[3] > scores[0]);
+        novelty.update_archive(&population, &scores);
+        assert_eq!(novelty.archive.len(), ARCHIVE_ADDS);
+        assert_eq!(novelty.archive[0], second);
+        assert!(NoveltySearch::parse("-1").is_err());
+    }
+}
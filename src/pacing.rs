@@ -0,0 +1,105 @@
+use crate::game::*;
+
+/// Seconds with less intensity than this count as stalled: no shots fired
+/// and the ships too far apart to threaten each other
+pub const STALL_INTENSITY: f32 = 0.25;
+/// Sparkline levels, lowest first
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Match time in one-second buckets
+#[derive(Clone, Copy, Debug, Default)]
+struct Second {
+    shots: usize,
+    proximity_sum: f32,
+    steps: u32,
+}
+
+/// How intensely the ships engaged over a match, one value per second of
+/// match time: shots fired by both ships plus their mean proximity (1 when
+/// touching, 0 at the arena's engagement range or more). Long runs of low values mark a
+/// stall-heavy match.
+#[derive(Clone, Debug, Default)]
+pub struct EngagementTimeline {
+    seconds: Vec<Second>,
+    shots: [usize; 2],
+}
+
+impl EngagementTimeline {
+    /// Record one simulation step; call after each `GameState::update`
+    pub fn record(&mut self, state: &GameState) {
+        let index = state.time.max(0.0) as usize;
+        if self.seconds.len() <= index {
+            self.seconds.resize(index + 1, Second::default());
+        }
+        let second = &mut self.seconds[index];
+        for (i, ship) in state.ships.iter().enumerate() {
+            second.shots += ship.shots_fired - self.shots[i];
+            self.shots[i] = ship.shots_fired;
+        }
+        let dx = toroidal_diff(state.ships[0].x, state.ships[1].x, state.arena.width);
+        let dy = toroidal_diff(state.ships[0].y, state.ships[1].y, state.arena.height);
+        let dist = (dx * dx + dy * dy).sqrt();
+        // Proximity falls to zero where it does in the fitness formula
+        second.proximity_sum += 1.0 - (dist / state.arena.engagement_range()).min(1.0);
+        second.steps += 1;
+    }
+
+    /// Intensity of every second recorded so far, the last one possibly partial
+    pub fn intensity(&self) -> Vec<f32> {
+        self.seconds
+            .iter()
+            .map(|s| s.shots as f32 + s.proximity_sum / s.steps.max(1) as f32)
+            .collect()
+    }
+
+    /// Share of the recorded seconds that were stalled, in [0, 1]
+    pub fn stall_fraction(&self) -> f32 {
+        let intensity = self.intensity();
+        let stalled = intensity.iter().filter(|&&v| v < STALL_INTENSITY).count();
+        stalled as f32 / intensity.len().max(1) as f32
+    }
+
+    /// The timeline as one block character per second, scaled to the busiest second
+    pub fn sparkline(&self) -> String {
+        let intensity = self.intensity();
+        let peak = intensity.iter().copied().fold(1.0f32, f32::max);
+        intensity
+            .iter()
+            .map(|v| {
+                let level = (v / peak * (SPARK_LEVELS.len() - 1) as f32).round() as usize;
+                SPARK_LEVELS[level.min(SPARK_LEVELS.len() - 1)]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn shots_and_distance_set_the_intensity() {
+        let mut state = ArenaLayout::default().new_match(&mut StdRng::seed_from_u64(1));
+        state.ships[0].x = 100.0;
+        state.ships[1].x = 100.0 + state.arena.engagement_range() + 50.0;
+        state.ships[0].y = 300.0;
+        state.ships[1].y = 300.0;
+        let mut timeline = EngagementTimeline::default();
+        // A quiet first second, then one shot in the second
+        for step in 1..=120 {
+            state.time = step as f32 / 60.0;
+            if step == 90 {
+                state.ships[0].shots_fired += 1;
+            }
+            timeline.record(&state);
+        }
+        let intensity = timeline.intensity();
+        assert_eq!(intensity.len(), 3);
+        assert_eq!(intensity[0], 0.0);
+        assert_eq!(intensity[1], 1.0);
+        assert!(timeline.stall_fraction() > 0.6);
+        assert!(timeline.sparkline().starts_with("▁█"));
+    }
+}
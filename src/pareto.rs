@@ -0,0 +1,125 @@
+/// Goals a genome is ranked on by Pareto selection
+pub const OBJECTIVE_COUNT: usize = 4;
+
+/// How a genome did on each goal separately rather than weighed into one
+/// fitness value, each in [0, 1] and higher being better: share of matches
+/// won, share of shots that hit, share of the match survived, and how close
+/// it kept and how often it fired
+pub type Objectives = [f32; OBJECTIVE_COUNT];
+
+/// Whether `a` is at least as good as `b` on every objective and better on one
+fn dominates(a: &Objectives, b: &Objectives) -> bool {
+    a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+}
+
+/// Non-dominated front of every point: 0 for points nothing dominates, 1 for
+/// those only front-0 points dominate, and so on
+pub fn fronts(points: &[Objectives]) -> Vec<usize> {
+    let n = points.len();
+    let mut dominated_by = vec![0usize; n];
+    let mut dominates_list: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for i in 0..n {
+        for j in i + 1..n {
+            if dominates(&points[i], &points[j]) {
+                dominates_list[i].push(j);
+                dominated_by[j] += 1;
+            } else if dominates(&points[j], &points[i]) {
+                dominates_list[j].push(i);
+                dominated_by[i] += 1;
+            }
+        }
+    }
+    let mut rank = vec![0; n];
+    let mut front: Vec<usize> = (0..n).filter(|&i| dominated_by[i] == 0).collect();
+    let mut level = 0;
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &i in &front {
+            rank[i] = level;
+            for &j in &dominates_list[i] {
+                dominated_by[j] -= 1;
+                if dominated_by[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        front = next;
+        level += 1;
+    }
+    rank
+}
+
+/// Crowding distance of every point within its front: the summed spread of
+/// its neighbours along each objective, infinite at a front's extremes, so
+/// points in sparse regions of the front are preferred
+pub fn crowding(points: &[Objectives], ranks: &[usize]) -> Vec<f32> {
+    let mut distance = vec![0.0f32; points.len()];
+    let levels = ranks.iter().max().map_or(0, |&r| r + 1);
+    let value = |i: usize, objective: usize| points[i][objective];
+    for level in 0..levels {
+        let members: Vec<usize> = (0..points.len()).filter(|&i| ranks[i] == level).collect();
+        for objective in 0..OBJECTIVE_COUNT {
+            let mut sorted = members.clone();
+            sorted.sort_by(|&a, &b| value(a, objective).total_cmp(&value(b, objective)));
+            let (Some(&low), Some(&high)) = (sorted.first(), sorted.last()) else {
+                continue;
+            };
+            // An objective the whole front ties on says nothing about spread
+            let span = value(high, objective) - value(low, objective);
+            if span <= 0.0 {
+                continue;
+            }
+            distance[low] = f32::INFINITY;
+            distance[high] = f32::INFINITY;
+            for window in sorted.windows(3) {
+                let gap = value(window[2], objective) - value(window[0], objective);
+                distance[window[1]] += gap / span;
+            }
+        }
+    }
+    distance
+}
+
+/// NSGA-II's crowded comparison as one number per point, higher being
+/// better: points in a lower front always beat those in a higher one, and
+/// within a front the less crowded point wins
+pub fn selection_scores(points: &[Objectives]) -> Vec<f32> {
+    let ranks = fronts(points);
+    let crowding = crowding(points, &ranks);
+    ranks
+        .iter()
+        .zip(crowding)
+        .map(|(&rank, c)| {
+            // Squeezed into [0, 0.5] so it never outweighs a whole front
+            let spread = if c.is_finite() {
+                0.5 * c / (1.0 + c)
+            } else {
+                0.5
+            };
+            spread - rank as f32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fronts_rank_dominance_and_crowding_favours_extremes() {
+        let points = [
+            [1.0, 0.0, 0.5, 0.5],
+            [0.0, 1.0, 0.5, 0.5],
+            [0.5, 0.5, 0.5, 0.5],
+            [0.4, 0.4, 0.4, 0.4],
+            [0.1, 0.1, 0.1, 0.1],
+        ];
+        assert_eq!(fronts(&points), vec![0, 0, 0, 1, 2]);
+
+        let scores = selection_scores(&points);
+        // The middle of the first front is more crowded than its ends
+        assert!(scores[0] > scores[2] && scores[1] > scores[2]);
+        assert!(scores[2] > scores[3] && scores[3] > scores[4]);
+        assert!(selection_scores(&[]).is_empty());
+    }
+}
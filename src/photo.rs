@@ -0,0 +1,170 @@
+use std::path::Path;
+
+use macroquad::prelude::*;
+
+use spaceship_duel::game::*;
+
+/// Closest and farthest the photo camera zooms
+const ZOOM_RANGE: (f32, f32) = (0.5, 6.0);
+/// Zoom change per second with PageUp/PageDown, and per mouse wheel notch
+const ZOOM_RATE: f32 = 1.5;
+const WHEEL_ZOOM_STEP: f32 = 1.1;
+/// Camera speed with the arrow keys, in screen px per second
+const PAN_SPEED: f32 = 400.0;
+/// Line width multiplier while thick lines are on
+const THICK_LINE_SCALE: f32 = 2.5;
+/// Screenshots are numbered photo_001.png, photo_002.png, ... up to this
+const MAX_PHOTOS: usize = 999;
+
+/// How the arena is drawn: plainly for watching, or dressed up for stills
+#[derive(Clone, Copy, Debug)]
+pub struct RenderStyle {
+    /// Multiplier on every outline's width
+    pub line_scale: f32,
+    /// Soft halo around ships and projectiles
+    pub glow: bool,
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        RenderStyle {
+            line_scale: 1.0,
+            glow: false,
+        }
+    }
+}
+
+/// A frozen frame with a free camera and no HUD, for promotional stills.
+/// F2 enters and leaves it; arrows or a mouse drag pan, PageUp/PageDown or
+/// the wheel zoom, Home resets the view, 1 toggles thick lines, 2 toggles
+/// glow and Enter saves a screenshot.
+#[derive(Default)]
+pub struct PhotoMode {
+    /// Frame being photographed, or None outside photo mode
+    frame: Option<GameState>,
+    center: Vec2,
+    zoom: f32,
+    thick_lines: bool,
+    glow: bool,
+    drag_from: Option<Vec2>,
+    capture_requested: bool,
+}
+
+impl PhotoMode {
+    pub fn is_active(&self) -> bool {
+        self.frame.is_some()
+    }
+
+    /// Enter photo mode on a copy of `shown`, or leave it
+    pub fn toggle(&mut self, shown: &GameState) {
+        if self.frame.take().is_none() {
+            self.frame = Some(shown.clone());
+            self.reset_view();
+        }
+    }
+
+    /// The frozen frame while photo mode is on
+    pub fn frame(&self) -> Option<&GameState> {
+        self.frame.as_ref()
+    }
+
+    fn reset_view(&mut self) {
+        self.center = vec2(ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0);
+        self.zoom = 1.0;
+    }
+
+    /// Apply this frame's camera and style keys
+    pub fn handle_input(&mut self, dt: f32) {
+        if !self.is_active() {
+            return;
+        }
+        let mut pan = Vec2::ZERO;
+        for (key, dir) in [
+            (KeyCode::Left, vec2(-1.0, 0.0)),
+            (KeyCode::Right, vec2(1.0, 0.0)),
+            (KeyCode::Up, vec2(0.0, -1.0)),
+            (KeyCode::Down, vec2(0.0, 1.0)),
+        ] {
+            if is_key_down(key) {
+                pan += dir;
+            }
+        }
+        self.center += pan * PAN_SPEED * dt / self.zoom;
+
+        // Dragging moves the scene with the mouse
+        let mouse = Vec2::from(mouse_position());
+        if is_mouse_button_down(MouseButton::Left) {
+            if let Some(from) = self.drag_from {
+                self.center -= (mouse - from) / self.zoom;
+            }
+            self.drag_from = Some(mouse);
+        } else {
+            self.drag_from = None;
+        }
+
+        let mut zoom = self.zoom;
+        if is_key_down(KeyCode::PageUp) {
+            zoom *= ZOOM_RATE.powf(dt);
+        }
+        if is_key_down(KeyCode::PageDown) {
+            zoom /= ZOOM_RATE.powf(dt);
+        }
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 {
+            zoom *= WHEEL_ZOOM_STEP.powf(wheel.signum());
+        }
+        self.zoom = zoom.clamp(ZOOM_RANGE.0, ZOOM_RANGE.1);
+
+        if is_key_pressed(KeyCode::Home) {
+            self.reset_view();
+        }
+        if is_key_pressed(KeyCode::Key1) {
+            self.thick_lines = !self.thick_lines;
+        }
+        if is_key_pressed(KeyCode::Key2) {
+            self.glow = !self.glow;
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            self.capture_requested = true;
+        }
+    }
+
+    /// Camera looking at the arena from the current pan and zoom
+    pub fn camera(&self) -> Camera2D {
+        let (w, h) = (screen_width() / self.zoom, screen_height() / self.zoom);
+        Camera2D::from_display_rect(Rect::new(
+            self.center.x - w / 2.0,
+            self.center.y - h / 2.0,
+            w,
+            h,
+        ))
+    }
+
+    pub fn style(&self) -> RenderStyle {
+        RenderStyle {
+            line_scale: if self.thick_lines {
+                THICK_LINE_SCALE
+            } else {
+                1.0
+            },
+            glow: self.glow,
+        }
+    }
+
+    /// Save the finished frame if a screenshot was asked for; call once the
+    /// scene is drawn and before the frame is presented
+    pub fn capture(&mut self) {
+        if !std::mem::take(&mut self.capture_requested) {
+            return;
+        }
+        let Some(path) = (1..=MAX_PHOTOS)
+            .map(|n| format!("photo_{:03}.png", n))
+            .find(|path| !Path::new(path).exists())
+        else {
+            eprintln!("Failed to save photo: photo_001.png to photo_{MAX_PHOTOS}.png all exist");
+            return;
+        };
+        get_screen_data().export_png(&path);
+        println!("Saved photo to {}", path);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::game::*;
+
+pub const PRESSURE_CELL_SIZE: f32 = 100.0;
+/// Seconds for accumulated pressure to fall to ~37% once projectiles leave a cell
+const PRESSURE_DECAY_TIME: f32 = 1.5;
+
+/// Coarse grid of recent projectile presence per owner, used to show
+/// which ship controls which part of the arena. Duel-only: it tracks the
+/// two ships of a duel.
+pub struct PressureGrid {
+    pub cols: usize,
+    pub rows: usize,
+    cells: Vec<[f32; 2]>,
+}
+
+impl Default for PressureGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PressureGrid {
+    pub fn new() -> Self {
+        let cols = (ARENA_WIDTH / PRESSURE_CELL_SIZE).ceil() as usize;
+        let rows = (ARENA_HEIGHT / PRESSURE_CELL_SIZE).ceil() as usize;
+        PressureGrid {
+            cols,
+            rows,
+            cells: vec![[0.0; 2]; cols * rows],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill([0.0; 2]);
+    }
+
+    /// Decay old pressure and add the projectiles currently in flight
+    pub fn update(&mut self, state: &GameState, dt: f32) {
+        debug_assert_eq!(state.ships.len(), 2, "pressure is tracked for duels only");
+        let decay = (-dt / PRESSURE_DECAY_TIME).exp();
+        for cell in &mut self.cells {
+            cell[0] *= decay;
+            cell[1] *= decay;
+        }
+        for p in &state.projectiles {
+            let col = ((p.x / PRESSURE_CELL_SIZE) as usize).min(self.cols - 1);
+            let row = ((p.y / PRESSURE_CELL_SIZE) as usize).min(self.rows - 1);
+            self.cells[row * self.cols + col][p.owner] += dt;
+        }
+    }
+
+    /// Accumulated pressure of both owners in a cell
+    pub fn cell(&self, col: usize, row: usize) -> [f32; 2] {
+        self.cells[row * self.cols + col]
+    }
+}
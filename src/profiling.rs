@@ -0,0 +1,234 @@
+//! Span timing for the simulation hot path, compiled in with the `profiling`
+//! feature. Without it `Span` is a zero-sized no-op.
+//!
+//! Self time is accumulated per call stack and can be written out in the
+//! folded-stack format read by flamegraph.pl and inferno-flamegraph. The
+//! first MAX_TRACE_EVENTS spans are also kept individually, for a Chrome
+//! trace (chrome://tracing, Perfetto or speedscope) that shows when each
+//! span ran on which thread.
+
+use std::io::{self, Write};
+
+/// Whether spans record anything in this build
+pub const ENABLED: bool = cfg!(feature = "profiling");
+/// Spans kept for the Chrome trace; later ones only count towards the
+/// folded stacks, since the hot path ends millions of spans a generation
+pub const MAX_TRACE_EVENTS: usize = 250_000;
+
+/// One finished span, with times in microseconds since the first span began
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceEvent {
+    pub name: &'static str,
+    /// Small number identifying the thread the span ran on
+    pub thread: u64,
+    pub start_micros: f64,
+    pub duration_micros: f64,
+}
+
+/// Times the enclosing scope until dropped
+#[must_use = "the span ends as soon as it is dropped"]
+pub struct Span {
+    #[cfg(feature = "profiling")]
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl Span {
+    #[inline]
+    pub fn enter(name: &'static str) -> Span {
+        #[cfg(feature = "profiling")]
+        imp::enter(name);
+        #[cfg(not(feature = "profiling"))]
+        let _ = name;
+        Span {
+            #[cfg(feature = "profiling")]
+            _not_send: std::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "profiling")]
+impl Drop for Span {
+    fn drop(&mut self) {
+        imp::exit();
+    }
+}
+
+/// Take the self time recorded so far as (stack, nanoseconds), outermost frame
+/// first and frames joined by ';'. Only spans whose outermost frame has ended
+/// are included.
+pub fn take_folded() -> Vec<(String, u64)> {
+    #[cfg(feature = "profiling")]
+    return imp::take();
+    #[cfg(not(feature = "profiling"))]
+    Vec::new()
+}
+
+/// Take the spans recorded so far for the Chrome trace, in the order their
+/// outermost frames ended. Like `take_folded` it waits for the outermost
+/// frame of a thread's spans to end.
+pub fn take_trace() -> Vec<TraceEvent> {
+    #[cfg(feature = "profiling")]
+    return imp::take_trace();
+    #[cfg(not(feature = "profiling"))]
+    Vec::new()
+}
+
+/// Write spans from `take_trace` as Chrome trace event JSON: one complete
+/// ("X") event per span
+pub fn write_chrome_trace(events: &[TraceEvent], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[")?;
+    for (i, event) in events.iter().enumerate() {
+        let separator = if i + 1 < events.len() { "," } else { "" };
+        let name = event.name.replace('\\', "\\\\").replace('"', "\\\"");
+        writeln!(
+            out,
+            "{{\"name\":\"{}\",\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{:.3},\"dur\":{:.3}}}{}",
+            name, event.thread, event.start_micros, event.duration_micros, separator
+        )?;
+    }
+    writeln!(out, "]}}")
+}
+
+/// Write stacks from `take_folded` in folded-stack format with microsecond counts
+pub fn write_folded(stacks: &[(String, u64)], out: &mut impl Write) -> io::Result<()> {
+    for (stack, nanos) in stacks {
+        writeln!(out, "{} {}", stack, nanos / 1000)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "profiling")]
+mod imp {
+    use std::cell::RefCell;
+    use std::collections::{BTreeMap, HashMap};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Instant;
+
+    use super::{TraceEvent, MAX_TRACE_EVENTS};
+
+    /// Stacks from every thread, merged whenever a thread's outermost span ends
+    static FOLDED: Mutex<BTreeMap<String, u64>> = Mutex::new(BTreeMap::new());
+    /// Spans from every thread for the Chrome trace, merged the same way
+    static TRACE: Mutex<Vec<TraceEvent>> = Mutex::new(Vec::new());
+    /// When the first span began; trace times count from here
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+    struct ThreadProfile {
+        thread: u64,
+        names: Vec<&'static str>,
+        /// Start time and time spent in child spans, per open frame
+        frames: Vec<(Instant, u64)>,
+        self_nanos: HashMap<Vec<&'static str>, u64>,
+        trace: Vec<TraceEvent>,
+    }
+
+    impl Default for ThreadProfile {
+        fn default() -> Self {
+            ThreadProfile {
+                thread: NEXT_THREAD.fetch_add(1, Ordering::Relaxed),
+                names: Vec::new(),
+                frames: Vec::new(),
+                self_nanos: HashMap::new(),
+                trace: Vec::new(),
+            }
+        }
+    }
+
+    thread_local! {
+        static PROFILE: RefCell<ThreadProfile> = RefCell::new(ThreadProfile::default());
+    }
+
+    pub fn enter(name: &'static str) {
+        EPOCH.get_or_init(Instant::now);
+        PROFILE.with(|p| {
+            let mut p = p.borrow_mut();
+            p.names.push(name);
+            p.frames.push((Instant::now(), 0));
+        });
+    }
+
+    pub fn exit() {
+        PROFILE.with(|p| {
+            let mut p = p.borrow_mut();
+            let Some((start, child_nanos)) = p.frames.pop() else {
+                return;
+            };
+            let elapsed = start.elapsed().as_nanos() as u64;
+            let own = elapsed.saturating_sub(child_nanos);
+
+            let p = &mut *p;
+            match p.self_nanos.get_mut(p.names.as_slice()) {
+                Some(total) => *total += own,
+                None => {
+                    p.self_nanos.insert(p.names.clone(), own);
+                }
+            }
+            let name = p.names.pop().unwrap_or_default();
+            if p.trace.len() < MAX_TRACE_EVENTS {
+                let epoch = *EPOCH.get_or_init(Instant::now);
+                p.trace.push(TraceEvent {
+                    name,
+                    thread: p.thread,
+                    start_micros: start.saturating_duration_since(epoch).as_nanos() as f64 / 1e3,
+                    duration_micros: elapsed as f64 / 1e3,
+                });
+            }
+
+            if let Some(parent) = p.frames.last_mut() {
+                parent.1 += elapsed;
+            } else {
+                let mut folded = FOLDED.lock().unwrap_or_else(|e| e.into_inner());
+                for (stack, nanos) in p.self_nanos.drain() {
+                    *folded.entry(stack.join(";")).or_insert(0) += nanos;
+                }
+                drop(folded);
+                let mut trace = TRACE.lock().unwrap_or_else(|e| e.into_inner());
+                let room = MAX_TRACE_EVENTS.saturating_sub(trace.len());
+                trace.extend(p.trace.drain(..).take(room));
+            }
+        });
+    }
+
+    pub fn take() -> Vec<(String, u64)> {
+        let mut folded = FOLDED.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *folded).into_iter().collect()
+    }
+
+    pub fn take_trace() -> Vec<TraceEvent> {
+        std::mem::take(&mut *TRACE.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chrome_traces_hold_one_complete_event_per_span() {
+        let events = [
+            TraceEvent {
+                name: "run_match",
+                thread: 2,
+                start_micros: 10.0,
+                duration_micros: 1.5,
+            },
+            TraceEvent {
+                name: "odd \"name\"",
+                thread: 3,
+                start_micros: 0.25,
+                duration_micros: 4.0,
+            },
+        ];
+        let mut out = Vec::new();
+        write_chrome_trace(&events, &mut out).unwrap();
+        let json = String::from_utf8(out).unwrap();
+        assert!(json.starts_with("{\"displayTimeUnit\":\"ms\",\"traceEvents\":["));
+        assert!(json.contains(
+            "{\"name\":\"run_match\",\"ph\":\"X\",\"pid\":1,\"tid\":2,\"ts\":10.000,\"dur\":1.500},"
+        ));
+        assert!(json.contains("\"name\":\"odd \\\"name\\\"\""));
+        assert!(json.trim_end().ends_with("}\n]}"));
+    }
+}
@@ -0,0 +1,143 @@
+use rand::Rng;
+
+use crate::achievements;
+use crate::archive::HallOfFame;
+use crate::events::EventLog;
+use crate::game::{ArenaLayout, GameState};
+use crate::simulation::{run_match_observed, FitnessWeights};
+
+/// Rating of the first archived champion, which anchors the scale
+pub const INITIAL_RATING: f32 = 1200.0;
+/// Largest rating change a single match can cause
+pub const K_FACTOR: f32 = 24.0;
+/// Matches a newly archived champion plays against its predecessors
+pub const RATING_MATCHES: usize = 8;
+/// Rating gap at which an opponent is scheduled e^-1 times as often as an equal
+pub const SCHEDULING_SPREAD: f32 = 200.0;
+
+/// Expected score (win 1, draw 0.5) of a player rated `rating` against `opponent`
+pub fn expected_score(rating: f32, opponent: f32) -> f32 {
+    1.0 / (1.0 + 10f32.powf((opponent - rating) / 400.0))
+}
+
+/// Both ratings after a match in which the first player scored `score`
+pub fn update(rating: f32, opponent: f32, score: f32) -> (f32, f32) {
+    let change = K_FACTOR * (score - expected_score(rating, opponent));
+    (rating + change, opponent - change)
+}
+
+/// Relative chance of scheduling an opponent rated `opponent` against a
+/// player rated `rating`; close matches teach the ratings the most
+pub fn scheduling_weight(rating: f32, opponent: f32, spread: f32) -> f32 {
+    let gap = (opponent - rating) / spread;
+    (-gap * gap).exp()
+}
+
+/// Rate the newest hall-of-fame entry by playing it against earlier ones,
+/// preferring opponents rated near it. It starts from its predecessor's
+/// rating and both sides of every match are updated, so the whole archive
+/// stays on one scale across generations. Feats the newcomer pulls off in
+/// these matches become its badges. Returns the matches played and the
+/// simulated seconds they took.
+pub fn rate_newest(
+    hall_of_fame: &mut HallOfFame,
+    layout: &ArenaLayout,
+    weights: &FitnessWeights,
+    rng: &mut impl Rng,
+) -> (usize, f64) {
+    let entries = &mut hall_of_fame.entries;
+    let Some(newest) = entries.len().checked_sub(1) else {
+        return (0, 0.0);
+    };
+    entries[newest].rating = match newest {
+        0 => INITIAL_RATING,
+        _ => entries[newest - 1].rating,
+    };
+    if newest == 0 {
+        return (0, 0.0);
+    }
+
+    let mut seconds = 0.0;
+    for m in 0..RATING_MATCHES {
+        let rating = entries[newest].rating;
+        let chances: Vec<f32> = entries[..newest]
+            .iter()
+            .map(|e| scheduling_weight(rating, e.rating, SCHEDULING_SPREAD))
+            .collect();
+        let mut pick = rng.gen_range(0.0..chances.iter().sum::<f32>().max(f32::MIN_POSITIVE));
+        let opponent = chances
+            .iter()
+            .position(|&c| {
+                pick -= c;
+                pick < 0.0
+            })
+            .unwrap_or(newest - 1);
+
+        // Alternate sides so spawn position cannot skew the ratings
+        let side = m % 2;
+        let (a, b) = (&entries[newest].genome, &entries[opponent].genome);
+        let mut log = EventLog::new();
+        let mut observe = |state: &GameState| {
+            log.record(state);
+        };
+        let result = if side == 0 {
+            run_match_observed(a, b, layout, weights, &mut observe, rng)
+        } else {
+            run_match_observed(b, a, layout, weights, &mut observe, rng)
+        };
+        let feats = achievements::earned(&log.events, side);
+        achievements::award(&mut entries[newest].badges, &feats);
+        let score = match result.winner {
+            Some(w) if w == side => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+        (entries[newest].rating, entries[opponent].rating) =
+            update(rating, entries[opponent].rating, score);
+        seconds += result.duration as f64;
+    }
+    (RATING_MATCHES, seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome::Genome;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn ratings_move_towards_results_and_anchor_on_the_first_champion() {
+        assert_eq!(expected_score(1500.0, 1500.0), 0.5);
+        assert!((expected_score(1600.0, 1200.0) - 0.909).abs() < 1e-3);
+        let (winner, loser) = update(1500.0, 1500.0, 1.0);
+        assert_eq!((winner, loser), (1512.0, 1488.0));
+        // An upset moves the ratings further than an expected result
+        let (upset, _) = update(1200.0, 1600.0, 1.0);
+        let (expected, _) = update(1600.0, 1200.0, 1.0);
+        assert!(upset - 1200.0 > expected - 1600.0);
+        assert!(
+            scheduling_weight(1200.0, 1250.0, SCHEDULING_SPREAD)
+                > scheduling_weight(1200.0, 1600.0, SCHEDULING_SPREAD)
+        );
+
+        let mut rng = StdRng::seed_from_u64(4);
+        let layout = ArenaLayout::default();
+        let weights = FitnessWeights::default();
+        let mut hall_of_fame = HallOfFame::default();
+        hall_of_fame.add(&Genome::random(&mut rng), 0);
+        assert_eq!(
+            rate_newest(&mut hall_of_fame, &layout, &weights, &mut rng).0,
+            0
+        );
+        assert_eq!(hall_of_fame.entries[0].rating, INITIAL_RATING);
+        hall_of_fame.add(&Genome::random(&mut rng), 1);
+        let (matches, _) = rate_newest(&mut hall_of_fame, &layout, &weights, &mut rng);
+        assert_eq!(matches, RATING_MATCHES);
+        // Rating is zero-sum, so the pair's mean stays on the anchor
+        let total: f32 = hall_of_fame.entries.iter().map(|e| e.rating).sum();
+        assert!((total - 2.0 * INITIAL_RATING).abs() < 1e-2);
+        // Only the champion being rated collects badges
+        assert!(hall_of_fame.entries[0].badges.is_empty());
+    }
+}
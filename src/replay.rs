@@ -0,0 +1,707 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::bots::Controller;
+use crate::checkpoint::{flow_field_spec, parse_flow_field};
+use crate::error::{check_range, DuelError};
+use crate::game::*;
+use crate::simulation::SIM_DT;
+
+/// Version written in the header of replay files
+pub const REPLAY_FILE_VERSION: u32 = 6;
+const REPLAY_FILE_MAGIC: &str = "spaceship-duel replay";
+/// Version written in the header of replay notes files
+pub const NOTES_FILE_VERSION: u32 = 1;
+const NOTES_FILE_MAGIC: &str = "spaceship-duel replay notes";
+/// Seconds a note stays on screen after the moment it marks
+pub const NOTE_DISPLAY_SECONDS: f32 = 3.0;
+/// A bookmark this close behind the playhead counts as the one just jumped
+/// to, so jumping back again moves on to the one before it
+const BOOKMARK_TOLERANCE: f32 = 0.25;
+
+/// One simulation step of a recorded match
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayStep {
+    pub dt: f32,
+    pub actions: [[f32; ACTION_SIZE]; 2],
+    /// Loadouts fitted before this step, when they changed (a new pilot took over)
+    pub loadouts: Option<[Loadout; 2]>,
+}
+
+/// A match recorded as its starting seed plus the controls of every step.
+/// The engine is deterministic, so re-simulating the steps reproduces the
+/// match exactly under the same game constants. Replay files are plain text
+/// and not compressed, which keeps the crate free of a compression
+/// dependency; they compress well with any external tool.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub layout: ArenaLayout,
+    /// Seed `layout.new_match` is called with to set up the arena
+    pub match_seed: u64,
+    pub steps: Vec<ReplayStep>,
+    /// Loadouts in force after the last recorded step
+    loadouts: [Loadout; 2],
+}
+
+impl Replay {
+    pub fn new(layout: ArenaLayout, match_seed: u64) -> Self {
+        Replay {
+            layout,
+            match_seed,
+            steps: Vec::new(),
+            loadouts: [Loadout::default(); 2],
+        }
+    }
+
+    /// The arena as the match started
+    pub fn initial_state(&self) -> GameState {
+        self.layout
+            .new_match(&mut StdRng::seed_from_u64(self.match_seed))
+    }
+
+    /// Approximate memory held by the recorded steps, in bytes
+    pub fn memory_bytes(&self) -> usize {
+        self.steps.capacity() * size_of::<ReplayStep>()
+    }
+
+    /// Record the step about to be applied to `state`; call right before
+    /// `GameState::update` with the same arguments
+    pub fn record(&mut self, state: &GameState, dt: f32, actions: &[[f32; ACTION_SIZE]; 2]) {
+        let loadouts = [state.ships[0].loadout, state.ships[1].loadout];
+        let changed = loadouts != self.loadouts;
+        self.loadouts = loadouts;
+        self.steps.push(ReplayStep {
+            dt,
+            actions: *actions,
+            loadouts: changed.then_some(loadouts),
+        });
+    }
+
+    /// Simulated seconds the recorded steps cover
+    pub fn duration(&self) -> f32 {
+        self.steps.iter().map(|s| s.dt).sum()
+    }
+
+    /// Text form: header, arena and match seed, then one line per step
+    /// ("step DT ACTIONS..." for ship 0 then ship 1), preceded by a
+    /// "loadouts SPEC SPEC" line whenever the loadouts change
+    pub fn to_file_string(&self) -> String {
+        let mut out = String::new();
+        let physics = &self.layout.physics;
+        // Writing to a String cannot fail
+        let _ = writeln!(out, "{} {}", REPLAY_FILE_MAGIC, REPLAY_FILE_VERSION);
+        let _ = writeln!(out, "config {:016x}", config_hash());
+        let _ = writeln!(
+            out,
+            "projectile_inheritance {}",
+            physics.projectile_inheritance
+        );
+        let _ = writeln!(out, "recoil {}", physics.recoil);
+        let _ = writeln!(
+            out,
+            "defenses {} {} {}",
+            physics.shield, physics.shield_regen, physics.hull
+        );
+        let _ = writeln!(out, "ammo {} {}", physics.ammo, physics.pickup_interval);
+        let _ = writeln!(out, "energy {} {}", physics.energy, physics.deflector);
+        let _ = writeln!(
+            out,
+            "projectile_collisions {}",
+            physics.projectile_collisions
+        );
+        let _ = writeln!(out, "asteroid_count {}", self.layout.asteroid_count);
+        let _ = writeln!(out, "class_matchups {}", self.layout.class_matchups.name());
+        let _ = writeln!(
+            out,
+            "randomize_flow_field {}",
+            self.layout.randomize_flow_field
+        );
+        let _ = writeln!(
+            out,
+            "flow_field {}",
+            flow_field_spec(&self.layout.flow_field)
+        );
+        let _ = writeln!(out, "match_seed {}", self.match_seed);
+        let _ = writeln!(out, "steps {}", self.steps.len());
+        for step in &self.steps {
+            if let Some([a, b]) = step.loadouts {
+                let _ = writeln!(out, "loadouts {} {}", a.spec(), b.spec());
+            }
+            let _ = write!(out, "step {}", step.dt);
+            for value in step.actions.iter().flatten() {
+                let _ = write!(out, " {}", value);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Parse the output of `to_file_string`. Replays recorded under other
+    /// game constants are rejected, since they would play out differently.
+    pub fn from_file_str(text: &str) -> Result<Replay, DuelError> {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default();
+        let version: u32 = header
+            .strip_prefix(REPLAY_FILE_MAGIC)
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| DuelError::InvalidValue {
+                what: "replay file header",
+                value: excerpt(header),
+            })?;
+        if version == 0 || version > REPLAY_FILE_VERSION {
+            return Err(DuelError::UnsupportedVersion {
+                found: version,
+                supported: REPLAY_FILE_VERSION,
+            });
+        }
+
+        let config = field(lines.next(), "config")?;
+        let found = u64::from_str_radix(config, 16).map_err(|_| DuelError::InvalidValue {
+            what: "config",
+            value: excerpt(config),
+        })?;
+        if found != config_hash() {
+            return Err(DuelError::ConfigMismatch {
+                found,
+                expected: config_hash(),
+            });
+        }
+
+        let mut layout = ArenaLayout::default();
+        layout.physics.projectile_inheritance = token(
+            "projectile_inheritance",
+            field(lines.next(), "projectile_inheritance")?,
+        )?;
+        layout.physics.recoil = token("recoil", field(lines.next(), "recoil")?)?;
+        // Version 1 replays predate shields and hull points
+        if version >= 2 {
+            let line = field(lines.next(), "defenses")?;
+            let mut values = line.split_whitespace();
+            layout.physics.shield = token("shield", values.next().unwrap_or_default())?;
+            layout.physics.shield_regen =
+                token("shield regeneration", values.next().unwrap_or_default())?;
+            layout.physics.hull = token("hull", values.next().unwrap_or_default())?;
+        }
+        // Version 3 added limited ammo
+        if version >= 3 {
+            let mut values = field(lines.next(), "ammo")?.split_whitespace();
+            layout.physics.ammo = token("ammo", values.next().unwrap_or_default())?;
+            layout.physics.pickup_interval =
+                token("pickup interval", values.next().unwrap_or_default())?;
+        }
+        // Version 5 added energy and the deflector
+        if version >= 5 {
+            let mut values = field(lines.next(), "energy")?.split_whitespace();
+            layout.physics.energy = token("energy", values.next().unwrap_or_default())?;
+            layout.physics.deflector = token("deflector", values.next().unwrap_or_default())?;
+        }
+        // Version 6 added projectile collisions
+        if version >= 6 {
+            layout.physics.projectile_collisions = token(
+                "projectile_collisions",
+                field(lines.next(), "projectile_collisions")?,
+            )?;
+        }
+        layout.asteroid_count = token("asteroid_count", field(lines.next(), "asteroid_count")?)?;
+        // Version 4 added ship classes
+        if version >= 4 {
+            layout.class_matchups =
+                ClassMatchups::parse(field(lines.next(), "class_matchups")?.trim())?;
+        }
+        layout.randomize_flow_field = token(
+            "randomize_flow_field",
+            field(lines.next(), "randomize_flow_field")?,
+        )?;
+        layout.flow_field = parse_flow_field(field(lines.next(), "flow_field")?)?;
+        layout.validate()?;
+        let match_seed = token("match_seed", field(lines.next(), "match_seed")?)?;
+
+        let count: usize = token("steps", field(lines.next(), "steps")?)?;
+        // Steps are read one at a time, so a huge declared count cannot force a huge allocation
+        let mut steps = Vec::new();
+        let mut loadouts = None;
+        while steps.len() < count {
+            let line = lines.next().unwrap_or_default();
+            if let Some(specs) = line.strip_prefix("loadouts ") {
+                let (a, b) = specs
+                    .split_once(' ')
+                    .ok_or_else(|| DuelError::InvalidValue {
+                        what: "loadouts",
+                        value: excerpt(line),
+                    })?;
+                loadouts = Some([Loadout::parse(a)?, Loadout::parse(b)?]);
+                continue;
+            }
+            let mut values = field(Some(line), "step")?.split_whitespace();
+            let dt: f32 = token("step dt", values.next().unwrap_or_default())?;
+            check_range("step dt", dt, 0.0, 1.0)?;
+            let mut actions = [[0.0; ACTION_SIZE]; 2];
+            for value in actions.iter_mut().flatten() {
+                *value = token("action", values.next().unwrap_or_default())?;
+                check_range("action", *value, f32::MIN, f32::MAX)?;
+            }
+            if values.next().is_some() {
+                return Err(DuelError::InvalidValue {
+                    what: "step",
+                    value: excerpt(line),
+                });
+            }
+            steps.push(ReplayStep {
+                dt,
+                actions,
+                loadouts: loadouts.take(),
+            });
+        }
+
+        let mut replay = Replay::new(layout, match_seed);
+        replay.steps = steps;
+        replay.loadouts = replay
+            .steps
+            .iter()
+            .rev()
+            .find_map(|s| s.loadouts)
+            .unwrap_or_default();
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DuelError> {
+        std::fs::write(path, self.to_file_string())?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, DuelError> {
+        Self::from_file_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Re-simulates a replay with stepping and seeking
+pub struct ReplayPlayer {
+    replay: Replay,
+    state: GameState,
+    /// Steps applied to `state` so far
+    position: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        let state = replay.initial_state();
+        ReplayPlayer {
+            replay,
+            state,
+            position: 0,
+        }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.replay.steps.len()
+    }
+
+    /// Apply the next recorded step; false once every step has been played
+    pub fn step(&mut self) -> bool {
+        let Some(step) = self.replay.steps.get(self.position) else {
+            return false;
+        };
+        if let Some(loadouts) = step.loadouts {
+            for (ship, loadout) in self.state.ships.iter_mut().zip(loadouts) {
+                ship.loadout = loadout;
+            }
+        }
+        self.state.update(step.dt, &step.actions);
+        self.position += 1;
+        true
+    }
+
+    /// Play up to the first step that reaches `time` seconds, re-simulating
+    /// from the start when that lies behind the current position
+    pub fn seek(&mut self, time: f32) {
+        if time < self.state.time {
+            self.state = self.replay.initial_state();
+            self.position = 0;
+        }
+        while self.state.time < time && self.step() {}
+    }
+
+    /// A "what if" continuation from the current position, the exact state
+    /// playback has reached
+    pub fn branch(&self) -> Branch {
+        let mut replay = Replay::new(self.replay.layout.clone(), self.replay.match_seed);
+        replay.steps = self.replay.steps[..self.position].to_vec();
+        replay.loadouts = [self.state.ships[0].loadout, self.state.ships[1].loadout];
+        Branch {
+            state: self.state.clone(),
+            recorded: self.replay.steps[self.position..].to_vec(),
+            played: 0,
+            replay,
+        }
+    }
+}
+
+/// A replay taken over from some moment on: ships handed a pilot are flown
+/// by it, the others keep repeating their recorded controls, which stop
+/// making sense once the match drifts from the recording. The branch is
+/// recorded as a replay of its own, the original steps up to the branch
+/// point followed by the new ones.
+pub struct Branch {
+    state: GameState,
+    /// Steps of the original after the branch point
+    recorded: Vec<ReplayStep>,
+    /// Steps played since the branch point
+    played: usize,
+    replay: Replay,
+}
+
+impl Branch {
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Play one step, at the recorded step's length while the recording
+    /// lasts and SIM_DT after it; false once the match is over
+    pub fn step(&mut self, pilots: [Option<&mut dyn Controller>; 2]) -> bool {
+        if self.state.match_over {
+            return false;
+        }
+        let recorded = self.recorded.get(self.played);
+        if let Some(loadouts) = recorded.and_then(|step| step.loadouts) {
+            for (ship, loadout) in self.state.ships.iter_mut().zip(loadouts) {
+                ship.loadout = loadout;
+            }
+        }
+        let mut actions = recorded.map_or([[0.0; ACTION_SIZE]; 2], |step| step.actions);
+        for (i, pilot) in pilots.into_iter().enumerate() {
+            if let Some(pilot) = pilot {
+                self.state.ships[i].loadout = pilot.loadout();
+                actions[i] = pilot.act(&self.state, i);
+            }
+        }
+        let dt = recorded.map_or(SIM_DT, |step| step.dt);
+        self.replay.record(&self.state, dt, &actions);
+        self.state.update(dt, &actions);
+        self.played += 1;
+        true
+    }
+}
+
+/// What an annotation marks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnotationKind {
+    /// Text shown while the moment plays
+    Note,
+    /// A moment playback can jump to
+    Bookmark,
+}
+
+impl AnnotationKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            AnnotationKind::Note => "note",
+            AnnotationKind::Bookmark => "bookmark",
+        }
+    }
+}
+
+/// Text attached to a moment of a replay
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annotation {
+    /// Match time in seconds
+    pub time: f32,
+    pub kind: AnnotationKind,
+    pub text: String,
+}
+
+/// Notes and bookmarks of one replay, kept in a file next to it so the
+/// replay itself stays a plain recording
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReplayNotes {
+    /// In time order
+    pub annotations: Vec<Annotation>,
+}
+
+impl ReplayNotes {
+    /// File the notes of the replay at `replay` are kept in: its path with
+    /// ".notes" appended
+    pub fn path_for(replay: impl AsRef<Path>) -> PathBuf {
+        let mut path = replay.as_ref().as_os_str().to_owned();
+        path.push(".notes");
+        PathBuf::from(path)
+    }
+
+    /// Add an annotation at `time`, after any already there; line breaks in
+    /// `text` become spaces so each annotation stays one line of the file
+    pub fn add(&mut self, time: f32, kind: AnnotationKind, text: &str) {
+        let index = self.annotations.partition_point(|a| a.time <= time);
+        let text = text.replace(['\n', '\r'], " ").trim().to_string();
+        self.annotations
+            .insert(index, Annotation { time, kind, text });
+    }
+
+    pub fn bookmarks(&self) -> impl Iterator<Item = &Annotation> {
+        self.annotations
+            .iter()
+            .filter(|a| a.kind == AnnotationKind::Bookmark)
+    }
+
+    /// Time of the first bookmark after `time`
+    pub fn next_bookmark(&self, time: f32) -> Option<f32> {
+        self.bookmarks().map(|b| b.time).find(|&t| t > time)
+    }
+
+    /// Time of the last bookmark before `time`, skipping one right behind it
+    pub fn previous_bookmark(&self, time: f32) -> Option<f32> {
+        self.bookmarks()
+            .map(|b| b.time)
+            .filter(|&t| t < time - BOOKMARK_TOLERANCE)
+            .last()
+    }
+
+    /// Annotations to show at `time`: those made in the last NOTE_DISPLAY_SECONDS
+    pub fn showing_at(&self, time: f32) -> impl Iterator<Item = &Annotation> {
+        self.annotations
+            .iter()
+            .filter(move |a| a.time <= time && time < a.time + NOTE_DISPLAY_SECONDS)
+    }
+
+    /// Text form: header, then one "KIND TIME TEXT" line per annotation
+    pub fn to_file_string(&self) -> String {
+        let mut out = format!("{} {}\n", NOTES_FILE_MAGIC, NOTES_FILE_VERSION);
+        for a in &self.annotations {
+            let _ = writeln!(out, "{} {} {}", a.kind.name(), a.time, a.text);
+        }
+        out
+    }
+
+    /// Parse the output of `to_file_string`
+    pub fn from_file_str(text: &str) -> Result<ReplayNotes, DuelError> {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default();
+        let version: u32 = header
+            .strip_prefix(NOTES_FILE_MAGIC)
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| DuelError::InvalidValue {
+                what: "replay notes header",
+                value: excerpt(header),
+            })?;
+        if version == 0 || version > NOTES_FILE_VERSION {
+            return Err(DuelError::UnsupportedVersion {
+                found: version,
+                supported: NOTES_FILE_VERSION,
+            });
+        }
+        let mut notes = ReplayNotes::default();
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            let mut parts = line.splitn(3, ' ');
+            let kind = match parts.next() {
+                Some("note") => AnnotationKind::Note,
+                Some("bookmark") => AnnotationKind::Bookmark,
+                _ => {
+                    return Err(DuelError::InvalidValue {
+                        what: "annotation",
+                        value: excerpt(line),
+                    })
+                }
+            };
+            let time: f32 = token("annotation time", parts.next().unwrap_or_default())?;
+            check_range("annotation time", time, 0.0, f32::MAX)?;
+            notes.add(time, kind, parts.next().unwrap_or_default());
+        }
+        Ok(notes)
+    }
+
+    /// Write the notes of the replay at `replay` next to it
+    pub fn save_for(&self, replay: impl AsRef<Path>) -> Result<(), DuelError> {
+        std::fs::write(Self::path_for(replay), self.to_file_string())?;
+        Ok(())
+    }
+
+    /// Notes of the replay at `replay`; none if it has no notes file yet
+    pub fn load_for(replay: impl AsRef<Path>) -> Result<ReplayNotes, DuelError> {
+        match std::fs::read_to_string(Self::path_for(replay)) {
+            Ok(text) => Self::from_file_str(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ReplayNotes::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Value of a "name value" line
+fn field<'a>(line: Option<&'a str>, name: &'static str) -> Result<&'a str, DuelError> {
+    let line = line.unwrap_or_default();
+    line.strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix(' '))
+        .ok_or_else(|| DuelError::InvalidValue {
+            what: name,
+            value: excerpt(line),
+        })
+}
+
+fn token<T: std::str::FromStr>(what: &'static str, text: &str) -> Result<T, DuelError> {
+    text.trim().parse().map_err(|_| DuelError::InvalidValue {
+        what,
+        value: excerpt(text),
+    })
+}
+
+/// Start of a rejected line, short enough to quote in an error message
+fn excerpt(line: &str) -> String {
+    line.chars().take(40).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::Bot;
+    use crate::genome::{Genome, NetworkState};
+
+    #[test]
+    fn saved_replays_play_back_the_same_match() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let genomes = [Genome::random(&mut rng), Genome::random(&mut rng)];
+        let layout = ArenaLayout {
+            asteroid_count: 2,
+            randomize_flow_field: true,
+            ..Default::default()
+        };
+        let mut replay = Replay::new(layout, 42);
+        let mut state = replay.initial_state();
+        let mut networks = [NetworkState::default(), NetworkState::default()];
+        for step in 0..600 {
+            if state.match_over {
+                break;
+            }
+            if step == 100 {
+                state.ships[1].loadout = Loadout {
+                    turn_rate: 1.5,
+                    smoothing: 0.1,
+                };
+            }
+            let actions = [0, 1]
+                .map(|i| genomes[i].evaluate(&Genome::get_inputs(&state, i), &mut networks[i]));
+            // Uneven frame times, as in the showcase
+            let dt = if step % 3 == 0 {
+                1.0 / 30.0
+            } else {
+                1.0 / 60.0
+            };
+            replay.record(&state, dt, &actions);
+            state.update(dt, &actions);
+        }
+
+        let loaded = Replay::from_file_str(&replay.to_file_string()).unwrap();
+        assert_eq!(loaded.steps, replay.steps);
+        let mut player = ReplayPlayer::new(loaded);
+        while player.step() {}
+        assert!(player.is_finished());
+        let end = player.state();
+        assert_eq!(end.time, state.time);
+        for (played, recorded) in end.ships.iter().zip(&state.ships) {
+            assert_eq!((played.x, played.y), (recorded.x, recorded.y));
+            assert_eq!(played.shots_fired, recorded.shots_fired);
+        }
+
+        // Seeking back re-simulates from the start
+        player.seek(1.0);
+        assert!(player.state().time >= 1.0 && player.state().time < 1.04);
+        let position = player.position();
+        player.seek(0.5);
+        assert!(player.position() < position);
+
+        let foreign = replay.to_file_string().replacen(
+            &format!("{:016x}", config_hash()),
+            "0123456789abcdef",
+            1,
+        );
+        assert!(matches!(
+            Replay::from_file_str(&foreign),
+            Err(DuelError::ConfigMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn branches_take_over_from_the_exact_moment() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let genome = Genome::random(&mut rng);
+        let mut replay = Replay::new(ArenaLayout::default(), 7);
+        let mut state = replay.initial_state();
+        let mut networks = [NetworkState::default(), NetworkState::default()];
+        for _ in 0..300 {
+            let actions =
+                [0, 1].map(|i| genome.evaluate(&Genome::get_inputs(&state, i), &mut networks[i]));
+            replay.record(&state, SIM_DT, &actions);
+            state.update(SIM_DT, &actions);
+        }
+        let mut player = ReplayPlayer::new(replay);
+        player.seek(2.0);
+
+        // Without pilots a branch repeats the recording
+        let mut branch = player.branch();
+        while branch.step([None, None]) && branch.state().time < state.time - SIM_DT / 2.0 {}
+        assert_eq!(branch.state().ships[0].x, state.ships[0].x);
+
+        let mut idle = Bot::Idle;
+        let mut branch = player.branch();
+        for _ in 0..60 {
+            branch.step([None, Some(&mut idle)]);
+        }
+        let moved = branch.state().ships[1].clone();
+        // The branch's own replay plays back to where it ended
+        let mut again = ReplayPlayer::new(branch.replay().clone());
+        while again.step() {}
+        assert_eq!(
+            (again.state().ships[1].x, again.state().ships[1].y),
+            (moved.x, moved.y)
+        );
+        assert_eq!(again.state().time, branch.state().time);
+    }
+
+    #[test]
+    fn replay_notes_round_trip_and_find_bookmarks() {
+        let mut notes = ReplayNotes::default();
+        notes.add(12.0, AnnotationKind::Bookmark, "");
+        notes.add(3.5, AnnotationKind::Note, "first pass\nat the edge");
+        notes.add(7.25, AnnotationKind::Bookmark, "kill");
+        assert_eq!(
+            notes.annotations.iter().map(|a| a.time).collect::<Vec<_>>(),
+            vec![3.5, 7.25, 12.0]
+        );
+        assert_eq!(notes.annotations[0].text, "first pass at the edge");
+
+        assert_eq!(notes.next_bookmark(0.0), Some(7.25));
+        assert_eq!(notes.next_bookmark(7.25), Some(12.0));
+        assert_eq!(notes.next_bookmark(12.0), None);
+        // Just after a jump, jumping back skips the bookmark jumped to
+        assert_eq!(notes.previous_bookmark(12.1), Some(7.25));
+        assert_eq!(notes.previous_bookmark(7.3), None);
+        assert_eq!(notes.showing_at(4.0).count(), 1);
+        assert_eq!(notes.showing_at(7.0).count(), 0);
+
+        let loaded = ReplayNotes::from_file_str(&notes.to_file_string()).unwrap();
+        assert_eq!(loaded, notes);
+        assert!(ReplayNotes::from_file_str("spaceship-duel replay notes 1\npin 3 x").is_err());
+        assert!(matches!(
+            ReplayNotes::from_file_str("spaceship-duel replay notes 9\n"),
+            Err(DuelError::UnsupportedVersion { .. })
+        ));
+        assert_eq!(
+            ReplayNotes::path_for("runs/final.replay"),
+            PathBuf::from("runs/final.replay.notes")
+        );
+    }
+}
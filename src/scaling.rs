@@ -0,0 +1,122 @@
+use rand::Rng;
+
+use crate::error::DuelError;
+use crate::game::{ArenaLayout, ArenaSize};
+use crate::gauntlet::Gauntlet;
+use crate::genome::Genome;
+
+/// Gauntlet score of a genome in one arena size
+#[derive(Clone, Debug)]
+pub struct ScalePoint {
+    pub arena: ArenaSize,
+    pub score: f32,
+    /// Score lost against the arena it trained in; negative if it did better
+    pub degradation: f32,
+}
+
+/// How a frozen genome's gauntlet score holds up as the arena is resized
+#[derive(Clone, Debug)]
+pub struct ScalingReport {
+    /// Score in the arena the genome trained in
+    pub stock: f32,
+    pub points: Vec<ScalePoint>,
+}
+
+impl ScalingReport {
+    /// Largest drop from the trained arena's score over all sizes
+    pub fn worst_degradation(&self) -> f32 {
+        self.points
+            .iter()
+            .map(|p| p.degradation)
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Score `genome` against the gauntlet, `matches_each` matches per opponent,
+/// first in `layout`'s arena, the one it trained in, and then in each of
+/// `arenas` under the same rules. Gauntlet opponents move to the new arena
+/// too, so a score that falls means the genome's tactics leaned on the
+/// trained field's size rather than on its opponent.
+pub fn measure(
+    genome: &Genome,
+    gauntlet: &Gauntlet,
+    layout: &ArenaLayout,
+    arenas: &[ArenaSize],
+    matches_each: usize,
+    rng: &mut impl Rng,
+) -> Result<ScalingReport, DuelError> {
+    let mut layout = layout.clone();
+    let stock = gauntlet.score(genome, &layout, matches_each, rng);
+    let points = arenas
+        .iter()
+        .map(|&arena| {
+            layout.arena = arena;
+            layout.validate()?;
+            let score = gauntlet.score(genome, &layout, matches_each, rng);
+            Ok(ScalePoint {
+                arena,
+                score,
+                degradation: stock - score,
+            })
+        })
+        .collect::<Result<_, DuelError>>()?;
+    Ok(ScalingReport { stock, points })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, Ship};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn matches_play_out_in_the_arena_they_are_given() {
+        // Ships wrap at the edge of a small arena instead of the stock one
+        let small = ArenaSize::parse("400x300").unwrap();
+        let mut state = GameState::new();
+        state.arena = small;
+        state.ships[0] = Ship::new(399.0, 150.0, 0.0);
+        state.ships[0].vx = 300.0;
+        state.ships[1] = Ship::new(100.0, 250.0, 0.0);
+        state.update(1.0 / 60.0, &[[0.0; 5]; 2]);
+        assert!(state.ships[0].x < 10.0);
+
+        // Distance sensors scale with the arena: the same share of the field
+        // reads the same in both
+        let mut stock = GameState::new();
+        stock.ships[0] = Ship::new(0.0, 0.0, 0.0);
+        stock.ships[1] = Ship::new(200.0, 0.0, 0.0);
+        let mut doubled = stock.clone();
+        doubled.arena = ArenaSize::scaled(2.0);
+        doubled.ships[1].x = 400.0;
+        assert_eq!(
+            Genome::get_inputs(&stock, 0)[0],
+            Genome::get_inputs(&doubled, 0)[0]
+        );
+        assert_eq!(ArenaSize::default().engagement_range(), 500.0);
+        assert!(ArenaSize::parse("10x10").is_err());
+        assert!(ArenaSize::parse("wide").is_err());
+
+        let mut rng = StdRng::seed_from_u64(6);
+        let gauntlet = Gauntlet {
+            opponents: vec![Genome::random(&mut rng)],
+        };
+        let genome = Genome::random(&mut rng);
+        let arenas = [ArenaSize::scaled(0.5), ArenaSize::scaled(2.0)];
+        let report = measure(
+            &genome,
+            &gauntlet,
+            &ArenaLayout::default(),
+            &arenas,
+            2,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(report.points.len(), 2);
+        for point in &report.points {
+            assert_eq!(point.degradation, report.stock - point.score);
+        }
+        assert!(report.worst_degradation() >= 0.0);
+    }
+}
@@ -0,0 +1,67 @@
+use std::fs;
+
+use macroquad::miniquad::date;
+use macroquad::time::get_time;
+
+/// How often the power supply and clock are re-checked, in seconds
+const CHECK_INTERVAL: f64 = 5.0;
+
+/// Conditions under which background evolution holds off between generations
+#[derive(Clone, Debug, Default)]
+pub struct PauseSchedule {
+    /// Quiet hours as [start, end) in UTC; wraps past midnight when start > end
+    pub quiet_hours_utc: Option<(u32, u32)>,
+    /// Pause while the machine is running from a discharging battery
+    pub on_battery: bool,
+    /// Show-only mode toggled from the keyboard
+    pub manual: bool,
+    /// macroquad's clock, which unlike `Instant` also runs in browsers
+    last_check: Option<f64>,
+    scheduled: bool,
+}
+
+impl PauseSchedule {
+    /// Whether evolution should currently be held, re-polling the system at most every few seconds
+    pub fn is_paused(&mut self) -> bool {
+        let now = get_time();
+        if self.last_check.is_none_or(|t| now - t >= CHECK_INTERVAL) {
+            self.last_check = Some(now);
+            self.scheduled = self.in_quiet_hours() || (self.on_battery && on_battery_power());
+        }
+        self.manual || self.scheduled
+    }
+
+    fn in_quiet_hours(&self) -> bool {
+        let Some((start, end)) = self.quiet_hours_utc else {
+            return false;
+        };
+        let secs = date::now().max(0.0) as u64;
+        let hour = ((secs / 3600) % 24) as u32;
+        if start <= end {
+            (start..end).contains(&hour)
+        } else {
+            hour >= start || hour < end
+        }
+    }
+}
+
+/// Parse an hour range like "22-7" into (start, end)
+pub fn parse_hours(spec: &str) -> Option<(u32, u32)> {
+    let (start, end) = spec.split_once('-')?;
+    let start: u32 = start.trim().parse().ok()?;
+    let end: u32 = end.trim().parse().ok()?;
+    (start < 24 && end < 24).then_some((start, end))
+}
+
+/// True if any battery reported by Linux sysfs is discharging
+fn on_battery_power() -> bool {
+    let Ok(entries) = fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+        let status = fs::read_to_string(path.join("status")).unwrap_or_default();
+        kind.trim() == "Battery" && status.trim() == "Discharging"
+    })
+}
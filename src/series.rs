@@ -0,0 +1,102 @@
+use macroquad::prelude::*;
+
+use spaceship_duel::game::*;
+
+use crate::i18n::{tr, tr_fill, Text};
+
+/// Matches in a showcase series
+pub const SERIES_LENGTH: usize = 5;
+/// Wins that settle a series before all its matches are played
+const WINS_NEEDED: usize = SERIES_LENGTH / 2 + 1;
+
+/// Best-of-five series between the two showcase ships, so which genome is
+/// stronger is judged on more than one random start. A drawn match counts
+/// toward the series length but for neither ship.
+#[derive(Debug, Default)]
+pub struct Series {
+    wins: [usize; 2],
+    played: usize,
+}
+
+impl Series {
+    /// Count a finished match won by `winner`, or drawn if None
+    pub fn record(&mut self, winner: Option<usize>) {
+        self.played += 1;
+        if let Some(ship) = winner {
+            self.wins[ship] += 1;
+        }
+    }
+
+    /// Whether a ship has won a majority of the matches or all were played
+    pub fn is_over(&self) -> bool {
+        self.wins.iter().any(|&w| w >= WINS_NEEDED) || self.played >= SERIES_LENGTH
+    }
+
+    /// Ship that took the series, None while it runs or if it ended level
+    pub fn winner(&self) -> Option<usize> {
+        if !self.is_over() || self.wins[0] == self.wins[1] {
+            return None;
+        }
+        Some((self.wins[1] > self.wins[0]) as usize)
+    }
+
+    /// Scoreboard at the top of the arena, and once the series is over its
+    /// result under the match result, in the winner's color
+    pub fn render(&self, colors: [Color; 2]) {
+        let grey = Color::new(0.5, 0.5, 0.5, 1.0);
+        let title = tr_fill(
+            Text::SeriesTitle,
+            &[
+                ("length", SERIES_LENGTH.to_string()),
+                ("match", (self.played + 1).min(SERIES_LENGTH).to_string()),
+            ],
+        );
+        let width = measure_text(&title, None, 18, 1.0).width;
+        draw_text(&title, (ARENA_WIDTH - width) / 2.0, 20.0, 18.0, grey);
+
+        let winner = self.winner();
+        let sides = [
+            format!("{} {}", tr(Text::ShipGreen), self.wins[0]),
+            format!("{} {}", self.wins[1], tr(Text::ShipBlue)),
+        ];
+        let separator = " : ";
+        let font_size = 24.0;
+        let measure = |text: &str| measure_text(text, None, font_size as u16, 1.0).width;
+        let mut x =
+            (ARENA_WIDTH - measure(&sides[0]) - measure(separator) - measure(&sides[1])) / 2.0;
+        for (ship, side) in sides.iter().enumerate() {
+            // The loser of a decided series fades
+            let color = match winner {
+                Some(w) if w != ship => Color {
+                    a: 0.4,
+                    ..colors[ship]
+                },
+                _ => colors[ship],
+            };
+            draw_text(side, x, 42.0, font_size, color);
+            x += measure(side);
+            if ship == 0 {
+                draw_text(separator, x, 42.0, font_size, grey);
+                x += measure(separator);
+            }
+        }
+
+        if !self.is_over() {
+            return;
+        }
+        let (msg, color) = match winner {
+            Some(0) => (tr(Text::SeriesGreenWins), colors[0]),
+            Some(_) => (tr(Text::SeriesBlueWins), colors[1]),
+            None => (tr(Text::SeriesDrawn), WHITE),
+        };
+        let msg = format!("{} {}-{}", msg, self.wins[0], self.wins[1]);
+        let width = measure_text(&msg, None, 26, 1.0).width;
+        draw_text(
+            &msg,
+            (ARENA_WIDTH - width) / 2.0,
+            ARENA_HEIGHT / 2.0 + 40.0,
+            26.0,
+            color,
+        );
+    }
+}
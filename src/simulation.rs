@@ -1,94 +1,614 @@
 use rand::Rng;
 
+use crate::bots::{Controller, GenomeController};
+use crate::error::{check_range, DuelError};
+use crate::events::{EventLog, MatchEvent};
 use crate::game::*;
 use crate::genome::*;
+use crate::novelty::{Behavior, BehaviorRecorder};
+use crate::pacing::EngagementTimeline;
+use crate::pareto::Objectives;
+use crate::profiling::Span;
 
-const SIM_DT: f32 = 1.0 / 60.0;
+/// Fixed simulation step of every match
+pub const SIM_DT: f32 = 1.0 / 60.0;
 const SIM_STEPS: usize = (MATCH_DURATION / SIM_DT) as usize;
+/// Largest magnitude accepted for a fitness coefficient
+const MAX_FITNESS_WEIGHT: f32 = 1e6;
+/// Melee score per kill, in units of outlasting every other ship
+pub const MELEE_KILL_SCORE: f32 = 0.5;
+/// Melee score per assist, so ships that wear a target down are not left
+/// empty-handed when another ship lands the last hit
+pub const MELEE_ASSIST_SCORE: f32 = 0.25;
 
 #[derive(Clone, Debug)]
 pub struct MatchResult {
     pub fitness: [f32; 2],
+    /// Simulated seconds until the match ended
+    pub duration: f32,
+    /// Surviving ship if the other was destroyed, None for a draw
+    pub winner: Option<usize>,
+    /// Class each ship flew, as the layout's class matchups dealt them
+    pub classes: [ShipClass; 2],
+    /// What each ship did, for novelty search
+    pub behavior: [Behavior; 2],
+    /// How each ship did on every objective, for Pareto selection
+    pub objectives: [Objectives; 2],
 }
 
 /// Run a full match between two genomes at max speed, returning fitness for each
-pub fn run_match(g1: &Genome, g2: &Genome, rng: &mut impl Rng) -> MatchResult {
-    let mut state = GameState::new_random(rng);
-    let genomes = [g1, g2];
+pub fn run_match(
+    g1: &Genome,
+    g2: &Genome,
+    layout: &ArenaLayout,
+    weights: &FitnessWeights,
+    rng: &mut impl Rng,
+) -> MatchResult {
+    play_match(g1, g2, layout, weights, None, rng)
+}
+
+/// `run_match` that also records the engagement timeline of the match; the
+/// match itself plays out exactly as it would without recording
+pub fn run_match_with_timeline(
+    g1: &Genome,
+    g2: &Genome,
+    layout: &ArenaLayout,
+    weights: &FitnessWeights,
+    rng: &mut impl Rng,
+) -> (MatchResult, EngagementTimeline) {
+    let mut timeline = EngagementTimeline::default();
+    let result = play_match(
+        g1,
+        g2,
+        layout,
+        weights,
+        Some(&mut |state: &GameState| timeline.record(state)),
+        rng,
+    );
+    (result, timeline)
+}
+
+/// `run_match` that shows `observe` the state after every step, e.g. to let
+/// someone watch; the match plays out exactly as it would unobserved
+pub fn run_match_observed(
+    g1: &Genome,
+    g2: &Genome,
+    layout: &ArenaLayout,
+    weights: &FitnessWeights,
+    observe: &mut dyn FnMut(&GameState),
+    rng: &mut impl Rng,
+) -> MatchResult {
+    play_match(g1, g2, layout, weights, Some(observe), rng)
+}
+
+fn play_match(
+    g1: &Genome,
+    g2: &Genome,
+    layout: &ArenaLayout,
+    weights: &FitnessWeights,
+    observe: Option<&mut dyn FnMut(&GameState)>,
+    rng: &mut impl Rng,
+) -> MatchResult {
+    let _span = Span::enter("run_match");
+    let (mut c1, mut c2) = (GenomeController::new(g1), GenomeController::new(g2));
+    play_controllers([&mut c1, &mut c2], layout, weights, observe, rng)
+}
+
+/// A full match between any two controllers, such as a genome and a
+/// scripted bot, each flying with its own loadout
+pub fn run_controllers(
+    controllers: [&mut dyn Controller; 2],
+    layout: &ArenaLayout,
+    weights: &FitnessWeights,
+    rng: &mut impl Rng,
+) -> MatchResult {
+    play_controllers(controllers, layout, weights, None, rng)
+}
+
+fn play_controllers(
+    controllers: [&mut dyn Controller; 2],
+    layout: &ArenaLayout,
+    weights: &FitnessWeights,
+    mut observe: Option<&mut dyn FnMut(&GameState)>,
+    rng: &mut impl Rng,
+) -> MatchResult {
+    let mut session = MatchSession::new(controllers, layout, weights, rng);
+    while session.step() {
+        if let Some(observe) = observe.as_deref_mut() {
+            observe(session.state());
+        }
+    }
+    session.result()
+}
 
+/// A match advanced one fixed step at a time, so a caller can inspect the
+/// full state between steps or fly either ship itself (a learning agent, an
+/// external client, a test). Stepped to the end without injected actions it
+/// plays exactly the match `run_controllers` would.
+pub struct MatchSession<'a> {
+    state: GameState,
+    controllers: [&'a mut dyn Controller; 2],
     // Track proximity over time for engagement scoring
-    let mut proximity_sum = [0.0f32; 2];
-    let mut step_count = 0u32;
+    tracker: FitnessTracker,
+    behavior: BehaviorRecorder,
+    steps: usize,
+}
 
-    for _ in 0..SIM_STEPS {
-        if state.match_over {
-            break;
+impl<'a> MatchSession<'a> {
+    /// Set up a fresh match in `layout`, scored with `weights`
+    pub fn new(
+        controllers: [&'a mut dyn Controller; 2],
+        layout: &ArenaLayout,
+        weights: &FitnessWeights,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut state = layout.new_match(rng);
+        for (ship, controller) in state.ships.iter_mut().zip(&controllers) {
+            ship.loadout = controller.loadout();
+        }
+        MatchSession {
+            state,
+            controllers,
+            tracker: FitnessTracker::new(weights.clone()),
+            behavior: BehaviorRecorder::default(),
+            steps: 0,
         }
+    }
 
-        let inputs0 = Genome::get_inputs(&state, 0);
-        let inputs1 = Genome::get_inputs(&state, 1);
-        let actions0 = genomes[0].evaluate(&inputs0);
-        let actions1 = genomes[1].evaluate(&inputs1);
-        state.update(SIM_DT, &[actions0, actions1]);
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
 
-        // Accumulate proximity each step
-        let dx = toroidal_diff(state.ships[0].x, state.ships[1].x, ARENA_WIDTH);
-        let dy = toroidal_diff(state.ships[0].y, state.ships[1].y, ARENA_HEIGHT);
-        let dist = (dx * dx + dy * dy).sqrt();
-        let prox = 1.0 - (dist / 500.0).min(1.0);
-        proximity_sum[0] += prox;
-        proximity_sum[1] += prox;
-        step_count += 1;
+    /// Fit each ship with a handicap; call before the first step
+    pub fn set_handicaps(&mut self, handicaps: [Handicap; 2]) {
+        for (ship, handicap) in self.state.ships.iter_mut().zip(handicaps) {
+            ship.handicap = handicap;
+        }
     }
 
-    let avg_proximity = if step_count > 0 {
-        [
-            proximity_sum[0] / step_count as f32,
-            proximity_sum[1] / step_count as f32,
-        ]
-    } else {
-        [0.0, 0.0]
-    };
-
-    // Compute fitness for each ship
-    let mut fitness = [0.0f32; 2];
-    for i in 0..2 {
-        let ship = &state.ships[i];
-        let opp = &state.ships[1 - i];
-
-        // Win bonus
-        if ship.alive && !opp.alive {
-            fitness[i] += 100.0;
+    /// Steps taken so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Whether a ship is gone or the match ran out of time
+    pub fn is_over(&self) -> bool {
+        self.state.match_over || self.steps >= SIM_STEPS
+    }
+
+    /// Advance one step with both controllers flying; false once the match is over
+    pub fn step(&mut self) -> bool {
+        self.step_with([None, None])
+    }
+
+    /// Advance one step, flying each ship given actions with them instead of
+    /// asking its controller; false once the match is over
+    pub fn step_with(&mut self, actions: [Option<[f32; ACTION_SIZE]>; 2]) -> bool {
+        if self.is_over() {
+            return false;
+        }
+        let actions =
+            [0, 1].map(|i| actions[i].unwrap_or_else(|| self.controllers[i].act(&self.state, i)));
+        self.state.update(SIM_DT, &actions);
+        self.tracker.record(&self.state);
+        self.behavior.record(&self.state);
+        self.steps += 1;
+        true
+    }
+
+    /// Fitness components of both ships so far
+    pub fn breakdown(&self) -> [FitnessBreakdown; 2] {
+        self.tracker.breakdown(&self.state)
+    }
+
+    /// Outcome of the match as it stands; final once `is_over`
+    pub fn result(&self) -> MatchResult {
+        let breakdown = self.breakdown();
+        MatchResult {
+            fitness: [breakdown[0].total(), breakdown[1].total()],
+            duration: self.state.time,
+            winner: self.state.winner,
+            classes: [self.state.ships[0].class, self.state.ships[1].class],
+            behavior: self.behavior.behaviors(),
+            objectives: self.tracker.objectives(&self.state),
+        }
+    }
+}
+
+/// Outcome of a free-for-all melee
+#[derive(Clone, Debug)]
+pub struct MeleeResult {
+    /// Score of each ship: the share of the other ships destroyed before it
+    /// was, so a sole survivor places 1 and the first ship down 0, plus
+    /// MELEE_KILL_SCORE per kill and MELEE_ASSIST_SCORE per assist, also
+    /// divided by the number of other ships
+    pub scores: Vec<f32>,
+    /// Ships each ship destroyed
+    pub kills: Vec<usize>,
+    /// Kills each ship assisted by damaging the target within ASSIST_WINDOW
+    pub assists: Vec<usize>,
+    /// Simulated seconds until the match ended
+    pub duration: f32,
+    /// The last ship standing; None if several outlasted the clock or none survived
+    pub winner: Option<usize>,
+}
+
+/// Play a free-for-all melee among `genomes` at max speed
+pub fn run_melee(
+    genomes: &[&Genome],
+    layout: &ArenaLayout,
+    rng: &mut impl Rng,
+) -> Result<MeleeResult, DuelError> {
+    let _span = Span::enter("run_melee");
+    let controllers = genomes
+        .iter()
+        .map(|&g| Box::new(GenomeController::new(g)) as Box<dyn Controller>)
+        .collect();
+    let mut session = MeleeSession::new(controllers, layout, rng)?;
+    while session.step() {}
+    Ok(session.result())
+}
+
+/// A free-for-all among MIN_MELEE_SHIPS to MAX_MELEE_SHIPS controllers,
+/// advanced one fixed step at a time like `MatchSession`. Genome sensors
+/// track the nearest ship still flying, and the match is scored by
+/// placement, kills and assists instead of with the duel's fitness formula.
+pub struct MeleeSession<'a> {
+    state: GameState,
+    controllers: Vec<Box<dyn Controller + 'a>>,
+    /// Ships destroyed before each ship was, for ships already destroyed
+    outlasted: Vec<Option<usize>>,
+    events: EventLog,
+    kills: Vec<usize>,
+    assists: Vec<usize>,
+    steps: usize,
+}
+
+impl<'a> MeleeSession<'a> {
+    /// Set up a fresh melee in `layout` with one ship per controller
+    pub fn new(
+        controllers: Vec<Box<dyn Controller + 'a>>,
+        layout: &ArenaLayout,
+        rng: &mut impl Rng,
+    ) -> Result<Self, DuelError> {
+        check_range(
+            "melee ships",
+            controllers.len() as f32,
+            MIN_MELEE_SHIPS as f32,
+            MAX_MELEE_SHIPS as f32,
+        )?;
+        let mut state = layout.new_melee(controllers.len(), rng);
+        for (ship, controller) in state.ships.iter_mut().zip(&controllers) {
+            ship.loadout = controller.loadout();
+        }
+        Ok(MeleeSession {
+            state,
+            outlasted: vec![None; controllers.len()],
+            events: EventLog::new(),
+            kills: vec![0; controllers.len()],
+            assists: vec![0; controllers.len()],
+            controllers,
+            steps: 0,
+        })
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn controllers(&self) -> &[Box<dyn Controller + 'a>] {
+        &self.controllers
+    }
+
+    /// Whether one ship is left or the match ran out of time
+    pub fn is_over(&self) -> bool {
+        self.state.match_over || self.steps >= SIM_STEPS
+    }
+
+    /// Advance one step with every controller flying; false once the match is over
+    pub fn step(&mut self) -> bool {
+        if self.is_over() {
+            return false;
+        }
+        let actions: Vec<_> = self
+            .controllers
+            .iter_mut()
+            .enumerate()
+            .map(|(i, controller)| controller.act(&self.state, i))
+            .collect();
+        let down = self.destroyed();
+        self.state.update(SIM_DT, &actions);
+        for (ship, outlasted) in self.state.ships.iter().zip(&mut self.outlasted) {
+            if !ship.alive && outlasted.is_none() {
+                *outlasted = Some(down);
+            }
         }
+        for event in self.events.record(&self.state) {
+            match *event {
+                MatchEvent::Destroyed { shooter, .. } => self.kills[shooter] += 1,
+                MatchEvent::Assisted { ship, .. } => self.assists[ship] += 1,
+                _ => {}
+            }
+        }
+        // Only the tallies matter, so the log need not grow for the whole match
+        self.events.events.clear();
+        self.steps += 1;
+        true
+    }
 
-        // Death penalty
-        if !ship.alive {
-            fitness[i] -= 20.0;
+    fn destroyed(&self) -> usize {
+        self.state.ships.iter().filter(|s| !s.alive).count()
+    }
+
+    /// Outcome of the melee as it stands; final once `is_over`
+    pub fn result(&self) -> MeleeResult {
+        let others = (self.state.ships.len() - 1) as f32;
+        let down = self.destroyed();
+        MeleeResult {
+            scores: (0..self.outlasted.len())
+                .map(|i| {
+                    let placement = self.outlasted[i].unwrap_or(down) as f32;
+                    let credit = self.kills[i] as f32 * MELEE_KILL_SCORE
+                        + self.assists[i] as f32 * MELEE_ASSIST_SCORE;
+                    (placement + credit) / others
+                })
+                .collect(),
+            kills: self.kills.clone(),
+            assists: self.assists.clone(),
+            duration: self.state.time,
+            winner: self.state.winner,
         }
+    }
+}
 
-        // Hit bonus
-        fitness[i] += ship.hits_scored as f32 * 50.0;
+/// Coefficients of the fitness formula; defaults are the tuned training values
+#[derive(Clone, Debug, PartialEq)]
+pub struct FitnessWeights {
+    pub win: f32,
+    pub death: f32,
+    pub hit: f32,
+    pub accuracy: f32,
+    pub per_shot: f32,
+    /// Shots beyond this count earn no further engagement reward
+    pub max_rewarded_shots: f32,
+    /// Fitness deducted for every shot fired, without limit. With `accuracy`
+    /// at 0 this prices ammunition instead of rewarding the hit ratio, which
+    /// a ship can game by firing once and hitting
+    pub shot_cost: f32,
+    pub proximity: f32,
+    pub survival: f32,
+    /// Survival credit for the time lived before being destroyed
+    pub survival_before_death: f32,
+    /// Fitness deducted per full energy pool drawn, so thrust, shots and the
+    /// deflector are spent where they pay off; only matters when ships run on energy
+    pub energy_cost: f32,
+}
 
-        // Accuracy bonus (reward aimed shots over spray)
-        if ship.shots_fired > 0 {
-            let accuracy = ship.hits_scored as f32 / ship.shots_fired as f32;
-            fitness[i] += accuracy * 30.0;
+impl Default for FitnessWeights {
+    fn default() -> Self {
+        FitnessWeights {
+            win: 100.0,
+            death: -20.0,
+            hit: 50.0,
+            accuracy: 30.0,
+            per_shot: 0.5,
+            max_rewarded_shots: 20.0,
+            shot_cost: 0.0,
+            proximity: 20.0,
+            survival: 15.0,
+            survival_before_death: 5.0,
+            energy_cost: 0.0,
         }
+    }
+}
 
-        // Active engagement: small reward for actually firing (prevents pure passive play)
-        fitness[i] += (ship.shots_fired as f32).min(20.0) * 0.5;
+impl FitnessWeights {
+    /// Every coefficient with the name `set` accepts for it
+    pub fn values(&self) -> [(&'static str, f32); 11] {
+        [
+            ("win", self.win),
+            ("death", self.death),
+            ("hit", self.hit),
+            ("accuracy", self.accuracy),
+            ("per_shot", self.per_shot),
+            ("max_rewarded_shots", self.max_rewarded_shots),
+            ("shot_cost", self.shot_cost),
+            ("proximity", self.proximity),
+            ("survival", self.survival),
+            ("survival_before_death", self.survival_before_death),
+            ("energy_cost", self.energy_cost),
+        ]
+    }
+
+    /// Set one coefficient by name, as used on the command line
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), DuelError> {
+        let field = match name {
+            "win" => &mut self.win,
+            "death" => &mut self.death,
+            "hit" => &mut self.hit,
+            "accuracy" => &mut self.accuracy,
+            "per_shot" => &mut self.per_shot,
+            "max_rewarded_shots" => &mut self.max_rewarded_shots,
+            "shot_cost" => &mut self.shot_cost,
+            "proximity" => &mut self.proximity,
+            "survival" => &mut self.survival,
+            "survival_before_death" => &mut self.survival_before_death,
+            "energy_cost" => &mut self.energy_cost,
+            _ => {
+                return Err(DuelError::UnknownName {
+                    kind: "fitness weight",
+                    name: name.to_string(),
+                })
+            }
+        };
+        check_range(name, value, -MAX_FITNESS_WEIGHT, MAX_FITNESS_WEIGHT)?;
+        *field = value;
+        Ok(())
+    }
+}
 
-        // Average proximity throughout the match (rewards aggressive positioning)
-        fitness[i] += avg_proximity[i] * 20.0;
+/// One ship's fitness split into its scoring components
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FitnessBreakdown {
+    pub win: f32,
+    pub death: f32,
+    pub hits: f32,
+    pub accuracy: f32,
+    pub shots: f32,
+    /// Cost of the ammunition fired, zero or negative
+    pub ammo: f32,
+    pub proximity: f32,
+    pub survival: f32,
+    /// Cost of the energy drawn, zero or negative
+    pub energy: f32,
+}
+
+impl FitnessBreakdown {
+    pub fn total(&self) -> f32 {
+        self.win
+            + self.death
+            + self.hits
+            + self.accuracy
+            + self.shots
+            + self.ammo
+            + self.proximity
+            + self.survival
+            + self.energy
+    }
 
-        // Survival time bonus (proportional, not binary)
-        if ship.alive {
-            fitness[i] += (state.time / MATCH_DURATION).min(1.0) * 15.0;
+    /// (label, value) pairs in display order
+    pub fn components(&self) -> [(&'static str, f32); 9] {
+        [
+            ("win", self.win),
+            ("death", self.death),
+            ("hits", self.hits),
+            ("accuracy", self.accuracy),
+            ("shots", self.shots),
+            ("ammo", self.ammo),
+            ("proximity", self.proximity),
+            ("survival", self.survival),
+            ("energy", self.energy),
+        ]
+    }
+}
+
+/// Accumulates the per-step statistics fitness is computed from, so the
+/// score of a match in progress can be read at any time. Duel-only: each
+/// ship is scored against the other, so states must hold exactly two ships.
+#[derive(Clone, Debug, Default)]
+pub struct FitnessTracker {
+    weights: FitnessWeights,
+    proximity_sum: f32,
+    step_count: u32,
+}
+
+impl FitnessTracker {
+    pub fn new(weights: FitnessWeights) -> Self {
+        FitnessTracker {
+            weights,
+            ..Default::default()
+        }
+    }
+
+    /// Record one simulation step; call after each `GameState::update`
+    pub fn record(&mut self, state: &GameState) {
+        debug_assert_eq!(state.ships.len(), 2, "fitness is tracked for duels only");
+        let dx = toroidal_diff(state.ships[0].x, state.ships[1].x, state.arena.width);
+        let dy = toroidal_diff(state.ships[0].y, state.ships[1].y, state.arena.height);
+        let dist = (dx * dx + dy * dy).sqrt();
+        self.proximity_sum += 1.0 - (dist / state.arena.engagement_range()).min(1.0);
+        self.step_count += 1;
+    }
+
+    /// Average proximity throughout the match, shared by both ships
+    fn avg_proximity(&self) -> f32 {
+        if self.step_count > 0 {
+            self.proximity_sum / self.step_count as f32
         } else {
-            // Partial credit for surviving longer before dying
-            fitness[i] += (state.time / MATCH_DURATION).min(1.0) * 5.0;
+            0.0
         }
     }
 
-    MatchResult { fitness }
+    /// Each ship's objectives if the match ended in `state`, unweighted
+    pub fn objectives(&self, state: &GameState) -> [Objectives; 2] {
+        debug_assert_eq!(state.ships.len(), 2, "fitness is tracked for duels only");
+        let proximity = self.avg_proximity();
+        [0, 1].map(|i| {
+            let ship = &state.ships[i];
+            let won = ship.alive && !state.ships[1 - i].alive;
+            let accuracy = if ship.shots_fired > 0 {
+                ship.hits_scored as f32 / ship.shots_fired as f32
+            } else {
+                0.0
+            };
+            let elapsed = (state.time / MATCH_DURATION).min(1.0);
+            let survival = if ship.alive { 1.0 } else { elapsed };
+            // Share of the fastest fire rate its ship allows that it kept up
+            let fire_rate = if state.time > 0.0 {
+                (ship.shots_fired as f32 * ship.fire_interval() / state.time).min(1.0)
+            } else {
+                0.0
+            };
+            [
+                won as u8 as f32,
+                accuracy,
+                survival,
+                (proximity + fire_rate) / 2.0,
+            ]
+        })
+    }
+
+    /// Fitness each ship would receive if the match ended in `state`
+    pub fn breakdown(&self, state: &GameState) -> [FitnessBreakdown; 2] {
+        debug_assert_eq!(state.ships.len(), 2, "fitness is tracked for duels only");
+        let avg_proximity = self.avg_proximity();
+
+        let w = &self.weights;
+        let mut result = [FitnessBreakdown::default(); 2];
+        for (i, f) in result.iter_mut().enumerate() {
+            let ship = &state.ships[i];
+            let opp = &state.ships[1 - i];
+
+            // Win bonus
+            if ship.alive && !opp.alive {
+                f.win = w.win;
+            }
+
+            // Death penalty
+            if !ship.alive {
+                f.death = w.death;
+            }
+
+            // Hit bonus
+            f.hits = ship.hits_scored as f32 * w.hit;
+
+            // Accuracy bonus (reward aimed shots over spray)
+            if ship.shots_fired > 0 {
+                let accuracy = ship.hits_scored as f32 / ship.shots_fired as f32;
+                f.accuracy = accuracy * w.accuracy;
+            }
+
+            // Active engagement: small reward for actually firing (prevents pure passive play)
+            f.shots = (ship.shots_fired as f32).min(w.max_rewarded_shots) * w.per_shot;
+
+            // Ammunition economy: every shot costs, so only shots likely to hit pay off
+            f.ammo = -(ship.shots_fired as f32) * w.shot_cost;
+
+            // Average proximity throughout the match (rewards aggressive positioning)
+            f.proximity = avg_proximity * w.proximity;
+
+            // Survival time bonus (proportional, not binary)
+            let elapsed = (state.time / MATCH_DURATION).min(1.0);
+            f.survival = if ship.alive {
+                elapsed * w.survival
+            } else {
+                // Partial credit for surviving longer before dying
+                elapsed * w.survival_before_death
+            };
+
+            // Energy economy: every unit drawn costs, rewarding efficient flying
+            f.energy = -ship.energy_spent * w.energy_cost;
+        }
+        result
+    }
 }
@@ -0,0 +1,97 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Latest value published by one thread for others to display, such as a
+/// copy of a `GameState` from a match running on an evaluation worker.
+/// Publishing never waits: if a reader is taking the value at that moment
+/// the update is dropped, and the next one gets through. Readers get an
+/// `Arc` they can hold for as long as rendering takes without holding up
+/// the writer.
+#[derive(Debug)]
+pub struct SnapshotCell<T> {
+    latest: Mutex<Option<Arc<T>>>,
+    /// Number of values published so far
+    version: AtomicU64,
+}
+
+impl<T> Default for SnapshotCell<T> {
+    fn default() -> Self {
+        SnapshotCell {
+            latest: Mutex::new(None),
+            version: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<T> SnapshotCell<T> {
+    /// Replace the published value unless the cell is busy; returns whether
+    /// `value` was published
+    pub fn publish(&self, value: T) -> bool {
+        // Allocate before and free after the critical section, keeping it to a pointer swap
+        let value = Arc::new(value);
+        let previous = match self.latest.try_lock() {
+            Ok(mut latest) => latest.replace(value),
+            Err(_) => return false,
+        };
+        self.version.fetch_add(1, Ordering::Release);
+        drop(previous);
+        true
+    }
+
+    /// The most recently published value, None before the first
+    pub fn latest(&self) -> Option<Arc<T>> {
+        // The lock only guards a pointer swap, so a poisoned one still holds a valid value
+        let latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+        latest.clone()
+    }
+
+    /// Number of values published so far, for readers to notice updates
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Drop the published value, e.g. once the match it showed has ended
+    pub fn clear(&self) {
+        let previous = self.latest.lock().unwrap_or_else(|e| e.into_inner()).take();
+        drop(previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn readers_see_whole_values_while_writers_keep_publishing() {
+        let cell = SnapshotCell::default();
+        assert!(cell.latest().is_none());
+        let held = thread::scope(|scope| {
+            scope.spawn(|| {
+                for i in 0..10_000u32 {
+                    cell.publish([i; 64]);
+                }
+            });
+            let mut held = Vec::new();
+            for _ in 0..1_000 {
+                if let Some(value) = cell.latest() {
+                    // Every published array is uniform, so a torn value would show
+                    assert!(value.iter().all(|&v| v == value[0]));
+                    held.push(value);
+                }
+            }
+            held
+        });
+        // Values handed out earlier stay intact after being replaced
+        assert!(held.iter().all(|v| v.iter().all(|&x| x == v[0])));
+        // A reader preempted while holding the lock can make every attempt drop
+        let published = cell.version();
+        assert!(published <= 10_000);
+        // Without a reader in the way publishing always succeeds
+        assert!(cell.publish([7; 64]));
+        assert_eq!(cell.version(), published + 1);
+        assert_eq!(cell.latest().map(|v| v[0]), Some(7));
+        cell.clear();
+        assert!(cell.latest().is_none());
+    }
+}
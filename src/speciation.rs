@@ -0,0 +1,200 @@
+use crate::error::{check_range, DuelError};
+use crate::genome::Genome;
+
+/// Default RMS weight difference below which two genomes share a species
+const DEFAULT_THRESHOLD: f32 = 0.4;
+/// Default generations a species may go without improving before it is culled
+const DEFAULT_STAGNATION: usize = 15;
+
+/// Cluster of similar genomes that share their fitness
+#[derive(Clone, Debug)]
+pub struct Species {
+    pub id: usize,
+    /// Genome newcomers are compared against: the fittest member of the last generation
+    pub representative: Genome,
+    /// Members in the last generation
+    pub size: usize,
+    /// Best raw fitness any member has reached
+    pub best_fitness: f32,
+    /// Generation in which `best_fitness` last improved
+    pub improved: usize,
+}
+
+/// Splits the population into species by weight distance so one strategy
+/// cannot take over: a genome's fitness is shared among its species before
+/// selection, and species that stop improving are culled from breeding.
+/// Species persist across generations through their representatives.
+#[derive(Clone, Debug)]
+pub struct Speciation {
+    /// RMS weight difference below which a genome joins a species
+    pub threshold: f32,
+    /// Generations without improvement after which a species stops breeding
+    pub stagnation: usize,
+    pub species: Vec<Species>,
+    /// Species culled for stagnation in the last generation
+    pub culled: usize,
+    next_id: usize,
+}
+
+impl Default for Speciation {
+    fn default() -> Self {
+        Self::new(DEFAULT_THRESHOLD, DEFAULT_STAGNATION)
+    }
+}
+
+impl Speciation {
+    pub fn new(threshold: f32, stagnation: usize) -> Self {
+        Speciation {
+            threshold,
+            stagnation,
+            species: Vec::new(),
+            culled: 0,
+            next_id: 0,
+        }
+    }
+
+    /// The spec `parse` turns back into these settings
+    pub fn spec(&self) -> String {
+        format!("{}:{}", self.threshold, self.stagnation)
+    }
+
+    /// Parse "THRESHOLD" or "THRESHOLD:STAGNATION"
+    pub fn parse(spec: &str) -> Result<Self, DuelError> {
+        let (threshold, stagnation) = match spec.split_once(':') {
+            Some((threshold, stagnation)) => (threshold, Some(stagnation)),
+            None => (spec, None),
+        };
+        let threshold = threshold
+            .parse::<f32>()
+            .map_err(|_| DuelError::InvalidValue {
+                what: "speciation threshold",
+                value: threshold.to_string(),
+            })?;
+        check_range("speciation threshold", threshold, 0.001, 100.0)?;
+        let stagnation = match stagnation {
+            Some(value) => value
+                .parse::<usize>()
+                .map_err(|_| DuelError::InvalidValue {
+                    what: "speciation stagnation",
+                    value: value.to_string(),
+                })?,
+            None => DEFAULT_STAGNATION,
+        };
+        check_range("speciation stagnation", stagnation as f32, 1.0, 1e6)?;
+        Ok(Self::new(threshold, stagnation))
+    }
+
+    /// Sort an evaluated generation into species and return the genomes that
+    /// may breed, with their fitness replaced by the shared fitness: raw
+    /// fitness above the generation's worst, divided by the species size.
+    /// Members of stagnant species are left out, except the species holding
+    /// the fittest genome.
+    pub fn select_parents(&mut self, genomes: &[Genome], generation: usize) -> Vec<Genome> {
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); self.species.len()];
+        for (i, genome) in genomes.iter().enumerate() {
+            let home = self
+                .species
+                .iter()
+                .position(|s| distance(genome, &s.representative) < self.threshold);
+            match home {
+                Some(s) => members[s].push(i),
+                None => {
+                    self.species.push(Species {
+                        id: self.next_id,
+                        representative: genome.clone(),
+                        size: 0,
+                        best_fitness: f32::NEG_INFINITY,
+                        improved: generation,
+                    });
+                    self.next_id += 1;
+                    members.push(vec![i]);
+                }
+            }
+        }
+
+        // Species without members have died out
+        let mut living = Vec::new();
+        for (mut species, members) in self.species.drain(..).zip(members) {
+            let Some(&best) = members
+                .iter()
+                .max_by(|&&a, &&b| genomes[a].fitness.total_cmp(&genomes[b].fitness))
+            else {
+                continue;
+            };
+            if genomes[best].fitness > species.best_fitness {
+                species.best_fitness = genomes[best].fitness;
+                species.improved = generation;
+            }
+            species.representative = genomes[best].clone();
+            species.size = members.len();
+            living.push((species, members));
+        }
+
+        let floor = genomes
+            .iter()
+            .map(|g| g.fitness)
+            .fold(f32::INFINITY, f32::min);
+        let fittest =
+            (0..genomes.len()).max_by(|&a, &b| genomes[a].fitness.total_cmp(&genomes[b].fitness));
+        let mut parents = Vec::new();
+        self.culled = 0;
+        for (species, members) in &living {
+            let stagnant = generation - species.improved >= self.stagnation;
+            if stagnant && !fittest.is_some_and(|i| members.contains(&i)) {
+                self.culled += 1;
+                continue;
+            }
+            for &i in members {
+                let mut parent = genomes[i].clone();
+                parent.fitness = (parent.fitness - floor) / members.len() as f32;
+                parents.push(parent);
+            }
+        }
+        self.species = living.into_iter().map(|(species, _)| species).collect();
+        parents
+    }
+}
+
+/// Root-mean-square weight difference, which unlike the Euclidean distance
+/// does not grow with the size of the network
+pub fn distance(a: &Genome, b: &Genome) -> f32 {
+    a.distance(b) / (a.weights.len().max(1) as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome::GENOME_SIZE;
+
+    fn genome(weight: f32, fitness: f32) -> Genome {
+        let mut genome = Genome::from_weights(vec![weight; GENOME_SIZE]).unwrap();
+        genome.fitness = fitness;
+        genome
+    }
+
+    #[test]
+    fn crowded_species_share_fitness_and_stagnant_ones_are_culled() {
+        let mut speciation = Speciation::parse("0.5:3").unwrap();
+        assert_eq!(speciation.spec(), "0.5:3");
+        let genomes = [
+            genome(0.0, 40.0),
+            genome(0.1, 40.0),
+            genome(0.2, 40.0),
+            genome(2.0, 10.0),
+        ];
+        let parents = speciation.select_parents(&genomes, 0);
+        assert_eq!(speciation.species.len(), 2);
+        // The crowded species splits its lead over the worst genome three ways
+        assert_eq!(parents[0].fitness, 10.0);
+        assert_eq!(parents[3].fitness, 0.0);
+
+        // Neither species improves again; the loner drops out of breeding,
+        // while the species holding the fittest genome is always kept
+        for generation in 1..=3 {
+            let parents = speciation.select_parents(&genomes, generation);
+            let culled = generation >= 3;
+            assert_eq!(parents.len(), if culled { 3 } else { 4 });
+            assert_eq!(speciation.culled, culled as usize);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::game::*;
+use crate::snapshot::SnapshotCell;
+
+/// Simulation steps between the snapshots a watched match publishes
+pub const PUBLISH_INTERVAL: usize = 4;
+
+/// Which part of an evaluation a live match belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchKind {
+    /// A new offspring warming up against an early champion
+    Curriculum,
+    /// Against another genome of the population
+    Pool,
+    /// Against a champion sampled from the hall of fame
+    Archive,
+}
+
+/// An evaluation match as its worker last published it
+#[derive(Clone, Debug)]
+pub struct LiveMatch {
+    pub state: GameState,
+    pub generation: usize,
+    /// Index of the genome being evaluated, flying ship 0
+    pub genome: usize,
+    pub kind: MatchKind,
+    /// Whether the match has ended, leaving `state` as its final position
+    pub finished: bool,
+}
+
+impl LiveMatch {
+    /// How worth watching the match is right now: projectiles in flight and
+    /// ships closing in score, finished matches are never picked
+    pub fn interest(&self) -> f32 {
+        let state = &self.state;
+        if self.finished {
+            return f32::NEG_INFINITY;
+        }
+        let dx = toroidal_diff(state.ships[0].x, state.ships[1].x, state.arena.width);
+        let dy = toroidal_diff(state.ships[0].y, state.ships[1].y, state.arena.height);
+        let range = state.arena.engagement_range();
+        let proximity = 1.0 - ((dx * dx + dy * dy).sqrt() / range).min(1.0);
+        state.projectiles.len() as f32 + 4.0 * proximity
+    }
+}
+
+/// Window onto the matches evaluation workers are playing. Each worker
+/// publishes snapshots of its current match to its own cell, but only while
+/// someone is watching, so evaluation runs at full speed otherwise.
+#[derive(Debug)]
+pub struct Spectator {
+    watching: AtomicBool,
+    cells: Vec<SnapshotCell<LiveMatch>>,
+}
+
+impl Default for Spectator {
+    fn default() -> Self {
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        Spectator {
+            watching: AtomicBool::new(false),
+            cells: (0..workers).map(|_| SnapshotCell::default()).collect(),
+        }
+    }
+}
+
+impl Spectator {
+    pub fn is_watching(&self) -> bool {
+        self.watching.load(Ordering::Relaxed)
+    }
+
+    /// Start or stop publishing; stopping clears the cells so a later
+    /// session does not open on stale matches
+    pub fn set_watching(&self, watching: bool) {
+        self.watching.store(watching, Ordering::Relaxed);
+        if !watching {
+            for cell in &self.cells {
+                cell.clear();
+            }
+        }
+    }
+
+    /// Cell worker `worker` publishes to
+    pub fn cell(&self, worker: usize) -> &SnapshotCell<LiveMatch> {
+        &self.cells[worker % self.cells.len()]
+    }
+
+    /// Latest snapshot of every worker's match, by worker
+    pub fn matches(&self) -> Vec<Option<Arc<LiveMatch>>> {
+        self.cells.iter().map(SnapshotCell::latest).collect()
+    }
+
+    /// Worker whose match is the most interesting to watch now, if any is running
+    pub fn most_interesting(&self) -> Option<usize> {
+        self.matches()
+            .iter()
+            .enumerate()
+            .filter_map(|(worker, m)| Some((worker, m.as_ref()?.interest())))
+            .filter(|(_, interest)| interest.is_finite())
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(worker, _)| worker)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evolution::{Population, MIN_POPULATION_SIZE};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn watched_evaluations_publish_matches_without_changing_fitness() {
+        let evaluate = |watching: bool| {
+            let mut rng = StdRng::seed_from_u64(12);
+            let mut pop =
+                Population::with_size(ArenaLayout::default(), MIN_POPULATION_SIZE, &mut rng);
+            pop.spectator.set_watching(watching);
+            pop.evaluate(&mut rng).unwrap();
+            let fitness: Vec<f32> = pop.genomes.iter().map(|g| g.fitness).collect();
+            (fitness, pop.spectator.matches())
+        };
+        let (unwatched, idle) = evaluate(false);
+        let (watched, live) = evaluate(true);
+        assert_eq!(watched, unwatched);
+        assert!(idle.iter().all(Option::is_none));
+        let published: Vec<_> = live.iter().flatten().collect();
+        assert!(!published.is_empty());
+        // Each worker's last match ran to the end
+        assert!(published.iter().all(|m| m.finished));
+        assert!(published.iter().all(|m| m.interest() == f32::NEG_INFINITY));
+    }
+}
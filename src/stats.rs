@@ -0,0 +1,230 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::error::DuelError;
+use crate::evolution::Population;
+
+const CSV_HEADER: &str = "generation,best,mean,median,std_dev,archive_win_rate";
+/// Entries a live per-generation history keeps by default, see `thin_history`
+pub const HISTORY_CAP: usize = 1000;
+
+/// Fitness statistics of one evaluated generation
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub best: f32,
+    pub mean: f32,
+    pub median: f32,
+    /// Population standard deviation of fitness
+    pub std_dev: f32,
+    /// Share of hall-of-fame matches won, None before the archive has entries
+    pub archive_win_rate: Option<f32>,
+}
+
+impl GenerationStats {
+    /// Statistics of the population's last evaluation
+    pub fn of(pop: &Population) -> Self {
+        let mut fitness: Vec<f32> = pop.genomes.iter().map(|g| g.fitness).collect();
+        fitness.sort_by(f32::total_cmp);
+        let n = fitness.len().max(1) as f32;
+        let mean = fitness.iter().sum::<f32>() / n;
+        let variance = fitness.iter().map(|f| (f - mean) * (f - mean)).sum::<f32>() / n;
+        let mid = fitness.len() / 2;
+        let median = match fitness.len() {
+            0 => 0.0,
+            len if len.is_multiple_of(2) => (fitness[mid - 1] + fitness[mid]) / 2.0,
+            _ => fitness[mid],
+        };
+        GenerationStats {
+            generation: pop.generation,
+            best: fitness.last().copied().unwrap_or(0.0),
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+            archive_win_rate: pop.archive_win_rate,
+        }
+    }
+
+    /// One CSV row matching CSV_HEADER; an unknown win rate is left empty
+    pub fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.generation,
+            self.best,
+            self.mean,
+            self.median,
+            self.std_dev,
+            self.archive_win_rate
+                .map_or(String::new(), |r| r.to_string())
+        )
+    }
+
+    /// Parse a row written by `csv_row`
+    pub fn from_csv_row(row: &str) -> Result<Self, DuelError> {
+        let invalid = || DuelError::InvalidValue {
+            what: "statistics row",
+            value: row.chars().take(60).collect(),
+        };
+        let fields: Vec<&str> = row.trim().split(',').collect();
+        let [generation, best, mean, median, std_dev, win_rate] = fields[..] else {
+            return Err(invalid());
+        };
+        let number = |field: &str| field.parse::<f32>().map_err(|_| invalid());
+        Ok(GenerationStats {
+            generation: generation.parse().map_err(|_| invalid())?,
+            best: number(best)?,
+            mean: number(mean)?,
+            median: number(median)?,
+            std_dev: number(std_dev)?,
+            archive_win_rate: match win_rate {
+                "" => None,
+                rate => Some(number(rate)?),
+            },
+        })
+    }
+
+    /// One JSON object; an unknown win rate is null
+    pub fn json_line(&self) -> String {
+        format!(
+            "{{\"generation\":{},\"best\":{},\"mean\":{},\"median\":{},\"std_dev\":{},\"archive_win_rate\":{}}}",
+            self.generation,
+            json_number(self.best),
+            json_number(self.mean),
+            json_number(self.median),
+            json_number(self.std_dev),
+            self.archive_win_rate.map_or("null".to_string(), json_number)
+        )
+    }
+}
+
+/// Per-generation statistics appended to a file for plotting learning
+/// curves: JSON lines if the path ends in ".jsonl", CSV otherwise. An
+/// existing file is extended, so a resumed run continues its history.
+pub struct StatsLog {
+    writer: BufWriter<File>,
+    json: bool,
+}
+
+impl StatsLog {
+    pub fn open(path: impl AsRef<Path>) -> Result<StatsLog, DuelError> {
+        let path = path.as_ref();
+        let json = path.extension().is_some_and(|e| e == "jsonl");
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let empty = file.metadata()?.len() == 0;
+        let mut writer = BufWriter::new(file);
+        if empty && !json {
+            writeln!(writer, "{}", CSV_HEADER)?;
+        }
+        Ok(StatsLog { writer, json })
+    }
+
+    /// Append the population's last evaluation, flushed so the file can be
+    /// plotted while training runs
+    pub fn record(&mut self, pop: &Population) -> Result<(), DuelError> {
+        let stats = GenerationStats::of(pop);
+        let line = if self.json {
+            stats.json_line()
+        } else {
+            stats.csv_row()
+        };
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Keep a per-generation history within `cap` entries: past the cap,
+/// every other entry of its older half is dropped, so the latest
+/// generations stay at full resolution while older ones are downsampled
+/// further each time. The first entry always stays; a cap of 0 keeps
+/// everything.
+pub fn thin_history<T>(history: &mut Vec<T>, cap: usize) {
+    if cap == 0 || history.len() <= cap {
+        return;
+    }
+    let older = history.len() / 2;
+    let mut i = 0;
+    history.retain(|_| {
+        let keep = i >= older || i % 2 == 0;
+        i += 1;
+        keep
+    });
+}
+
+/// Every generation in a CSV file written by `StatsLog`
+pub fn read_csv(path: impl AsRef<Path>) -> Result<Vec<GenerationStats>, DuelError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut stats = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line == CSV_HEADER || line.trim().is_empty() {
+            continue;
+        }
+        stats.push(GenerationStats::from_csv_row(&line)?);
+    }
+    Ok(stats)
+}
+
+/// JSON has no NaN or infinity, so those become null
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        format!("{}", value)
+    } else {
+        "null".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evolution::MIN_POPULATION_SIZE;
+    use crate::game::ArenaLayout;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn summarizes_the_fitness_distribution() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut pop = Population::with_size(ArenaLayout::default(), MIN_POPULATION_SIZE, &mut rng);
+        for (i, genome) in pop.genomes.iter_mut().enumerate() {
+            genome.fitness = i as f32;
+        }
+        pop.archive_win_rate = Some(0.25);
+        let stats = GenerationStats::of(&pop);
+        assert_eq!(stats.best, 6.0);
+        assert_eq!(stats.mean, 3.0);
+        assert_eq!(stats.median, 3.0);
+        assert_eq!(stats.std_dev, 2.0);
+        assert_eq!(stats.csv_row(), "0,6,3,3,2,0.25");
+        assert_eq!(
+            GenerationStats::from_csv_row(&stats.csv_row()).unwrap(),
+            stats
+        );
+
+        pop.archive_win_rate = None;
+        assert!(GenerationStats::of(&pop)
+            .json_line()
+            .ends_with("\"archive_win_rate\":null}"));
+        let unrated = GenerationStats::of(&pop);
+        assert_eq!(
+            GenerationStats::from_csv_row(&unrated.csv_row()).unwrap(),
+            unrated
+        );
+        assert!(GenerationStats::from_csv_row("1,2,3").is_err());
+    }
+
+    #[test]
+    fn thinned_histories_stay_bounded_with_recent_entries_intact() {
+        let mut history = Vec::new();
+        for generation in 0..10_000 {
+            history.push(generation);
+            thin_history(&mut history, 100);
+            assert!(history.len() <= 100);
+        }
+        assert_eq!(history[0], 0);
+        let recent = &history[history.len() - 50..];
+        assert!(recent.windows(2).all(|pair| pair[1] == pair[0] + 1));
+        assert_eq!(history.last(), Some(&9_999));
+    }
+}
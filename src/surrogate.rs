@@ -0,0 +1,276 @@
+use crate::genome::{Genome, NetworkConfig};
+
+/// Ridge penalty on the weight coefficients
+const RIDGE_LAMBDA: f64 = 10.0;
+/// Weight kept by older generations each time a new one is added
+const HISTORY_DECAY: f64 = 0.7;
+/// Smoothing of the accuracy estimate across generations
+const ACCURACY_SMOOTHING: f32 = 0.5;
+/// Most offspring generated per slot when the surrogate ranks perfectly
+const MAX_OVERSAMPLE: usize = 4;
+
+/// Ridge-regression fitness predictor over genome weights, refit every
+/// generation. Offspring are pre-screened by predicted fitness so real
+/// matches go to promising candidates; how many extra candidates are screened
+/// scales with the rank correlation the model achieved on genomes it had
+/// not yet seen, so a surrogate that predicts badly stops filtering.
+#[derive(Clone, Debug)]
+pub struct Surrogate {
+    /// Weights per genome; each is one feature, plus a constant term
+    weights: usize,
+    /// Decayed normal equations X^T X (row-major) and X^T y
+    xtx: Vec<f64>,
+    xty: Vec<f64>,
+    coefficients: Option<Vec<f64>>,
+    /// Smoothed out-of-sample Spearman correlation, in [-1, 1]
+    pub accuracy: f32,
+    /// Generations the model has been fitted on
+    pub generations_seen: usize,
+}
+
+impl Surrogate {
+    /// An unfitted model over every weight of genomes shaped by `network`
+    pub fn new(network: &NetworkConfig) -> Self {
+        let features = network.genome_size() + 1;
+        Surrogate {
+            weights: network.genome_size(),
+            xtx: vec![0.0; features * features],
+            xty: vec![0.0; features],
+            coefficients: None,
+            accuracy: 0.0,
+            generations_seen: 0,
+        }
+    }
+
+    fn features(&self) -> usize {
+        self.weights + 1
+    }
+
+    /// Predicted fitness, standardized within a generation, or None before the first fit
+    pub fn predict(&self, genome: &Genome) -> Option<f32> {
+        let coefficients = self.coefficients.as_ref()?;
+        let bias = coefficients[self.weights];
+        let sum = genome
+            .weights
+            .iter()
+            .take(self.weights)
+            .zip(coefficients)
+            .fold(bias, |acc, (&w, &c)| acc + w as f64 * c);
+        Some(sum as f32)
+    }
+
+    /// Offspring to generate per slot in the next generation (1 = no screening)
+    pub fn oversample(&self) -> usize {
+        if self.coefficients.is_none() {
+            return 1;
+        }
+        let trust = self.accuracy.max(0.0);
+        1 + ((MAX_OVERSAMPLE - 1) as f32 * trust).round() as usize
+    }
+
+    /// Score the current model on a freshly evaluated generation, then fold
+    /// that generation into the training data and refit
+    pub fn observe(&mut self, genomes: &[Genome]) {
+        if genomes.len() < 2 {
+            return;
+        }
+
+        // Fitness scale drifts with the opponents, so targets are z-scores within the generation
+        let n = genomes.len() as f64;
+        let mean = genomes.iter().map(|g| g.fitness as f64).sum::<f64>() / n;
+        let variance = genomes
+            .iter()
+            .map(|g| (g.fitness as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n;
+        let std_dev = variance.sqrt().max(1e-6);
+
+        if self.coefficients.is_some() {
+            let predicted: Vec<f32> = genomes.iter().filter_map(|g| self.predict(g)).collect();
+            let actual: Vec<f32> = genomes.iter().map(|g| g.fitness).collect();
+            let correlation = spearman(&predicted, &actual);
+            self.accuracy = if self.generations_seen > 1 {
+                ACCURACY_SMOOTHING * self.accuracy + (1.0 - ACCURACY_SMOOTHING) * correlation
+            } else {
+                correlation
+            };
+        }
+
+        for v in self.xtx.iter_mut().chain(self.xty.iter_mut()) {
+            *v *= HISTORY_DECAY;
+        }
+        let features = self.features();
+        let mut x = vec![0.0f64; features];
+        for genome in genomes {
+            for (xi, &w) in x.iter_mut().zip(genome.weights.iter().take(self.weights)) {
+                *xi = w as f64;
+            }
+            x[self.weights] = 1.0;
+            let y = (genome.fitness as f64 - mean) / std_dev;
+            for i in 0..features {
+                self.xty[i] += x[i] * y;
+                let row = &mut self.xtx[i * features..(i + 1) * features];
+                for (cell, &xj) in row[..=i].iter_mut().zip(&x) {
+                    *cell += x[i] * xj;
+                }
+            }
+        }
+
+        self.coefficients = self.solve();
+        self.generations_seen += 1;
+    }
+
+    /// Solve (X^T X + lambda I) beta = X^T y by Cholesky decomposition of the
+    /// lower triangle. The bias term is not penalized.
+    fn solve(&self) -> Option<Vec<f64>> {
+        let n = self.features();
+        let mut l = self.xtx.clone();
+        for i in 0..self.weights {
+            l[i * n + i] += RIDGE_LAMBDA;
+        }
+        // Keeps the system positive definite before the bias column has any data
+        l[self.weights * n + self.weights] += 1e-6;
+
+        for j in 0..n {
+            let mut diag = l[j * n + j];
+            for k in 0..j {
+                diag -= l[j * n + k] * l[j * n + k];
+            }
+            if diag <= 0.0 {
+                return None;
+            }
+            let diag = diag.sqrt();
+            l[j * n + j] = diag;
+            for i in j + 1..n {
+                let mut sum = l[i * n + j];
+                for k in 0..j {
+                    sum -= l[i * n + k] * l[j * n + k];
+                }
+                l[i * n + j] = sum / diag;
+            }
+        }
+
+        // Forward substitution L z = b, then back substitution L^T beta = z
+        let mut z = self.xty.clone();
+        for i in 0..n {
+            for k in 0..i {
+                z[i] -= l[i * n + k] * z[k];
+            }
+            z[i] /= l[i * n + i];
+        }
+        for i in (0..n).rev() {
+            for k in i + 1..n {
+                z[i] -= l[k * n + i] * z[k];
+            }
+            z[i] /= l[i * n + i];
+        }
+        Some(z)
+    }
+}
+
+/// Rank correlation between two equal-length samples
+fn spearman(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.len() < 2 {
+        return 0.0;
+    }
+    let ra = ranks(a);
+    let rb = ranks(b);
+    let n = a.len() as f32;
+    let mean = (n - 1.0) / 2.0;
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for (x, y) in ra.iter().zip(&rb) {
+        cov += (x - mean) * (y - mean);
+        var_a += (x - mean) * (x - mean);
+        var_b += (y - mean) * (y - mean);
+    }
+    if var_a <= 0.0 || var_b <= 0.0 {
+        return 0.0;
+    }
+    cov / (var_a * var_b).sqrt()
+}
+
+/// Fractional ranks, ties sharing their average rank
+fn ranks(values: &[f32]) -> Vec<f32> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&i, &j| values[i].total_cmp(&values[j]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end - 1) as f32 / 2.0;
+        for &idx in &order[start..end] {
+            ranks[idx] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn solve_recovers_a_linear_fitness() {
+        // Three weight coefficients, then the bias
+        let expected = [0.8, -1.5, 0.3, 2.0];
+        let features = expected.len();
+        let mut surrogate = Surrogate {
+            weights: features - 1,
+            xtx: vec![0.0; features * features],
+            xty: vec![0.0; features],
+            coefficients: None,
+            accuracy: 0.0,
+            generations_seen: 0,
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..5000 {
+            // The constant feature last, as `observe` lays it out
+            let x: Vec<f64> = (1..features)
+                .map(|_| rng.gen_range(-1.0..1.0))
+                .chain([1.0])
+                .collect();
+            let y: f64 = x.iter().zip(&expected).map(|(x, c)| x * c).sum();
+            for i in 0..features {
+                surrogate.xty[i] += x[i] * y;
+                for j in 0..=i {
+                    surrogate.xtx[i * features + j] += x[i] * x[j];
+                }
+            }
+        }
+        let solved = surrogate.solve().unwrap();
+        for (solved, expected) in solved.iter().zip(&expected) {
+            assert!(
+                (solved - expected).abs() < 0.02,
+                "{} vs {}",
+                solved,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rank_correlation_follows_order_and_shares_ties() {
+        assert_eq!(ranks(&[3.0, 1.0, 3.0, 2.0]), [2.5, 0.0, 2.5, 1.0]);
+
+        let x = [0.5, -2.0, 1.0, 7.0, 3.0];
+        let cubed: Vec<f32> = x.iter().map(|v| v * v * v).collect();
+        let negated: Vec<f32> = x.iter().map(|v| -v).collect();
+        assert!((spearman(&x, &cubed) - 1.0).abs() < 1e-6);
+        assert!((spearman(&x, &negated) + 1.0).abs() < 1e-6);
+        let tied = [1.0, 1.0, 2.0, 2.0, 3.0];
+        let r = spearman(&[1.0, 2.0, 3.0, 4.0, 5.0], &tied);
+        assert!(r > 0.9 && r < 1.0);
+
+        // Constant and mismatched samples carry no rank information
+        assert_eq!(spearman(&x, &[4.0; 5]), 0.0);
+        assert_eq!(spearman(&x, &x[..4]), 0.0);
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use crate::stats::GenerationStats;
+
+/// Spread of one generation's fitness statistics across the runs of a seed
+/// sweep
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepPoint {
+    pub generation: usize,
+    /// Runs that reached this generation
+    pub runs: usize,
+    pub best_mean: f32,
+    pub best_std: f32,
+    /// Mean and standard deviation of the runs' population mean fitness
+    pub mean_mean: f32,
+    pub mean_std: f32,
+}
+
+pub const CSV_HEADER: &str = "generation,runs,best_mean,best_std,mean_mean,mean_std";
+
+impl SweepPoint {
+    /// One CSV row matching CSV_HEADER
+    pub fn csv_row(&self) -> String {
+        format!(
+            "{},{},{:.2},{:.2},{:.2},{:.2}",
+            self.generation,
+            self.runs,
+            self.best_mean,
+            self.best_std,
+            self.mean_mean,
+            self.mean_std
+        )
+    }
+}
+
+/// Mean and population standard deviation of `values`
+fn mean_std(values: &[f32]) -> (f32, f32) {
+    let n = values.len().max(1) as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / n;
+    (mean, variance.sqrt())
+}
+
+/// Fitness curves of independent runs combined generation by generation.
+/// Runs that stopped early simply drop out of the later points, which
+/// record how many runs they cover.
+pub fn aggregate(runs: &[Vec<GenerationStats>]) -> Vec<SweepPoint> {
+    let mut by_generation: BTreeMap<usize, (Vec<f32>, Vec<f32>)> = BTreeMap::new();
+    for run in runs {
+        for stats in run {
+            let (best, mean) = by_generation.entry(stats.generation).or_default();
+            best.push(stats.best);
+            mean.push(stats.mean);
+        }
+    }
+    by_generation
+        .into_iter()
+        .map(|(generation, (best, mean))| {
+            let (best_mean, best_std) = mean_std(&best);
+            let (mean_mean, mean_std) = mean_std(&mean);
+            SweepPoint {
+                generation,
+                runs: best.len(),
+                best_mean,
+                best_std,
+                mean_mean,
+                mean_std,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_runs_per_generation() {
+        let stats = |generation, best, mean| GenerationStats {
+            generation,
+            best,
+            mean,
+            median: mean,
+            std_dev: 0.0,
+            archive_win_rate: None,
+        };
+        let runs = vec![
+            vec![stats(0, 10.0, 2.0), stats(1, 20.0, 4.0)],
+            vec![stats(0, 30.0, 2.0)],
+        ];
+        let points = aggregate(&runs);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].runs, 2);
+        assert_eq!((points[0].best_mean, points[0].best_std), (20.0, 10.0));
+        assert_eq!((points[0].mean_mean, points[0].mean_std), (2.0, 0.0));
+        // The shorter run no longer counts once it has stopped
+        assert_eq!(points[1].runs, 1);
+        assert_eq!(points[1].best_mean, 20.0);
+        assert_eq!(points[1].csv_row(), "1,1,20.00,0.00,4.00,0.00");
+        assert!(aggregate(&[]).is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+
+use macroquad::prelude::*;
+
+use spaceship_duel::events::MatchEvent;
+use spaceship_duel::game::*;
+
+use crate::i18n::{fill, tr, Text};
+
+/// Misses in a row before the first streak remark, and between later ones
+const MISS_STREAK: usize = 5;
+/// A wrap-around counts as daring with the opponent this close (px)
+const DARING_WRAP_DISTANCE: f32 = 250.0;
+/// Match time a rule stays quiet after making a remark
+const RULE_COOLDOWN: f32 = 4.0;
+/// Remarks waiting to scroll past; newer ones are dropped while it is full
+const QUEUE_LEN: usize = 3;
+const SCROLL_SPEED: f32 = 260.0;
+const FONT_SIZE: u16 = 22;
+
+/// Patterns in the event stream worth a remark
+#[derive(Clone, Copy)]
+enum Rule {
+    NearMiss,
+    MissStreak,
+    DaringWrap,
+    Comeback,
+}
+
+impl Rule {
+    const COUNT: usize = 4;
+
+    /// Template with '|'-separated alternative phrasings
+    fn template(self) -> &'static str {
+        tr(match self {
+            Rule::NearMiss => Text::TickerNearMiss,
+            Rule::MissStreak => Text::TickerMissStreak,
+            Rule::DaringWrap => Text::TickerDaringWrap,
+            Rule::Comeback => Text::TickerComeback,
+        })
+    }
+}
+
+/// Rule-based color commentary for the showcase match, scrolled across the
+/// top of the arena. Duel-only, like the `EventLog` it reads: ships are 0
+/// and 1, each the other's opponent.
+pub struct Ticker {
+    /// Consecutive missed shots per ship
+    misses: [usize; 2],
+    /// Match time of each rule's latest remark
+    last_remark: [f32; Rule::COUNT],
+    /// Rotates through each template's alternatives
+    variant: usize,
+    queue: VecDeque<String>,
+    /// Remark on screen, its x position and its width
+    scrolling: Option<(String, f32, f32)>,
+}
+
+impl Default for Ticker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ticker {
+    pub fn new() -> Self {
+        Ticker {
+            misses: [0; 2],
+            last_remark: [f32::NEG_INFINITY; Rule::COUNT],
+            variant: 0,
+            queue: VecDeque::with_capacity(QUEUE_LEN),
+            scrolling: None,
+        }
+    }
+
+    /// Reset streaks for a new match; remarks already queued still play out
+    pub fn start_match(&mut self) {
+        self.misses = [0; 2];
+        self.last_remark = [f32::NEG_INFINITY; Rule::COUNT];
+    }
+
+    /// Run the rules over the events of one step
+    pub fn observe(&mut self, events: &[MatchEvent]) {
+        for event in events {
+            debug_assert!(
+                event.ships().all(|ship| ship < 2),
+                "commentary follows duels only"
+            );
+            match *event {
+                MatchEvent::ShotMissed { ship, time } => {
+                    self.misses[ship] += 1;
+                    let count = self.misses[ship];
+                    if count.is_multiple_of(MISS_STREAK) {
+                        self.remark(
+                            Rule::MissStreak,
+                            time,
+                            &[("ship", ship_name(ship)), ("count", count.to_string())],
+                        );
+                    }
+                }
+                MatchEvent::NearMiss {
+                    shooter,
+                    target,
+                    distance,
+                    time,
+                } => self.remark(
+                    Rule::NearMiss,
+                    time,
+                    &[
+                        ("shooter", ship_name(shooter)),
+                        ("target", ship_name(target)),
+                        ("distance", format!("{:.0}", distance)),
+                    ],
+                ),
+                MatchEvent::Wrapped {
+                    ship,
+                    opponent,
+                    opponent_distance,
+                    time,
+                } if opponent_distance < DARING_WRAP_DISTANCE => self.remark(
+                    Rule::DaringWrap,
+                    time,
+                    &[("ship", ship_name(ship)), ("opponent", ship_name(opponent))],
+                ),
+                MatchEvent::Destroyed {
+                    shooter,
+                    target,
+                    time,
+                    ..
+                } => {
+                    let count = std::mem::take(&mut self.misses[shooter]);
+                    if count >= MISS_STREAK {
+                        self.remark(
+                            Rule::Comeback,
+                            time,
+                            &[
+                                ("shooter", ship_name(shooter)),
+                                ("target", ship_name(target)),
+                                ("count", count.to_string()),
+                            ],
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn remark(&mut self, rule: Rule, time: f32, args: &[(&str, String)]) {
+        let last = &mut self.last_remark[rule as usize];
+        if time - *last < RULE_COOLDOWN || self.queue.len() == QUEUE_LEN {
+            return;
+        }
+        *last = time;
+        let phrasings: Vec<&str> = rule.template().split('|').collect();
+        let template = phrasings[self.variant % phrasings.len()];
+        self.variant += 1;
+        self.queue.push_back(fill(template, args));
+    }
+
+    /// Scroll the current remark and bring in the next one once it has left
+    pub fn update(&mut self, dt: f32) {
+        if let Some((_, x, width)) = &mut self.scrolling {
+            *x -= SCROLL_SPEED * dt;
+            if *x + *width < 0.0 {
+                self.scrolling = None;
+            }
+        }
+        if self.scrolling.is_none() {
+            if let Some(text) = self.queue.pop_front() {
+                let width = measure_text(&text, None, FONT_SIZE, 1.0).width;
+                self.scrolling = Some((text, ARENA_WIDTH, width));
+            }
+        }
+    }
+
+    pub fn render(&self) {
+        if let Some((text, x, _)) = &self.scrolling {
+            let y = 90.0;
+            draw_rectangle(
+                0.0,
+                y - 20.0,
+                ARENA_WIDTH,
+                28.0,
+                Color::new(0.0, 0.0, 0.0, 0.5),
+            );
+            draw_text(
+                text,
+                *x,
+                y,
+                FONT_SIZE as f32,
+                Color::new(1.0, 0.85, 0.3, 1.0),
+            );
+        }
+    }
+}
+
+fn ship_name(ship: usize) -> String {
+    tr(if ship == 0 {
+        Text::ShipGreen
+    } else {
+        Text::ShipBlue
+    })
+    .to_string()
+}
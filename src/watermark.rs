@@ -0,0 +1,110 @@
+use crate::genome::{Genome, WEIGHT_LIMIT};
+
+/// Low mantissa bits of every weight that carry the watermark
+pub const WATERMARK_BITS: u32 = 8;
+/// Largest change marking makes to a weight, relative to its magnitude: the
+/// marked bits plus one borrow from the bit above them, out of f32's 23-bit
+/// mantissa. Zero weights move by a subnormal amount instead.
+pub const RELATIVE_TOLERANCE: f32 = (1u32 << (WATERMARK_BITS + 1)) as f32 / (1u32 << 23) as f32;
+/// Share of marked bits that must match for `is_marked_by`. Bits of an
+/// unmarked genome match by chance half the time, while a marked genome keeps
+/// every bit of the weights mutation has not redrawn since.
+pub const MATCH_THRESHOLD: f32 = 0.75;
+
+const MASK: u32 = (1 << WATERMARK_BITS) - 1;
+
+/// Embed `owner`'s mark in the low mantissa bits of `genome`'s weights, so a
+/// ladder can tell which user or run submitted it. Every weight moves by at
+/// most `RELATIVE_TOLERANCE` of itself and stays within +-WEIGHT_LIMIT, and
+/// file round trips keep the bits, since weights are saved at full precision.
+/// The mark is keyed by the owner string only: it discourages resubmitting
+/// someone else's champion, it is not a cryptographic signature.
+pub fn embed(genome: &mut Genome, owner: &str) {
+    let key = owner_key(owner);
+    for (i, w) in genome.weights.iter_mut().enumerate() {
+        let bits = w.to_bits();
+        let mut marked = (bits & !MASK) | mark(key, i);
+        // Weights at the limit would be pushed past it; borrow from the bit
+        // above the mark instead, which leaves the exponent alone
+        if f32::from_bits(marked).abs() > WEIGHT_LIMIT {
+            marked -= MASK + 1;
+        }
+        *w = f32::from_bits(marked);
+    }
+}
+
+/// Share of `genome`'s marked bits that agree with `owner`'s mark: 1 for a
+/// genome marked by `owner` and untouched since, about 0.5 for any other
+pub fn match_rate(genome: &Genome, owner: &str) -> f32 {
+    if genome.weights.is_empty() {
+        return 0.0;
+    }
+    let key = owner_key(owner);
+    let matching: u32 = genome
+        .weights
+        .iter()
+        .enumerate()
+        .map(|(i, w)| WATERMARK_BITS - ((w.to_bits() ^ mark(key, i)) & MASK).count_ones())
+        .sum();
+    matching as f32 / (genome.weights.len() as u32 * WATERMARK_BITS) as f32
+}
+
+/// Whether `genome` carries `owner`'s mark, surviving a few generations of
+/// mutation by anyone who copied it
+pub fn is_marked_by(genome: &Genome, owner: &str) -> bool {
+    match_rate(genome, owner) >= MATCH_THRESHOLD
+}
+
+/// FNV-1a of the owner string, fixed across Rust versions and platforms
+/// so a ladder server built separately verifies the same marks
+fn owner_key(owner: &str) -> u64 {
+    owner.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The mark bits of weight `index` (splitmix64 of the key and index)
+fn mark(key: u64, index: usize) -> u32 {
+    let mut z = key.wrapping_add((index as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (z ^ (z >> 31)) as u32 & MASK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome::{NetworkState, SENSOR_COUNT};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn marks_identify_their_owner_without_changing_play() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let original = Genome::random(&mut rng);
+        let mut marked = original.clone();
+        embed(&mut marked, "alice");
+        for (w, m) in original.weights.iter().zip(&marked.weights) {
+            assert!((w - m).abs() <= w.abs() * RELATIVE_TOLERANCE);
+        }
+        let inputs: [f32; SENSOR_COUNT] = std::array::from_fn(|_| rng.gen_range(-1.0..1.0));
+        let before = original.evaluate(&inputs, &mut NetworkState::default());
+        let after = marked.evaluate(&inputs, &mut NetworkState::default());
+        for (a, b) in before.iter().zip(&after) {
+            assert!((a - b).abs() < 1e-3);
+        }
+
+        let (loaded, _) = Genome::from_file_str(&marked.to_file_string(3)).unwrap();
+        assert_eq!(match_rate(&loaded, "alice"), 1.0);
+        assert!(!is_marked_by(&loaded, "bob"));
+        assert!(!is_marked_by(&original, "alice"));
+
+        // Weights at the limit or zero stay valid
+        let mut edges = original.clone();
+        edges.weights[..3].copy_from_slice(&[WEIGHT_LIMIT, -WEIGHT_LIMIT, 0.0]);
+        embed(&mut edges, "alice");
+        assert!(edges.weights[..3].iter().all(|w| w.abs() <= WEIGHT_LIMIT));
+        assert!(edges.weights[2].abs() < f32::MIN_POSITIVE);
+        assert_eq!(match_rate(&edges, "alice"), 1.0);
+    }
+}